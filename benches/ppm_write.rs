@@ -0,0 +1,47 @@
+// Compares the original per-pixel, unbuffered PPM writer with `ppm::write_ppm` behind a
+// `BufWriter`. Run with `cargo bench --bench ppm_write`.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use maze::draw::Canvas;
+use maze::ppm::write_ppm;
+
+const SIZE: usize = 2048;
+const RUNS: u32 = 3;
+
+// The writer as it was before rows were batched: one 3-byte `write` per pixel straight to the file
+fn write_per_pixel(file: &mut File, canvas: &Canvas) -> std::io::Result<()> {
+    write!(file, "P6\n{} {} 255\n", canvas.width(), canvas.height())?;
+    for pixel in canvas.pixels() {
+        file.write_all(&[(pixel >> 24) as u8, (pixel >> 16) as u8, (pixel >> 8) as u8])?;
+    }
+    Ok(())
+}
+
+fn time(name: &str, mut run: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        run();
+    }
+    let avg = start.elapsed() / RUNS;
+    println!("{:>24}: {:>10.2?} per {}x{} image", name, avg, SIZE, SIZE);
+    avg
+}
+
+fn main() {
+    let canvas = Canvas::new(SIZE, SIZE, 0x32A852FF);
+    let path = std::env::temp_dir().join("maze_bench.ppm");
+
+    let naive = time("per-pixel, unbuffered", || {
+        write_per_pixel(&mut File::create(&path).unwrap(), &canvas).unwrap();
+    });
+    let batched = time("row batches, BufWriter", || {
+        let mut out = BufWriter::new(File::create(&path).unwrap());
+        write_ppm(&mut out, &canvas).unwrap();
+        out.flush().unwrap();
+    });
+    println!("{:>24}: {:.1}x", "speedup", naive.as_secs_f64() / batched.as_secs_f64());
+    let _ = fs::remove_file(path);
+}
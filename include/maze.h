@@ -0,0 +1,119 @@
+#ifndef MAZE_H
+#define MAZE_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Output formats understood by `maze_export`.
+ */
+typedef enum MazeFormat {
+  MAZE_FORMAT_PPM,
+  MAZE_FORMAT_PNG,
+  MAZE_FORMAT_SVG,
+  MAZE_FORMAT_TEXT,
+} MazeFormat;
+
+/**
+ * Opaque maze handle returned by `maze_generate` and released with `maze_free`.
+ */
+typedef struct Maze Maze;
+
+/**
+ * A cell position; row 0 is the top row and column 0 the left column.
+ */
+typedef struct MazeCell {
+  size_t row;
+  size_t col;
+} MazeCell;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Generates a `width` x `height` maze from `seed`; the same seed gives the same maze as
+ * `maze --seed`. Returns NULL if either dimension is zero, the maze would have more than
+ * 2^32 - 1 cells, or it cannot be allocated.
+ */
+struct Maze *maze_generate(size_t width, size_t height, uint64_t seed);
+
+/**
+ * Releases a maze. Passing NULL is a no-op.
+ *
+ * # Safety
+ * `maze` must be NULL or a handle from `maze_generate` that has not been freed yet.
+ */
+void maze_free(struct Maze *maze);
+
+/**
+ * # Safety
+ * `maze` must be a live handle from `maze_generate`.
+ */
+size_t maze_width(const struct Maze *maze);
+
+/**
+ * # Safety
+ * `maze` must be a live handle from `maze_generate`.
+ */
+size_t maze_height(const struct Maze *maze);
+
+/**
+ * Bitmask of the open sides of a cell: 1 = north, 2 = east, 4 = south, 8 = west. Cells outside
+ * the maze have no open sides.
+ *
+ * # Safety
+ * `maze` must be a live handle from `maze_generate`.
+ */
+uint8_t maze_open_sides(const struct Maze *maze, struct MazeCell cell);
+
+/**
+ * Finds the shortest path from `start` to `goal` and writes up to `capacity` of its cells
+ * (start and goal included) to `path`. Returns the full path length, so a return value larger
+ * than `capacity` means the buffer was too small; 0 means a cell was outside the maze.
+ *
+ * # Safety
+ * `maze` must be a live handle from `maze_generate` and `path` must point to at least
+ * `capacity` writable `MazeCell`s (it may be NULL when `capacity` is 0).
+ */
+size_t maze_solve(const struct Maze *maze,
+                  struct MazeCell start,
+                  struct MazeCell goal,
+                  struct MazeCell *path,
+                  size_t capacity);
+
+/**
+ * Rasterizes the maze (blocky style, `scale` pixels per logical pixel) as 8-bit RGBA rows,
+ * ready to upload as a texture. Writes nothing unless `capacity` bytes are enough and returns
+ * the number of bytes the image needs; `width`/`height` receive its size when not NULL.
+ *
+ * # Safety
+ * `maze` must be a live handle from `maze_generate`, `pixels` must point to `capacity`
+ * writable bytes (or be NULL with `capacity` 0) and `width`/`height` must be NULL or writable.
+ */
+size_t maze_render_rgba(const struct Maze *maze,
+                        size_t scale,
+                        uint8_t *pixels,
+                        size_t capacity,
+                        size_t *width,
+                        size_t *height);
+
+/**
+ * Writes the maze to the file at `path` (UTF-8). `scale` only applies to PPM and PNG. Returns 0
+ * on success and -1 if the path is invalid or the file could not be written.
+ *
+ * # Safety
+ * `maze` must be a live handle from `maze_generate` and `path` a NUL-terminated string.
+ */
+int maze_export(const struct Maze *maze, const char *path, enum MazeFormat format, size_t scale);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MAZE_H */
@@ -0,0 +1,149 @@
+// Organic caves instead of mazes, by cellular automaton: every cell starts as rock or floor at
+// random, a few rounds of smoothing turn the noise into blobs, and a connectivity pass digs
+// tunnels until all the floor is one cave. Rock is left out of the maze (see `Env::exclude`) and
+// neighbouring floor cells are always joined, so caves render, solve and export like mazes.
+
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use rand::Rng;
+
+use crate::{Cell, Env};
+
+// Share of the cells that start out as rock
+pub const DEFAULT_FILL: f32 = 0.45;
+// Smoothing rounds
+pub const DEFAULT_STEPS: usize = 4;
+// A cell turns to rock with at least this many rock cells among its eight neighbours, and to floor
+// with at most `FLOOR_BELOW` of them; the cells around the grid count as rock
+const ROCK_FROM: usize = 5;
+const FLOOR_BELOW: usize = 3;
+// Pockets of floor smaller than this are filled in rather than tunnelled to
+const MIN_POCKET: usize = 8;
+
+fn rock_around(rock: &[bool], width: usize, height: usize, cell: Cell) -> usize {
+    let mut count = 0;
+    for dr in -1isize..=1 {
+        for dc in -1isize..=1 {
+            if (dr, dc) == (0, 0) {
+                continue;
+            }
+            count += match (cell.row.checked_add_signed(dr), cell.col.checked_add_signed(dc)) {
+                (Some(row), Some(col)) if row < height && col < width => rock[(row * width) + col] as usize,
+                _ => 1,
+            };
+        }
+    }
+    count
+}
+
+// Floor cells joined to `from` through floor, without going through `seen` ones, which it marks
+fn pocket(rock: &[bool], width: usize, height: usize, from: usize, seen: &mut [bool]) -> Vec<usize> {
+    let mut cells = vec![from];
+    seen[from] = true;
+    let mut i = 0;
+    while i < cells.len() {
+        let ind = cells[i];
+        i += 1;
+        let (row, col) = (ind / width, ind % width);
+        let around = [(row > 0).then(|| ind - width), (row + 1 < height).then_some(ind + width), (col > 0).then(|| ind - 1), (col + 1 < width).then_some(ind + 1)];
+        for next in around.into_iter().flatten() {
+            if !rock[next] && !seen[next] {
+                seen[next] = true;
+                cells.push(next);
+            }
+        }
+    }
+    cells
+}
+
+// Carves a cave into `env`, which should have every wall standing: about `fill` of it rock to
+// begin with, smoothed `steps` times. The top-left and bottom-right cells are always floor.
+pub fn gen_cave(env: &mut Env, rng: &mut impl Rng, fill: f32, steps: usize) {
+    let (width, height) = (env.width, env.height);
+    let (start, goal) = (0, env.cell_count() - 1);
+    let mut rock: Vec<bool> = (0..env.cell_count()).map(|_| rng.gen::<f32>() < fill).collect();
+    for _ in 0..steps {
+        rock = (0..env.cell_count()).map(|i| match rock_around(&rock, width, height, env.cell(i)) {
+            n if n >= ROCK_FROM => true,
+            n if n <= FLOOR_BELOW => false,
+            _ => rock[i],
+        }).collect();
+    }
+    rock[start] = false;
+    rock[goal] = false;
+
+    // Fill in the small pockets, then tunnel from the cave around the start to the nearest
+    // other one until none is left
+    let mut seen = vec![false; env.cell_count()];
+    for i in 0..env.cell_count() {
+        if !rock[i] && !seen[i] {
+            let cells = pocket(&rock, width, height, i, &mut seen);
+            if cells.len() < MIN_POCKET && !cells.contains(&start) && !cells.contains(&goal) {
+                for ind in cells {
+                    rock[ind] = true;
+                }
+            }
+        }
+    }
+    let mut joined = vec![false; env.cell_count()];
+    pocket(&rock, width, height, start, &mut joined);
+    loop {
+        // Breadth-first search out of the joined cave, through rock, to the closest floor outside it
+        let mut came_from: Vec<Option<usize>> = vec![None; env.cell_count()];
+        let mut queue: VecDeque<usize> = (0..env.cell_count()).filter(|&i| joined[i]).collect();
+        for &i in queue.iter() {
+            came_from[i] = Some(i);
+        }
+        let mut found = None;
+        while let Some(ind) = queue.pop_front() {
+            if !rock[ind] && !joined[ind] {
+                found = Some(ind);
+                break;
+            }
+            for next in env.adjacent(env.cell(ind)).map(|n| env.ind(n)) {
+                if came_from[next].is_none() {
+                    came_from[next] = Some(ind);
+                    queue.push_back(next);
+                }
+            }
+        }
+        let Some(found) = found else { break };
+        let mut ind = found;
+        while !joined[ind] {
+            rock[ind] = false;
+            ind = came_from[ind].expect("every cell on the tunnel was reached");
+        }
+        pocket(&rock, width, height, found, &mut joined);
+    }
+
+    for (i, _) in rock.iter().enumerate().filter(|&(_, &r)| r) {
+        env.exclude(env.cell(i));
+    }
+    for cell in env.cells().collect::<Vec<_>>() {
+        let east = Cell { row: cell.row, col: cell.col + 1 };
+        let south = Cell { row: cell.row + 1, col: cell.col };
+        if env.is_excluded(cell) {
+            continue;
+        }
+        if east.col < width && !env.is_excluded(east) {
+            env.carve(cell, east);
+        }
+        if south.row < height && !env.is_excluded(south) {
+            env.carve(cell, south);
+        }
+    }
+    // The cave opens up outwards from the start
+    let mut order = VecDeque::from([env.cell(start)]);
+    let mut reached = vec![false; env.cell_count()];
+    reached[start] = true;
+    while let Some(cell) = order.pop_front() {
+        env.visit_order.push(env.ind(cell) as u32);
+        for next in env.neighbors(cell) {
+            if !core::mem::replace(&mut reached[env.ind(next)], true) {
+                order.push_back(next);
+            }
+        }
+    }
+}
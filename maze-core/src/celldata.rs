@@ -0,0 +1,73 @@
+// Application data attached to the cells of a maze: items, monsters, biomes, or free-form tags for
+// the JSON export. Kept beside the `Env` rather than inside it, so the maze model stays the same
+// for every application and one maze can carry several layers of data. Storage is dense, one slot
+// per cell indexed like `Env::ind`, so lookups never hash and it works without `std`.
+
+use alloc::vec::Vec;
+
+use crate::{Cell, Env};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellData<T> {
+    width: usize,
+    height: usize,
+    values: Vec<Option<T>>,
+}
+
+impl<T> CellData<T> {
+    // No data on any cell of a `width` x `height` maze
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, values: (0..width * height).map(|_| None).collect() }
+    }
+
+    // No data on any cell of `env`
+    pub fn for_env(env: &Env) -> Self {
+        Self::new(env.width, env.height)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn ind(&self, cell: Cell) -> Option<usize> {
+        (cell.row < self.height && cell.col < self.width).then_some((cell.row * self.width) + cell.col)
+    }
+
+    // The data on `cell`, if any; `None` for cells outside the maze
+    pub fn get(&self, cell: Cell) -> Option<&T> {
+        self.ind(cell).and_then(|i| self.values[i].as_ref())
+    }
+
+    pub fn get_mut(&mut self, cell: Cell) -> Option<&mut T> {
+        self.ind(cell).and_then(|i| self.values[i].as_mut())
+    }
+
+    // Puts `value` on `cell` and returns what was there before. Panics if `cell` is outside the
+    // maze, like indexing past the end of a slice.
+    pub fn insert(&mut self, cell: Cell, value: T) -> Option<T> {
+        let i = self.ind(cell).expect("cell outside the maze");
+        self.values[i].replace(value)
+    }
+
+    pub fn remove(&mut self, cell: Cell) -> Option<T> {
+        self.ind(cell).and_then(|i| self.values[i].take())
+    }
+
+    // Cells with data, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = (Cell, &T)> + '_ {
+        self.values.iter().enumerate().filter_map(move |(i, value)| value.as_ref().map(|v| (Cell { row: i / self.width, col: i % self.width }, v)))
+    }
+
+    // Number of cells with data
+    pub fn len(&self) -> usize {
+        self.values.iter().filter(|value| value.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(Option::is_none)
+    }
+}
@@ -0,0 +1,69 @@
+// Cutting a region out of a maze, for zoomed renders or to split a large maze into chunks after
+// generating it. The region keeps its passages and left-out cells; passages that crossed its
+// border are walled off, and `Env::crop_with_entrances` lists them so callers can draw them as
+// entrances. A region of a perfect maze can fall apart into several pieces, since the paths
+// joining them may run outside it; check `is_connected` where that matters.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::error::MazeError;
+use crate::stitch::Edge;
+use crate::{Cell, Env};
+
+// A block of cells: the top-left one and the size
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rect {
+    pub row: usize,
+    pub col: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Env {
+    // The cells of `rect` as a maze of their own, with every passage out of them sealed
+    pub fn crop(&self, rect: Rect) -> Result<Env, MazeError> {
+        self.crop_with_entrances(rect).map(|(env, _)| env)
+    }
+
+    // `crop`, along with the passages it sealed: the cell of the new maze each one left from and
+    // the side it left through, in row-major order and north, east, south, west within a cell
+    pub fn crop_with_entrances(&self, rect: Rect) -> Result<(Env, Vec<(Cell, Edge)>), MazeError> {
+        let mut env = Env::try_new(rect.width, rect.height)?;
+        if rect.col + rect.width > self.width || rect.row + rect.height > self.height {
+            return Err(MazeError::InvalidData(format!(
+                "the {}x{} region at row {}, column {} does not fit in the {}x{} maze",
+                rect.width,
+                rect.height,
+                rect.row,
+                rect.col,
+                self.width,
+                self.height
+            )));
+        }
+        let mut entrances = Vec::new();
+        for row in 0..rect.height {
+            for col in 0..rect.width {
+                let cell = Cell { row, col };
+                let from = Cell { row: rect.row + row, col: rect.col + col };
+                if self.is_excluded(from) {
+                    env.exclude(cell);
+                }
+                if col + 1 < rect.width && self.open_east(from) {
+                    env.carve(cell, Cell { row, col: col + 1 });
+                }
+                if row + 1 < rect.height && self.open_south(from) {
+                    env.carve(cell, Cell { row: row + 1, col });
+                }
+                let sealed = [
+                    row == 0 && from.row > 0 && self.open_south(Cell { row: from.row - 1, col: from.col }),
+                    col + 1 == rect.width && self.open_east(from),
+                    row + 1 == rect.height && self.open_south(from),
+                    col == 0 && from.col > 0 && self.open_east(Cell { row: from.row, col: from.col - 1 }),
+                ];
+                entrances.extend(Edge::ALL.into_iter().zip(sealed).filter(|&(_, sealed)| sealed).map(|(edge, _)| (cell, edge)));
+            }
+        }
+        Ok((env, entrances))
+    }
+}
@@ -0,0 +1,147 @@
+// Steering a maze to a set number of dead ends, for puzzles pitched at a given age: fewer dead ends
+// make a maze easier to walk by hand, more of them give more wrong turns to take. Dead ends are
+// removed by braiding, opening a wall at each one, and added by re-carving: passages in the middle
+// of corridors are walled off and the pieces joined up again through walls between cells that are
+// not dead ends. Re-carving keeps a perfect maze perfect; braiding adds loops.
+
+use alloc::vec::Vec;
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::graph::DisjointSets;
+use crate::{round, Cell, Env};
+
+// How many dead ends to aim for
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DeadEnds {
+    Count(usize),
+    // Share of the cells left in the maze, between 0 and 1
+    Fraction(f32),
+}
+
+impl DeadEnds {
+    // `N` for a count or `P%` for a percentage of the cells
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok().filter(|p| (0.0..=100.0).contains(p)).map(|p| DeadEnds::Fraction(p / 100.0)),
+            None => text.parse().ok().map(DeadEnds::Count),
+        }
+    }
+
+    // The number of dead ends this asks of `env`
+    pub fn target(&self, env: &Env) -> usize {
+        match *self {
+            DeadEnds::Count(count) => count,
+            DeadEnds::Fraction(fraction) => round(env.included_count() as f32 * fraction),
+        }
+    }
+}
+
+fn degree(env: &Env, cell: Cell) -> u32 {
+    env.open_sides(cell).count_ones()
+}
+
+// Opens a wall at dead ends until only `target` are left, taking out two at once by joining two
+// dead ends where that does not overshoot
+fn braid_to(env: &mut Env, rng: &mut impl Rng, target: usize) {
+    let mut dead_ends: Vec<Cell> = env.dead_ends().collect();
+    let mut count = dead_ends.len();
+    dead_ends.shuffle(rng);
+    for cell in dead_ends {
+        if count <= target {
+            break;
+        }
+        // An earlier carve may already have opened this one up
+        if degree(env, cell) != 1 {
+            continue;
+        }
+        // Opening into a cell with no passages would only move the dead end there
+        let mut walled: Vec<Cell> = env.adjacent(cell).filter(|&n| !env.has_passage(cell, n) && degree(env, n) > 0).collect();
+        walled.shuffle(rng);
+        let pair = count - target >= 2;
+        let Some(&next) = walled.iter().find(|&&n| (degree(env, n) == 1) == pair).or(walled.first()) else { continue };
+        count -= if degree(env, next) == 1 { 2 } else { 1 };
+        env.carve(cell, next);
+    }
+}
+
+// Walls off corridor passages until there are `target` dead ends, then joins the pieces up again
+// through walls whose cells both have two or more passages, so no dead end is lost. Pieces that
+// can only be joined through a dead end are joined that way, giving some back.
+fn recarve_to(env: &mut Env, rng: &mut impl Rng, target: usize) {
+    let mut count = env.dead_ends().count();
+    // Which cells were connected to begin with, so only the splits made here are joined
+    let mut before = DisjointSets::new(env.cell_count());
+    for wall in env.walls() {
+        before.union(env.ind(wall.start), env.ind(wall.target));
+    }
+    let mut passages: Vec<(Cell, Cell)> = env.walls().map(|wall| (wall.start, wall.target)).collect();
+    passages.shuffle(rng);
+    for (a, b) in passages {
+        if count >= target {
+            break;
+        }
+        let (da, db) = (degree(env, a), degree(env, b));
+        let gained = (da == 2) as usize + (db == 2) as usize;
+        if da >= 2 && db >= 2 && gained > 0 && count + gained <= target {
+            env.set_passage(a, b, false);
+            count += gained;
+        }
+    }
+
+    let mut after = DisjointSets::new(env.cell_count());
+    for wall in env.walls() {
+        after.union(env.ind(wall.start), env.ind(wall.target));
+    }
+    let mut walls: Vec<(Cell, Cell)> = env
+        .cells()
+        .filter(|&cell| !env.is_excluded(cell))
+        .flat_map(|cell| [Cell { row: cell.row, col: cell.col + 1 }, Cell { row: cell.row + 1, col: cell.col }].map(|n| (cell, n)))
+        .filter(|&(_, n)| n.row < env.height && n.col < env.width && !env.is_excluded(n))
+        .filter(|&(cell, n)| !env.has_passage(cell, n))
+        .collect();
+    walls.shuffle(rng);
+    // Walls between cells that keep their dead ends first, then any that are left
+    for keep in [true, false] {
+        for &(a, b) in walls.iter() {
+            let joins = before.find(env.ind(a)) == before.find(env.ind(b));
+            if joins && (!keep || (degree(env, a) >= 2 && degree(env, b) >= 2)) && after.union(env.ind(a), env.ind(b)) {
+                env.carve(a, b);
+            }
+        }
+    }
+}
+
+// Passes in a row that may fail to get closer to the target before giving up; each one reshuffles
+const RETRIES: usize = 8;
+
+// Braids or re-carves `env` until it has `target` dead ends, or as close as it gets, and returns
+// how many it ended up with. A perfect maze stays perfect when dead ends are added.
+pub fn tune_dead_ends(env: &mut Env, rng: &mut impl Rng, target: usize) -> usize {
+    let mut best = env.dead_ends().count();
+    let mut kept: Vec<(Cell, Cell)> = env.walls().map(|wall| (wall.start, wall.target)).collect();
+    let mut retries = 0;
+    while best != target && retries < RETRIES {
+        if best > target {
+            braid_to(env, rng, target);
+        } else {
+            recarve_to(env, rng, target);
+        }
+        let count = env.dead_ends().count();
+        if count.abs_diff(target) < best.abs_diff(target) {
+            (best, retries) = (count, 0);
+            kept = env.walls().map(|wall| (wall.start, wall.target)).collect();
+            continue;
+        }
+        // Back to the closest maze so far, to try again from there
+        retries += 1;
+        for (a, b) in env.walls().map(|wall| (wall.start, wall.target)).collect::<Vec<_>>() {
+            env.set_passage(a, b, false);
+        }
+        for &(a, b) in kept.iter() {
+            env.carve(a, b);
+        }
+    }
+    best
+}
@@ -0,0 +1,91 @@
+use alloc::format;
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::progress::Cancelled;
+
+// Everything the library's generation, rendering and I/O APIs can fail with
+#[derive(Debug)]
+pub enum MazeError {
+    // Reading or writing failed
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    // A `Progress` cancelled the job; whatever it produced so far is incomplete
+    Cancelled,
+    // Mazes need at least one cell in each direction, and at most `MAX_CELLS` cells that fit in memory
+    InvalidSize { width: usize, height: usize },
+    // Two mazes that must have the same size do not
+    SizeMismatch { first: (usize, usize), second: (usize, usize) },
+    // A maze or trace file that does not parse, or describes an impossible maze
+    InvalidData(String),
+}
+
+pub type Result<T> = core::result::Result<T, MazeError>;
+
+impl MazeError {
+    // The same error, with `path` named in the message
+    pub fn in_file(self, path: &str) -> MazeError {
+        match self {
+            #[cfg(feature = "std")]
+            MazeError::Io(err) => MazeError::Io(io::Error::new(err.kind(), format!("`{}`: {}", path, err))),
+            MazeError::InvalidData(message) => MazeError::InvalidData(format!("`{}`: {}", path, message)),
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            MazeError::Io(err) => write!(f, "{}", err),
+            MazeError::Cancelled => write!(f, "{}", Cancelled),
+            MazeError::InvalidSize { width, height } if *width == 0 || *height == 0 => {
+                write!(f, "maze dimensions must be non-zero, got {}x{}", width, height)
+            },
+            MazeError::InvalidSize { width, height } => write!(f, "a {}x{} maze is too large", width, height),
+            MazeError::SizeMismatch { first, second } => {
+                write!(f, "mazes must have the same size, got {}x{} and {}x{}", first.0, first.1, second.0, second.1)
+            },
+            MazeError::InvalidData(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for MazeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            MazeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for MazeError {
+    fn from(err: io::Error) -> Self {
+        MazeError::Io(err)
+    }
+}
+
+impl From<Cancelled> for MazeError {
+    fn from(_: Cancelled) -> Self {
+        MazeError::Cancelled
+    }
+}
+
+// For callers that only deal in `io::Error`, like the web server and terminal views
+#[cfg(feature = "std")]
+impl From<MazeError> for io::Error {
+    fn from(err: MazeError) -> Self {
+        match err {
+            MazeError::Io(err) => err,
+            MazeError::Cancelled => Cancelled.into(),
+            err => io::Error::new(io::ErrorKind::InvalidData, err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::error::MazeError;
+use crate::Env;
+
+// An undirected graph to carve a maze over, for layouts other than the square grid: nodes are
+// numbered `0..nodes`, and `edges` are the candidate passages between them
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    pub nodes: usize,
+    pub edges: Vec<(usize, usize)>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Algorithm {
+    // Randomized depth-first search, like `gen_maze_with`: long winding corridors
+    Dfs,
+    // Randomized Kruskal's: edges in random order, kept when they join two separate trees
+    Kruskal,
+    // Randomized Prim's: grows one tree by a random frontier edge at a time, giving short dead ends
+    Prim,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 3] = [Algorithm::Dfs, Algorithm::Kruskal, Algorithm::Prim];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Dfs => "dfs",
+            Algorithm::Kruskal => "kruskal",
+            Algorithm::Prim => "prim",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Algorithm::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+// Which edges of a grid carry over to the opposite side
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Wrap {
+    None,
+    // The last column neighbours the first, like the side of a cylinder
+    Horizontal,
+    // The last column neighbours the first upside down, like a Möbius strip: the cell at the end
+    // of the top row joins the start of the bottom row
+    Mobius,
+    // The last row neighbours the first as well, like the surface of a torus
+    Both,
+}
+
+impl Graph {
+    // Fails if an edge names a node that does not exist
+    pub fn new(nodes: usize, edges: Vec<(usize, usize)>) -> Result<Self, MazeError> {
+        if let Some(&(a, b)) = edges.iter().find(|&&(a, b)| a >= nodes || b >= nodes) {
+            return Err(MazeError::InvalidData(format!("edge {}-{} names a node past the last one ({})", a, b, nodes.saturating_sub(1))));
+        }
+        Ok(Self { nodes, edges })
+    }
+
+    // The square grid `Env` mazes are carved over, with node `Env::ind(cell)` for every cell
+    pub fn grid(width: usize, height: usize) -> Self {
+        Self::wrapped_grid(width, height, Wrap::None)
+    }
+
+    // `grid` with the edges of `wrap` on top. A side wraps only when it is at least three cells
+    // long; shorter ones would join cells that are already neighbours, or a cell to itself.
+    pub fn wrapped_grid(width: usize, height: usize, wrap: Wrap) -> Self {
+        let mut edges = Vec::with_capacity(2 * width * height);
+        for row in 0..height {
+            for col in 0..width {
+                let node = row * width + col;
+                if col + 1 < width {
+                    edges.push((node, node + 1));
+                } else if width >= 3 {
+                    match wrap {
+                        Wrap::Horizontal | Wrap::Both => edges.push((node - col, node)),
+                        Wrap::Mobius => edges.push(((height - 1 - row) * width, node)),
+                        Wrap::None => {},
+                    }
+                }
+                if row + 1 < height {
+                    edges.push((node, node + width));
+                } else if wrap == Wrap::Both && height >= 3 {
+                    edges.push((col, node));
+                }
+            }
+        }
+        Self { nodes: width * height, edges }
+    }
+
+    // Neighbours of every node, each with the index of the edge leading there
+    fn adjacency(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut adjacency = vec![vec![]; self.nodes];
+        for (i, &(a, b)) in self.edges.iter().enumerate() {
+            // Self-loops can never be part of a tree
+            if a != b {
+                adjacency[a].push((b, i));
+                adjacency[b].push((a, i));
+            }
+        }
+        adjacency
+    }
+
+    // Edges of a random spanning tree picked by `algorithm`; a seeded `rng` always picks the same
+    // one. On a disconnected graph every component gets its own tree.
+    pub fn spanning_tree(&self, algorithm: Algorithm, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        let chosen = match algorithm {
+            Algorithm::Dfs => self.dfs(rng),
+            Algorithm::Kruskal => self.kruskal(rng),
+            Algorithm::Prim => self.prim(rng),
+        };
+        chosen.into_iter().map(|i| self.edges[i]).collect()
+    }
+
+    // Component roots: a random first node, then every node in order so no component is missed
+    fn roots(&self, rng: &mut impl Rng) -> Vec<usize> {
+        if self.nodes == 0 {
+            return vec![];
+        }
+        let mut roots = vec![rng.gen_range(0..self.nodes)];
+        roots.extend(0..self.nodes);
+        roots
+    }
+
+    fn dfs(&self, rng: &mut impl Rng) -> Vec<usize> {
+        let adjacency = self.adjacency();
+        let (mut visited, mut chosen) = (vec![false; self.nodes], vec![]);
+        for root in self.roots(rng) {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![root];
+            while let Some(&current) = stack.last() {
+                let unvisited: Vec<&(usize, usize)> = adjacency[current].iter().filter(|(n, _)| !visited[*n]).collect();
+                let Some(&&(next, edge)) = unvisited.choose(rng) else {
+                    stack.pop();
+                    continue;
+                };
+                visited[next] = true;
+                chosen.push(edge);
+                stack.push(next);
+            }
+        }
+        chosen
+    }
+
+    fn kruskal(&self, rng: &mut impl Rng) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.edges.len()).collect();
+        order.shuffle(rng);
+        let mut sets = DisjointSets::new(self.nodes);
+        order.into_iter().filter(|&i| sets.union(self.edges[i].0, self.edges[i].1)).collect()
+    }
+
+    fn prim(&self, rng: &mut impl Rng) -> Vec<usize> {
+        let adjacency = self.adjacency();
+        let (mut visited, mut chosen) = (vec![false; self.nodes], vec![]);
+        for root in self.roots(rng) {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            // Edges out of the tree; ones whose far end joined the tree since are dropped when drawn
+            let mut frontier = adjacency[root].clone();
+            while !frontier.is_empty() {
+                let (next, edge) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+                if visited[next] {
+                    continue;
+                }
+                visited[next] = true;
+                chosen.push(edge);
+                frontier.extend(adjacency[next].iter().filter(|(n, _)| !visited[*n]));
+            }
+        }
+        chosen
+    }
+}
+
+// Union-find over `0..len`, with path halving and union by size
+pub struct DisjointSets {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSets {
+    pub fn new(len: usize) -> Self {
+        Self { parent: (0..len).collect(), size: vec![1; len] }
+    }
+
+    // The representative of the set `x` is in
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    // Merges the sets of `a` and `b`; `false` if they already were one
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            core::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+}
+
+// Nodes from `start` to `goal` along the edges of `tree`, a spanning tree of `nodes` nodes such as
+// `Graph::spanning_tree` picks
+pub fn tree_path(nodes: usize, tree: &[(usize, usize)], start: usize, goal: usize) -> Vec<usize> {
+    let mut adjacency = vec![vec![]; nodes];
+    for &(a, b) in tree {
+        adjacency[a].push(b);
+        adjacency[b].push(a);
+    }
+    let mut came_from = vec![None; nodes];
+    came_from[start] = Some(start);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for &next in adjacency[node].iter() {
+            if came_from[next].is_none() {
+                came_from[next] = Some(node);
+                queue.push_back(next);
+            }
+        }
+    }
+    let mut path = vec![goal];
+    while let Some(&last) = path.last().filter(|&&n| n != start) {
+        path.push(came_from[last].expect("a spanning tree reaches every node"));
+    }
+    path.reverse();
+    path
+}
+
+// Carves `edges` of a `Graph::grid` of the same size into `env`. Fails on an edge between cells
+// that are not neighbours.
+pub fn carve_grid(env: &mut Env, edges: &[(usize, usize)]) -> Result<(), MazeError> {
+    for &(a, b) in edges {
+        if a >= env.cell_count() || b >= env.cell_count() {
+            return Err(MazeError::InvalidData(format!("edge {}-{} is outside the {}x{} grid", a, b, env.width, env.height)));
+        }
+        let (a, b) = (env.cell(a), env.cell(b));
+        if a.row.abs_diff(b.row) + a.col.abs_diff(b.col) != 1 {
+            return Err(MazeError::InvalidData(format!("cells ({}, {}) and ({}, {}) are not neighbours", a.row, a.col, b.row, b.col)));
+        }
+        env.carve(a, b);
+    }
+    Ok(())
+}
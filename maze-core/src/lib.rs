@@ -0,0 +1,648 @@
+// The maze engine: the `Env` grid model, the generators and the solvers. It only needs `alloc`, so
+// it runs on microcontrollers driving LED matrices or e-ink displays; the `std` feature adds what
+// needs an operating system (`io::Error` in `MazeError`, the thread RNG and the terminal progress
+// bar). File formats, renderers and the rest are in the `maze` crate, which re-exports all of this.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod bitset;
+pub mod cave;
+pub mod celldata;
+pub mod crop;
+pub mod deadends;
+pub mod error;
+pub mod graph;
+pub mod observer;
+pub mod progress;
+pub mod sized;
+pub mod solve;
+pub mod sparse;
+pub mod stitch;
+pub mod transform;
+pub mod unicursal;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use bitset::BitSet;
+use error::MazeError;
+use progress::Progress;
+
+#[derive(Default)]
+pub struct Stack<T: Default + Copy + Clone> {
+    items: Vec<T>,
+}
+
+impl<T: Default + Copy + Clone> Stack<T> {
+    pub fn push(&mut self, val: T) {
+        self.items.push(val);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Items from the bottom of the stack to the top
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T: Default + Copy + Clone> From<Vec<T>> for Stack<T> {
+    // A stack with the last item on top
+    fn from(items: Vec<T>) -> Self {
+        Self { items }
+    }
+}
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Cell {
+    pub row: usize,
+    pub col: usize,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum NeighborDir {
+    Center,
+    North,
+    South,
+    West,
+    East,
+}
+
+#[derive(Default, Copy, Clone, PartialEq)]
+pub enum WallKind {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+// An open passage between two neighbouring cells
+#[derive(Default, Copy, Clone)]
+pub struct Wall {
+    // The cell above/left of the opening
+    pub start: Cell,
+    // The cell below/right of the opening
+    pub target: Cell,
+    pub kind: WallKind,
+}
+
+// Default maze width and height, in cells
+pub const MAZE_SIZE: usize = 10;
+
+// Bits of `Env::open_sides`
+pub const OPEN_NORTH: u8 = 1;
+pub const OPEN_EAST: u8 = 2;
+pub const OPEN_SOUTH: u8 = 4;
+pub const OPEN_WEST: u8 = 8;
+
+// Passage bits per cell: the opening to the east neighbour and the one to the south neighbour.
+// Openings to the north/west are stored on the neighbouring cell.
+const PASSAGE_BITS: usize = 2;
+const EAST: usize = 0;
+const SOUTH: usize = 1;
+
+// Most cells a maze may have; `visit_order` stores cell indices as `u32`
+pub const MAX_CELLS: usize = u32::MAX as usize;
+
+#[derive(Default)]
+pub struct Env {
+    pub width: usize,
+    pub height: usize,
+    // `PASSAGE_BITS` per cell, indexed by `Env::ind`
+    passages: BitSet,
+    visited: BitSet,
+    // Cells left out of the maze, rendered solid; empty until `exclude` is first called
+    excluded: BitSet,
+    // Indices of cells in the order the generator first visited them
+    pub visit_order: Vec<u32>,
+}
+
+impl Env {
+    // A `MAZE_SIZE` x `MAZE_SIZE` grid with every wall standing
+    pub fn init() -> Self {
+        Self::new(MAZE_SIZE, MAZE_SIZE)
+    }
+
+    // Panics on a zero or oversized dimension; `try_new` reports it instead
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::try_new(width, height).expect("maze dimensions must be non-zero and at most `MAX_CELLS` cells")
+    }
+
+    // Fails with `InvalidSize` on a zero dimension, more than `MAX_CELLS` cells, or a grid that
+    // cannot be allocated
+    pub fn try_new(width: usize, height: usize) -> Result<Self, MazeError> {
+        let invalid = MazeError::InvalidSize { width, height };
+        let cells = match width.checked_mul(height) {
+            Some(cells) if cells > 0 && cells <= MAX_CELLS => cells,
+            _ => return Err(invalid),
+        };
+        let (Some(passages), Some(visited)) = (BitSet::try_new(cells * PASSAGE_BITS), BitSet::try_new(cells)) else {
+            return Err(invalid);
+        };
+        Ok(Self { width, height, passages, visited, excluded: BitSet::default(), visit_order: vec![] })
+    }
+
+    pub fn ind(&self, cell: Cell) -> usize {
+        cell.row * self.width + cell.col
+    }
+
+    pub fn cell(&self, ind: usize) -> Cell {
+        Cell { row: ind / self.width, col: ind % self.width }
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    // Every cell in row-major order
+    pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.cell_count()).map(|i| self.cell(i))
+    }
+
+    // Whether `cell` is part of the maze at all; see `exclude`
+    pub fn is_excluded(&self, cell: Cell) -> bool {
+        !self.excluded.is_empty() && self.excluded.get(self.ind(cell))
+    }
+
+    // Leave `cell` out of the maze: generators carve around it, it has no neighbours and renderers
+    // draw it solid. Call before generating.
+    pub fn exclude(&mut self, cell: Cell) {
+        if self.excluded.is_empty() {
+            self.excluded = BitSet::new(self.cell_count());
+        }
+        let ind = self.ind(cell);
+        self.excluded.set(ind, true);
+    }
+
+    // Cells that are part of the maze
+    pub fn included_count(&self) -> usize {
+        self.cell_count() - self.excluded.count_ones()
+    }
+
+    pub fn is_visited(&self, cell: Cell) -> bool {
+        self.visited.get(self.ind(cell))
+    }
+
+    pub fn mark_visited(&mut self, cell: Cell) {
+        let ind = self.ind(cell);
+        self.visited.set(ind, true);
+    }
+
+    // Bit holding the wall between two orthogonally adjacent cells
+    fn passage_bit(&self, a: Cell, b: Cell) -> usize {
+        let (first, second) = if self.ind(a) < self.ind(b) { (a, b) } else { (b, a) };
+        assert!(
+            first.row.abs_diff(second.row) + first.col.abs_diff(second.col) == 1,
+            "cells are not neighbours"
+        );
+        let dir = if first.row == second.row { EAST } else { SOUTH };
+        (self.ind(first) * PASSAGE_BITS) + dir
+    }
+
+    pub fn has_passage(&self, a: Cell, b: Cell) -> bool {
+        self.passages.get(self.passage_bit(a, b))
+    }
+
+    // Remove the wall between two neighbouring cells
+    pub fn carve(&mut self, a: Cell, b: Cell) {
+        self.set_passage(a, b, true);
+    }
+
+    // Open (`true`) or wall off (`false`) the slot between two neighbouring cells. Walling off a
+    // passage can split the maze; check `is_connected` afterwards where that matters.
+    pub fn set_passage(&mut self, a: Cell, b: Cell, open: bool) {
+        let bit = self.passage_bit(a, b);
+        self.passages.set(bit, open);
+    }
+
+    pub fn open_east(&self, cell: Cell) -> bool {
+        cell.col + 1 < self.width && self.passages.get(self.ind(cell) * PASSAGE_BITS + EAST)
+    }
+
+    pub fn open_south(&self, cell: Cell) -> bool {
+        cell.row + 1 < self.height && self.passages.get(self.ind(cell) * PASSAGE_BITS + SOUTH)
+    }
+
+    // Whether `cell` has a passage out of the grid through the east and the south border. Only
+    // damaged mazes have them; `open_east` and `open_south` ignore them.
+    pub fn border_openings(&self, cell: Cell) -> (bool, bool) {
+        let bit = self.ind(cell) * PASSAGE_BITS;
+        (cell.col + 1 == self.width && self.passages.get(bit + EAST), cell.row + 1 == self.height && self.passages.get(bit + SOUTH))
+    }
+
+    // Closes both of `cell`'s passages out of the grid, if it has any
+    pub fn close_border(&mut self, cell: Cell) {
+        let (east, south) = self.border_openings(cell);
+        let bit = self.ind(cell) * PASSAGE_BITS;
+        if east {
+            self.passages.set(bit + EAST, false);
+        }
+        if south {
+            self.passages.set(bit + SOUTH, false);
+        }
+    }
+
+    // Every open passage, in row-major order of the cell above/left of it
+    pub fn walls(&self) -> impl Iterator<Item = Wall> + '_ {
+        self.cells().flat_map(move |start| {
+            let east = self.open_east(start).then(|| Wall {
+                start,
+                target: Cell { row: start.row, col: start.col + 1 },
+                kind: WallKind::Vertical,
+            });
+            let south = self.open_south(start).then(|| Wall {
+                start,
+                target: Cell { row: start.row + 1, col: start.col },
+                kind: WallKind::Horizontal,
+            });
+            east.into_iter().chain(south)
+        })
+    }
+
+    pub fn passage_count(&self) -> usize {
+        self.passages.count_ones()
+    }
+
+    // Open neighbours of `cell`
+    pub fn neighbors(&self, cell: Cell) -> impl Iterator<Item = Cell> {
+        let mut out = [None; 4];
+        if cell.row > 0 && self.open_south(Cell { row: cell.row - 1, col: cell.col }) {
+            out[0] = Some(Cell { row: cell.row - 1, col: cell.col });
+        }
+        if self.open_south(cell) {
+            out[1] = Some(Cell { row: cell.row + 1, col: cell.col });
+        }
+        if cell.col > 0 && self.open_east(Cell { row: cell.row, col: cell.col - 1 }) {
+            out[2] = Some(Cell { row: cell.row, col: cell.col - 1 });
+        }
+        if self.open_east(cell) {
+            out[3] = Some(Cell { row: cell.row, col: cell.col + 1 });
+        }
+        out.into_iter().flatten()
+    }
+
+    // `OPEN_*` bits of the sides of `cell` that have a passage
+    pub fn open_sides(&self, cell: Cell) -> u8 {
+        self.neighbors(cell).fold(0, |sides, n| {
+            sides | match (n.row.cmp(&cell.row), n.col.cmp(&cell.col)) {
+                (Ordering::Less, _) => OPEN_NORTH,
+                (Ordering::Greater, _) => OPEN_SOUTH,
+                (_, Ordering::Greater) => OPEN_EAST,
+                _ => OPEN_WEST,
+            }
+        })
+    }
+
+    // Neighbouring cells inside the grid and not excluded, whether or not a wall is in the way
+    pub fn adjacent(&self, cell: Cell) -> impl Iterator<Item = Cell> + '_ {
+        let (width, height) = (self.width, self.height);
+        let up = (cell.row > 0).then(|| Cell { row: cell.row - 1, col: cell.col });
+        let down = (cell.row + 1 < height).then(|| Cell { row: cell.row + 1, col: cell.col });
+        let left = (cell.col > 0).then(|| Cell { row: cell.row, col: cell.col - 1 });
+        let right = (cell.col + 1 < width).then(|| Cell { row: cell.row, col: cell.col + 1 });
+        [up, down, left, right].into_iter().flatten().filter(|&n| !self.is_excluded(n))
+    }
+
+    // Cells with exactly one open side
+    pub fn dead_ends(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.cells().filter(|&cell| self.open_sides(cell).count_ones() == 1)
+    }
+
+    // Number of cells reachable from `from` through open passages, `from` included
+    pub fn reachable_count(&self, from: Cell) -> usize {
+        let mut seen = BitSet::new(self.cell_count());
+        let mut stack = vec![from];
+        seen.set(self.ind(from), true);
+        let mut count = 0;
+        while let Some(cell) = stack.pop() {
+            count += 1;
+            for next in self.neighbors(cell) {
+                if !seen.get(self.ind(next)) {
+                    seen.set(self.ind(next), true);
+                    stack.push(next);
+                }
+            }
+        }
+        count
+    }
+
+    // Whether every cell can be reached from every other one, excluded cells aside
+    pub fn is_connected(&self) -> bool {
+        let Some(first) = self.cells().find(|&cell| !self.is_excluded(cell)) else { return true };
+        self.reachable_count(first) == self.included_count()
+    }
+
+    // Connected without loops, i.e. exactly one path between any two cells (what `gen_maze` makes)
+    pub fn is_perfect(&self) -> bool {
+        self.passage_count() + 1 == self.included_count() && self.is_connected()
+    }
+
+    // Adjacency list of open passages, indexed by `Env::ind`
+    pub fn passages(&self) -> Vec<Vec<usize>> {
+        (0..self.cell_count())
+            .map(|i| self.neighbors(self.cell(i)).map(|n| self.ind(n)).collect())
+            .collect()
+    }
+
+    // Size of the block map: every cell and every wall slot (including corner posts) is a block
+    pub fn block_dims(&self) -> (usize, usize) {
+        ((2 * self.height) + 1, (2 * self.width) + 1)
+    }
+
+    // Whether block (`i`, `j`) of the block map is solid; odd/odd blocks are cells, even/even
+    // blocks are corner posts and the rest are wall slots
+    pub fn is_solid_block(&self, i: usize, j: usize) -> bool {
+        match (i % 2, j % 2) {
+            (1, 1) => self.is_excluded(Cell { row: i / 2, col: j / 2 }),
+            // A post stands unless all four wall slots around it are open, as inside a room
+            (0, 0) => i == 0 || j == 0 || i == 2 * self.height || j == 2 * self.width
+                || [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)].iter().any(|&(a, b)| self.is_solid_block(a, b)),
+            // Horizontal wall slot below cell ((i / 2) - 1, j / 2)
+            (0, _) => i == 0 || i == 2 * self.height || !self.open_south(Cell { row: (i / 2) - 1, col: j / 2 }),
+            // Vertical wall slot to the right of cell (i / 2, (j / 2) - 1)
+            _ => j == 0 || j == 2 * self.width || !self.open_east(Cell { row: i / 2, col: (j / 2) - 1 }),
+        }
+    }
+
+    // Expand the walls into a block map where `true` means a solid block
+    pub fn blocks(&self) -> Vec<Vec<bool>> {
+        let (rows, cols) = self.block_dims();
+        (0..rows).map(|i| (0..cols).map(|j| self.is_solid_block(i, j)).collect()).collect()
+    }
+
+    // Heap bytes used by the maze representation
+    pub fn byte_size(&self) -> usize {
+        self.passages.byte_size() + self.visited.byte_size() + self.excluded.byte_size() + self.visit_order.capacity() * 4
+    }
+
+    // `byte_size` of a fully generated `width`x`height` maze, without allocating it
+    pub fn footprint(width: usize, height: usize) -> usize {
+        let cells = width * height;
+        ((cells * PASSAGE_BITS).div_ceil(64) * 8) + (cells.div_ceil(64) * 8) + (cells * 4)
+    }
+}
+
+fn in_bound(val: i32, low: i32, high: i32) -> bool {
+    (val >= low) && (val < high)
+}
+
+fn unvisited_neighbors(env: &Env, rng: &mut impl Rng, row: usize, col: usize) -> NeighborDir {
+    let mut directions = [
+        NeighborDir::North,
+        NeighborDir::South,
+        NeighborDir::East,
+        NeighborDir::West,
+    ];
+    // Shuffle the order in which neighboring cells are 'checked'
+    directions.shuffle(rng);
+    let mut new_row;
+    let mut new_col;
+
+    for el in directions.iter() {
+        // Reset values for the next direction
+        new_row = row as i32;
+        new_col = col as i32;
+
+        match el {
+            NeighborDir::North => new_row -= 1,
+            NeighborDir::South => new_row += 1,
+            NeighborDir::West => new_col -= 1,
+            NeighborDir::East => new_col += 1,
+            NeighborDir::Center => unreachable!(),
+        };
+
+        if in_bound(new_row, 0, env.height as i32) &&
+            in_bound(new_col, 0, env.width as i32) &&
+            !env.is_visited(Cell { row: new_row as usize, col: new_col as usize }) &&
+            !env.is_excluded(Cell { row: new_row as usize, col: new_col as usize }) {
+            return *el;
+        }
+    }
+    NeighborDir::Center
+}
+
+// `gen_maze_with` on the thread-local RNG, for callers that do not need to reproduce the maze.
+// Every generator takes its randomness from a `&mut impl Rng` the caller passes in, so embedders
+// can supply seeded, hardware or recorded streams instead; this is the only one that picks its own.
+#[cfg(feature = "std")]
+pub fn gen_maze(env: &mut Env) {
+    gen_maze_with(env, &mut rand::thread_rng());
+}
+
+// Randomized depth-first search driven by `rng`; a seeded `rng` always carves the same maze
+pub fn gen_maze_with(env: &mut Env, rng: &mut impl Rng) {
+    gen_maze_progress(env, rng, &mut ()).expect("`()` never cancels");
+}
+
+// `x.round() as usize` for `x` of zero or more, which `no_std` builds have no `f32::round` for
+pub fn round(x: f32) -> usize {
+    (x + 0.5) as usize
+}
+
+// Cells visited (or explored, for solvers) between two progress updates and cancellation checks
+pub const PROGRESS_STEP: usize = 1024;
+
+// Steps of the depth-first generator, in the order it takes them
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GenEvent {
+    // The randomly picked first cell
+    Start(Cell),
+    // Opened the wall from the current cell into an unvisited neighbour, which becomes current
+    Carve(Cell, Cell),
+    // The cell has no unvisited neighbours left; the generator steps back to the one before it
+    Backtrack(Cell),
+}
+
+// `gen_maze_with` that reports the number of visited cells to `progress` as it carves. On
+// cancellation (`MazeError::Cancelled`) `env` keeps the passages carved so far.
+pub fn gen_maze_progress(env: &mut Env, rng: &mut impl Rng, progress: &mut dyn Progress) -> Result<(), MazeError> {
+    gen_maze_events(env, rng, progress, &mut |_| {})
+}
+
+// Randomized depth-first search one step at a time: every `next()` takes one generator step and
+// returns it, so callers can drive generation at their own pace and look at the maze in between
+// through `env()`. Consumes `rng` exactly like `gen_maze_with`, so the same seed carves the same
+// maze either way.
+pub struct GeneratorSteps<'a, R: Rng> {
+    env: &'a mut Env,
+    rng: &'a mut R,
+    stack: Stack<Cell>,
+    started: bool,
+}
+
+impl<'a, R: Rng> GeneratorSteps<'a, R> {
+    // Steps through the generation of `env`, which should have every wall standing
+    pub fn new(env: &'a mut Env, rng: &'a mut R) -> Self {
+        Self { env, rng, stack: Stack::default(), started: false }
+    }
+
+    // Carries on from a generation that was stopped partway: `env` with the passages, visited cells
+    // and `visit_order` it had then, and the cells that were on `stack()`, bottom first
+    pub fn resume(env: &'a mut Env, rng: &'a mut R, stack: Vec<Cell>) -> Self {
+        Self { env, rng, stack: Stack::from(stack), started: true }
+    }
+
+    // The maze as carved so far
+    pub fn env(&self) -> &Env {
+        self.env
+    }
+
+    // The path back from the current cell, bottom first
+    pub fn stack(&self) -> &[Cell] {
+        self.stack.as_slice()
+    }
+
+    // The RNG the remaining steps draw from, e.g. to reseed it
+    pub fn rng(&mut self) -> &mut R {
+        self.rng
+    }
+}
+
+impl<R: Rng> Iterator for GeneratorSteps<'_, R> {
+    type Item = GenEvent;
+
+    fn next(&mut self) -> Option<GenEvent> {
+        let env = &mut *self.env;
+        if !self.started {
+            self.started = true;
+            // Initial random row and col, drawn again while it lands on an excluded cell
+            let current = loop {
+                let row = self.rng.gen_range(0..env.height);
+                let col = self.rng.gen_range(0..env.width);
+                let cell = Cell { row, col };
+                if !env.is_excluded(cell) {
+                    break cell;
+                }
+            };
+            // Mark current cell as visited
+            env.mark_visited(current);
+            env.visit_order.push(env.ind(current) as u32);
+            // Push random initial cell to the stack
+            self.stack.push(current);
+            return Some(GenEvent::Start(current));
+        }
+
+        // Pop cell from the stack; an empty stack means every cell has been carved
+        let current = self.stack.pop()?;
+        // Get the direction of a random unvisited neighbor
+        let unvisited = unvisited_neighbors(env, self.rng, current.row, current.col);
+        // If unvisited neighbor is center that means all of the current cell's neighbors are visited
+        if unvisited == NeighborDir::Center {
+            return Some(GenEvent::Backtrack(current));
+        }
+        // Push current cell to the stack
+        self.stack.push(current);
+
+        let mut target_row = current.row;
+        let mut target_col = current.col;
+        match unvisited {
+            NeighborDir::North => target_row -= 1,
+            NeighborDir::South => target_row += 1,
+            NeighborDir::West => target_col -= 1,
+            NeighborDir::East => target_col += 1,
+            NeighborDir::Center => unreachable!(),
+        }
+        let target = Cell { row: target_row, col: target_col };
+        // Remove wall between current and target cell
+        env.carve(current, target);
+        // Mark target cell as visited
+        env.mark_visited(target);
+        env.visit_order.push(env.ind(target) as u32);
+        self.stack.push(target);
+        Some(GenEvent::Carve(current, target))
+    }
+}
+
+// `gen_maze_progress` that also hands every step to `on_event` as it happens
+pub fn gen_maze_events(env: &mut Env, rng: &mut impl Rng, progress: &mut dyn Progress, on_event: &mut dyn FnMut(GenEvent)) -> Result<(), MazeError> {
+    let mut steps = GeneratorSteps::new(env, rng);
+    while let Some(event) = steps.next() {
+        on_event(event);
+        let visited = steps.env().visit_order.len();
+        if matches!(event, GenEvent::Carve(..)) && visited.is_multiple_of(PROGRESS_STEP) {
+            progress.checkpoint(visited, steps.env().cell_count())?;
+        }
+    }
+    progress.update(env.cell_count(), env.cell_count());
+    Ok(())
+}
+
+// Remove about `fraction` of the dead ends by opening one more wall in each, preferring walls into
+// another dead end so one carve removes two. The loops this adds mean the maze is no longer
+// perfect, and wall followers can circle forever.
+pub fn braid(env: &mut Env, rng: &mut impl Rng, fraction: f32) {
+    let mut dead_ends: Vec<Cell> = env.dead_ends().collect();
+    dead_ends.shuffle(rng);
+    let target = round(dead_ends.len() as f32 * fraction.clamp(0.0, 1.0));
+    let mut removed = 0;
+    for cell in dead_ends {
+        if removed >= target {
+            break;
+        }
+        // An earlier carve may already have opened this one up
+        if env.open_sides(cell).count_ones() != 1 {
+            continue;
+        }
+        let mut walled: Vec<Cell> = env.adjacent(cell).filter(|&n| !env.has_passage(cell, n)).collect();
+        walled.shuffle(rng);
+        let Some(&next) = walled.iter().find(|&&n| env.open_sides(n).count_ones() == 1).or(walled.first()) else { continue };
+        removed += if env.open_sides(next).count_ones() == 1 { 2 } else { 1 };
+        env.carve(cell, next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn generated(seed: u64) -> Env {
+        let mut env = Env::new(12, 9);
+        gen_maze_with(&mut env, &mut StdRng::seed_from_u64(seed));
+        env
+    }
+
+    #[test]
+    fn generated_mazes_are_perfect() {
+        for seed in 0..8 {
+            assert!(generated(seed).is_perfect(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn removed_passage_disconnects() {
+        let mut env = generated(1);
+        let wall = env.walls().next().expect("a carved maze has passages");
+        env.set_passage(wall.start, wall.target, false);
+        assert!(!env.is_connected());
+        assert!(!env.is_perfect());
+    }
+
+    #[test]
+    fn extra_passage_makes_a_loop() {
+        let mut env = generated(2);
+        let (a, b) = env.cells().find_map(|a| {
+            let b = Cell { row: a.row, col: a.col + 1 };
+            (b.col < env.width && !env.has_passage(a, b)).then_some((a, b))
+        }).expect("a perfect maze has walls between neighbours");
+        env.carve(a, b);
+        assert!(env.is_connected());
+        assert!(!env.is_perfect());
+    }
+}
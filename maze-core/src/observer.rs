@@ -0,0 +1,66 @@
+use crate::error::MazeError;
+use crate::progress::Progress;
+use crate::{gen_maze_events, Cell, Env, GenEvent};
+
+use rand::Rng;
+
+// Callbacks generators and solvers invoke as they work, for statistics or custom visualizations.
+// Every method does nothing by default, so observers only implement what they need.
+pub trait Observer {
+    // The generator reached `cell` for the first time; the start cell comes first
+    fn on_visit(&mut self, _cell: Cell) {}
+    // The generator opened the wall between `from` and the unvisited `to`
+    fn on_carve(&mut self, _from: Cell, _to: Cell) {}
+    // The generator left `cell` for good, all of its neighbours being visited
+    fn on_backtrack(&mut self, _cell: Cell) {}
+    // A solver expanded (or, for the wall follower and Tremaux, walked into) `cell`
+    fn on_expand(&mut self, _cell: Cell) {}
+    // Tremaux's solver marked the passage between `from` and `to`, which now has `marks` marks
+    fn on_mark(&mut self, _from: Cell, _to: Cell, _marks: u8) {}
+}
+
+impl Observer for () {}
+
+// Tallies of every callback
+#[derive(Clone, Debug, Default)]
+pub struct Counts {
+    pub visits: usize,
+    pub carves: usize,
+    pub backtracks: usize,
+    pub expansions: usize,
+    pub marks: usize,
+}
+
+impl Observer for Counts {
+    fn on_visit(&mut self, _cell: Cell) {
+        self.visits += 1;
+    }
+
+    fn on_carve(&mut self, _from: Cell, _to: Cell) {
+        self.carves += 1;
+    }
+
+    fn on_backtrack(&mut self, _cell: Cell) {
+        self.backtracks += 1;
+    }
+
+    fn on_expand(&mut self, _cell: Cell) {
+        self.expansions += 1;
+    }
+
+    fn on_mark(&mut self, _from: Cell, _to: Cell, _marks: u8) {
+        self.marks += 1;
+    }
+}
+
+// `gen_maze_progress` that reports every step to `observer`
+pub fn gen_maze_observed(env: &mut Env, rng: &mut impl Rng, progress: &mut dyn Progress, observer: &mut dyn Observer) -> Result<(), MazeError> {
+    gen_maze_events(env, rng, progress, &mut |event| match event {
+        GenEvent::Start(cell) => observer.on_visit(cell),
+        GenEvent::Carve(from, to) => {
+            observer.on_carve(from, to);
+            observer.on_visit(to);
+        },
+        GenEvent::Backtrack(cell) => observer.on_backtrack(cell),
+    })
+}
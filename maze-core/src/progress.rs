@@ -0,0 +1,149 @@
+use alloc::sync::Arc;
+use core::error::Error;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+// Receives `done` out of `total` units of work from long-running generators, solvers and
+// renderers, and tells them whether to stop early. `()` ignores every update and any
+// `FnMut(usize, usize)` closure can be used directly; neither ever cancels.
+pub trait Progress {
+    fn update(&mut self, done: usize, total: usize);
+
+    // Polled alongside `update`; once it returns true the job stops with `Cancelled`
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+
+    // Report progress, then bail out if the job was cancelled
+    fn checkpoint(&mut self, done: usize, total: usize) -> Result<(), Cancelled> {
+        self.update(done, total);
+        if self.is_cancelled() { Err(Cancelled) } else { Ok(()) }
+    }
+}
+
+impl Progress for () {
+    fn update(&mut self, _done: usize, _total: usize) {}
+}
+
+impl<F: FnMut(usize, usize)> Progress for F {
+    fn update(&mut self, done: usize, total: usize) {
+        self(done, total)
+    }
+}
+
+// Returned by a job that stopped because its `Progress` was cancelled. Whatever the job had
+// produced so far (e.g. a partially carved maze) is left in place but is incomplete.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "operation cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+#[cfg(feature = "std")]
+impl From<Cancelled> for io::Error {
+    fn from(err: Cancelled) -> Self {
+        io::Error::new(io::ErrorKind::Interrupted, err)
+    }
+}
+
+// Shared flag another thread can raise to cancel a running job; clones share the same flag
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+// A bare token ignores progress updates and only cancels
+impl Progress for CancelToken {
+    fn update(&mut self, _done: usize, _total: usize) {}
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Forwards updates to `progress` while letting `token` cancel the job
+pub struct Cancellable<P: Progress> {
+    pub progress: P,
+    pub token: CancelToken,
+}
+
+impl<P: Progress> Progress for Cancellable<P> {
+    fn update(&mut self, done: usize, total: usize) {
+        self.progress.update(done, total);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled() || self.progress.is_cancelled()
+    }
+}
+
+// Width of the bar itself, in characters
+#[cfg(feature = "std")]
+const BAR_WIDTH: usize = 30;
+// Minimum time between two redraws
+#[cfg(feature = "std")]
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+// Single-line terminal progress bar with an ETA, drawn on stderr
+#[cfg(feature = "std")]
+pub struct ProgressBar {
+    label: String,
+    start: Instant,
+    last_draw: Option<Instant>,
+    finished: bool,
+}
+
+#[cfg(feature = "std")]
+impl ProgressBar {
+    pub fn new(label: &str) -> Self {
+        Self { label: label.to_string(), start: Instant::now(), last_draw: None, finished: false }
+    }
+
+    fn draw(&self, done: usize, total: usize) {
+        let fraction = if total == 0 { 1.0 } else { (done as f64 / total as f64).min(1.0) };
+        let filled = (fraction * BAR_WIDTH as f64) as usize;
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let eta = if done == 0 {
+            String::from("--")
+        } else {
+            format!("{:.1}s", elapsed * (1.0 - fraction) / fraction)
+        };
+        let mut err = io::stderr().lock();
+        let _ = write!(
+            err, "\r{} [{}{}] {:3.0}% ETA {}   ",
+            self.label, "#".repeat(filled), ".".repeat(BAR_WIDTH - filled), fraction * 100.0, eta
+        );
+        let _ = err.flush();
+    }
+}
+
+#[cfg(feature = "std")]
+impl Progress for ProgressBar {
+    fn update(&mut self, done: usize, total: usize) {
+        if self.finished {
+            return;
+        }
+        let complete = done >= total;
+        if complete || self.last_draw.is_none_or(|t| t.elapsed() >= REDRAW_INTERVAL) {
+            self.draw(done, total);
+            self.last_draw = Some(Instant::now());
+        }
+        if complete {
+            self.finished = true;
+            eprintln!("done in {:.1}s", self.start.elapsed().as_secs_f64());
+        }
+    }
+}
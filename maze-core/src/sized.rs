@@ -0,0 +1,161 @@
+// Fixed-size mazes for callers that know the size at compile time, typically on embedded targets
+// without a heap: a `Maze<W, H>` is a plain `[[u8; W]; H]` array, carving never allocates, and
+// cells named with const parameters (`Maze::cell::<ROW, COL>()`) are bounds-checked when the
+// program is compiled. It coexists with `Env`, which handles sizes only known at runtime, and
+// converts to and from it for everything else (solving, rendering, file formats).
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::{Cell, Env};
+
+// Every cell is one byte: its passages east and south and, while carving, whether the generator
+// reached it and the direction back to the cell it came from (`NONE` for the start)
+const EAST: u8 = 1 << 0;
+const SOUTH: u8 = 1 << 1;
+const VISITED: u8 = 1 << 2;
+const PARENT_SHIFT: u8 = 4;
+const PARENT_MASK: u8 = 0b111 << PARENT_SHIFT;
+
+// Steps to the neighbours, in the order `gen_maze_with` shuffles them so both carve the same maze
+// from the same RNG; a parent direction is stored as its index plus one
+const STEPS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, 1), (0, -1)];
+const NONE: u8 = 0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Maze<const W: usize, const H: usize> {
+    cells: [[u8; W]; H],
+}
+
+impl<const W: usize, const H: usize> Default for Maze<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> Maze<W, H> {
+    pub const WIDTH: usize = W;
+    pub const HEIGHT: usize = H;
+
+    // Every wall standing; a zero-sized maze does not compile
+    pub const fn new() -> Self {
+        const { assert!(W > 0 && H > 0, "mazes need at least one cell in each direction") };
+        Self { cells: [[0; W]; H] }
+    }
+
+    // A cell checked against the size when compiling, e.g. `Maze::<8, 8>::cell::<7, 7>()`
+    pub const fn cell<const ROW: usize, const COL: usize>() -> Cell {
+        const { assert!(ROW < H && COL < W, "cell outside the maze") };
+        Cell { row: ROW, col: COL }
+    }
+
+    pub const fn contains(cell: Cell) -> bool {
+        cell.row < H && cell.col < W
+    }
+
+    // A perfect maze carved by `gen_with`
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        let mut maze = Self::new();
+        maze.gen_with(rng);
+        maze
+    }
+
+    pub fn open_east(&self, cell: Cell) -> bool {
+        Self::contains(cell) && cell.col + 1 < W && self.cells[cell.row][cell.col] & EAST != 0
+    }
+
+    pub fn open_south(&self, cell: Cell) -> bool {
+        Self::contains(cell) && cell.row + 1 < H && self.cells[cell.row][cell.col] & SOUTH != 0
+    }
+
+    // Whether `a` and `b` are neighbours with an open passage between them
+    pub fn has_passage(&self, a: Cell, b: Cell) -> bool {
+        let (first, second) = if (a.row, a.col) <= (b.row, b.col) { (a, b) } else { (b, a) };
+        match (second.row - first.row, second.col.wrapping_sub(first.col)) {
+            (0, 1) => self.open_east(first),
+            (1, 0) => self.open_south(first),
+            _ => false,
+        }
+    }
+
+    // Opens the passage between the neighbours `a` and `b`; panics if they are not neighbours
+    // inside the maze, like `Env::carve`
+    pub fn carve(&mut self, a: Cell, b: Cell) {
+        assert!(Self::contains(a) && Self::contains(b), "cell outside the maze");
+        let (first, second) = if (a.row, a.col) <= (b.row, b.col) { (a, b) } else { (b, a) };
+        match (second.row - first.row, second.col.wrapping_sub(first.col)) {
+            (0, 1) => self.cells[first.row][first.col] |= EAST,
+            (1, 0) => self.cells[first.row][first.col] |= SOUTH,
+            _ => panic!("cells ({}, {}) and ({}, {}) are not neighbours", a.row, a.col, b.row, b.col),
+        }
+    }
+
+    // Randomized depth-first search into a maze with every wall standing, backtracking through
+    // the parent direction of every cell instead of a stack. Takes the same draws from `rng` as
+    // `gen_maze_with`, so the same seed carves the same maze as an `Env` of the same size.
+    pub fn gen_with(&mut self, rng: &mut impl Rng) {
+        let row = rng.gen_range(0..H);
+        let col = rng.gen_range(0..W);
+        let mut current = Cell { row, col };
+        self.cells[row][col] |= VISITED;
+        loop {
+            let mut order = STEPS;
+            order.shuffle(rng);
+            let next = order.iter().find_map(|&step| self.step(current, step).filter(|n| self.cells[n.row][n.col] & VISITED == 0).map(|n| (n, step)));
+            match next {
+                Some((target, step)) => {
+                    self.carve(current, target);
+                    let back = STEPS.iter().position(|&s| s == (-step.0, -step.1)).expect("every step has an opposite") as u8 + 1;
+                    self.cells[target.row][target.col] |= VISITED | (back << PARENT_SHIFT);
+                    current = target;
+                },
+                None => {
+                    let back = (self.cells[current.row][current.col] & PARENT_MASK) >> PARENT_SHIFT;
+                    if back == NONE {
+                        break;
+                    }
+                    current = self.step(current, STEPS[(back - 1) as usize]).expect("parents are inside the maze");
+                },
+            }
+        }
+        // Only the passages are left, so equal mazes compare equal however they were made
+        for cell in self.cells.iter_mut().flatten() {
+            *cell &= EAST | SOUTH;
+        }
+    }
+
+    fn step(&self, cell: Cell, (dr, dc): (isize, isize)) -> Option<Cell> {
+        let row = cell.row.checked_add_signed(dr).filter(|&r| r < H)?;
+        let col = cell.col.checked_add_signed(dc).filter(|&c| c < W)?;
+        Some(Cell { row, col })
+    }
+
+    // The same passages in an `Env`, to solve, render or save
+    pub fn to_env(&self) -> Env {
+        let mut env = Env::new(W, H);
+        for row in 0..H {
+            for col in 0..W {
+                let cell = Cell { row, col };
+                if self.open_east(cell) {
+                    env.carve(cell, Cell { row, col: col + 1 });
+                }
+                if self.open_south(cell) {
+                    env.carve(cell, Cell { row: row + 1, col });
+                }
+            }
+        }
+        env
+    }
+
+    // The passages of `env`, if it is `W` x `H`; excluded cells become ordinary walled-in cells
+    pub fn from_env(env: &Env) -> Option<Self> {
+        if (env.width, env.height) != (W, H) {
+            return None;
+        }
+        let mut maze = Self::new();
+        for wall in env.walls() {
+            maze.carve(wall.start, wall.target);
+        }
+        Some(maze)
+    }
+}
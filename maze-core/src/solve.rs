@@ -0,0 +1,236 @@
+use alloc::collections::{BTreeMap, BinaryHeap, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use crate::error::MazeError;
+use crate::observer::Observer;
+use crate::progress::Progress;
+use crate::{Cell, Env, PROGRESS_STEP};
+
+pub struct Solution {
+    // Cells from start to goal, both included
+    pub path: Vec<Cell>,
+    // Cells in the order the solver expanded (or walked through) them, possibly with repeats
+    pub explored: Vec<Cell>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Solver {
+    Bfs,
+    AStar,
+    WallFollower,
+    Tremaux,
+}
+
+impl Solver {
+    pub const ALL: [Solver; 4] = [Solver::Bfs, Solver::AStar, Solver::WallFollower, Solver::Tremaux];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solver::Bfs => "bfs",
+            Solver::AStar => "a*",
+            Solver::WallFollower => "wall-follower",
+            Solver::Tremaux => "tremaux",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Solver::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    // Returns `None` if the goal is unreachable
+    pub fn solve(&self, env: &Env, start: Cell, goal: Cell) -> Option<Solution> {
+        self.solve_with_progress(env, start, goal, &mut ()).expect("`()` never cancels")
+    }
+
+    // Reports the number of explored cells to `progress`, which can cancel the search
+    pub fn solve_with_progress(&self, env: &Env, start: Cell, goal: Cell, progress: &mut dyn Progress) -> Result<Option<Solution>, MazeError> {
+        self.solve_observed(env, start, goal, progress, &mut ())
+    }
+
+    // `solve_with_progress` that also reports every explored cell to `observer`
+    pub fn solve_observed(&self, env: &Env, start: Cell, goal: Cell, progress: &mut dyn Progress, observer: &mut dyn Observer) -> Result<Option<Solution>, MazeError> {
+        match self {
+            Solver::Bfs => bfs(env, start, goal, progress, observer),
+            Solver::AStar => astar(env, start, goal, progress, observer),
+            Solver::WallFollower => wall_follower(env, start, goal, progress, observer),
+            Solver::Tremaux => tremaux(env, start, goal, progress, observer),
+        }
+    }
+}
+
+// Walk the `came_from` links back from `goal` to `start`
+fn reconstruct(env: &Env, came_from: &[Option<usize>], start: Cell, goal: Cell) -> Option<Vec<Cell>> {
+    came_from[env.ind(goal)]?;
+    let mut path = vec![goal];
+    let mut ind = env.ind(goal);
+    while ind != env.ind(start) {
+        ind = came_from[ind].unwrap();
+        path.push(env.cell(ind));
+    }
+    path.reverse();
+    Some(path)
+}
+
+// Adds `cell` to the explored trace, tells `observer` and reports every `PROGRESS_STEP` cells
+fn explore(env: &Env, cell: Cell, explored: &mut Vec<Cell>, progress: &mut dyn Progress, observer: &mut dyn Observer) -> Result<(), MazeError> {
+    explored.push(cell);
+    observer.on_expand(cell);
+    if explored.len().is_multiple_of(PROGRESS_STEP) {
+        progress.checkpoint(explored.len().min(env.cell_count()), env.cell_count())?;
+    }
+    Ok(())
+}
+
+// Breadth-first search from `start` to `goal`
+fn bfs(env: &Env, start: Cell, goal: Cell, progress: &mut dyn Progress, observer: &mut dyn Observer) -> Result<Option<Solution>, MazeError> {
+    let passages = env.passages();
+    let mut came_from: Vec<Option<usize>> = vec![None; passages.len()];
+    let mut explored = vec![];
+    let mut queue = VecDeque::from([env.ind(start)]);
+    came_from[env.ind(start)] = Some(env.ind(start));
+
+    while let Some(ind) = queue.pop_front() {
+        explore(env, env.cell(ind), &mut explored, progress, observer)?;
+        if ind == env.ind(goal) {
+            break;
+        }
+        for &next in passages[ind].iter() {
+            if came_from[next].is_none() {
+                came_from[next] = Some(ind);
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(reconstruct(env, &came_from, start, goal).map(|path| Solution { path, explored }))
+}
+
+fn manhattan(a: Cell, b: Cell) -> usize {
+    a.row.abs_diff(b.row) + a.col.abs_diff(b.col)
+}
+
+// A* search guided by the Manhattan distance to `goal`
+fn astar(env: &Env, start: Cell, goal: Cell, progress: &mut dyn Progress, observer: &mut dyn Observer) -> Result<Option<Solution>, MazeError> {
+    let passages = env.passages();
+    let mut came_from: Vec<Option<usize>> = vec![None; passages.len()];
+    let mut cost = vec![usize::MAX; passages.len()];
+    let mut explored = vec![];
+    // Ordered by estimated total cost, then by the heuristic alone to favour cells nearer the goal
+    let mut open = BinaryHeap::from([Reverse((manhattan(start, goal), 0, env.ind(start)))]);
+    came_from[env.ind(start)] = Some(env.ind(start));
+    cost[env.ind(start)] = 0;
+
+    while let Some(Reverse((_, _, ind))) = open.pop() {
+        explore(env, env.cell(ind), &mut explored, progress, observer)?;
+        if ind == env.ind(goal) {
+            break;
+        }
+        for &next in passages[ind].iter() {
+            let next_cost = cost[ind] + 1;
+            if next_cost < cost[next] {
+                cost[next] = next_cost;
+                came_from[next] = Some(ind);
+                let h = manhattan(env.cell(next), goal);
+                open.push(Reverse((next_cost + h, h, next)));
+            }
+        }
+    }
+    Ok(reconstruct(env, &came_from, start, goal).map(|path| Solution { path, explored }))
+}
+
+// Right-hand rule: keep a hand on the wall to the right until the goal is reached. The explored
+// trace is the full walk, dead ends included; the path is that walk with every loop erased.
+fn wall_follower(env: &Env, start: Cell, goal: Cell, progress: &mut dyn Progress, observer: &mut dyn Observer) -> Result<Option<Solution>, MazeError> {
+    // North, east, south, west; turning right is `+ 1`
+    const DIRS: [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+    let passages = env.passages();
+    let open_towards = |ind: usize, dir: usize| -> Option<usize> {
+        let cell = env.cell(ind);
+        let r = cell.row as i32 + DIRS[dir].0;
+        let c = cell.col as i32 + DIRS[dir].1;
+        if r < 0 || c < 0 || r >= env.height as i32 || c >= env.width as i32 {
+            return None;
+        }
+        let next = Cell { row: r as usize, col: c as usize };
+        env.has_passage(cell, next).then(|| env.ind(next))
+    };
+
+    let mut ind = env.ind(start);
+    let mut dir = 1;
+    let mut explored = vec![start];
+    observer.on_expand(start);
+    let mut path = vec![env.ind(start)];
+    // Every (cell, heading) pair can be seen at most once before the walk starts repeating
+    for _ in 0..(4 * passages.len()) {
+        if ind == env.ind(goal) {
+            let path = path.into_iter().map(|i| env.cell(i)).collect();
+            return Ok(Some(Solution { path, explored }));
+        }
+        // Try right, straight, left, then back
+        let turn = [1, 0, 3, 2].into_iter().find_map(|t| {
+            let d = (dir + t) % 4;
+            open_towards(ind, d).map(|next| (d, next))
+        });
+        let Some((d, next)) = turn else { return Ok(None) };
+        dir = d;
+        ind = next;
+        explore(env, env.cell(ind), &mut explored, progress, observer)?;
+        if let Some(pos) = path.iter().position(|&i| i == ind) {
+            path.truncate(pos + 1);
+        } else {
+            path.push(ind);
+        }
+    }
+    Ok(None)
+}
+
+// Adds a mark to the passage between `a` and `b` and tells `observer` how many it now has
+fn mark(env: &Env, marks: &mut BTreeMap<(usize, usize), u8>, a: usize, b: usize, observer: &mut dyn Observer) {
+    let count = marks.entry((a.min(b), a.max(b))).or_insert(0);
+    *count += 1;
+    observer.on_mark(env.cell(a), env.cell(b), *count);
+}
+
+// Tremaux's algorithm, which a person can follow with a piece of chalk: mark every passage each
+// time it is walked, always take an unmarked passage when there is one, turn straight back on
+// entering an already visited cell through a fresh passage, and otherwise retreat the way you
+// came. No passage is walked more than twice, and the once-marked passages form the path. The
+// explored trace is the full walk, retreats included.
+fn tremaux(env: &Env, start: Cell, goal: Cell, progress: &mut dyn Progress, observer: &mut dyn Observer) -> Result<Option<Solution>, MazeError> {
+    let passages = env.passages();
+    let mut marks = BTreeMap::new();
+    let mut visited = vec![false; passages.len()];
+    let mut ind = env.ind(start);
+    visited[ind] = true;
+    let mut explored = vec![start];
+    observer.on_expand(start);
+    let mut path = vec![ind];
+
+    while ind != env.ind(goal) {
+        let fresh = passages[ind].iter().copied().find(|&next| !marks.contains_key(&(ind.min(next), ind.max(next))));
+        match fresh {
+            Some(next) => {
+                mark(env, &mut marks, ind, next, observer);
+                explore(env, env.cell(next), &mut explored, progress, observer)?;
+                if core::mem::replace(&mut visited[next], true) {
+                    // A loop back into walked territory: return at once, sealing the passage off
+                    mark(env, &mut marks, next, ind, observer);
+                    explore(env, env.cell(ind), &mut explored, progress, observer)?;
+                } else {
+                    path.push(next);
+                    ind = next;
+                }
+            },
+            None => {
+                path.pop();
+                let Some(&back) = path.last() else { return Ok(None) };
+                mark(env, &mut marks, ind, back, observer);
+                ind = back;
+                explore(env, env.cell(ind), &mut explored, progress, observer)?;
+            },
+        }
+    }
+    let path = path.into_iter().map(|i| env.cell(i)).collect();
+    Ok(Some(Solution { path, explored }))
+}
@@ -0,0 +1,73 @@
+// Sparse mazes: a random share of the cells is left out (see `Env::exclude`) before generating, so
+// the maze winds through an irregular, airy region instead of filling the whole grid.
+//
+// Cells are only ever removed where that cannot split the region: a cell goes if the included
+// cells around it form a single run that touches it on some side, because every path through it
+// can then go around it instead. Removing such cells one at a time keeps the region connected all
+// the way down.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::error::MazeError;
+use crate::{round, Cell, Env};
+
+// The eight cells around a cell, clockwise from north, as row and column offsets. Every one is
+// next to the one before it, and the even ones are next to the cell itself.
+const RING: [(isize, isize); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+
+// Whether every included neighbour of `cell` can reach every other one around it, without going
+// through `cell`
+fn is_removable(env: &Env, cell: Cell) -> bool {
+    let included = RING.map(|(dr, dc)| {
+        let (row, col) = (cell.row.checked_add_signed(dr), cell.col.checked_add_signed(dc));
+        matches!((row, col), (Some(row), Some(col)) if row < env.height && col < env.width && !env.is_excluded(Cell { row, col }))
+    });
+    if included.iter().all(|&i| i) {
+        return true;
+    }
+    // Walk the ring once from an excluded cell, counting runs that touch `cell` on a side
+    let first = included.iter().position(|&i| !i).expect("some cell is excluded");
+    let (mut runs, mut touching) = (0, false);
+    for k in (1..=8).map(|k| (first + k) % 8) {
+        if included[k] {
+            touching |= k % 2 == 0;
+        } else {
+            runs += touching as usize;
+            touching = false;
+        }
+    }
+    runs == 1
+}
+
+// Leaves about `1 - density` of the cells of `env` out of the maze, never any of `keep`, so the
+// rest stays connected. Fewer go if the region gets too thin to lose any more. `env` should not be
+// carved yet.
+pub fn exclude_cells(env: &mut Env, rng: &mut impl Rng, density: f32, keep: &[Cell]) -> Result<(), MazeError> {
+    if !(density > 0.0 && density <= 1.0) {
+        return Err(MazeError::InvalidData(format!("density must be above 0 and at most 1, not {}", density)));
+    }
+    let target = round((1.0 - density) * env.cell_count() as f32);
+    let mut candidates: Vec<Cell> = env.cells().filter(|cell| !keep.contains(cell)).collect();
+    let mut excluded = 0;
+    // Cells that could not go yet may be able to once their neighbours have gone
+    while excluded < target {
+        candidates.shuffle(rng);
+        let before = excluded;
+        candidates.retain(|&cell| {
+            if excluded < target && is_removable(env, cell) {
+                env.exclude(cell);
+                excluded += 1;
+                return false;
+            }
+            true
+        });
+        if excluded == before {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// Joining mazes side by side, to compose a bigger maze from hand-made and generated sections.
+// The two mazes keep their passages, left-out cells stay left out, and the wall between them only
+// opens at the chosen connection points: stitching two perfect mazes at one point gives a perfect
+// maze.
+
+use alloc::format;
+
+use crate::error::MazeError;
+use crate::{Cell, Env};
+
+// The side of a maze another one is stitched onto
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Edge {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Edge::North, Edge::East, Edge::South, Edge::West];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edge::North => "north",
+            Edge::East => "east",
+            Edge::South => "south",
+            Edge::West => "west",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Edge::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    // Cells along this side of `env`: its width for north and south, its height for east and west
+    pub fn length(&self, env: &Env) -> usize {
+        match self {
+            Edge::North | Edge::South => env.width,
+            Edge::East | Edge::West => env.height,
+        }
+    }
+}
+
+impl Env {
+    // A new maze with `other` placed against `edge` of this one, the wall between them opened at
+    // `connections`: columns for north and south, rows for east and west, counted from the top
+    // left. The two mazes must have the same length along `edge`, there has to be at least one
+    // connection, and no connection may lead into a left-out cell.
+    pub fn stitch(&self, other: &Env, edge: Edge, connections: &[usize]) -> Result<Env, MazeError> {
+        let len = edge.length(self);
+        if edge.length(other) != len {
+            return Err(MazeError::InvalidData(format!(
+                "cannot stitch a {}x{} maze onto the {} edge of a {}x{} maze, the edge is {} cells long",
+                other.width,
+                other.height,
+                edge.name(),
+                self.width,
+                self.height,
+                len
+            )));
+        }
+        if connections.is_empty() {
+            return Err(MazeError::InvalidData(format!("stitching onto the {} edge needs at least one connection", edge.name())));
+        }
+        // Size of the result and where each maze's top-left cell lands in it
+        let (width, height, mine, theirs) = match edge {
+            Edge::North => (len, self.height + other.height, (other.height, 0), (0, 0)),
+            Edge::South => (len, self.height + other.height, (0, 0), (self.height, 0)),
+            Edge::West => (self.width + other.width, len, (0, other.width), (0, 0)),
+            Edge::East => (self.width + other.width, len, (0, 0), (0, self.width)),
+        };
+        let mut env = Env::try_new(width, height)?;
+        for (part, (dr, dc)) in [(self, mine), (other, theirs)] {
+            let shift = |cell: Cell| Cell { row: cell.row + dr, col: cell.col + dc };
+            for cell in part.cells().filter(|&cell| part.is_excluded(cell)) {
+                env.exclude(shift(cell));
+            }
+            for wall in part.walls() {
+                env.carve(shift(wall.start), shift(wall.target));
+            }
+        }
+        for &at in connections {
+            if at >= len {
+                return Err(MazeError::InvalidData(format!("connection {} is past the end of the {} edge, which is {} cells long", at, edge.name(), len)));
+            }
+            // The cell of this maze on the edge, then its neighbour in `other`
+            let (a, b) = match edge {
+                Edge::North => (Cell { row: mine.0, col: at }, Cell { row: mine.0 - 1, col: at }),
+                Edge::South => (Cell { row: theirs.0 - 1, col: at }, Cell { row: theirs.0, col: at }),
+                Edge::West => (Cell { row: at, col: mine.1 }, Cell { row: at, col: mine.1 - 1 }),
+                Edge::East => (Cell { row: at, col: theirs.1 - 1 }, Cell { row: at, col: theirs.1 }),
+            };
+            if env.is_excluded(a) || env.is_excluded(b) {
+                return Err(MazeError::InvalidData(format!("connection {} on the {} edge leads into a left-out cell", at, edge.name())));
+            }
+            env.carve(a, b);
+        }
+        Ok(env)
+    }
+}
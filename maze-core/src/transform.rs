@@ -0,0 +1,150 @@
+// Rotated and mirrored copies of a maze: the eight ways to turn or flip a grid onto itself. Every
+// passage, left-out cell and generator step moves along with its cells, and `CellData` and the
+// edges `Env::crop_with_entrances` reports can be moved the same way, so a maze and everything
+// attached to it stay in step.
+
+use crate::celldata::CellData;
+use crate::stitch::Edge;
+use crate::{Cell, Env};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Transform {
+    Identity,
+    // Quarter turns clockwise
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    // Left and right swapped
+    MirrorHorizontal,
+    // Top and bottom swapped
+    MirrorVertical,
+    // Rows become columns: mirrored along the diagonal from the top-left corner
+    Transpose,
+    // Mirrored along the diagonal from the top-right corner
+    AntiTranspose,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::MirrorHorizontal,
+        Transform::MirrorVertical,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transform::Identity => "identity",
+            Transform::Rotate90 => "rotate90",
+            Transform::Rotate180 => "rotate180",
+            Transform::Rotate270 => "rotate270",
+            Transform::MirrorHorizontal => "mirror-horizontal",
+            Transform::MirrorVertical => "mirror-vertical",
+            Transform::Transpose => "transpose",
+            Transform::AntiTranspose => "anti-transpose",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Transform::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    // Whether width and height trade places
+    pub fn swaps_axes(&self) -> bool {
+        matches!(self, Transform::Rotate90 | Transform::Rotate270 | Transform::Transpose | Transform::AntiTranspose)
+    }
+
+    // Width and height of a transformed `width` x `height` maze
+    pub fn dims(&self, width: usize, height: usize) -> (usize, usize) {
+        if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    // Where `cell` of a `width` x `height` maze ends up
+    pub fn cell(&self, cell: Cell, width: usize, height: usize) -> Cell {
+        let Cell { row, col } = cell;
+        let (row, col) = match self {
+            Transform::Identity => (row, col),
+            Transform::Rotate90 => (col, height - 1 - row),
+            Transform::Rotate180 => (height - 1 - row, width - 1 - col),
+            Transform::Rotate270 => (width - 1 - col, row),
+            Transform::MirrorHorizontal => (row, width - 1 - col),
+            Transform::MirrorVertical => (height - 1 - row, col),
+            Transform::Transpose => (col, row),
+            Transform::AntiTranspose => (width - 1 - col, height - 1 - row),
+        };
+        Cell { row, col }
+    }
+
+    // The side `edge` ends up on
+    pub fn edge(&self, edge: Edge) -> Edge {
+        let (dr, dc) = match edge {
+            Edge::North => (-1, 0),
+            Edge::East => (0, 1),
+            Edge::South => (1, 0),
+            Edge::West => (0, -1),
+        };
+        let (dr, dc) = match self {
+            Transform::Identity => (dr, dc),
+            Transform::Rotate90 => (dc, -dr),
+            Transform::Rotate180 => (-dr, -dc),
+            Transform::Rotate270 => (-dc, dr),
+            Transform::MirrorHorizontal => (dr, -dc),
+            Transform::MirrorVertical => (-dr, dc),
+            Transform::Transpose => (dc, dr),
+            Transform::AntiTranspose => (-dc, -dr),
+        };
+        match (dr, dc) {
+            (-1, 0) => Edge::North,
+            (0, 1) => Edge::East,
+            (1, 0) => Edge::South,
+            _ => Edge::West,
+        }
+    }
+
+    // The transform that puts everything back
+    pub fn inverse(&self) -> Transform {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            t => *t,
+        }
+    }
+}
+
+impl Env {
+    // A copy with the cells moved by `transform`, passages, left-out cells and `visit_order`
+    // included. Openings through the border of a damaged maze are not carried over.
+    pub fn transformed(&self, transform: Transform) -> Env {
+        let (width, height) = transform.dims(self.width, self.height);
+        let mut env = Env::new(width, height);
+        let moved = |cell: Cell| transform.cell(cell, self.width, self.height);
+        for cell in self.cells().filter(|&cell| self.is_excluded(cell)) {
+            env.exclude(moved(cell));
+        }
+        for wall in self.walls() {
+            env.carve(moved(wall.start), moved(wall.target));
+        }
+        env.visit_order = self.visit_order.iter().map(|&i| env.ind(moved(self.cell(i as usize))) as u32).collect();
+        env
+    }
+}
+
+impl<T: Clone> CellData<T> {
+    // A copy with the data moved along with its cells, to match `Env::transformed`
+    pub fn transformed(&self, transform: Transform) -> CellData<T> {
+        let (width, height) = transform.dims(self.width(), self.height());
+        let mut data = CellData::new(width, height);
+        for (cell, value) in self.iter() {
+            data.insert(transform.cell(cell, self.width(), self.height()), value.clone());
+        }
+        data
+    }
+}
@@ -0,0 +1,84 @@
+// Unicursal "mazes": a single passage that visits every cell once, with no branches at all, for
+// plotter art and CNC toolpaths rather than puzzles. The passage follows a space-filling curve and
+// is carved into an ordinary `Env`, so every renderer and exporter draws it like any other maze.
+
+use alloc::vec::Vec;
+
+use crate::{Cell, Env};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Curve {
+    // The Hilbert curve, from the top-left cell to the bottom-left one; square grids with a side
+    // that is a power of two only
+    Hilbert,
+    // Back and forth along the rows, like an ox ploughing a field
+    Serpentine,
+}
+
+impl Curve {
+    pub const ALL: [Curve; 2] = [Curve::Hilbert, Curve::Serpentine];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Curve::Hilbert => "hilbert",
+            Curve::Serpentine => "serpentine",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Curve::ALL.into_iter().find(|c| c.name() == name)
+    }
+
+    // Whether the curve covers a `width` x `height` grid
+    pub fn fits(&self, width: usize, height: usize) -> bool {
+        match self {
+            Curve::Hilbert => width == height && width.is_power_of_two(),
+            Curve::Serpentine => true,
+        }
+    }
+
+    // Every cell of a `width` x `height` grid in the order the curve passes through them
+    pub fn cells(&self, width: usize, height: usize) -> Vec<Cell> {
+        match self {
+            Curve::Hilbert => (0..width * width).map(|d| hilbert_cell(width, d)).collect(),
+            Curve::Serpentine => (0..height).flat_map(|row| {
+                (0..width).map(move |i| Cell { row, col: if row % 2 == 0 { i } else { width - 1 - i } })
+            }).collect(),
+        }
+    }
+}
+
+// The `d`th cell along the Hilbert curve over a `side` x `side` grid, `side` a power of two: the
+// curve is built up from 2x2 blocks, turning every quadrant so its ends meet the next one
+fn hilbert_cell(side: usize, d: usize) -> Cell {
+    let (mut row, mut col, mut t) = (0, 0, d);
+    let mut s = 1;
+    while s < side {
+        let down = 1 & (t / 2);
+        let across = 1 & (t ^ down);
+        if across == 0 {
+            if down == 1 {
+                row = s - 1 - row;
+                col = s - 1 - col;
+            }
+            core::mem::swap(&mut row, &mut col);
+        }
+        row += s * down;
+        col += s * across;
+        t /= 4;
+        s *= 2;
+    }
+    Cell { row, col }
+}
+
+// Carves `curve` into `env`, which should have every wall standing and fit the curve (see
+// `Curve::fits`); the visit order runs along the curve
+pub fn gen_curve(env: &mut Env, curve: Curve) {
+    let cells = curve.cells(env.width, env.height);
+    for pair in cells.windows(2) {
+        env.carve(pair[0], pair[1]);
+    }
+    for cell in cells {
+        env.visit_order.push(env.ind(cell) as u32);
+    }
+}
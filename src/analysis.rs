@@ -0,0 +1,303 @@
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+
+use crate::solve::Solver;
+use crate::{Cell, Env};
+
+// How hard a maze is to solve by hand, from the shortest path between start and goal and the
+// choices a solver meets along it
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub width: usize,
+    pub height: usize,
+    // Cells on the shortest path, start and goal included; 0 when the goal is unreachable
+    pub solution_length: usize,
+    // Open sides of solution cells that lead off the solution, i.e. wrong turns on offer
+    pub branches_on_solution: usize,
+    // Solution cells with three or more open sides, where the solver has to choose
+    pub decision_points: usize,
+    pub dead_ends: usize,
+    // Decision points per solution cell
+    pub decision_density: f32,
+    pub score: f32,
+    // Filled in on request, see `stats`
+    pub stats: Option<Stats>,
+    // Filled in on request, see `chokepoints`
+    pub chokepoints: Option<Vec<Bridge>>,
+}
+
+// Upper score bounds of every label but the last, matching the `--difficulty` preset names
+const LABELS: [(f32, &str); 3] = [(8.0, "easy"), (30.0, "medium"), (72.0, "hard")];
+
+impl Analysis {
+    // Name of the difficulty preset whose mazes usually score like this one
+    pub fn label(&self) -> &'static str {
+        LABELS.iter().find(|(bound, _)| self.score < *bound).map_or("extreme", |(_, name)| name)
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            concat!(
+                "{{\"width\":{},\"height\":{},\"solution_length\":{},\"branches_on_solution\":{},",
+                "\"decision_points\":{},\"dead_ends\":{},\"decision_density\":{:.4},\"score\":{:.2},\"label\":\"{}\"{}{}}}"
+            ),
+            self.width,
+            self.height,
+            self.solution_length,
+            self.branches_on_solution,
+            self.decision_points,
+            self.dead_ends,
+            self.decision_density,
+            self.score,
+            self.label(),
+            self.stats.as_ref().map_or(String::new(), |stats| format!(",\"stats\":{}", stats.to_json())),
+            self.chokepoints.as_ref().map_or(String::new(), |bridges| {
+                let bridges: Vec<String> = bridges.iter().map(|bridge| bridge.to_json()).collect();
+                format!(",\"chokepoints\":[{}]", bridges.join(","))
+            })
+        )
+    }
+}
+
+// The score grows with the solution length, and more so when the path is dense with decisions
+// or the maze with dead ends to get lost in
+pub fn analyze(env: &Env, start: Cell, goal: Cell) -> Analysis {
+    let path = Solver::Bfs.solve(env, start, goal).map_or(vec![], |s| s.path);
+    let mut on_path = vec![false; env.cell_count()];
+    for cell in path.iter() {
+        on_path[env.ind(*cell)] = true;
+    }
+    let branches_on_solution = path.iter().map(|&cell| env.neighbors(cell).filter(|n| !on_path[env.ind(*n)]).count()).sum();
+    let decision_points = path.iter().filter(|&&cell| env.open_sides(cell).count_ones() >= 3).count();
+    let dead_ends = env.dead_ends().count();
+    let decision_density = if path.is_empty() { 0.0 } else { decision_points as f32 / path.len() as f32 };
+    let dead_end_ratio = dead_ends as f32 / env.included_count() as f32;
+    let score = path.len() as f32 * (1.0 + (2.0 * decision_density)) * (1.0 + dead_end_ratio) / 10.0;
+    Analysis {
+        width: env.width,
+        height: env.height,
+        solution_length: path.len(),
+        branches_on_solution,
+        decision_points,
+        dead_ends,
+        decision_density,
+        score,
+        stats: None,
+        chokepoints: None,
+    }
+}
+
+// Structural metrics of the whole maze, independent of start and goal, for comparing generators
+#[derive(Clone, Debug)]
+pub struct Stats {
+    pub dead_ends: usize,
+    // Corridors are the passages between cells that are not plain corridor cells (dead ends and
+    // junctions); lengths count the steps from one end to the other
+    pub corridors: usize,
+    pub average_corridor_length: f32,
+    // Most cells passed in one direction without turning
+    pub longest_straight_run: usize,
+    // Number of cells with 0 through 4 open sides
+    pub branching: [usize; 5],
+    // Steps along the longest path, see `longest_path`
+    pub diameter: usize,
+    // Share of cells with exactly two open sides: high for long winding passages with few, long
+    // dead ends, low for bushy mazes full of short ones
+    pub river: f32,
+}
+
+impl Stats {
+    pub fn to_json(&self) -> String {
+        let branching: Vec<String> = self.branching.iter().map(|n| n.to_string()).collect();
+        format!(
+            concat!(
+                "{{\"dead_ends\":{},\"corridors\":{},\"average_corridor_length\":{:.4},",
+                "\"longest_straight_run\":{},\"branching\":[{}],\"diameter\":{},\"river\":{:.4}}}"
+            ),
+            self.dead_ends,
+            self.corridors,
+            self.average_corridor_length,
+            self.longest_straight_run,
+            branching.join(","),
+            self.diameter,
+            self.river
+        )
+    }
+}
+
+// Steps from `from` through `first` and on along plain corridor cells until a cell that is not one
+fn corridor_length(env: &Env, from: Cell, first: Cell) -> usize {
+    let (mut prev, mut cur, mut steps) = (from, first, 1);
+    while env.open_sides(cur).count_ones() == 2 && cur != from {
+        let next = env.neighbors(cur).find(|n| *n != prev).expect("corridor cells have two open sides");
+        (prev, cur, steps) = (cur, next, steps + 1);
+    }
+    steps
+}
+
+// Most cells in one row or column joined by passages in a line
+fn longest_straight_run(env: &Env) -> usize {
+    let mut longest = 0;
+    for row in 0..env.height {
+        let mut run = 1;
+        for col in 0..env.width {
+            longest = longest.max(run);
+            run = if env.open_east(Cell { row, col }) { run + 1 } else { 1 };
+        }
+    }
+    for col in 0..env.width {
+        let mut run = 1;
+        for row in 0..env.height {
+            longest = longest.max(run);
+            run = if env.open_south(Cell { row, col }) { run + 1 } else { 1 };
+        }
+    }
+    longest
+}
+
+pub fn stats(env: &Env) -> Stats {
+    let mut branching = [0; 5];
+    for cell in env.cells().filter(|&cell| !env.is_excluded(cell)) {
+        branching[env.open_sides(cell).count_ones() as usize] += 1;
+    }
+    // Every corridor is walked once from each end
+    let (mut ends, mut steps) = (0, 0);
+    for cell in env.cells().filter(|&cell| env.open_sides(cell).count_ones() != 2) {
+        for first in env.neighbors(cell) {
+            ends += 1;
+            steps += corridor_length(env, cell, first);
+        }
+    }
+    let corridors = ends / 2;
+    Stats {
+        dead_ends: branching[1],
+        corridors,
+        average_corridor_length: if ends == 0 { 0.0 } else { steps as f32 / ends as f32 },
+        longest_straight_run: longest_straight_run(env),
+        branching,
+        diameter: longest_path(env).len().saturating_sub(1),
+        river: branching[2] as f32 / env.included_count() as f32,
+    }
+}
+
+// Breadth-first search from `from` over open passages: the last cell reached, which is one of the
+// farthest from `from`, and every reached cell's predecessor
+fn farthest(env: &Env, from: Cell) -> (Cell, Vec<Option<usize>>) {
+    let mut came_from = vec![None; env.cell_count()];
+    came_from[env.ind(from)] = Some(env.ind(from));
+    let (mut queue, mut last) = (VecDeque::from([from]), from);
+    while let Some(cell) = queue.pop_front() {
+        last = cell;
+        for next in env.neighbors(cell) {
+            if came_from[env.ind(next)].is_none() {
+                came_from[env.ind(next)] = Some(env.ind(cell));
+                queue.push_back(next);
+            }
+        }
+    }
+    (last, came_from)
+}
+
+// Steps along open passages from `from` to every cell, `None` for cells it cannot reach. Every
+// passage has the same length, so a breadth-first search gives the Dijkstra distances.
+pub fn distances(env: &Env, from: Cell) -> Vec<Option<usize>> {
+    let mut distance = vec![None; env.cell_count()];
+    distance[env.ind(from)] = Some(0);
+    let mut queue = VecDeque::from([from]);
+    while let Some(cell) = queue.pop_front() {
+        let next_distance = distance[env.ind(cell)].map(|d| d + 1);
+        for next in env.neighbors(cell) {
+            if distance[env.ind(next)].is_none() {
+                distance[env.ind(next)] = next_distance;
+                queue.push_back(next);
+            }
+        }
+    }
+    distance
+}
+
+// Longest path through the maze (its diameter), found with two breadth-first searches: the cell
+// farthest from any cell is one end, and the cell farthest from that the other. Its ends are the
+// hardest start and goal pair. Exact for perfect mazes; with loops it is the longest shortest
+// path the two searches find, and it only covers the part connected to the top-left cell.
+pub fn longest_path(env: &Env) -> Vec<Cell> {
+    let (start, _) = farthest(env, Cell { row: 0, col: 0 });
+    let (goal, came_from) = farthest(env, start);
+    let mut path = vec![goal];
+    let mut ind = env.ind(goal);
+    while ind != env.ind(start) {
+        ind = came_from[ind].expect("every cell on the path was reached");
+        path.push(env.cell(ind));
+    }
+    path.reverse();
+    path
+}
+
+// A passage that is the only way between two parts of the maze: walling it off splits its region
+// in two. In a perfect maze every passage is one; once loops are braided in, the ones left are the
+// chokepoints where a door, a key or a guard cannot be walked around.
+#[derive(Clone, Debug)]
+pub struct Bridge {
+    pub a: Cell,
+    pub b: Cell,
+    // Cells on the smaller side, the part the passage cuts off
+    pub cut_off: usize,
+}
+
+impl Bridge {
+    pub fn to_json(&self) -> String {
+        format!("{{\"from\":[{},{}],\"to\":[{},{}],\"cut_off\":{}}}", self.a.row, self.a.col, self.b.row, self.b.col, self.cut_off)
+    }
+}
+
+// Every bridge of the maze, found with Tarjan's low-link depth-first search: a tree passage into a
+// cell is a bridge when nothing below that cell has a passage back above it. Kept iterative, as the
+// search goes as deep as the maze has cells.
+pub fn bridges(env: &Env) -> Vec<Bridge> {
+    const UNSEEN: usize = usize::MAX;
+    let count = env.cell_count();
+    let (mut discovered, mut low, mut below, mut parent) = (vec![UNSEEN; count], vec![0; count], vec![1; count], vec![UNSEEN; count]);
+    let (mut bridges, mut time) = (vec![], 0);
+    for root in env.cells().filter(|&cell| !env.is_excluded(cell)).map(|cell| env.ind(cell)) {
+        if discovered[root] != UNSEEN {
+            continue;
+        }
+        (discovered[root], low[root], time) = (time, time, time + 1);
+        // Tree passages of this region that are bridges, as (parent, child)
+        let mut found = vec![];
+        // Cells on the search path, each with the number of its neighbors tried so far
+        let mut stack = vec![(root, 0)];
+        while let Some(top) = stack.last_mut() {
+            let (u, tried) = *top;
+            top.1 += 1;
+            match env.neighbors(env.cell(u)).nth(tried).map(|next| env.ind(next)) {
+                Some(v) if discovered[v] == UNSEEN => {
+                    (parent[v], discovered[v], low[v], time) = (u, time, time, time + 1);
+                    stack.push((v, 0));
+                },
+                Some(v) if v != parent[u] => low[u] = low[u].min(discovered[v]),
+                Some(_) => {},
+                None => {
+                    stack.pop();
+                    if let Some(&(p, _)) = stack.last() {
+                        low[p] = low[p].min(low[u]);
+                        below[p] += below[u];
+                        if low[u] > discovered[p] {
+                            found.push((p, u));
+                        }
+                    }
+                },
+            }
+        }
+        let region = below[root];
+        bridges.extend(found.into_iter().map(|(p, u)| Bridge { a: env.cell(p), b: env.cell(u), cut_off: below[u].min(region - below[u]) }));
+    }
+    bridges
+}
+
+// The bridges that cut off at least `min_cut_off` cells, the ones worth guarding first
+pub fn chokepoints(env: &Env, min_cut_off: usize) -> Vec<Bridge> {
+    let mut bridges: Vec<Bridge> = bridges(env).into_iter().filter(|bridge| bridge.cut_off >= min_cut_off).collect();
+    bridges.sort_by_key(|bridge| (Reverse(bridge.cut_off), env.ind(bridge.a), env.ind(bridge.b)));
+    bridges
+}
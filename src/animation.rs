@@ -0,0 +1,254 @@
+// Step-by-step playback of a maze being generated and solved, shared by the `gui` window and the
+// animation file exporters
+
+use crate::draw::{blend, fill_rect, Canvas};
+use crate::render::raster::draw_maze;
+use crate::render::{cell_center, image_size, Theme, BORDER_THICKNESS, OPEN_PATH_SIZE};
+use crate::observer::Observer;
+use crate::solve::Solver;
+use crate::trace::Trace;
+use crate::{Cell, Env, GenEvent};
+
+const HEAD_COLOR: u32 = 0xF2D13AFF;
+const EXPLORED_COLOR: u32 = 0x2856A1A0;
+const PATH_COLOR: u32 = 0xE04040FF;
+// Tremaux's passage marks, by count
+const MARK_COLORS: [u32; 2] = [0xF0A030FF, 0x7A1F1FFF];
+// Side of a mark, in logical pixels
+const MARK_SIZE: f32 = 4.0;
+
+// One unit of change in the animation
+pub enum Step {
+    // The generator moved into `to`, opening the wall from `from` (`None` for the first cell)
+    Carve(Option<Cell>, Cell),
+    // The generator stepped back to `to` (`None` once it is done)
+    Backtrack(Option<Cell>),
+    // The solver expanded a cell
+    Explore(Cell),
+    // The solver marked the passage between two cells, which now has this many marks
+    Mark(Cell, Cell, u8),
+    // The final path grew by one cell, drawn in the given color
+    Path(Cell, Cell, u32),
+    // A racer moved into the cell, tinting it with its translucent color
+    Visit(Cell, u32),
+}
+
+// Replays generation in visit order: every cell is carved in from the earlier-visited neighbour
+// it is connected to, which is its parent in the depth-first tree
+fn carve_steps(env: &Env) -> Vec<Step> {
+    let mut rank = vec![usize::MAX; env.cell_count()];
+    for (i, &ind) in env.visit_order.iter().enumerate() {
+        rank[ind as usize] = i;
+    }
+    env.visit_order
+        .iter()
+        .map(|&ind| {
+            let cell = env.cell(ind as usize);
+            let parent = env.neighbors(cell).filter(|n| rank[env.ind(*n)] < rank[ind as usize]).min_by_key(|n| rank[env.ind(*n)]);
+            Step::Carve(parent, cell)
+        })
+        .collect()
+}
+
+// Replays a recorded run step by step, backtracking included
+fn trace_steps(trace: &Trace) -> Vec<Step> {
+    let mut stack = vec![];
+    trace
+        .events()
+        .into_iter()
+        .map(|event| match event {
+            GenEvent::Start(cell) => {
+                stack.push(cell);
+                Step::Carve(None, cell)
+            },
+            GenEvent::Carve(from, to) => {
+                stack.push(to);
+                Step::Carve(Some(from), to)
+            },
+            GenEvent::Backtrack(_) => {
+                stack.pop();
+                Step::Backtrack(stack.last().copied())
+            },
+        })
+        .collect()
+}
+
+// The maze as drawn so far, in pixels `unit` times the logical image size
+pub struct Animation {
+    pub canvas: Canvas,
+    unit: usize,
+    theme: Theme,
+    head: Option<Cell>,
+}
+
+impl Animation {
+    // A maze of solid blocks waiting to be carved
+    pub fn new(env: &Env, unit: usize, theme: Theme) -> Self {
+        let canvas = Canvas::new(image_size(env.width) * unit, image_size(env.height) * unit, theme.solid_color);
+        Animation { canvas, unit, theme, head: None }
+    }
+
+    // The finished maze, for animations of solvers alone
+    pub fn carved(env: &Env, unit: usize, theme: Theme) -> Self {
+        let mut canvas = Canvas::new(image_size(env.width), image_size(env.height), theme.open_color);
+        draw_maze(env, &mut canvas, &theme);
+        Animation { canvas: canvas.upscale(unit), unit, theme, head: None }
+    }
+
+    // Pixel rectangle covering the open interior of `cell`
+    fn cell_rect(&self, cell: Cell) -> (usize, usize, usize, usize) {
+        let pitch = (OPEN_PATH_SIZE + BORDER_THICKNESS) as usize;
+        let x = ((cell.col * pitch) + BORDER_THICKNESS as usize) * self.unit;
+        let y = ((cell.row * pitch) + BORDER_THICKNESS as usize) * self.unit;
+        (x, y, OPEN_PATH_SIZE as usize * self.unit, OPEN_PATH_SIZE as usize * self.unit)
+    }
+
+    // Pixel rectangle covering both cells and the wall slot between them
+    fn span_rect(&self, a: Cell, b: Cell) -> (usize, usize, usize, usize) {
+        let (ax, ay, w, h) = self.cell_rect(a);
+        let (bx, by, _, _) = self.cell_rect(b);
+        let (x, y) = (ax.min(bx), ay.min(by));
+        (x, y, ax.max(bx) - x + w, ay.max(by) - y + h)
+    }
+
+    fn fill(&mut self, (x, y, w, h): (usize, usize, usize, usize), color: u32) {
+        fill_rect(&mut self.canvas, x, y, w, h, color);
+    }
+
+    fn tint(&mut self, (x0, y0, w, h): (usize, usize, usize, usize), color: u32) {
+        for y in y0..(y0 + h) {
+            for x in x0..(x0 + w) {
+                if let Some(under) = self.canvas.get(x, y) {
+                    self.canvas.set(x, y, blend(under, color));
+                }
+            }
+        }
+    }
+
+    pub fn apply(&mut self, step: &Step) {
+        let open = self.theme.open_color;
+        match *step {
+            Step::Carve(from, to) => {
+                if let Some(head) = self.head.take() {
+                    self.fill(self.cell_rect(head), open);
+                }
+                if let Some(from) = from {
+                    self.fill(self.span_rect(from, to), open);
+                }
+                self.fill(self.cell_rect(to), HEAD_COLOR);
+                self.head = Some(to);
+            },
+            Step::Backtrack(to) => {
+                if let Some(head) = self.head.take() {
+                    self.fill(self.cell_rect(head), open);
+                }
+                if let Some(to) = to {
+                    self.fill(self.cell_rect(to), HEAD_COLOR);
+                    self.head = Some(to);
+                }
+            },
+            Step::Explore(cell) => {
+                if let Some(head) = self.head.take() {
+                    self.fill(self.cell_rect(head), open);
+                }
+                self.tint(self.cell_rect(cell), EXPLORED_COLOR);
+            },
+            Step::Mark(from, to, marks) => {
+                // Centered on the wall slot between the two cells
+                let u = self.unit as f32;
+                let (fx, fy) = cell_center(&from);
+                let (tx, ty) = cell_center(&to);
+                let (x, y) = ((((fx + tx) / 2.0) - (MARK_SIZE / 2.0)) * u, (((fy + ty) / 2.0) - (MARK_SIZE / 2.0)) * u);
+                let color = MARK_COLORS[(marks.clamp(1, 2) - 1) as usize];
+                self.fill((x as usize, y as usize, (MARK_SIZE * u) as usize, (MARK_SIZE * u) as usize), color);
+            },
+            Step::Visit(cell, color) => self.tint(self.cell_rect(cell), color),
+            Step::Path(from, to, color) => {
+                let u = self.unit as f32;
+                let half = (OPEN_PATH_SIZE as f32 / 5.0) * u;
+                let (sx, sy) = cell_center(&from);
+                let (tx, ty) = cell_center(&to);
+                let (x, y) = ((sx.min(tx) * u) - half, (sy.min(ty) * u) - half);
+                let (w, h) = (((tx - sx).abs() * u) + (2.0 * half), ((ty - sy).abs() * u) + (2.0 * half));
+                self.fill((x as usize, y as usize, w as usize, h as usize), color);
+            },
+        }
+    }
+}
+
+// Records a solver's explored cells and passage marks as steps, in the order they happen
+struct Recorder(Vec<Step>);
+
+impl Observer for Recorder {
+    fn on_expand(&mut self, cell: Cell) {
+        self.0.push(Step::Explore(cell));
+    }
+
+    fn on_mark(&mut self, from: Cell, to: Cell, marks: u8) {
+        self.0.push(Step::Mark(from, to, marks));
+    }
+}
+
+// Every step of generating `env` and then `solver` running from the top-left to the bottom-right
+// cell, and how many of them are generation. Generation is replayed from `trace` when there is
+// one, and reconstructed from the visit order otherwise.
+pub fn steps(env: &Env, solver: Solver, trace: Option<&Trace>) -> (Vec<Step>, usize) {
+    let goal = Cell { row: env.height - 1, col: env.width - 1 };
+    let steps = trace.map_or_else(|| carve_steps(env), trace_steps);
+    let carve_count = steps.len();
+    let mut recorder = Recorder(steps);
+    let solution = solver
+        .solve_observed(env, Cell { row: 0, col: 0 }, goal, &mut (), &mut recorder)
+        .expect("`()` never cancels")
+        .expect("generated mazes are fully connected");
+    let Recorder(mut steps) = recorder;
+    steps.extend(solution.path.windows(2).map(|pair| Step::Path(pair[0], pair[1], PATH_COLOR)));
+    (steps, carve_count)
+}
+
+// The animation sampled at a fixed number of steps per frame, for writing to a file
+pub struct Capture {
+    steps: Vec<Step>,
+    animation: Animation,
+    per_frame: usize,
+    // Steps applied so far, or `None` before the first frame
+    next: Option<usize>,
+}
+
+impl Capture {
+    // Spreads `steps` over at most `max_frames` frames, the first showing `animation` as it is
+    pub fn new(animation: Animation, steps: Vec<Step>, max_frames: usize) -> Self {
+        let per_frame = steps.len().div_ceil(max_frames.saturating_sub(1).max(1)).max(1);
+        Capture { steps, animation, per_frame, next: None }
+    }
+
+    pub fn width(&self) -> usize {
+        self.animation.canvas.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.animation.canvas.height()
+    }
+
+    pub fn frame_count(&self) -> usize {
+        1 + self.steps.len().div_ceil(self.per_frame)
+    }
+
+    // The next frame, or `None` once the last step has been shown
+    pub fn next_frame(&mut self) -> Option<&Canvas> {
+        let start = match self.next {
+            None => {
+                self.next = Some(0);
+                return Some(&self.animation.canvas);
+            },
+            Some(next) if next >= self.steps.len() => return None,
+            Some(next) => next,
+        };
+        let end = (start + self.per_frame).min(self.steps.len());
+        for step in &self.steps[start..end] {
+            self.animation.apply(step);
+        }
+        self.next = Some(end);
+        Some(&self.animation.canvas)
+    }
+}
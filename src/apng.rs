@@ -0,0 +1,112 @@
+use std::io::{self, Write};
+
+use crate::deflate::zlib_compress;
+use crate::draw::Canvas;
+use crate::png::{crc32_table, write_chunk};
+
+// Animated PNG: a regular PNG whose image is the first frame, plus `fcTL`/`fdAT` chunk pairs for
+// the rest. Every later frame only stores the rectangle that changed since the one before, drawn
+// over it, so long animations of a maze being carved stay small.
+// See https://wiki.mozilla.org/APNG_Specification
+pub struct ApngEncoder<'a> {
+    out: &'a mut dyn Write,
+    table: [u32; 256],
+    width: usize,
+    height: usize,
+    frames_left: usize,
+    // Sequence number of the next `fcTL` or `fdAT` chunk
+    sequence: u32,
+    previous: Option<Canvas>,
+}
+
+// Filtered (filter type 0) RGBA scanlines of a rectangle of `canvas`
+fn scanlines(canvas: &Canvas, (x, y, w, h): (usize, usize, usize, usize)) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(h * ((w * 4) + 1));
+    for row in canvas.pixels().chunks(canvas.width()).skip(y).take(h) {
+        raw.push(0);
+        for pixel in &row[x..x + w] {
+            raw.extend_from_slice(&pixel.to_be_bytes());
+        }
+    }
+    raw
+}
+
+// Smallest rectangle holding every pixel that differs between `a` and `b`
+fn changed(a: &Canvas, b: &Canvas) -> Option<(usize, usize, usize, usize)> {
+    let (mut x0, mut y0, mut x1, mut y1) = (usize::MAX, usize::MAX, 0, 0);
+    for (i, (pa, pb)) in a.pixels().iter().zip(b.pixels()).enumerate() {
+        if pa != pb {
+            let (x, y) = (i % a.width(), i / a.width());
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+        }
+    }
+    (x0 != usize::MAX).then(|| (x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+}
+
+impl<'a> ApngEncoder<'a> {
+    // Writes the header of a `width`x`height` animation of `frame_count` frames that loops forever
+    pub fn new(out: &'a mut dyn Write, width: usize, height: usize, frame_count: usize) -> io::Result<Self> {
+        let table = crc32_table();
+        out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'])?;
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(width as u32).to_be_bytes());
+        header.extend_from_slice(&(height as u32).to_be_bytes());
+        // Bit depth 8, color type 6 (RGBA), default compression/filter, no interlace
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+        write_chunk(out, &table, b"IHDR", &header)?;
+        let mut control = Vec::with_capacity(8);
+        control.extend_from_slice(&(frame_count as u32).to_be_bytes());
+        // Zero plays means loop forever
+        control.extend_from_slice(&0u32.to_be_bytes());
+        write_chunk(out, &table, b"acTL", &control)?;
+        Ok(ApngEncoder { out, table, width, height, frames_left: frame_count, sequence: 0, previous: None })
+    }
+
+    // Adds the next frame, shown for `delay_ms` milliseconds
+    pub fn add_frame(&mut self, canvas: &Canvas, delay_ms: u16) -> io::Result<()> {
+        if self.frames_left == 0 || (canvas.width(), canvas.height()) != (self.width, self.height) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame count or size differs from the APNG header"));
+        }
+        self.frames_left -= 1;
+        // An unchanged frame still needs a region, so it repeats one pixel
+        let rect = match &self.previous {
+            None => (0, 0, self.width, self.height),
+            Some(previous) => changed(previous, canvas).unwrap_or((0, 0, 1, 1)),
+        };
+
+        let mut control = Vec::with_capacity(26);
+        control.extend_from_slice(&self.sequence.to_be_bytes());
+        for value in [rect.2, rect.3, rect.0, rect.1] {
+            control.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        control.extend_from_slice(&delay_ms.to_be_bytes());
+        control.extend_from_slice(&1000u16.to_be_bytes());
+        // Leave the frame in place for the next one (dispose op 0) and replace the region's pixels
+        // rather than blending onto them (blend op 0)
+        control.extend_from_slice(&[0, 0]);
+        write_chunk(self.out, &self.table, b"fcTL", &control)?;
+        self.sequence += 1;
+
+        let data = zlib_compress(&scanlines(canvas, rect));
+        if self.previous.is_none() {
+            write_chunk(self.out, &self.table, b"IDAT", &data)?;
+        } else {
+            let mut frame = Vec::with_capacity(data.len() + 4);
+            frame.extend_from_slice(&self.sequence.to_be_bytes());
+            frame.extend_from_slice(&data);
+            write_chunk(self.out, &self.table, b"fdAT", &frame)?;
+            self.sequence += 1;
+        }
+        self.previous = Some(canvas.clone());
+        Ok(())
+    }
+
+    // Ends the file once every frame was added
+    pub fn finish(self) -> io::Result<()> {
+        if self.frames_left != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} APNG frames are missing", self.frames_left)));
+        }
+        write_chunk(self.out, &self.table, b"IEND", &[])?;
+        self.out.flush()
+    }
+}
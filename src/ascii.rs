@@ -0,0 +1,157 @@
+// Reads text mazes back in. Two layouts are understood:
+//
+//   ┌───────┬───┐      +---+---+---+      #########
+//   │       │   │      | S     |   |      #S  #   #
+//   │   ╶───┘   │      +   +---+   +      # ### # #
+//   │           │      |         E |      #     #E#
+//   └───────────┘      +---+---+---+      #########
+//
+// Drawn walls, as `TextRenderer` writes them or in plain ASCII: every cell is three columns wide
+// between posts, and any non-space character in a wall slot is a wall. Wall maps, like
+// `Env::blocks`: one character per block (or two, as `play` draws them), where any character but a
+// space or `.` is solid. Either way `S` marks the start cell and `E` or `G` the goal; ANSI color
+// escapes are ignored, and the outer border is assumed closed. Cells filled in solid (`███` when
+// drawn) are left out of the maze.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use crate::error::MazeError;
+use crate::mazefile::MazeFile;
+use crate::render::text::EXCLUDED_CELL;
+use crate::{Cell, Env};
+
+pub const EXTENSION: &str = "txt";
+
+// Columns from one wall post to the next in drawn walls
+const PITCH: usize = 4;
+const START_MARKERS: [char; 1] = ['S'];
+const GOAL_MARKERS: [char; 2] = ['E', 'G'];
+
+fn invalid(message: String) -> MazeError {
+    MazeError::InvalidData(message)
+}
+
+// `text` without `ESC [ ... letter` escape sequences
+fn strip_escapes(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                chars.by_ref().find(|c| ('@'..='~').contains(c));
+            }
+            continue;
+        }
+        plain.push(c);
+    }
+    plain
+}
+
+fn is_drawn_wall(c: char) -> bool {
+    matches!(c, '+' | '-' | '|') || ('─'..='╿').contains(&c)
+}
+
+// Carves the cells of a maze drawn with posts, wall slots and three-column cells
+fn read_drawn(grid: &[Vec<char>]) -> Result<(Env, Option<Cell>, Option<Cell>), MazeError> {
+    let columns = grid[0].len();
+    if grid.len() < 3 || grid.len().is_multiple_of(2) || columns < PITCH + 1 || columns % PITCH != 1 {
+        return Err(invalid(format!("expected 2N+1 lines of 4N+1 columns, found {} lines of up to {}", grid.len(), columns)));
+    }
+    let (width, height) = (columns / PITCH, grid.len() / 2);
+    let mut env = Env::new(width, height);
+    let (mut start, mut goal) = (None, None);
+    for row in 0..height {
+        let (line, below) = (&grid[(2 * row) + 1], &grid[(2 * row) + 2]);
+        for col in 0..width {
+            let cell = Cell { row, col };
+            let inside = &line[(PITCH * col) + 1..PITCH * (col + 1)];
+            if inside.iter().any(|c| START_MARKERS.contains(c)) {
+                start = Some(cell);
+            }
+            if inside.iter().any(|c| GOAL_MARKERS.contains(c)) {
+                goal = Some(cell);
+            }
+            if inside.iter().copied().eq(EXCLUDED_CELL.chars()) {
+                env.exclude(cell);
+                continue;
+            }
+            if col + 1 < width && line[PITCH * (col + 1)] == ' ' {
+                env.carve(cell, Cell { row, col: col + 1 });
+            }
+            if row + 1 < height && below[(PITCH * col) + 1..PITCH * (col + 1)].iter().all(|&c| c == ' ') {
+                env.carve(cell, Cell { row: row + 1, col });
+            }
+        }
+    }
+    Ok((env, start, goal))
+}
+
+// Carves the cells of a one-character-per-block wall map
+fn read_blocks(grid: &[Vec<char>]) -> Result<(Env, Option<Cell>, Option<Cell>), MazeError> {
+    let columns = grid[0].len();
+    // Two characters per block show up as pairs of equal characters across an even width
+    let doubled = columns.is_multiple_of(2) && grid.iter().all(|line| line.chunks(2).all(|pair| pair[0] == pair[1]));
+    let grid: Vec<Vec<char>> = if doubled { grid.iter().map(|line| line.iter().step_by(2).copied().collect()).collect() } else { grid.to_vec() };
+    let columns = grid[0].len();
+    if grid.len() < 3 || grid.len().is_multiple_of(2) || columns < 3 || columns.is_multiple_of(2) {
+        return Err(invalid(format!("expected an odd number of lines and blocks per line, found {} lines of up to {} blocks", grid.len(), columns)));
+    }
+    let (width, height) = (columns / 2, grid.len() / 2);
+    let mut env = Env::new(width, height);
+    let (mut start, mut goal) = (None, None);
+    let open = |c: char| c == ' ' || c == '.' || START_MARKERS.contains(&c) || GOAL_MARKERS.contains(&c);
+    for row in 0..height {
+        let (i, line) = ((2 * row) + 1, &grid[(2 * row) + 1]);
+        for col in 0..width {
+            let (cell, j) = (Cell { row, col }, (2 * col) + 1);
+            if START_MARKERS.contains(&line[j]) {
+                start = Some(cell);
+            }
+            if GOAL_MARKERS.contains(&line[j]) {
+                goal = Some(cell);
+            }
+            if !open(line[j]) {
+                env.exclude(cell);
+                continue;
+            }
+            if col + 1 < width && open(line[j + 1]) && open(line[j + 2]) {
+                env.carve(cell, Cell { row, col: col + 1 });
+            }
+            if row + 1 < height && open(grid[i + 1][j]) && open(grid[i + 2][j]) {
+                env.carve(cell, Cell { row: row + 1, col });
+            }
+        }
+    }
+    Ok((env, start, goal))
+}
+
+pub fn read(mut input: impl Read) -> Result<MazeFile, MazeError> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let text = strip_escapes(&text);
+    let mut lines: Vec<Vec<char>> = text.lines().map(|line| line.trim_end().chars().collect()).collect();
+    // Blank lines around the maze are not part of it
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let first = lines.iter().position(|line| !line.is_empty()).ok_or_else(|| invalid(String::from("no maze in the text")))?;
+    lines.drain(..first);
+    // Editors drop trailing spaces, so pad every line back to the widest one
+    let columns = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    for line in lines.iter_mut() {
+        line.resize(columns, ' ');
+    }
+
+    let drawn = lines.iter().flatten().any(|&c| is_drawn_wall(c));
+    let (env, start, goal) = if drawn { read_drawn(&lines)? } else { read_blocks(&lines)? };
+    let mut maze = MazeFile::new(env);
+    maze.start = start.unwrap_or(maze.start);
+    maze.goal = goal.unwrap_or(maze.goal);
+    Ok(maze)
+}
+
+pub fn load(path: &Path) -> Result<MazeFile, MazeError> {
+    read(BufReader::new(File::open(path)?))
+}
@@ -0,0 +1,23 @@
+// Standard base64 with `=` padding, for embedding binary images in terminal escape sequences
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn encoded_len(len: usize) -> usize {
+    len.div_ceil(3) * 4
+}
+
+pub fn encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(encoded_len(bytes.len()));
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (i, b)| acc | ((*b as u32) << (16 - (8 * i))));
+        // Every input byte spans two output characters; the rest of the group is padding
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[((group >> (18 - (6 * i))) & 0x3F) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
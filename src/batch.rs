@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::error::MazeError;
+use crate::progress::{CancelToken, Progress};
+use crate::template::Fields;
+
+// Seed of maze `index` of a batch generated from `master`. SplitMix64 scrambles the pair, so
+// neighbouring indices (and neighbouring master seeds) give unrelated mazes.
+pub fn maze_seed(master: u64, index: usize) -> u64 {
+    let mut z = master.wrapping_add((index as u64).wrapping_mul(0x9E3779B97F4A7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+// Name of one maze of a batch: `template` expanded with `fields`, which should have an index.
+// Without an `{index}` or `{seed}`, `_{index}` is added before the extension so every maze still
+// gets its own file (`out.png` becomes `out_007.png`).
+pub fn output_path(template: &str, fields: &Fields) -> String {
+    if template.contains("{index}") || template.contains("{seed}") {
+        return fields.expand(template);
+    }
+    let template = match template.rsplit_once('.') {
+        Some((stem, ext)) if !ext.contains('/') => format!("{}_{{index}}.{}", stem, ext),
+        _ => format!("{}_{{index}}", template),
+    };
+    fields.expand(&template)
+}
+
+// Runs `job(index, seed)` for mazes 1 through `count` on up to `threads` threads, with seeds from
+// `maze_seed(master, index)`. `progress` counts finished mazes. The first error stops the batch:
+// jobs already running finish, no new ones start, and the error is returned.
+pub fn run<F>(count: usize, master: u64, threads: usize, progress: &mut dyn Progress, job: F) -> Result<(), MazeError>
+where
+    F: Fn(usize, u64) -> Result<(), MazeError> + Sync,
+{
+    let next = AtomicUsize::new(1);
+    let stop = &CancelToken::default();
+    let (job, next) = (&job, &next);
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..threads.clamp(1, count.max(1)) {
+            let sender = sender.clone();
+            scope.spawn(move || {
+                while !stop.is_cancelled() {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index > count {
+                        break;
+                    }
+                    let result = job(index, maze_seed(master, index));
+                    if result.is_err() {
+                        stop.cancel();
+                    }
+                    if sender.send(result).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let (mut done, mut first_error) = (0, None);
+        progress.update(0, count);
+        for result in receiver {
+            match result {
+                Ok(()) => done += 1,
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                },
+            }
+            if progress.checkpoint(done, count).is_err() {
+                stop.cancel();
+                first_error.get_or_insert(MazeError::Cancelled);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    })
+}
+
+// Runs `job(index)` for jobs 1 through `count` on up to `threads` threads and hands every result to
+// `report` on the calling thread as its job finishes, in whatever order they finish. Unlike `run`,
+// every job runs whatever the others return.
+pub fn run_all<T, F, R>(count: usize, threads: usize, job: F, mut report: R)
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+    R: FnMut(usize, T),
+{
+    let next = AtomicUsize::new(1);
+    let (job, next) = (&job, &next);
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..threads.clamp(1, count.max(1)) {
+            let sender = sender.clone();
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index > count || sender.send((index, job(index))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        for (index, result) in receiver {
+            report(index, result);
+        }
+    });
+}
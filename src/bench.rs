@@ -0,0 +1,97 @@
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::metadata::Metadata;
+use crate::parallel::gen_maze_parallel;
+use crate::render::raster::{ImageFormat, Overlays, RasterRenderer, Style};
+use crate::render::stream::StreamingPpmRenderer;
+use crate::render::svg::SvgRenderer;
+use crate::render::text::TextRenderer;
+use crate::render::{image_size, Renderer, Theme};
+use crate::{gen_maze_with, Env};
+
+// Maze sizes (cells per side) timed when none are given
+pub const DEFAULT_SIZES: [usize; 3] = [32, 128, 512];
+// Every benchmark uses the same seed so runs are comparable
+const SEED: u64 = 1;
+
+fn per_sec(count: usize, elapsed: Duration) -> String {
+    let rate = count as f64 / elapsed.as_secs_f64().max(1e-9);
+    match rate {
+        r if r >= 1e9 => format!("{:.2}G", r / 1e9),
+        r if r >= 1e6 => format!("{:.2}M", r / 1e6),
+        r if r >= 1e3 => format!("{:.2}K", r / 1e3),
+        r => format!("{:.0}", r),
+    }
+}
+
+fn print_row(name: &str, size: usize, elapsed: Duration, cells: usize, pixels: Option<usize>) {
+    println!(
+        "{:<22} {:>9} {:>11.1} {:>12} {:>12}",
+        name, format!("{0}x{0}", size), elapsed.as_secs_f64() * 1000.0,
+        per_sec(cells, elapsed), pixels.map_or(String::from("-"), |p| per_sec(p, elapsed))
+    );
+}
+
+fn generate(size: usize) -> Env {
+    let mut env = Env::new(size, size);
+    gen_maze_with(&mut env, &mut StdRng::seed_from_u64(SEED));
+    env
+}
+
+// Times every generator and renderer on square mazes of each size in `sizes` and prints a table
+// of throughputs. Renderers write to a sink, so only rasterizing and encoding are measured.
+pub fn run(sizes: &[usize]) -> io::Result<()> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    println!("{:<22} {:>9} {:>11} {:>12} {:>12}", "benchmark", "size", "time (ms)", "cells/sec", "pixels/sec");
+
+    for &size in sizes {
+        let cells = size * size;
+        let start = Instant::now();
+        generate(size);
+        print_row("generate dfs", size, start.elapsed(), cells, None);
+
+        for t in [1, threads] {
+            let mut env = Env::new(size, size);
+            let start = Instant::now();
+            gen_maze_parallel(&mut env, SEED, t, &mut ()).expect("`()` never cancels");
+            print_row(&format!("generate regions x{}", t), size, start.elapsed(), cells, None);
+            if threads == 1 {
+                break;
+            }
+        }
+
+        let env = generate(size);
+        let theme = Theme::default();
+        let rasters = [
+            ("render ppm", ImageFormat::Ppm, Style::Blocky),
+            ("render png", ImageFormat::Png, Style::Blocky),
+            ("render qoi", ImageFormat::Qoi, Style::Blocky),
+            ("render ppm rounded", ImageFormat::Ppm, Style::Rounded),
+            ("render ppm isometric", ImageFormat::Ppm, Style::Isometric),
+        ];
+        for (name, format, style) in rasters {
+            let renderer = RasterRenderer { format, style, scale: 1, supersample: 1, overlays: Overlays::default(), metadata: Metadata::default() };
+            let start = Instant::now();
+            let canvas = renderer.rasterize(&env, &theme);
+            format.write(&mut io::sink(), &canvas)?;
+            print_row(name, size, start.elapsed(), cells, Some(canvas.width() * canvas.height()));
+        }
+
+        let others: [(&str, Box<dyn Renderer>, Option<usize>); 3] = [
+            ("render ppm --stream", Box::new(StreamingPpmRenderer { scale: 1, metadata: Metadata::default() }), Some(image_size(size) * image_size(size))),
+            ("render svg", Box::new(SvgRenderer::default()), None),
+            ("render txt", Box::new(TextRenderer { color: false }), None),
+        ];
+        for (name, renderer, pixels) in others {
+            let start = Instant::now();
+            renderer.render(&env, &theme, &mut io::sink())?;
+            print_row(name, size, start.elapsed(), cells, pixels);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::io::{self, Write};
+
+use crate::draw::Canvas;
+use crate::error::MazeError;
+
+// File header plus BITMAPINFOHEADER
+const HEADER_SIZE: usize = 14 + 40;
+// 2835 pixels per meter is 72 DPI
+const PIXELS_PER_METER: u32 = 2835;
+
+// Bytes per pixel row, which BMP pads to a multiple of four
+fn row_size(width: usize) -> usize {
+    (width * 3).next_multiple_of(4)
+}
+
+// Exact size in bytes of `write_bmp`'s output for a `width`x`height` canvas
+pub fn encoded_size(width: usize, height: usize) -> usize {
+    HEADER_SIZE + (row_size(width) * height)
+}
+
+// Pixels are in 0xRRGGBBAA format and are written as uncompressed 24-bit BGR, bottom row first.
+// Like PPM, the alpha channel is dropped.
+pub fn write_bmp(out: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+    let (width, height) = (canvas.width(), canvas.height());
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(b"BM");
+    header.extend_from_slice(&(encoded_size(width, height) as u32).to_le_bytes());
+    header.extend_from_slice(&[0; 4]);
+    header.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+    header.extend_from_slice(&40u32.to_le_bytes());
+    header.extend_from_slice(&(width as i32).to_le_bytes());
+    header.extend_from_slice(&(height as i32).to_le_bytes());
+    // One plane, 24 bits per pixel, no compression
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&24u16.to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(&((row_size(width) * height) as u32).to_le_bytes());
+    header.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+    header.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+    // No palette
+    header.extend_from_slice(&[0; 8]);
+    out.write_all(&header)?;
+
+    let mut row = Vec::with_capacity(row_size(width));
+    for pixels in canvas.pixels().chunks(width.max(1)).rev() {
+        row.clear();
+        for pixel in pixels {
+            let [r, g, b, _] = pixel.to_be_bytes();
+            row.extend_from_slice(&[b, g, r]);
+        }
+        row.resize(row_size(width), 0);
+        out.write_all(&row)?;
+    }
+    out.flush()
+}
+
+// Decodes an uncompressed BMP with 8 (palette), 24 or 32 bits per pixel into 0xRRGGBBAA pixels.
+// 32-bit pixels are read as BGRA, and an all-zero alpha channel as opaque.
+pub fn read_bmp(bytes: &[u8]) -> Result<Canvas, MazeError> {
+    let invalid = |message: &str| MazeError::InvalidData(format!("unsupported or broken BMP: {}", message));
+    if bytes.len() < HEADER_SIZE || !bytes.starts_with(b"BM") {
+        return Err(invalid("missing header"));
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]) as usize;
+    let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().expect("four bytes"));
+    let (offset, info_size) = (u32_at(10) as usize, u32_at(14) as usize);
+    let (width, height) = (u32_at(18) as i32, u32_at(22) as i32);
+    let (bits, compression) = (u16_at(28), u32_at(30));
+    // Bitfields with 32 bits per pixel are almost always plain BGRA masks
+    if compression != 0 && !(compression == 3 && bits == 32) {
+        return Err(invalid("compressed images are not supported"));
+    }
+    if width <= 0 || height == 0 || ![8, 24, 32].contains(&bits) {
+        return Err(invalid("unsupported dimensions or bits per pixel"));
+    }
+    let palette: Vec<[u8; 3]> = if bits == 8 {
+        let colors = match u32_at(46) as usize { 0 => 256, n => n };
+        let palette = bytes.get(14..).and_then(|rest| rest.get(info_size..)?.get(..colors.checked_mul(4)?));
+        palette.ok_or_else(|| invalid("truncated palette"))?.chunks(4).map(|c| [c[2], c[1], c[0]]).collect()
+    } else {
+        vec![]
+    };
+    // Positive heights store the bottom row first
+    let (width, top_down, height) = (width as usize, height < 0, height.unsigned_abs() as usize);
+    // Forged dimensions can overflow the size of the pixel data, which is then too short as well
+    let row_size = (width as u64 * bits as u64 / 8).next_multiple_of(4);
+    let data = match usize::try_from(row_size * height as u64) {
+        Ok(size) => bytes.get(offset..).and_then(|rest| rest.get(..size)),
+        Err(_) => None,
+    };
+    let (row_size, data) = (row_size as usize, data.ok_or_else(|| invalid("pixel data is too short"))?);
+    let opaque = bits != 32 || data.chunks(row_size).all(|row| row[..width * 4].chunks(4).all(|p| p[3] == 0));
+
+    let mut canvas = Canvas::new(width, height, 0);
+    for (i, row) in data.chunks(row_size).enumerate() {
+        let y = if top_down { i } else { height - 1 - i };
+        for x in 0..width {
+            let [r, g, b, a] = match bits {
+                8 => {
+                    let [r, g, b] = *palette.get(row[x] as usize).ok_or_else(|| invalid("palette index out of range"))?;
+                    [r, g, b, 0xFF]
+                },
+                24 => [row[3 * x + 2], row[3 * x + 1], row[3 * x], 0xFF],
+                _ => [row[4 * x + 2], row[4 * x + 1], row[4 * x], if opaque { 0xFF } else { row[4 * x + 3] }],
+            };
+            canvas.set(x, y, u32::from_be_bytes([r, g, b, a]));
+        }
+    }
+    Ok(canvas)
+}
@@ -0,0 +1,83 @@
+use std::io::{self, Write};
+
+use crate::analysis;
+use crate::pdf::{self, Pdf, FONT_BOLD, FONT_REGULAR, PAGE_HEIGHT, PAGE_WIDTH};
+use crate::render::pdf::{draw_maze, maze_box};
+use crate::render::Theme;
+use crate::solve::Solver;
+use crate::{Cell, Env};
+
+// Books are printed, so walls are black on white whatever the on-screen theme
+const INK: u32 = 0x000000FF;
+const PAPER: u32 = 0xFFFFFFFF;
+const MUTED: u32 = 0x606060FF;
+const MARGIN: f32 = 50.0;
+// Solutions are printed smaller, this many to a page
+const SOLUTION_COLS: usize = 2;
+const SOLUTION_ROWS: usize = 2;
+
+pub struct BookMaze {
+    pub env: Env,
+    pub seed: u64,
+}
+
+// Difficulty label of a maze solved from the top-left to the bottom-right cell, capitalized
+pub fn difficulty(env: &Env) -> String {
+    let goal = Cell { row: env.height - 1, col: env.width - 1 };
+    let label = analysis::analyze(env, Cell { row: 0, col: 0 }, goal).label();
+    label[..1].to_uppercase() + &label[1..]
+}
+
+fn solution(env: &Env) -> Vec<Cell> {
+    let goal = Cell { row: env.height - 1, col: env.width - 1 };
+    Solver::Bfs.solve(env, Cell { row: 0, col: 0 }, goal).expect("generated mazes are fully connected").path
+}
+
+// Start and finish labels next to the top-left and bottom-right corners of the maze area
+fn corner_labels(area: (f32, f32, f32, f32), size: f32) -> String {
+    let (x, y, w, h) = area;
+    pdf::text(x, y + 4.0, size, FONT_REGULAR, MUTED, "START")
+        + &pdf::text(x + w - pdf::text_width("FINISH", size), y - h - size - 2.0, size, FONT_REGULAR, MUTED, "FINISH")
+}
+
+// A puzzle book: a title page, one maze per page with its title and difficulty, then a solutions
+// section with every maze and its path, four to a page. Mazes are solved from the top-left to
+// the bottom-right cell.
+pub fn write_book(out: &mut dyn Write, title: &str, mazes: &[BookMaze]) -> io::Result<()> {
+    let theme = Theme { solid_color: INK, open_color: PAPER };
+    let paths: Vec<Vec<Cell>> = mazes.iter().map(|maze| solution(&maze.env)).collect();
+    let mut doc = Pdf::new();
+
+    doc.add_page(
+        pdf::centered_text(PAGE_HEIGHT * 0.6, 36.0, FONT_BOLD, INK, title)
+            + &pdf::centered_text(PAGE_HEIGHT * 0.6 - 40.0, 16.0, FONT_REGULAR, MUTED, &format!("{} mazes with solutions", mazes.len())),
+    );
+
+    for (i, maze) in mazes.iter().enumerate() {
+        let heading = format!("Maze {}", i + 1);
+        let label = format!("Difficulty: {}   {}x{}", difficulty(&maze.env), maze.env.width, maze.env.height);
+        let area = (MARGIN, PAGE_HEIGHT - 150.0, PAGE_WIDTH - (2.0 * MARGIN), PAGE_HEIGHT - 230.0);
+        let mut page = pdf::centered_text(PAGE_HEIGHT - 80.0, 28.0, FONT_BOLD, INK, &heading);
+        page += &pdf::centered_text(PAGE_HEIGHT - 110.0, 14.0, FONT_REGULAR, MUTED, &label);
+        page += &draw_maze(&maze.env, &theme, area, None);
+        page += &corner_labels(maze_box(&maze.env, area), 10.0);
+        page += &pdf::centered_text(40.0, 10.0, FONT_REGULAR, MUTED, &format!("seed {}", maze.seed));
+        doc.add_page(page);
+    }
+
+    let per_page = SOLUTION_COLS * SOLUTION_ROWS;
+    let (slot_w, slot_h) = ((PAGE_WIDTH - (2.0 * MARGIN)) / SOLUTION_COLS as f32, (PAGE_HEIGHT - 140.0) / SOLUTION_ROWS as f32);
+    for (page_index, chunk) in mazes.iter().zip(&paths).collect::<Vec<_>>().chunks(per_page).enumerate() {
+        let mut page = pdf::centered_text(PAGE_HEIGHT - 60.0, 24.0, FONT_BOLD, INK, "Solutions");
+        for (k, (maze, path)) in chunk.iter().enumerate() {
+            let number = (page_index * per_page) + k + 1;
+            let x = MARGIN + ((k % SOLUTION_COLS) as f32 * slot_w);
+            let y = PAGE_HEIGHT - 90.0 - ((k / SOLUTION_COLS) as f32 * slot_h);
+            let caption = format!("Maze {}", number);
+            page += &pdf::text(x + ((slot_w - pdf::text_width(&caption, 12.0)) / 2.0), y - 14.0, 12.0, FONT_BOLD, INK, &caption);
+            page += &draw_maze(&maze.env, &theme, (x + 10.0, y - 24.0, slot_w - 20.0, slot_h - 40.0), Some(path));
+        }
+        doc.add_page(page);
+    }
+    doc.write(out)
+}
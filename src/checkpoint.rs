@@ -0,0 +1,206 @@
+// Checkpoints of a long depth-first generation, so a maze that takes hours to carve survives the
+// process being stopped: `generate` saves the grid, the stack and the RNG state to a file every
+// `every` visited cells and once more at the end, and `resume` picks up from the last save.
+//
+// `StdRng` cannot be saved as it is, so at every checkpoint the generator draws a new seed from it,
+// saves that and reseeds from it. The same seed and interval therefore always carve the same maze,
+// interrupted or not; it is the one `gen_maze_with` carves from that seed only if it is finished
+// before the first checkpoint.
+//
+// The file is binary and little-endian:
+//
+//   "maze checkpoint 1\n", then width, height and the seed to resume from as u64s
+//   one byte per cell in row-major order: bit 0 the passage east, 1 the passage south, 2 left out
+//   the number of visited cells as a u64, then their indices in visit order as u32s
+//   the number of cells on the stack as a u64, then their indices from the bottom up as u32s
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::MazeError;
+use crate::progress::Progress;
+use crate::{Cell, Env, GenEvent, GeneratorSteps, MAX_CELLS, PROGRESS_STEP};
+
+const MAGIC: &[u8] = b"maze checkpoint 1\n";
+const EAST: u8 = 1 << 0;
+const SOUTH: u8 = 1 << 1;
+const EXCLUDED: u8 = 1 << 2;
+
+// A generation stopped partway
+pub struct Checkpoint {
+    // The maze as carved so far, with its visited cells and `visit_order`
+    pub env: Env,
+    // The path back from the current cell, bottom first
+    pub stack: Vec<Cell>,
+    // What the RNG is seeded with for the rest of the generation
+    pub seed: u64,
+}
+
+pub fn write(out: &mut dyn Write, env: &Env, stack: &[Cell], seed: u64) -> Result<(), MazeError> {
+    out.write_all(MAGIC)?;
+    for n in [env.width as u64, env.height as u64, seed] {
+        out.write_all(&n.to_le_bytes())?;
+    }
+    let cells: Vec<u8> = env
+        .cells()
+        .map(|cell| {
+            let east = if env.open_east(cell) { EAST } else { 0 };
+            let south = if env.open_south(cell) { SOUTH } else { 0 };
+            east | south | if env.is_excluded(cell) { EXCLUDED } else { 0 }
+        })
+        .collect();
+    out.write_all(&cells)?;
+    out.write_all(&(env.visit_order.len() as u64).to_le_bytes())?;
+    for &i in &env.visit_order {
+        out.write_all(&i.to_le_bytes())?;
+    }
+    out.write_all(&(stack.len() as u64).to_le_bytes())?;
+    for &cell in stack {
+        out.write_all(&(env.ind(cell) as u32).to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_u64(input: &mut dyn Read) -> Result<u64, MazeError> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+// `count` cell indices below `cells`, preceded by `count` itself
+fn read_indices(input: &mut dyn Read, cells: usize, what: &str) -> Result<Vec<usize>, MazeError> {
+    let count = read_u64(input)? as usize;
+    if count > cells {
+        return Err(MazeError::InvalidData(format!("{} {} cells in a maze of {}", count, what, cells)));
+    }
+    let mut bytes = vec![0; count * 4];
+    input.read_exact(&mut bytes)?;
+    let indices: Vec<usize> = bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize).collect();
+    match indices.iter().find(|&&i| i >= cells) {
+        Some(i) => Err(MazeError::InvalidData(format!("{} cell {} is outside the maze", what, i))),
+        None => Ok(indices),
+    }
+}
+
+pub fn read(input: &mut dyn Read) -> Result<Checkpoint, MazeError> {
+    let mut magic = [0; MAGIC.len()];
+    input.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(MazeError::InvalidData(String::from("not a maze checkpoint")));
+    }
+    let (width, height, seed) = (read_u64(input)?, read_u64(input)?, read_u64(input)?);
+    // The size is checked before anything is allocated, and the maze only once the file has
+    // actually held that many cells
+    let count = width.checked_mul(height).filter(|&count| count > 0 && count <= MAX_CELLS as u64);
+    let (Some(count), Ok(width), Ok(height)) = (count, usize::try_from(width), usize::try_from(height)) else {
+        return Err(MazeError::InvalidData(format!("a {}x{} maze is empty or too large", width, height)));
+    };
+    let mut cells = vec![];
+    input.take(count).read_to_end(&mut cells)?;
+    if cells.len() as u64 != count {
+        return Err(MazeError::InvalidData(format!("the checkpoint ends after {} of {} cells", cells.len(), count)));
+    }
+    let mut env = Env::try_new(width, height)?;
+    for (i, &bits) in cells.iter().enumerate() {
+        let cell = env.cell(i);
+        if bits & EXCLUDED != 0 {
+            env.exclude(cell);
+        }
+        if bits & EAST != 0 && cell.col + 1 < width {
+            env.carve(cell, Cell { row: cell.row, col: cell.col + 1 });
+        }
+        if bits & SOUTH != 0 && cell.row + 1 < height {
+            env.carve(cell, Cell { row: cell.row + 1, col: cell.col });
+        }
+    }
+    for i in read_indices(input, env.cell_count(), "visited")? {
+        env.mark_visited(env.cell(i));
+        env.visit_order.push(i as u32);
+    }
+    let stack = read_indices(input, env.cell_count(), "stacked")?.into_iter().map(|i| env.cell(i)).collect();
+    Ok(Checkpoint { env, stack, seed })
+}
+
+// Writes next to `path` first and then renames, so a process stopped while saving leaves the
+// previous checkpoint intact
+fn save(path: &str, env: &Env, stack: &[Cell], seed: u64) -> Result<(), MazeError> {
+    let partial = format!("{}.partial", path);
+    let mut out = BufWriter::new(File::create(&partial)?);
+    write(&mut out, env, stack, seed)?;
+    out.flush()?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+pub fn load(path: &str) -> Result<Checkpoint, MazeError> {
+    read(&mut BufReader::new(File::open(path)?))
+}
+
+// Carves `env`, which should have every wall standing, from `seed`, saving a checkpoint to `path`
+// every `every` visited cells and once more when it is done
+pub fn generate(env: &mut Env, seed: u64, path: &str, every: usize, progress: &mut dyn Progress) -> Result<(), MazeError> {
+    carve(env, vec![], seed, path, every, progress)
+}
+
+// The maze of `checkpoint`, carved to the end and saved to `path` along the way like `generate`.
+// A checkpoint of a finished generation gives the finished maze straight away.
+pub fn resume(checkpoint: Checkpoint, path: &str, every: usize, progress: &mut dyn Progress) -> Result<Env, MazeError> {
+    let Checkpoint { mut env, stack, seed } = checkpoint;
+    carve(&mut env, stack, seed, path, every, progress)?;
+    Ok(env)
+}
+
+fn carve(env: &mut Env, stack: Vec<Cell>, seed: u64, path: &str, every: usize, progress: &mut dyn Progress) -> Result<(), MazeError> {
+    let cell_count = env.cell_count();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut steps = if env.visit_order.is_empty() { GeneratorSteps::new(env, &mut rng) } else { GeneratorSteps::resume(env, &mut rng, stack) };
+    let mut carved = false;
+    while let Some(event) = steps.next() {
+        carved = true;
+        let visited = steps.env().visit_order.len();
+        if !matches!(event, GenEvent::Carve(..)) {
+            continue;
+        }
+        if visited.is_multiple_of(PROGRESS_STEP) {
+            progress.checkpoint(visited, cell_count)?;
+        }
+        // Counted over the whole generation, so resuming saves at the same points
+        if visited.is_multiple_of(every) {
+            let next = steps.rng().gen();
+            save(path, steps.env(), steps.stack(), next)?;
+            *steps.rng() = StdRng::seed_from_u64(next);
+        }
+    }
+    if carved {
+        let next = steps.rng().gen();
+        save(path, steps.env(), steps.stack(), next)?;
+    }
+    progress.update(cell_count, cell_count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(width: u64, height: u64) -> Vec<u8> {
+        [MAGIC, &width.to_le_bytes(), &height.to_le_bytes(), &7u64.to_le_bytes()].concat()
+    }
+
+    #[test]
+    fn rejects_bad_sizes_before_allocating() {
+        for (width, height) in [(1 << 40, 1 << 40), (u64::MAX, 2), (0, 3), (1 << 20, 1 << 20)] {
+            assert!(read(&mut &header(width, height)[..]).is_err(), "{}x{}", width, height);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_cells() {
+        let mut bytes = header(4, 4);
+        bytes.extend_from_slice(&[0; 15]);
+        assert!(read(&mut &bytes[..]).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+// Config files: default command-line options, plus named profiles that bundle the options for one
+// kind of output (size, theme, format...) so the same maze can be produced in several variants.
+// The format is a small subset of TOML:
+//
+//   # Applied to every run
+//   scale = 2
+//
+//   [profile.print]
+//   size = "60x40"
+//   format = "pdf"
+//
+//   [profile.web]
+//   size = 20
+//   format = "png"
+//   open-color = "#1e1e2e"
+//   transparent = true
+//
+// Every key is a command-line option without its leading dashes, one of those the caller accepts.
+// `true` turns a flag on and `false` leaves it off; any other value is passed to the option as it
+// is, quotes removed.
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::MazeError;
+
+pub const DEFAULT_PATH: &str = "maze.toml";
+
+pub struct Config {
+    // Key-value pairs before the first section
+    pub defaults: Vec<(String, String)>,
+    // Every `[profile.NAME]` section with its pairs, in file order
+    pub profiles: Vec<(String, Vec<(String, String)>)>,
+}
+
+fn invalid(line: usize, message: &str) -> MazeError {
+    MazeError::InvalidData(format!("line {}: {}", line, message))
+}
+
+impl Config {
+    // `options` are the option names a key may be
+    pub fn parse(text: &str, options: &[&str]) -> Result<Config, MazeError> {
+        let mut config = Config { defaults: vec![], profiles: vec![] };
+        for (i, line) in text.lines().enumerate() {
+            let number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = section.trim().strip_prefix("profile.").filter(|n| !n.is_empty());
+                let Some(name) = name else {
+                    return Err(invalid(number, "sections must be `[profile.NAME]`"));
+                };
+                if config.profile(name).is_some() {
+                    return Err(invalid(number, &format!("profile `{}` is defined twice", name)));
+                }
+                config.profiles.push((name.to_string(), vec![]));
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid(number, "expected `key = value`"));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+            if key.is_empty() || key.starts_with('-') || key.contains(char::is_whitespace) {
+                return Err(invalid(number, &format!("`{}` is not an option name", key)));
+            }
+            if !options.contains(&key) {
+                return Err(invalid(number, &format!("unknown option `{}`", key)));
+            }
+            let pairs = match config.profiles.last_mut() {
+                Some((_, pairs)) => pairs,
+                None => &mut config.defaults,
+            };
+            pairs.push((key.to_string(), value.to_string()));
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path, options: &[&str]) -> Result<Config, MazeError> {
+        Config::parse(&fs::read_to_string(path)?, options)
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&[(String, String)]> {
+        self.profiles.iter().find(|(n, _)| n == name).map(|(_, pairs)| pairs.as_slice())
+    }
+
+    // Command-line arguments for the defaults, followed by those of `profile` if one is named
+    pub fn args(&self, profile: Option<&str>) -> Result<Vec<String>, MazeError> {
+        let selected = match profile {
+            Some(name) => self.profile(name).ok_or_else(|| {
+                let names: Vec<&str> = self.profiles.iter().map(|(n, _)| n.as_str()).collect();
+                MazeError::InvalidData(format!("no profile `{}` (profiles: {})", name, if names.is_empty() { String::from("none") } else { names.join(", ") }))
+            })?,
+            None => &[],
+        };
+        let mut args = vec![];
+        for (key, value) in self.defaults.iter().chain(selected.iter()) {
+            match value.as_str() {
+                "true" => args.push(format!("--{}", key)),
+                "false" => {},
+                _ => args.extend([format!("--{}", key), value.clone()]),
+            }
+        }
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: &[&str] = &["size", "format", "transparent"];
+
+    #[test]
+    fn profiles_follow_the_defaults() {
+        let config = Config::parse("size = 20\n\n[profile.web]\nformat = \"png\"\ntransparent = true\n", OPTIONS).expect("a valid config");
+        assert_eq!(config.args(None).expect("no profile"), ["--size", "20"]);
+        assert_eq!(config.args(Some("web")).expect("a known profile"), ["--size", "20", "--format", "png", "--transparent"]);
+        assert!(config.args(Some("print")).is_err());
+    }
+
+    #[test]
+    fn unknown_options_name_their_line() {
+        let err = Config::parse("size = 20\n[profile.web]\nbogus = 1\n", OPTIONS).err().expect("an unknown option");
+        assert_eq!(err.to_string(), "line 3: unknown option `bogus`");
+    }
+}
@@ -0,0 +1,208 @@
+// Mazes on the surface of a cube: six `size` x `size` faces stitched together along their edges, so
+// passages run over the edges from one face onto the next and the maze wraps around like the
+// surface of a small planet. Cells are found by their position in space, so neighbours across a
+// face edge come out of the same lookup as neighbours within a face. Drawn either as an unfolded
+// net to cut out and fold, or as a cube-map strip of the six faces.
+//
+//        [Top]
+// [Left][Front][Right][Back]
+//       [Bottom]
+
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+
+use crate::draw::{draw_line, fill_rect, Canvas};
+use crate::graph::{tree_path, Algorithm, Graph};
+use crate::render::{Theme, BORDER_THICKNESS, OPEN_PATH_SIZE, TRANSPARENT_COLOR};
+
+const SOLUTION_COLOR: u32 = 0xE04040FF;
+const SOLUTION_THICKNESS: f32 = 4.0;
+
+type Vector = [i64; 3];
+
+// A face of the cube spanning 0..size on every axis: its top-left corner (as 0 or 1 times the
+// size), the directions its columns and rows run in, and the direction it faces
+struct Face {
+    corner: Vector,
+    right: Vector,
+    down: Vector,
+    normal: Vector,
+    // Position in the net, in faces
+    net: (usize, usize),
+}
+
+// In cube-map order: +X, -X, +Y, -Y, +Z, -Z, i.e. right, left, top, bottom, front, back
+const FACES: [Face; 6] = [
+    Face { corner: [1, 1, 1], right: [0, 0, -1], down: [0, -1, 0], normal: [1, 0, 0], net: (2, 1) },
+    Face { corner: [0, 1, 0], right: [0, 0, 1], down: [0, -1, 0], normal: [-1, 0, 0], net: (0, 1) },
+    Face { corner: [0, 1, 0], right: [1, 0, 0], down: [0, 0, 1], normal: [0, 1, 0], net: (1, 0) },
+    Face { corner: [0, 0, 1], right: [1, 0, 0], down: [0, 0, -1], normal: [0, -1, 0], net: (1, 2) },
+    Face { corner: [0, 1, 1], right: [1, 0, 0], down: [0, -1, 0], normal: [0, 0, 1], net: (1, 1) },
+    Face { corner: [1, 1, 0], right: [-1, 0, 0], down: [0, -1, 0], normal: [0, 0, -1], net: (3, 1) },
+];
+const FRONT: usize = 4;
+const BACK: usize = 5;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Layout {
+    // The cross-shaped net above, four faces wide and three high
+    Net,
+    // The six faces side by side in cube-map order
+    Strip,
+}
+
+impl Layout {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "net" => Layout::Net,
+            "strip" => Layout::Strip,
+            _ => return None,
+        })
+    }
+}
+
+fn add(a: Vector, b: Vector, times: i64) -> Vector {
+    [a[0] + (times * b[0]), a[1] + (times * b[1]), a[2] + (times * b[2])]
+}
+
+fn dot(a: Vector, b: Vector) -> i64 {
+    (a[0] * b[0]) + (a[1] * b[1]) + (a[2] * b[2])
+}
+
+pub struct CubeMaze {
+    // Cells along each side of a face
+    pub size: usize,
+    // Cells numbered face by face in `FACES` order, then row by row
+    pub graph: Graph,
+    pub passages: HashSet<(usize, usize)>,
+    // Top-left cell of the front face and bottom-right cell of the back one, on opposite sides
+    pub start: usize,
+    pub goal: usize,
+}
+
+// Cells of a cube with `size` x `size` faces and the neighbours of each, over face edges included
+pub fn cube_graph(size: usize) -> Graph {
+    let n = size as i64;
+    let mut ids = HashMap::new();
+    for (f, face) in FACES.iter().enumerate() {
+        for row in 0..size {
+            for col in 0..size {
+                ids.insert(center(face, n, row, col), (f * size * size) + (row * size) + col);
+            }
+        }
+    }
+    let mut edges = vec![];
+    for (f, face) in FACES.iter().enumerate() {
+        for row in 0..size {
+            for col in 0..size {
+                let (id, here) = ((f * size * size) + (row * size) + col, center(face, n, row, col));
+                for step in [face.right, face.down, add([0; 3], face.right, -1), add([0; 3], face.down, -1)] {
+                    // A whole cell along the face, or half a cell to its edge and half a cell down
+                    // the face on the other side
+                    let next = ids.get(&add(here, step, 2)).or_else(|| ids.get(&add(add(here, step, 1), face.normal, -1)));
+                    if let Some(&next) = next.filter(|&&next| id < next) {
+                        edges.push((id, next));
+                    }
+                }
+            }
+        }
+    }
+    Graph { nodes: 6 * size * size, edges }
+}
+
+// Twice the position of a cell's center, so it is whole
+fn center(face: &Face, n: i64, row: usize, col: usize) -> Vector {
+    let corner = face.corner.map(|k| 2 * k * n);
+    add(add(corner, face.right, (2 * col as i64) + 1), face.down, (2 * row as i64) + 1)
+}
+
+// Carves a maze over a cube with `size` x `size` faces with `algorithm`
+pub fn generate(size: usize, algorithm: Algorithm, rng: &mut impl Rng) -> CubeMaze {
+    let graph = cube_graph(size);
+    let passages = graph.spanning_tree(algorithm, rng).into_iter().collect();
+    let start = FRONT * size * size;
+    let goal = (BACK * size * size) + (size * size) - 1;
+    CubeMaze { size, graph, passages, start, goal }
+}
+
+impl CubeMaze {
+    // Face, row and column of a cell
+    fn locate(&self, id: usize) -> (usize, usize, usize) {
+        let per_face = self.size * self.size;
+        (id / per_face, (id % per_face) / self.size, id % self.size)
+    }
+
+    // Cells from `start` to `goal` along open passages
+    pub fn solve(&self) -> Vec<usize> {
+        let passages: Vec<(usize, usize)> = self.passages.iter().copied().collect();
+        tree_path(self.graph.nodes, &passages, self.start, self.goal)
+    }
+
+    // Which way `to` lies from `from` on the face of `from`, as columns and rows (-1, 0 or 1)
+    fn direction(&self, from: usize, to: usize) -> (i64, i64) {
+        let n = self.size as i64;
+        let ((fa, ra, ca), (fb, rb, cb)) = (self.locate(from), self.locate(to));
+        let delta = add(center(&FACES[fb], n, rb, cb), center(&FACES[fa], n, ra, ca), -1);
+        (dot(delta, FACES[fa].right).signum(), dot(delta, FACES[fa].down).signum())
+    }
+
+    // Rasterized in `layout` at `scale` pixels per logical pixel, optionally with the solution.
+    // Every face is drawn like a square maze, with gaps in its border where passages lead onto
+    // the next face; the space around the net is transparent.
+    pub fn render(&self, layout: Layout, theme: &Theme, scale: usize, solve: bool) -> Canvas {
+        let (open, border) = (OPEN_PATH_SIZE as usize, BORDER_THICKNESS as usize);
+        let pitch = open + border;
+        let side = self.size * pitch;
+        let place = |f: usize| match layout {
+            Layout::Net => (FACES[f].net.0 * side, FACES[f].net.1 * side),
+            Layout::Strip => (f * side, 0),
+        };
+        let (columns, rows) = match layout {
+            Layout::Net => (4, 3),
+            Layout::Strip => (6, 1),
+        };
+        let mut canvas = Canvas::new((columns * side) + border, (rows * side) + border, TRANSPARENT_COLOR);
+        for f in 0..FACES.len() {
+            let (x, y) = place(f);
+            fill_rect(&mut canvas, x, y, side + border, side + border, theme.solid_color);
+        }
+        // Top-left corner of every cell in the image, then every open side of every cell
+        let at = |id: usize| {
+            let (f, row, col) = self.locate(id);
+            let (x, y) = place(f);
+            (x + (col * pitch) + border, y + (row * pitch) + border)
+        };
+        for id in 0..self.graph.nodes {
+            let (x, y) = at(id);
+            fill_rect(&mut canvas, x, y, open, open, theme.open_color);
+        }
+        for &(a, b) in self.passages.iter() {
+            for (from, to) in [(a, b), (b, a)] {
+                let (x, y) = at(from);
+                match self.direction(from, to) {
+                    (1, _) => fill_rect(&mut canvas, x + open, y, border, open, theme.open_color),
+                    (-1, _) => fill_rect(&mut canvas, x - border, y, border, open, theme.open_color),
+                    (_, 1) => fill_rect(&mut canvas, x, y + open, open, border, theme.open_color),
+                    _ => fill_rect(&mut canvas, x, y - border, open, border, theme.open_color),
+                }
+            }
+        }
+        if solve {
+            // Half a step out of each cell towards the next, so crossings between faces that are
+            // apart in the image still show where the path leaves and comes back
+            let half = pitch as f32 / 2.0;
+            let path = self.solve();
+            for pair in path.windows(2) {
+                for (from, to) in [(pair[0], pair[1]), (pair[1], pair[0])] {
+                    let (x, y) = at(from);
+                    let middle = (x as f32 + (open as f32 / 2.0), y as f32 + (open as f32 / 2.0));
+                    let (dx, dy) = self.direction(from, to);
+                    let end = (middle.0 + (dx as f32 * half), middle.1 + (dy as f32 * half));
+                    draw_line(&mut canvas, middle, end, SOLUTION_THICKNESS, SOLUTION_COLOR);
+                }
+            }
+        }
+        canvas.upscale(scale)
+    }
+}
@@ -0,0 +1,124 @@
+// Mazes on the side of a cylinder: a grid whose last column neighbours its first, so passages can
+// leave through the right edge and come back in on the left, like a maze printed around a mug or
+// a side-scrolling level that loops. Only the columns wrap (see `Wrap::Horizontal`); the top and
+// bottom rows are the ends of the cylinder. A twisted cylinder is a Möbius strip instead: the
+// seam flips the rows over (see `Wrap::Mobius`), so a passage leaving the right edge of a row comes
+// back in on the left edge of the mirrored row. Drawn unrolled, with a matching pair of markers at
+// both ends of every passage across the seam.
+
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::draw::{draw_line, fill_convex_polygon, fill_rect, Canvas};
+use crate::graph::{tree_path, Algorithm, Graph, Wrap};
+use crate::render::{image_size, Theme, BORDER_THICKNESS, OPEN_PATH_SIZE, TRANSPARENT_COLOR};
+
+const SOLUTION_COLOR: u32 = 0xE04040FF;
+const SOLUTION_THICKNESS: f32 = 4.0;
+// Seam markers, one color per crossing from the top down, so the two ends of each pair up
+const SEAM_COLORS: [u32; 6] = [0x4080E0FF, 0xF0C030FF, 0xC060E0FF, 0x60C060FF, 0xF08040FF, 0x40E0E0FF];
+
+pub struct CylinderMaze {
+    pub width: usize,
+    pub height: usize,
+    // Whether the seam flips the rows over, making a Möbius strip
+    pub twisted: bool,
+    // Cells numbered row by row, like `Env::ind`
+    pub graph: Graph,
+    pub passages: HashSet<(usize, usize)>,
+    // Top-left cell, and the bottom cell halfway around the cylinder from it
+    pub start: usize,
+    pub goal: usize,
+}
+
+// Carves a maze around a cylinder, or a Möbius strip if `twisted`, `width` cells around and
+// `height` cells tall with `algorithm`
+pub fn generate(width: usize, height: usize, twisted: bool, algorithm: Algorithm, rng: &mut impl Rng) -> CylinderMaze {
+    let graph = Graph::wrapped_grid(width, height, if twisted { Wrap::Mobius } else { Wrap::Horizontal });
+    let passages = graph.spanning_tree(algorithm, rng).into_iter().collect();
+    let goal = ((height - 1) * width) + (width / 2);
+    CylinderMaze { width, height, twisted, graph, passages, start: 0, goal }
+}
+
+impl CylinderMaze {
+    // Cells from `start` to `goal` along open passages
+    pub fn solve(&self) -> Vec<usize> {
+        let passages: Vec<(usize, usize)> = self.passages.iter().copied().collect();
+        tree_path(self.graph.nodes, &passages, self.start, self.goal)
+    }
+
+    // Which way `to` lies from its neighbour `from` in the unrolled image, as columns and rows;
+    // across the seam that is off the edge of the image
+    fn direction(&self, from: usize, to: usize) -> (isize, isize) {
+        let (from_col, to_col) = ((from % self.width) as isize, (to % self.width) as isize);
+        match to_col - from_col {
+            0 => (0, if to > from { 1 } else { -1 }),
+            dc if dc.abs() == 1 => (dc, 0),
+            dc => (-dc.signum(), 0),
+        }
+    }
+
+    // Rows of the right and left ends of every passage across the seam, from the top down
+    fn seam_crossings(&self) -> Vec<(usize, usize)> {
+        let is_seam = |&&(a, b): &&(usize, usize)| self.width >= 3 && (a % self.width).abs_diff(b % self.width) == self.width - 1;
+        let mut rows: Vec<(usize, usize)> = self.passages.iter().filter(is_seam).map(|&(a, b)| {
+            let (left, right) = if a % self.width == 0 { (a, b) } else { (b, a) };
+            (right / self.width, left / self.width)
+        }).collect();
+        rows.sort_unstable();
+        rows
+    }
+
+    // Rasterized at `scale` pixels per logical pixel, optionally with the solution, with a
+    // transparent margin on both sides for the seam markers
+    pub fn render(&self, theme: &Theme, scale: usize, solve: bool) -> Canvas {
+        let (open, border) = (OPEN_PATH_SIZE as usize, BORDER_THICKNESS as usize);
+        let (pitch, margin) = (open + border, open);
+        let mut canvas = Canvas::new(image_size(self.width) + (2 * margin), image_size(self.height), TRANSPARENT_COLOR);
+        fill_rect(&mut canvas, margin, 0, image_size(self.width), image_size(self.height), theme.solid_color);
+        let at = |id: usize| (margin + ((id % self.width) * pitch) + border, ((id / self.width) * pitch) + border);
+        for id in 0..self.graph.nodes {
+            let (x, y) = at(id);
+            fill_rect(&mut canvas, x, y, open, open, theme.open_color);
+        }
+        for &(a, b) in self.passages.iter() {
+            for (from, to) in [(a, b), (b, a)] {
+                let (x, y) = at(from);
+                match self.direction(from, to) {
+                    (1, _) => fill_rect(&mut canvas, x + open, y, border, open, theme.open_color),
+                    (-1, _) => fill_rect(&mut canvas, x - border, y, border, open, theme.open_color),
+                    (_, 1) => fill_rect(&mut canvas, x, y + open, open, border, theme.open_color),
+                    _ => fill_rect(&mut canvas, x, y - border, open, border, theme.open_color),
+                }
+            }
+        }
+        // Arrows pointing off both edges where a row carries on around the other side, in the
+        // same color at both ends
+        let right = (margin + image_size(self.width)) as f32;
+        let span = |row: usize| (((row * pitch) + border) as f32, ((row * pitch) + border) as f32 + (open as f32 / 2.0), ((row * pitch) + pitch) as f32);
+        for (i, (right_row, left_row)) in self.seam_crossings().into_iter().enumerate() {
+            let color = SEAM_COLORS[i % SEAM_COLORS.len()];
+            let (top, middle, bottom) = span(left_row);
+            fill_convex_polygon(&mut canvas, &[(margin as f32, top), (margin as f32, bottom), (0.0, middle)], color);
+            let (top, middle, bottom) = span(right_row);
+            fill_convex_polygon(&mut canvas, &[(right, top), (right + margin as f32, middle), (right, bottom)], color);
+        }
+        if solve {
+            // Half a step out of each cell towards the next, so the path runs into the markers
+            // where it crosses the seam
+            let half = pitch as f32 / 2.0;
+            let path = self.solve();
+            for pair in path.windows(2) {
+                for (from, to) in [(pair[0], pair[1]), (pair[1], pair[0])] {
+                    let (x, y) = at(from);
+                    let middle = (x as f32 + (open as f32 / 2.0), y as f32 + (open as f32 / 2.0));
+                    let (dx, dy) = self.direction(from, to);
+                    let end = (middle.0 + (dx as f32 * half), middle.1 + (dy as f32 * half));
+                    draw_line(&mut canvas, middle, end, SOLUTION_THICKNESS, SOLUTION_COLOR);
+                }
+            }
+        }
+        canvas.upscale(scale)
+    }
+}
@@ -0,0 +1,249 @@
+// Deflate (RFC 1951) compression inside a zlib (RFC 1950) wrapper, for the PNG writers. Blocks use
+// the fixed Huffman codes with LZ77 matches found through hash chains, which is enough to squeeze
+// the long runs of equal pixels in maze images; a block that would not get smaller is stored as
+// it is, so the output never exceeds `max_zlib_size`.
+
+use crate::inflate::{DIST_BASE, DIST_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+
+// Input bytes per block, the most a stored block can hold
+const BLOCK_SIZE: usize = 0xFFFF;
+// How far back matches may reach
+const WINDOW: usize = 1 << 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+// Earlier positions with the same hash tried per match; more find longer matches, more slowly
+const MAX_CHAIN: usize = 64;
+// End of a hash chain
+const NONE: u32 = u32::MAX;
+const END_OF_BLOCK: usize = 256;
+
+pub(crate) fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for x in chunk {
+            a += *x as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+// Packs bits least significant first, as deflate reads them
+struct Bits {
+    bytes: Vec<u8>,
+    pending: u64,
+    count: u32,
+}
+
+impl Bits {
+    fn bits(&mut self, value: u32, count: u32) {
+        self.pending |= (value as u64) << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes are stored most significant bit first
+    fn code(&mut self, code: u32, len: u32) {
+        self.bits(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn align(&mut self) {
+        if self.count > 0 {
+            self.bits(0, 8 - self.count);
+        }
+    }
+}
+
+enum Token {
+    Literal(u8),
+    Match { len: usize, dist: usize },
+}
+
+// Fixed code and its length for literal/length symbol `symbol`
+fn literal_code(symbol: usize) -> (u32, u32) {
+    match symbol {
+        0..=143 => (0x30 + symbol as u32, 8),
+        144..=255 => (0x190 + (symbol - 144) as u32, 9),
+        256..=279 => ((symbol - 256) as u32, 7),
+        _ => (0xC0 + (symbol - 280) as u32, 8),
+    }
+}
+
+// Index into `LENGTH_BASE`/`DIST_BASE` of the code covering `value`
+fn base_index(bases: &[u16], value: usize) -> usize {
+    bases.iter().rposition(|&base| base as usize <= value).expect("lengths and distances are in range")
+}
+
+impl Token {
+    // Bits the token takes with the fixed codes
+    fn cost(&self) -> usize {
+        match *self {
+            Token::Literal(byte) => literal_code(byte as usize).1 as usize,
+            Token::Match { len, dist } => {
+                let (i, j) = (base_index(&LENGTH_BASE, len), base_index(&DIST_BASE, dist));
+                literal_code(257 + i).1 as usize + LENGTH_EXTRA[i] as usize + 5 + DIST_EXTRA[j] as usize
+            },
+        }
+    }
+
+    fn write(&self, out: &mut Bits) {
+        match *self {
+            Token::Literal(byte) => {
+                let (code, len) = literal_code(byte as usize);
+                out.code(code, len);
+            },
+            Token::Match { len, dist } => {
+                let (i, j) = (base_index(&LENGTH_BASE, len), base_index(&DIST_BASE, dist));
+                let (code, bits) = literal_code(257 + i);
+                out.code(code, bits);
+                out.bits((len - LENGTH_BASE[i] as usize) as u32, LENGTH_EXTRA[i] as u32);
+                out.code(j as u32, 5);
+                out.bits((dist - DIST_BASE[j] as usize) as u32, DIST_EXTRA[j] as u32);
+            },
+        }
+    }
+}
+
+// Earlier positions of every three-byte sequence, newest first
+struct Chains<'a> {
+    data: &'a [u8],
+    head: Vec<u32>,
+    prev: Vec<u32>,
+}
+
+impl Chains<'_> {
+    fn hash(&self, pos: usize) -> usize {
+        let bytes = u32::from_le_bytes([self.data[pos], self.data[pos + 1], self.data[pos + 2], 0]);
+        (bytes.wrapping_mul(0x9E3779B1) >> (32 - HASH_BITS)) as usize
+    }
+
+    fn insert(&mut self, pos: usize) {
+        if pos + MIN_MATCH <= self.data.len() {
+            let hash = self.hash(pos);
+            self.prev[pos % WINDOW] = self.head[hash];
+            self.head[hash] = pos as u32;
+        }
+    }
+
+    // Longest match for the bytes at `pos` that ends by `end`, as (length, distance)
+    fn longest(&self, pos: usize, end: usize) -> Option<(usize, usize)> {
+        let most = (end - pos).min(MAX_MATCH);
+        if most < MIN_MATCH {
+            return None;
+        }
+        let (mut best, mut candidate) = (None, self.head[self.hash(pos)]);
+        for _ in 0..MAX_CHAIN {
+            if candidate == NONE || pos - candidate as usize > WINDOW {
+                break;
+            }
+            let from = candidate as usize;
+            let len = self.data[from..].iter().zip(&self.data[pos..pos + most]).take_while(|(a, b)| a == b).count();
+            if len >= MIN_MATCH && best.is_none_or(|(best_len, _)| len > best_len) {
+                best = Some((len, pos - from));
+                if len == most {
+                    break;
+                }
+            }
+            // Slots are reused after `WINDOW` positions, so a chain only ever goes back
+            let next = self.prev[from % WINDOW];
+            if next == NONE || next as usize >= from {
+                break;
+            }
+            candidate = next;
+        }
+        best
+    }
+}
+
+// Largest size `zlib_compress` can make of `len` bytes: every block stored
+pub(crate) fn max_zlib_size(len: usize) -> usize {
+    2 + len + (len.div_ceil(BLOCK_SIZE).max(1) * 5) + 4
+}
+
+// Compresses `raw` into a zlib stream
+pub(crate) fn zlib_compress(raw: &[u8]) -> Vec<u8> {
+    // CMF/FLG: deflate, 32K window, no preset dictionary, "fast" compression
+    let mut out = Bits { bytes: Vec::with_capacity((raw.len() / 8) + 64), pending: 0, count: 0 };
+    out.bytes.extend_from_slice(&[0x78, 0x5E]);
+    let mut chains = Chains { data: raw, head: vec![NONE; 1 << HASH_BITS], prev: vec![NONE; WINDOW.min(raw.len())] };
+    let mut start = 0;
+    loop {
+        let end = (start + BLOCK_SIZE).min(raw.len());
+        let last = end == raw.len();
+        let mut tokens = vec![];
+        let mut pos = start;
+        while pos < end {
+            let token = match chains.longest(pos, end) {
+                Some((len, dist)) => Token::Match { len, dist },
+                None => Token::Literal(raw[pos]),
+            };
+            let len = if let Token::Match { len, .. } = token { len } else { 1 };
+            for at in pos..pos + len {
+                chains.insert(at);
+            }
+            tokens.push(token);
+            pos += len;
+        }
+
+        let fixed = 3 + tokens.iter().map(Token::cost).sum::<usize>() + literal_code(END_OF_BLOCK).1 as usize;
+        if fixed < 3 + 32 + (8 * (end - start)) {
+            out.bits(last as u32, 1);
+            out.bits(1, 2);
+            for token in tokens.iter() {
+                token.write(&mut out);
+            }
+            let (code, len) = literal_code(END_OF_BLOCK);
+            out.code(code, len);
+        } else {
+            out.bits(last as u32, 1);
+            out.bits(0, 2);
+            out.align();
+            let len = (end - start) as u16;
+            out.bytes.extend_from_slice(&len.to_le_bytes());
+            out.bytes.extend_from_slice(&(!len).to_le_bytes());
+            out.bytes.extend_from_slice(&raw[start..end]);
+        }
+        if last {
+            break;
+        }
+        start = end;
+    }
+    out.align();
+    out.bytes.extend_from_slice(&adler32(raw).to_be_bytes());
+    out.bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::inflate::zlib_decompress;
+
+    #[test]
+    fn round_trips_through_inflate() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let noise: Vec<u8> = (0..3 * BLOCK_SIZE).map(|_| rng.gen()).collect();
+        let runs: Vec<u8> = (0..200_000).map(|i| [0x20, 0x20, 0x20, 0xFF, 0xE0][(i / 37) % 5]).collect();
+        let mixed: Vec<u8> = noise.iter().zip(runs.iter()).map(|(&a, &b)| if a < 40 { a } else { b }).collect();
+        for raw in [vec![], vec![7], b"abcabcabcabcabd".to_vec(), noise, runs, mixed] {
+            let compressed = zlib_compress(&raw);
+            assert!(compressed.len() <= max_zlib_size(raw.len()));
+            assert_eq!(zlib_decompress(&compressed).expect("a valid stream"), raw);
+        }
+    }
+
+    #[test]
+    fn runs_compress() {
+        let raw: Vec<u8> = (0..100_000).map(|i| if (i / 400) % 2 == 0 { 0xFF } else { 0x10 }).collect();
+        assert!(zlib_compress(&raw).len() < raw.len() / 50);
+    }
+}
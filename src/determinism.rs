@@ -0,0 +1,61 @@
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::parallel::gen_maze_parallel;
+use crate::render::diff::diff;
+use crate::{gen_maze_with, Env};
+
+// Two runs with the same seed that carved different mazes
+#[derive(Debug)]
+pub struct Mismatch {
+    pub first: String,
+    pub second: String,
+    // Passages open in only one of the two mazes
+    pub differing: usize,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} and {} differ in {} passages", self.first, self.second, self.differing)
+    }
+}
+
+// The first run of `runs` that differs from `runs[0]`
+fn compare(runs: &[(String, Env)]) -> Result<(), Mismatch> {
+    let Some((first, reference)) = runs.first() else { return Ok(()) };
+    for (name, env) in runs[1..].iter() {
+        let changes = diff(reference, env).expect("every run has the same size");
+        if !changes.is_empty() {
+            return Err(Mismatch { first: first.clone(), second: name.clone(), differing: changes.only_a.len() + changes.only_b.len() });
+        }
+    }
+    Ok(())
+}
+
+// Generates the `width`x`height` maze for `seed` twice on one thread, and once with parallel
+// generation for every count in `thread_counts`, and checks that the serial runs carved the same
+// passages and so did the parallel ones. The two generators make different mazes from the same
+// seed, so they are only compared among themselves. Returns the number of runs that agreed.
+pub fn verify(width: usize, height: usize, seed: u64, thread_counts: &[usize]) -> Result<usize, Mismatch> {
+    let serial: Vec<(String, Env)> = (1..=2)
+        .map(|run| {
+            let mut env = Env::new(width, height);
+            gen_maze_with(&mut env, &mut StdRng::seed_from_u64(seed));
+            (format!("serial run {}", run), env)
+        })
+        .collect();
+    compare(&serial)?;
+
+    let parallel: Vec<(String, Env)> = thread_counts
+        .iter()
+        .map(|&threads| {
+            let mut env = Env::new(width, height);
+            gen_maze_parallel(&mut env, seed, threads, &mut ()).expect("`()` never cancels");
+            (format!("parallel generation on {} thread{}", threads, if threads == 1 { "" } else { "s" }), env)
+        })
+        .collect();
+    compare(&parallel)?;
+    Ok(serial.len() + parallel.len())
+}
@@ -0,0 +1,97 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::batch::maze_seed;
+use crate::graph::Algorithm;
+use crate::manifest::Generator;
+use crate::solve::Solver;
+use crate::{braid, Cell, Env};
+
+// Candidates generated while looking for a long enough solution; the longest one wins if none
+// of them reaches the preset's minimum
+const MAX_ATTEMPTS: usize = 64;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Extreme,
+}
+
+// What a difficulty level turns into: the size, the generator, the loops and the solution length
+#[derive(Copy, Clone, Debug)]
+pub struct Preset {
+    // Width and height, in cells
+    pub size: usize,
+    // Prim's many short dead ends make easy mazes; depth-first search's long winding corridors
+    // make hard ones
+    pub algorithm: Generator,
+    // Fraction of dead ends opened up into loops, see `braid`
+    pub braid: f32,
+    // Shortest acceptable solution from the top-left to the bottom-right cell, as a fraction of
+    // all cells
+    pub min_solution: f32,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Extreme];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Extreme => "extreme",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    pub fn preset(self) -> Preset {
+        match self {
+            Difficulty::Easy => Preset { size: 10, algorithm: Generator::Tree(Algorithm::Prim), braid: 0.0, min_solution: 0.0 },
+            Difficulty::Medium => Preset { size: 20, algorithm: Generator::Tree(Algorithm::Kruskal), braid: 0.0, min_solution: 0.1 },
+            Difficulty::Hard => Preset { size: 40, algorithm: Generator::Tree(Algorithm::Dfs), braid: 0.1, min_solution: 0.15 },
+            Difficulty::Extreme => Preset { size: 80, algorithm: Generator::Tree(Algorithm::Dfs), braid: 0.25, min_solution: 0.2 },
+        }
+    }
+}
+
+fn solution_len(env: &Env) -> usize {
+    let goal = Cell { row: env.height - 1, col: env.width - 1 };
+    Solver::Bfs.solve(env, Cell { row: 0, col: 0 }, goal).map_or(0, |s| s.path.len())
+}
+
+// Generates a `width`x`height` maze following `preset`. The first candidate is seeded with
+// `seed` itself, so presets without a minimum solution match `--seed` with the preset's
+// `--algorithm`; later candidates use `maze_seed(seed, attempt)`. Returns the maze and the seed of
+// the candidate that was kept.
+pub fn generate(preset: Preset, width: usize, height: usize, seed: u64) -> (Env, u64) {
+    let min_len = (preset.min_solution * (width * height) as f32).ceil() as usize;
+    let mut best: Option<(Env, u64, usize)> = None;
+    for attempt in 0..MAX_ATTEMPTS {
+        let candidate_seed = if attempt == 0 { seed } else { maze_seed(seed, attempt) };
+        let mut rng = StdRng::seed_from_u64(candidate_seed);
+        let mut env = Env::new(width, height);
+        preset.algorithm.carve(&mut env, &mut rng).expect("grids are carved along their own edges");
+        braid(&mut env, &mut rng, preset.braid);
+        let len = solution_len(&env);
+        if best.as_ref().is_none_or(|(_, _, best_len)| len > *best_len) {
+            best = Some((env, candidate_seed, len));
+        }
+        if len >= min_len {
+            break;
+        }
+    }
+    let (env, seed, _) = best.expect("at least one attempt");
+    (env, seed)
+}
+
+// `generate` with the seed of the first candidate drawn from `rng`, for callers supplying their
+// own randomness; the kept seed still reproduces the maze through `generate`
+pub fn generate_with(preset: Preset, width: usize, height: usize, rng: &mut impl Rng) -> (Env, u64) {
+    generate(preset, width, height, rng.gen())
+}
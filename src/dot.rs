@@ -0,0 +1,258 @@
+// Graphviz DOT: every cell is a node named "row,col" and every open passage an undirected edge.
+//
+//   graph maze {
+//       node [shape=point];
+//       "0,0" [pos="0,0!", start=true];
+//       "5,7" [pos="7,-5!", goal=true];
+//       "0,0" -- "0,1";
+//       ...
+//   }
+//
+// `pos` pins the cells to a grid for `neato -n`. The reader takes any DOT file whose node names
+// hold two numbers, row then column ("3,4", "r3c4", "cell_3_4"), sizes the maze to the largest
+// ones and carves every edge; `start`/`goal` attributes other than `false` mark those cells.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+
+use crate::error::MazeError;
+use crate::mazefile::MazeFile;
+use crate::render::{Renderer, Theme};
+use crate::{Cell, Env};
+
+pub const EXTENSIONS: [&str; 2] = ["dot", "gv"];
+
+fn node(out: &mut dyn Write, cell: Cell, attributes: &str) -> Result<(), MazeError> {
+    Ok(writeln!(out, "    \"{},{}\" [pos=\"{},{}!\"{}];", cell.row, cell.col, cell.col, -(cell.row as i64), attributes)?)
+}
+
+pub fn write(out: &mut dyn Write, maze: &MazeFile) -> Result<(), MazeError> {
+    write_parts(out, &maze.env, maze.start, maze.goal)
+}
+
+fn write_parts(out: &mut dyn Write, env: &Env, start: Cell, goal: Cell) -> Result<(), MazeError> {
+    writeln!(out, "graph maze {{")?;
+    writeln!(out, "    node [shape=point];")?;
+    if start == goal {
+        node(out, start, ", start=true, goal=true")?;
+    } else {
+        node(out, start, ", start=true")?;
+        node(out, goal, ", goal=true")?;
+    }
+    // The bottom-right cell fixes the size even when it is neither start nor goal and walled in
+    let corner = Cell { row: env.height - 1, col: env.width - 1 };
+    if corner != start && corner != goal {
+        node(out, corner, "")?;
+    }
+    for cell in env.cells() {
+        let east = Cell { row: cell.row, col: cell.col + 1 };
+        let south = Cell { row: cell.row + 1, col: cell.col };
+        for next in [east, south].into_iter().filter(|&n| n.row < env.height && n.col < env.width && env.has_passage(cell, n)) {
+            writeln!(out, "    \"{},{}\" -- \"{},{}\";", cell.row, cell.col, next.row, next.col)?;
+        }
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+// Saves plain mazes (default start and goal) through the common renderer interface
+pub struct DotRenderer;
+
+impl Renderer for DotRenderer {
+    fn render(&self, env: &Env, _theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        write_parts(out, env, Cell { row: 0, col: 0 }, Cell { row: env.height - 1, col: env.width - 1 })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    // A name, number or quoted string, quotes removed
+    Id(String),
+    // Any other punctuation: `{ } [ ] = ; ,` and the `--`/`->` edge operators
+    Symbol(&'static str),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, MazeError> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    let mut at_line_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                at_line_start = true;
+                continue;
+            },
+            c if c.is_whitespace() => continue,
+            // Preprocessor-style lines and line comments run to the end of the line
+            '#' if at_line_start => {
+                chars.by_ref().find(|&c| c == '\n');
+                continue;
+            },
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|&c| c == '\n');
+                at_line_start = true;
+                continue;
+            },
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                if !chars.by_ref().any(|c| std::mem::replace(&mut last, c) == '*' && c == '/') {
+                    return Err(MazeError::InvalidData(String::from("unterminated comment")));
+                }
+            },
+            '"' => {
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => id.extend(chars.next()),
+                        Some(c) => id.push(c),
+                        None => return Err(MazeError::InvalidData(String::from("unterminated string"))),
+                    }
+                }
+                tokens.push(Token::Id(id));
+            },
+            '-' if matches!(chars.peek(), Some('-') | Some('>')) => {
+                chars.next();
+                tokens.push(Token::Symbol("--"));
+            },
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' => {
+                let symbols = ["{", "}", "[", "]", "=", ";", ",", ":"];
+                tokens.push(Token::Symbol(symbols.into_iter().find(|s| s.starts_with(c)).expect("listed above")));
+            },
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = String::from(c);
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_' || **c == '.') {
+                    id.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Id(id));
+            },
+            c => return Err(MazeError::InvalidData(format!("unexpected `{}`", c))),
+        }
+        at_line_start = false;
+    }
+    Ok(tokens)
+}
+
+// Row and column named by a node, the first two numbers in its name
+fn cell(name: &str) -> Result<Cell, MazeError> {
+    let numbers: Vec<usize> = name.split(|c: char| !c.is_ascii_digit()).filter(|s| !s.is_empty()).filter_map(|s| s.parse().ok()).collect();
+    match numbers[..] {
+        [row, col] => Ok(Cell { row, col }),
+        _ => Err(MazeError::InvalidData(format!("node `{}` does not name a cell as row and column", name))),
+    }
+}
+
+// `[key=value, ...]` lists following a statement, as (key, value) pairs
+fn attributes(tokens: &[Token], i: &mut usize) -> Result<Vec<(String, String)>, MazeError> {
+    let mut pairs = vec![];
+    while tokens.get(*i) == Some(&Token::Symbol("[")) {
+        *i += 1;
+        loop {
+            match (tokens.get(*i), tokens.get(*i + 1), tokens.get(*i + 2)) {
+                (Some(Token::Symbol("]")), ..) => {
+                    *i += 1;
+                    break;
+                },
+                (Some(Token::Symbol(";" | ",")), ..) => *i += 1,
+                (Some(Token::Id(key)), Some(Token::Symbol("=")), Some(Token::Id(value))) => {
+                    pairs.push((key.clone(), value.clone()));
+                    *i += 3;
+                },
+                // A bare key means `key=true`
+                (Some(Token::Id(key)), ..) => {
+                    pairs.push((key.clone(), String::from("true")));
+                    *i += 1;
+                },
+                _ => return Err(MazeError::InvalidData(String::from("malformed attribute list"))),
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+pub fn read(mut input: impl Read) -> Result<MazeFile, MazeError> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let tokens = tokenize(&text)?;
+
+    let (mut nodes, mut edges) = (vec![], vec![]);
+    let (mut start, mut goal) = (None, None);
+    let mut i = 0;
+    while i < tokens.len() {
+        let Token::Id(name) = &tokens[i] else {
+            i += 1;
+            continue;
+        };
+        let keyword = name.to_ascii_lowercase();
+        i += 1;
+        match keyword.as_str() {
+            "strict" | "graph" | "digraph" | "subgraph" => {
+                // An optional graph name before `{`
+                if matches!(tokens.get(i), Some(Token::Id(_))) && keyword != "strict" {
+                    i += 1;
+                }
+                // `graph [...]` sets defaults for the whole graph
+                attributes(&tokens, &mut i)?;
+                continue;
+            },
+            "node" | "edge" => {
+                attributes(&tokens, &mut i)?;
+                continue;
+            },
+            _ => {},
+        }
+        // `key = value` statements set graph attributes
+        if tokens.get(i) == Some(&Token::Symbol("=")) {
+            i += 2;
+            continue;
+        }
+        // Ports (`node:port`) attach edges to the same cell
+        while tokens.get(i) == Some(&Token::Symbol(":")) {
+            i += 2;
+        }
+        let mut chain = vec![cell(name)?];
+        while tokens.get(i) == Some(&Token::Symbol("--")) {
+            let Some(Token::Id(next)) = tokens.get(i + 1) else {
+                return Err(MazeError::InvalidData(String::from("edges must join two named nodes")));
+            };
+            chain.push(cell(next)?);
+            i += 2;
+            while tokens.get(i) == Some(&Token::Symbol(":")) {
+                i += 2;
+            }
+        }
+        let attributes = attributes(&tokens, &mut i)?;
+        if chain.len() == 1 {
+            let marked = |key: &str| attributes.iter().any(|(k, v)| k == key && v != "false");
+            if marked("start") {
+                start = Some(chain[0]);
+            }
+            if marked("goal") {
+                goal = Some(chain[0]);
+            }
+        }
+        nodes.extend(chain.iter().copied());
+        edges.extend(chain.windows(2).map(|pair| (pair[0], pair[1])));
+    }
+
+    let height = nodes.iter().map(|c| c.row + 1).max().ok_or_else(|| MazeError::InvalidData(String::from("graph has no nodes")))?;
+    let width = nodes.iter().map(|c| c.col + 1).max().unwrap_or(0);
+    let mut env = Env::try_new(width, height)?;
+    for (a, b) in edges {
+        if a.row.abs_diff(b.row) + a.col.abs_diff(b.col) != 1 {
+            return Err(MazeError::InvalidData(format!("cells ({}, {}) and ({}, {}) are not neighbours", a.row, a.col, b.row, b.col)));
+        }
+        env.carve(a, b);
+    }
+    let mut maze = MazeFile::new(env);
+    maze.start = start.unwrap_or(maze.start);
+    maze.goal = goal.unwrap_or(maze.goal);
+    Ok(maze)
+}
+
+pub fn load(path: &Path) -> Result<MazeFile, MazeError> {
+    read(BufReader::new(File::open(path)?))
+}
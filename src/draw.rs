@@ -0,0 +1,289 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+// Rows handed to each worker by `Canvas::fill_bands`
+const BAND_ROWS: usize = 64;
+
+// Heap-allocated, row-major RGBA image. Pixels are in 0xRRGGBBAA format and every access is
+// bounds-checked, so large or oddly shaped images never index out of range.
+#[derive(Clone)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize, color: u32) -> Self {
+        Self { width, height, pixels: vec![color; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<u32> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    // Writes are silently dropped outside the canvas
+    pub fn set(&mut self, x: usize, y: usize, color: u32) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    // Calls `fill(y, row)` for every row of the canvas. Rows are grouped into independent bands
+    // of `BAND_ROWS`, which run in parallel when the `rayon` feature is enabled.
+    pub fn fill_bands<F>(&mut self, fill: F)
+    where
+        F: Fn(usize, &mut [u32]) + Send + Sync,
+    {
+        let width = self.width.max(1);
+        let band = |(i, rows): (usize, &mut [u32])| {
+            for (j, row) in rows.chunks_mut(width).enumerate() {
+                fill((i * BAND_ROWS) + j, row);
+            }
+        };
+        #[cfg(feature = "rayon")]
+        self.pixels.par_chunks_mut(width * BAND_ROWS).enumerate().for_each(band);
+        #[cfg(not(feature = "rayon"))]
+        self.pixels.chunks_mut(width * BAND_ROWS).enumerate().for_each(band);
+    }
+
+    // Nearest-neighbor upscale: every pixel becomes a `factor`x`factor` block
+    pub fn upscale(&self, factor: usize) -> Canvas {
+        let mut out = Canvas::new(self.width * factor, self.height * factor, 0);
+        out.fill_bands(|y, row| {
+            let src = &self.pixels[(y / factor) * self.width..][..self.width];
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = src[x / factor];
+            }
+        });
+        out
+    }
+
+    // Copy of the `w`x`h` region at (`x`, `y`), clipped to the canvas
+    pub fn crop(&self, x: usize, y: usize, w: usize, h: usize) -> Canvas {
+        let (x0, x1) = (x.min(self.width), x.saturating_add(w).min(self.width));
+        let (y0, y1) = (y.min(self.height), y.saturating_add(h).min(self.height));
+        let mut out = Canvas::new(x1 - x0, y1 - y0, 0);
+        for (dst, y) in out.pixels.chunks_exact_mut((x1 - x0).max(1)).zip(y0..y1) {
+            dst.copy_from_slice(&self.pixels[(y * self.width) + x0..(y * self.width) + x1]);
+        }
+        out
+    }
+
+    // Copy `src` onto this canvas with its top-left corner at (`x`, `y`), clipped to the canvas
+    pub fn blit(&mut self, src: &Canvas, x: usize, y: usize) {
+        let w = src.width.min(self.width.saturating_sub(x));
+        // Nothing lands on the canvas, and the first row's offset may already be past its end
+        if w == 0 {
+            return;
+        }
+        for sy in 0..src.height.min(self.height.saturating_sub(y)) {
+            let dst = ((y + sy) * self.width) + x;
+            self.pixels[dst..dst + w].copy_from_slice(&src.pixels[sy * src.width..][..w]);
+        }
+    }
+
+    // Box-filter downsample: every `factor`x`factor` block is averaged into one pixel
+    pub fn downsample(&self, factor: usize) -> Canvas {
+        let mut out = Canvas::new(self.width / factor, self.height / factor, 0);
+        let samples = (factor * factor) as u32;
+        out.fill_bands(|oy, row| {
+            for (ox, out_pixel) in row.iter_mut().enumerate() {
+                let mut sum = [0u32; 4];
+                for y in (oy * factor)..((oy + 1) * factor) {
+                    for x in (ox * factor)..((ox + 1) * factor) {
+                        let pixel = self.pixels[y * self.width + x];
+                        for (i, channel) in sum.iter_mut().enumerate() {
+                            *channel += (pixel >> (8 * (3 - i))) & 0xFF;
+                        }
+                    }
+                }
+                *out_pixel = sum.iter().fold(0, |acc, channel| (acc << 8) | (channel / samples));
+            }
+        });
+        out
+    }
+}
+
+// Drawing primitives work at any resolution and clip shapes against the canvas instead of
+// asserting, so shapes partly or entirely off the canvas (or with non-finite coordinates) just
+// draw fewer pixels.
+
+fn clip_span(lo: f32, hi: f32, max: usize) -> (usize, usize) {
+    let lo = lo.floor().max(0.0) as usize;
+    let hi = (hi.ceil().max(0.0) as usize).min(max);
+    (lo.min(hi), hi)
+}
+
+// Clipped to the canvas, then filled one row slice at a time
+pub fn fill_rect(canvas: &mut Canvas, rx: usize, ry: usize, rw: usize, rh: usize, color: u32) {
+    let (width, height) = (canvas.width, canvas.height);
+    let (x0, x1) = (rx.min(width), rx.saturating_add(rw).min(width));
+    let (y0, y1) = (ry.min(height), ry.saturating_add(rh).min(height));
+    if x0 == x1 {
+        return;
+    }
+    for row in canvas.pixels[(y0 * width)..(y1 * width)].chunks_exact_mut(width) {
+        row[x0..x1].fill(color);
+    }
+}
+
+// Fill every pixel whose center lies within `r` of (`cx`, `cy`)
+pub fn fill_circle(canvas: &mut Canvas, cx: f32, cy: f32, r: f32, color: u32) {
+    let (width, height) = (canvas.width, canvas.height);
+    let (x0, x1) = clip_span(cx - r, cx + r, width);
+    let (y0, y1) = clip_span(cy - r, cy + r, height);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            if dx * dx + dy * dy <= r * r {
+                canvas.pixels[y * width + x] = color;
+            }
+        }
+    }
+}
+
+// Distance from (`px`, `py`) to the closest point of the segment from `a` to `b`
+fn segment_distance(px: f32, py: f32, a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 { (((px - a.0) * dx) + ((py - a.1) * dy)) / length_sq } else { 0.0 };
+    let t = t.clamp(0.0, 1.0);
+    let (ex, ey) = (a.0 + (t * dx) - px, a.1 + (t * dy) - py);
+    (ex * ex + ey * ey).sqrt()
+}
+
+// Stroke `thickness` wide from `a` to `b` with round caps: every pixel whose center lies within
+// half the thickness of the segment
+pub fn draw_line(canvas: &mut Canvas, a: (f32, f32), b: (f32, f32), thickness: f32, color: u32) {
+    let (width, height) = (canvas.width, canvas.height);
+    let half = thickness / 2.0;
+    let (x0, x1) = clip_span(a.0.min(b.0) - half, a.0.max(b.0) + half, width);
+    let (y0, y1) = clip_span(a.1.min(b.1) - half, a.1.max(b.1) + half, height);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            if segment_distance(x as f32 + 0.5, y as f32 + 0.5, a, b) <= half {
+                canvas.pixels[y * width + x] = color;
+            }
+        }
+    }
+}
+
+// `draw_line` through every point in turn; the round caps join the segments without gaps. A
+// single point is drawn as a dot.
+pub fn draw_polyline(canvas: &mut Canvas, points: &[(f32, f32)], thickness: f32, color: u32) {
+    if let [point] = points {
+        draw_line(canvas, *point, *point, thickness, color);
+    }
+    for pair in points.windows(2) {
+        draw_line(canvas, pair[0], pair[1], thickness, color);
+    }
+}
+
+// Outline of the circle of radius `r` around (`cx`, `cy`), `thickness` wide and centered on it
+pub fn draw_circle(canvas: &mut Canvas, cx: f32, cy: f32, r: f32, thickness: f32, color: u32) {
+    let (width, height) = (canvas.width, canvas.height);
+    let half = thickness / 2.0;
+    let outer = r + half;
+    let (x0, x1) = clip_span(cx - outer, cx + outer, width);
+    let (y0, y1) = clip_span(cy - outer, cy + outer, height);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            if ((dx * dx + dy * dy).sqrt() - r).abs() <= half {
+                canvas.pixels[y * width + x] = color;
+            }
+        }
+    }
+}
+
+// Fill the rectangle (`rx`, `ry`, `rw`, `rh`) with its corners rounded off by `radius`
+pub fn fill_rounded_rect(canvas: &mut Canvas, rx: f32, ry: f32, rw: f32, rh: f32, radius: f32, color: u32) {
+    let (width, height) = (canvas.width, canvas.height);
+    let radius = radius.min(rw / 2.0).min(rh / 2.0);
+    let (x0, x1) = clip_span(rx, rx + rw, width);
+    let (y0, y1) = clip_span(ry, ry + rh, height);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let px = x as f32 + 0.5;
+            let py = y as f32 + 0.5;
+            if px < rx || px > rx + rw || py < ry || py > ry + rh {
+                continue;
+            }
+            // Distance from the pixel to the inner rectangle the corner arcs are centered on
+            let dx = (rx + radius - px).max(px - (rx + rw - radius)).max(0.0);
+            let dy = (ry + radius - py).max(py - (ry + rh - radius)).max(0.0);
+            if dx * dx + dy * dy <= radius * radius {
+                canvas.pixels[y * width + x] = color;
+            }
+        }
+    }
+}
+
+// Fill a convex polygon given its vertices in either winding order
+pub fn fill_convex_polygon(canvas: &mut Canvas, points: &[(f32, f32)], color: u32) {
+    let (width, height) = (canvas.width, canvas.height);
+    let min_x = points.iter().map(|p| p.0).fold(f32::INFINITY, f32::min);
+    let max_x = points.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+    let min_y = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+    let max_y = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+    let (x0, x1) = clip_span(min_x, max_x, width);
+    let (y0, y1) = clip_span(min_y, max_y, height);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let mut sign = 0.0f32;
+            let inside = points.iter().zip(points.iter().cycle().skip(1)).all(|(a, b)| {
+                let cross = (b.0 - a.0) * (py - a.1) - (b.1 - a.1) * (px - a.0);
+                if cross == 0.0 {
+                    return true;
+                }
+                if sign == 0.0 {
+                    sign = cross.signum();
+                }
+                cross.signum() == sign
+            });
+            if inside {
+                canvas.pixels[y * width + x] = color;
+            }
+        }
+    }
+}
+
+// Scale the RGB channels of a 0xRRGGBBAA color by `factor`, keeping its alpha
+pub fn shade(color: u32, factor: f32) -> u32 {
+    let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * factor).clamp(0.0, 255.0) as u32) << shift;
+    channel(24) | channel(16) | channel(8) | (color & 0xFF)
+}
+
+// Linear blend between two 0xRRGGBBAA colors, `t` = 0 gives `a` and `t` = 1 gives `b`
+pub fn lerp_color(a: u32, b: u32, t: f32) -> u32 {
+    let t = t.clamp(0.0, 1.0);
+    (0..4).fold(0, |acc, i| {
+        let shift = 8 * (3 - i);
+        let ca = ((a >> shift) & 0xFF) as f32;
+        let cb = ((b >> shift) & 0xFF) as f32;
+        acc | ((ca + (cb - ca) * t).round() as u32) << shift
+    })
+}
+
+// Composite `over` on top of `under` using `over`'s alpha ("source over")
+pub fn blend(under: u32, over: u32) -> u32 {
+    let alpha = (over & 0xFF) as f32 / 255.0;
+    let out_alpha = alpha + ((under & 0xFF) as f32 / 255.0) * (1.0 - alpha);
+    let rgb = lerp_color(under, over, alpha) & 0xFFFFFF00;
+    rgb | (out_alpha * 255.0).round() as u32
+}
@@ -0,0 +1,207 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute, queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+
+use crate::mazefile::{self, MazeFile};
+use crate::play::{scroll, wall_color, BLOCK_WIDTH};
+use crate::solve::Solver;
+use crate::{Cell, Env};
+
+const PATH_COLOR: Color = Color::Blue;
+const START_COLOR: Color = Color::Green;
+const GOAL_COLOR: Color = Color::Red;
+const CURSOR_COLOR: Color = Color::Yellow;
+
+// The two cells on either side of wall slot (`i`, `j`) of the block map, if it is an interior
+// slot (posts, cells and the outer border have none)
+fn slot_cells(env: &Env, i: usize, j: usize) -> Option<(Cell, Cell)> {
+    match (i % 2, j % 2) {
+        (1, 0) if j > 0 && j < 2 * env.width => Some((Cell { row: i / 2, col: (j / 2) - 1 }, Cell { row: i / 2, col: j / 2 })),
+        (0, 1) if i > 0 && i < 2 * env.height => Some((Cell { row: (i / 2) - 1, col: j / 2 }, Cell { row: i / 2, col: j / 2 })),
+        _ => None,
+    }
+}
+
+struct Editor {
+    maze: MazeFile,
+    path: String,
+    // Cursor position on the block map, so it can sit on cells and wall slots alike
+    cursor: (usize, usize),
+    // Blocks covered by the current solution, indexed like the block map
+    solution: Vec<bool>,
+    solution_len: Option<usize>,
+    message: String,
+    dirty: bool,
+}
+
+impl Editor {
+    fn cursor_cell(&self) -> Option<Cell> {
+        let (i, j) = self.cursor;
+        (i % 2 == 1 && j % 2 == 1).then_some(Cell { row: i / 2, col: j / 2 })
+    }
+
+    fn block_index(&self, i: usize, j: usize) -> usize {
+        i * ((2 * self.maze.env.width) + 1) + j
+    }
+
+    // Re-run the solver after every change so the path on screen is always current
+    fn resolve(&mut self) {
+        let (rows, cols) = self.maze.env.block_dims();
+        self.solution = vec![false; rows * cols];
+        let solution = Solver::Bfs.solve(&self.maze.env, self.maze.start, self.maze.goal);
+        self.solution_len = solution.as_ref().map(|s| s.path.len());
+        for pair in solution.iter().flat_map(|s| s.path.windows(2)) {
+            let (a, b) = (pair[0], pair[1]);
+            for (i, j) in [((2 * a.row) + 1, (2 * a.col) + 1), (a.row + b.row + 1, a.col + b.col + 1), ((2 * b.row) + 1, (2 * b.col) + 1)] {
+                let index = self.block_index(i, j);
+                self.solution[index] = true;
+            }
+        }
+    }
+
+    // Whether the maze is still a proper one, for the status line
+    fn connectivity(&self) -> String {
+        let env = &self.maze.env;
+        let cut_off = env.cell_count() - env.reachable_count(self.maze.start);
+        if cut_off > 0 {
+            format!("{} cells cut off", cut_off)
+        } else if env.passage_count() + 1 > env.cell_count() {
+            format!("{} loops", env.passage_count() + 1 - env.cell_count())
+        } else {
+            String::from("perfect")
+        }
+    }
+
+    fn toggle(&mut self) {
+        let (i, j) = self.cursor;
+        let Some((a, b)) = slot_cells(&self.maze.env, i, j) else {
+            self.message = String::from("only interior wall slots can be toggled");
+            return;
+        };
+        let open = !self.maze.env.has_passage(a, b);
+        self.maze.env.set_passage(a, b, open);
+        self.dirty = true;
+        self.message.clear();
+        self.resolve();
+    }
+
+    fn save(&mut self) {
+        let result = File::create(&self.path).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            mazefile::write(&mut out, &self.maze)?;
+            out.flush()
+        });
+        self.message = match result {
+            Ok(()) => {
+                self.dirty = false;
+                format!("saved to {}", self.path)
+            },
+            Err(err) => format!("failed to save to {}: {}", self.path, err),
+        };
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let env = &self.maze.env;
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        let (block_rows, block_cols) = env.block_dims();
+        let view_rows = (rows as usize).saturating_sub(2).max(1).min(block_rows);
+        let view_cols = (cols as usize / BLOCK_WIDTH).max(1).min(block_cols);
+        let top = scroll(self.cursor.0, view_rows, block_rows);
+        let left = scroll(self.cursor.1, view_cols, block_cols);
+        let block_of = |cell: Cell| ((2 * cell.row) + 1, (2 * cell.col) + 1);
+
+        queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+        for (y, i) in (top..top + view_rows).enumerate() {
+            queue!(out, cursor::MoveTo(0, y as u16))?;
+            for j in left..left + view_cols {
+                let solid = env.is_solid_block(i, j);
+                let (color, glyph) = if (i, j) == block_of(self.maze.start) {
+                    (START_COLOR, "S ")
+                } else if (i, j) == block_of(self.maze.goal) {
+                    (GOAL_COLOR, "G ")
+                } else if solid {
+                    (wall_color(), "██")
+                } else if self.solution[self.block_index(i, j)] {
+                    (PATH_COLOR, "░░")
+                } else {
+                    (Color::Reset, "  ")
+                };
+                if (i, j) == self.cursor {
+                    queue!(out, SetForegroundColor(CURSOR_COLOR), SetAttribute(Attribute::Reverse), Print(glyph), SetAttribute(Attribute::NoReverse))?;
+                } else {
+                    queue!(out, SetForegroundColor(color), Print(glyph))?;
+                }
+            }
+        }
+        let path = self.solution_len.map_or(String::from("no path"), |len| format!("path {} cells", len));
+        let dirty = if self.dirty { " *" } else { "" };
+        let status = format!("{}{}  {}x{}  {}  {}  {}", self.path, dirty, env.width, env.height, self.connectivity(), path, self.message);
+        let help = "Arrows/HJKL: move  Space: toggle wall  S/G: set start/goal  W: save  Q: quit";
+        queue!(out, ResetColor, cursor::MoveTo(0, view_rows as u16), Print(status), cursor::MoveTo(0, view_rows as u16 + 1), Print(help))?;
+        out.flush()
+    }
+}
+
+// Terminal editor: walls can be toggled one at a time, start and goal moved, and the result saved
+// as a `.maze` file at `path`. The solution is recomputed after every change, and the status line
+// says whether the maze is still connected and loop-free.
+pub fn edit(maze: MazeFile, path: &str) -> io::Result<()> {
+    let (rows, cols) = maze.env.block_dims();
+    let mut editor = Editor {
+        maze,
+        path: path.to_string(),
+        cursor: (1, 1),
+        solution: vec![false; rows * cols],
+        solution_len: None,
+        message: String::new(),
+        dirty: false,
+    };
+    editor.resolve();
+    let mut out = io::stdout();
+
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = (|| -> io::Result<()> {
+        let mut confirm_quit = false;
+        loop {
+            editor.draw(&mut out)?;
+            let Event::Key(key) = event::read()? else { continue };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let (i, j) = editor.cursor;
+            let quitting = matches!(key.code, KeyCode::Char('q') | KeyCode::Esc);
+            match key.code {
+                KeyCode::Char('k') | KeyCode::Up => editor.cursor.0 = i.saturating_sub(1),
+                KeyCode::Char('j') | KeyCode::Down => editor.cursor.0 = (i + 1).min(rows - 1),
+                KeyCode::Char('h') | KeyCode::Left => editor.cursor.1 = j.saturating_sub(1),
+                KeyCode::Char('l') | KeyCode::Right => editor.cursor.1 = (j + 1).min(cols - 1),
+                KeyCode::Char(' ') | KeyCode::Enter => editor.toggle(),
+                KeyCode::Char(key @ ('s' | 'g')) => match editor.cursor_cell() {
+                    Some(cell) => {
+                        if key == 's' { editor.maze.start = cell } else { editor.maze.goal = cell }
+                        editor.dirty = true;
+                        editor.message.clear();
+                        editor.resolve();
+                    },
+                    None => editor.message = String::from("move the cursor onto a cell first"),
+                },
+                KeyCode::Char('w') => editor.save(),
+                _ if quitting && (!editor.dirty || confirm_quit) => return Ok(()),
+                _ if quitting => editor.message = String::from("unsaved changes; press Q again to quit without saving"),
+                _ => {},
+            }
+            confirm_quit = quitting;
+        }
+    })();
+    execute!(out, ResetColor, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
@@ -0,0 +1,166 @@
+// C API. Doc comments here use `///` because cbindgen copies them into `include/maze.h`.
+
+use std::ffi::{c_char, c_int, CStr};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ptr;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::metadata::Metadata;
+use crate::render::raster::{ImageFormat, Overlays, RasterRenderer, Style};
+use crate::render::svg::SvgRenderer;
+use crate::render::text::TextRenderer;
+use crate::render::{Renderer, Theme};
+use crate::solve::Solver;
+use crate::{gen_maze_with, Cell, Env};
+
+/// Opaque maze handle returned by `maze_generate` and released with `maze_free`.
+pub struct Maze {
+    env: Env,
+}
+
+/// A cell position; row 0 is the top row and column 0 the left column.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct MazeCell {
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Output formats understood by `maze_export`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum MazeFormat {
+    Ppm,
+    Png,
+    Svg,
+    Text,
+}
+
+fn blocky(scale: usize) -> RasterRenderer {
+    RasterRenderer { format: ImageFormat::Png, style: Style::Blocky, scale: scale.max(1), supersample: 1, overlays: Overlays::default(), metadata: Metadata::default() }
+}
+
+/// Generates a `width` x `height` maze from `seed`; the same seed gives the same maze as
+/// `maze --seed`. Returns NULL if either dimension is zero, the maze would have more than
+/// 2^32 - 1 cells, or it cannot be allocated.
+#[no_mangle]
+pub extern "C" fn maze_generate(width: usize, height: usize, seed: u64) -> *mut Maze {
+    let Ok(mut env) = Env::try_new(width, height) else {
+        return ptr::null_mut();
+    };
+    gen_maze_with(&mut env, &mut StdRng::seed_from_u64(seed));
+    Box::into_raw(Box::new(Maze { env }))
+}
+
+/// Releases a maze. Passing NULL is a no-op.
+///
+/// # Safety
+/// `maze` must be NULL or a handle from `maze_generate` that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn maze_free(maze: *mut Maze) {
+    if !maze.is_null() {
+        drop(Box::from_raw(maze));
+    }
+}
+
+/// # Safety
+/// `maze` must be a live handle from `maze_generate`.
+#[no_mangle]
+pub unsafe extern "C" fn maze_width(maze: *const Maze) -> usize {
+    (*maze).env.width
+}
+
+/// # Safety
+/// `maze` must be a live handle from `maze_generate`.
+#[no_mangle]
+pub unsafe extern "C" fn maze_height(maze: *const Maze) -> usize {
+    (*maze).env.height
+}
+
+/// Bitmask of the open sides of a cell: 1 = north, 2 = east, 4 = south, 8 = west. Cells outside
+/// the maze have no open sides.
+///
+/// # Safety
+/// `maze` must be a live handle from `maze_generate`.
+#[no_mangle]
+pub unsafe extern "C" fn maze_open_sides(maze: *const Maze, cell: MazeCell) -> u8 {
+    let env = &(*maze).env;
+    if cell.row >= env.height || cell.col >= env.width {
+        return 0;
+    }
+    env.open_sides(Cell { row: cell.row, col: cell.col })
+}
+
+/// Finds the shortest path from `start` to `goal` and writes up to `capacity` of its cells
+/// (start and goal included) to `path`. Returns the full path length, so a return value larger
+/// than `capacity` means the buffer was too small; 0 means a cell was outside the maze.
+///
+/// # Safety
+/// `maze` must be a live handle from `maze_generate` and `path` must point to at least
+/// `capacity` writable `MazeCell`s (it may be NULL when `capacity` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn maze_solve(maze: *const Maze, start: MazeCell, goal: MazeCell, path: *mut MazeCell, capacity: usize) -> usize {
+    let env = &(*maze).env;
+    let inside = |c: MazeCell| c.row < env.height && c.col < env.width;
+    if !inside(start) || !inside(goal) {
+        return 0;
+    }
+    let start = Cell { row: start.row, col: start.col };
+    let goal = Cell { row: goal.row, col: goal.col };
+    let Some(solution) = Solver::Bfs.solve(env, start, goal) else { return 0 };
+    for (i, cell) in solution.path.iter().take(capacity).enumerate() {
+        *path.add(i) = MazeCell { row: cell.row, col: cell.col };
+    }
+    solution.path.len()
+}
+
+/// Rasterizes the maze (blocky style, `scale` pixels per logical pixel) as 8-bit RGBA rows,
+/// ready to upload as a texture. Writes nothing unless `capacity` bytes are enough and returns
+/// the number of bytes the image needs; `width`/`height` receive its size when not NULL.
+///
+/// # Safety
+/// `maze` must be a live handle from `maze_generate`, `pixels` must point to `capacity`
+/// writable bytes (or be NULL with `capacity` 0) and `width`/`height` must be NULL or writable.
+#[no_mangle]
+pub unsafe extern "C" fn maze_render_rgba(maze: *const Maze, scale: usize, pixels: *mut u8, capacity: usize, width: *mut usize, height: *mut usize) -> usize {
+    let canvas = blocky(scale).rasterize(&(*maze).env, &Theme::default());
+    if !width.is_null() {
+        *width = canvas.width();
+    }
+    if !height.is_null() {
+        *height = canvas.height();
+    }
+    let needed = canvas.width() * canvas.height() * 4;
+    if needed <= capacity {
+        for (i, pixel) in canvas.pixels().iter().enumerate() {
+            ptr::copy_nonoverlapping(pixel.to_be_bytes().as_ptr(), pixels.add(i * 4), 4);
+        }
+    }
+    needed
+}
+
+/// Writes the maze to the file at `path` (UTF-8). `scale` only applies to PPM and PNG. Returns 0
+/// on success and -1 if the path is invalid or the file could not be written.
+///
+/// # Safety
+/// `maze` must be a live handle from `maze_generate` and `path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn maze_export(maze: *const Maze, path: *const c_char, format: MazeFormat, scale: usize) -> c_int {
+    let Ok(path) = CStr::from_ptr(path).to_str() else { return -1 };
+    let env = &(*maze).env;
+    let renderer: Box<dyn Renderer> = match format {
+        MazeFormat::Ppm => Box::new(RasterRenderer { format: ImageFormat::Ppm, ..blocky(scale) }),
+        MazeFormat::Png => Box::new(blocky(scale)),
+        MazeFormat::Svg => Box::new(SvgRenderer::default()),
+        MazeFormat::Text => Box::new(TextRenderer { color: false }),
+    };
+    let result = File::create(path).and_then(|file| {
+        let mut out = BufWriter::new(file);
+        renderer.render(env, &Theme::default(), &mut out)?;
+        out.flush()
+    });
+    if result.is_ok() { 0 } else { -1 }
+}
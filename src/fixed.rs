@@ -0,0 +1,164 @@
+// Passages a generated maze must have and walls it must keep, read from a text file:
+//
+//   # A corridor across the middle, closed off at its east end
+//   passage 5 2 5 12
+//   wall 5 12 5 13
+//
+// Every line names two cells, row then column. `passage` opens every wall on the straight run
+// between them, `wall` keeps the wall between two neighbouring cells standing. The generator
+// carves the rest of the maze around them and it stays perfect, so the passages must not form a
+// loop and the walls must not cut any cell off.
+
+use std::fs;
+use std::path::Path;
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::bitset::BitSet;
+use crate::error::MazeError;
+use crate::graph::DisjointSets;
+use crate::progress::Progress;
+use crate::{Cell, Env, PROGRESS_STEP};
+
+#[derive(Clone, Debug, Default)]
+pub struct Fixed {
+    // Pairs of neighbouring cells
+    pub passages: Vec<(Cell, Cell)>,
+    pub walls: Vec<(Cell, Cell)>,
+}
+
+fn invalid(line: usize, message: &str) -> MazeError {
+    MazeError::InvalidData(format!("line {}: {}", line, message))
+}
+
+fn are_neighbours(a: Cell, b: Cell) -> bool {
+    a.row.abs_diff(b.row) + a.col.abs_diff(b.col) == 1
+}
+
+// Index of the wall slot between two neighbouring cells of a `width` wide maze: two per cell, to
+// the east and to the south
+fn slot(width: usize, a: Cell, b: Cell) -> usize {
+    let (first, second) = if (a.row, a.col) < (b.row, b.col) { (a, b) } else { (b, a) };
+    (2 * ((first.row * width) + first.col)) + (first.row != second.row) as usize
+}
+
+impl Fixed {
+    // `#` starts a comment; blank lines are skipped
+    pub fn parse(text: &str) -> Result<Self, MazeError> {
+        let mut fixed = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+            let Some(kind) = words.next() else { continue };
+            let numbers: Vec<usize> = words.map(|w| w.parse()).collect::<Result<_, _>>().map_err(|_| invalid(i + 1, "expected row and column numbers"))?;
+            let [row, col, to_row, to_col] = numbers[..] else {
+                return Err(invalid(i + 1, "expected two cells, as `row col row col`"));
+            };
+            let (a, b) = (Cell { row, col }, Cell { row: to_row, col: to_col });
+            match kind {
+                "passage" if a != b && (a.row == b.row || a.col == b.col) => {
+                    let run: Vec<Cell> = if a.row == b.row {
+                        (a.col.min(b.col)..=a.col.max(b.col)).map(|col| Cell { row, col }).collect()
+                    } else {
+                        (a.row.min(b.row)..=a.row.max(b.row)).map(|row| Cell { row, col }).collect()
+                    };
+                    fixed.passages.extend(run.windows(2).map(|pair| (pair[0], pair[1])));
+                },
+                "passage" => return Err(invalid(i + 1, "a passage runs straight along a row or column")),
+                "wall" if are_neighbours(a, b) => fixed.walls.push((a, b)),
+                "wall" => return Err(invalid(i + 1, "a wall stands between two neighbouring cells")),
+                _ => return Err(invalid(i + 1, &format!("unknown `{}`, expected `passage` or `wall`", kind))),
+            }
+        }
+        Ok(fixed)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, MazeError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    // Whether a perfect `width`x`height` maze can have all of the passages and walls
+    pub fn check(&self, width: usize, height: usize) -> Result<(), MazeError> {
+        let outside = |c: &Cell| c.row >= height || c.col >= width;
+        if let Some(c) = self.passages.iter().chain(self.walls.iter()).flat_map(|&(a, b)| [a, b]).find(outside) {
+            return Err(MazeError::InvalidData(format!("cell ({}, {}) is outside the {}x{} maze", c.row, c.col, width, height)));
+        }
+        let mut walled = BitSet::new(width * height * 2);
+        for &(a, b) in self.walls.iter() {
+            walled.set(slot(width, a, b), true);
+        }
+        let mut sets = DisjointSets::new(width * height);
+        for &(a, b) in self.passages.iter() {
+            if walled.get(slot(width, a, b)) {
+                return Err(MazeError::InvalidData(format!("cells ({}, {}) and ({}, {}) need both a passage and a wall", a.row, a.col, b.row, b.col)));
+            }
+            if !sets.union((a.row * width) + a.col, (b.row * width) + b.col) {
+                return Err(MazeError::InvalidData(format!("the passages form a loop through ({}, {}) and ({}, {})", a.row, a.col, b.row, b.col)));
+            }
+        }
+        // Every cell must still be reachable around the walls
+        let mut sets = DisjointSets::new(width * height);
+        let mut joined = 0;
+        for (row, col) in (0..height).flat_map(|row| (0..width).map(move |col| (row, col))) {
+            let cell = Cell { row, col };
+            for next in [Cell { row, col: col + 1 }, Cell { row: row + 1, col }] {
+                if !outside(&next) && !walled.get(slot(width, cell, next)) && sets.union((row * width) + col, (next.row * width) + next.col) {
+                    joined += 1;
+                }
+            }
+        }
+        if joined + 1 < width * height {
+            return Err(MazeError::InvalidData(String::from("the walls cut part of the maze off")));
+        }
+        Ok(())
+    }
+}
+
+// Marks `cell` visited along with every cell the fixed passages join it to, and pushes them all
+fn enter(env: &mut Env, cell: Cell, stack: &mut Vec<Cell>) {
+    env.mark_visited(cell);
+    let mut run = vec![cell];
+    while let Some(current) = run.pop() {
+        env.visit_order.push(env.ind(current) as u32);
+        stack.push(current);
+        let joined: Vec<Cell> = env.neighbors(current).filter(|&n| !env.is_visited(n)).collect();
+        for next in joined {
+            env.mark_visited(next);
+            run.push(next);
+        }
+    }
+}
+
+// Randomized depth-first search around `fixed`: its passages are carved up front and entering one
+// cell of a run of them enters the whole run, while its walls are never opened. `env` should have
+// every wall standing. Fails if `fixed` does not fit, see `Fixed::check`.
+pub fn gen_maze_fixed(env: &mut Env, rng: &mut impl Rng, fixed: &Fixed, progress: &mut dyn Progress) -> Result<(), MazeError> {
+    fixed.check(env.width, env.height)?;
+    let mut walled = BitSet::new(env.cell_count() * 2);
+    for &(a, b) in fixed.walls.iter() {
+        walled.set(slot(env.width, a, b), true);
+    }
+    for &(a, b) in fixed.passages.iter() {
+        env.carve(a, b);
+    }
+
+    let mut stack = vec![];
+    let start = Cell { row: rng.gen_range(0..env.height), col: rng.gen_range(0..env.width) };
+    enter(env, start, &mut stack);
+    while let Some(&current) = stack.last() {
+        let unvisited: Vec<Cell> = env.adjacent(current).filter(|&n| !env.is_visited(n) && !walled.get(slot(env.width, current, n))).collect();
+        let Some(&next) = unvisited.choose(rng) else {
+            stack.pop();
+            continue;
+        };
+        env.carve(current, next);
+        let before = env.visit_order.len();
+        enter(env, next, &mut stack);
+        if env.visit_order.len() / PROGRESS_STEP > before / PROGRESS_STEP {
+            progress.checkpoint(env.visit_order.len(), env.cell_count())?;
+        }
+    }
+    progress.update(env.cell_count(), env.cell_count());
+    Ok(())
+}
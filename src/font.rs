@@ -0,0 +1,87 @@
+use crate::draw::{fill_rect, Canvas};
+
+pub const GLYPH_WIDTH: usize = 3;
+pub const GLYPH_HEIGHT: usize = 5;
+
+// 3x5 bitmap glyphs, one byte per row with the leftmost pixel in bit 2.
+// Lowercase letters are drawn with the uppercase glyphs.
+fn glyph(ch: char) -> Option<[u8; GLYPH_HEIGHT]> {
+    Some(match ch.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        ' ' => [0; GLYPH_HEIGHT],
+        _ => return None,
+    })
+}
+
+// Whether `ch` has a glyph, rather than being skipped
+pub fn can_draw(ch: char) -> bool {
+    glyph(ch).is_some()
+}
+
+// Width in logical pixels of `text` drawn with one pixel of spacing between glyphs
+pub fn text_width(text: &str) -> usize {
+    let n = text.chars().count();
+    if n == 0 { 0 } else { (n * (GLYPH_WIDTH + 1)) - 1 }
+}
+
+// Draws `text` with its top-left corner at (`x`, `y`), each font pixel `unit` pixels wide.
+// Characters without a glyph are skipped but still take up space.
+pub fn draw_text(canvas: &mut Canvas, x: usize, y: usize, text: &str, unit: usize, color: u32) {
+    for (i, ch) in text.chars().enumerate() {
+        let Some(rows) = glyph(ch) else { continue };
+        let gx = x + (i * (GLYPH_WIDTH + 1) * unit);
+        for (dy, bits) in rows.iter().enumerate() {
+            for dx in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - dx)) != 0 {
+                    fill_rect(canvas, gx + (dx * unit), y + (dy * unit), unit, unit, color);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
+
+use crate::animation::{self, Animation};
+use crate::render::{image_size, Theme};
+use crate::solve::Solver;
+use crate::trace::Trace;
+use crate::Env;
+
+// The window is scaled up to roughly this many pixels on its longer side
+const TARGET_WINDOW_SIZE: usize = 800;
+const FPS: usize = 60;
+
+// Opens a window that replays the generation of `env` and then `solver` running from the
+// top-left to the bottom-right cell. Generation is replayed from `trace` when there is one, and
+// reconstructed from the visit order otherwise. Space pauses, N/Right steps while paused,
+// Up/Down change the speed and Escape/Q close the window.
+pub fn run(env: &Env, solver: Solver, trace: Option<&Trace>) -> minifb::Result<()> {
+    let (steps, carve_count) = animation::steps(env, solver, trace);
+    let (logical_w, logical_h) = (image_size(env.width), image_size(env.height));
+    let unit = (TARGET_WINDOW_SIZE / logical_w.max(logical_h)).max(1);
+    let mut anim = Animation::new(env, unit, Theme::default());
+
+    let mut window = Window::new("maze", anim.canvas.width(), anim.canvas.height(), WindowOptions::default())?;
+    window.set_target_fps(FPS);
+    let mut buffer = vec![0u32; anim.canvas.width() * anim.canvas.height()];
+    let (mut next, mut speed, mut paused) = (0, 1usize, false);
+
+    while window.is_open() && !window.is_key_down(Key::Escape) && !window.is_key_down(Key::Q) {
+        if window.is_key_pressed(Key::Space, KeyRepeat::No) {
+            paused = !paused;
+        }
+        if window.is_key_pressed(Key::Up, KeyRepeat::Yes) {
+            speed = (speed * 2).min(1 << 16);
+        }
+        if window.is_key_pressed(Key::Down, KeyRepeat::Yes) {
+            speed = (speed / 2).max(1);
+        }
+        let single = paused && (window.is_key_pressed(Key::N, KeyRepeat::Yes) || window.is_key_pressed(Key::Right, KeyRepeat::Yes));
+        let count = if single { 1 } else if paused { 0 } else { speed };
+        for step in steps.iter().skip(next).take(count) {
+            anim.apply(step);
+        }
+        next = (next + count).min(steps.len());
+
+        let phase = match next {
+            n if n < carve_count => format!("generating {}/{}", n, carve_count),
+            n if n < steps.len() => format!("solving ({})", solver.name()),
+            _ => String::from("done"),
+        };
+        window.set_title(&format!("maze - {} - {} steps/frame{}", phase, speed, if paused { " - paused" } else { "" }));
+        for (out, pixel) in buffer.iter_mut().zip(anim.canvas.pixels()) {
+            // minifb expects 0x00RRGGBB
+            *out = pixel >> 8;
+        }
+        window.update_with_buffer(&buffer, anim.canvas.width(), anim.canvas.height())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::ascii;
+use crate::dot;
+use crate::error::MazeError;
+use crate::json;
+use crate::mazefile::{self, MazeFile};
+use crate::scan;
+use crate::tga;
+
+// Reads a maze from any file format the crate can load, picked by the file extension: pictures,
+// text drawings, DOT graphs, JSON and `.maze` files, which is also what anything unrecognized is
+// read as.
+pub fn load(path: &Path) -> Result<MazeFile, MazeError> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        ascii::EXTENSION => ascii::load(path),
+        json::EXTENSION => json::load(path),
+        e if dot::EXTENSIONS.contains(&e) => dot::load(path),
+        e if scan::EXTENSIONS.contains(&e) => scan::load(path),
+        _ => mazefile::load(path),
+    }
+}
+
+// Reads a maze without a file name to go by, so the format is told from the first bytes: image
+// signatures, the `.maze` header, a JSON object or a DOT graph, and a text drawing otherwise
+pub fn read(bytes: &[u8]) -> Result<MazeFile, MazeError> {
+    let text = String::from_utf8_lossy(bytes);
+    let start = text.trim_start();
+    let keyword = start.split(|c: char| !c.is_ascii_alphabetic()).next().unwrap_or("").to_ascii_lowercase();
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] | [b'P', b'5' | b'6', ..] | [b'B', b'M', ..] | [b'q', b'o', b'i', b'f', ..] => scan::read(bytes),
+        _ if tga::is_tga(bytes) => scan::read(bytes),
+        _ if start.starts_with("maze ") => mazefile::read(bytes),
+        _ if start.starts_with('{') => json::read(bytes),
+        _ if ["graph", "digraph", "strict"].contains(&keyword.as_str()) => dot::read(bytes),
+        _ => ascii::read(bytes),
+    }
+}
+
+// `load`, or `read` from standard input for `-`, so mazes can be piped from one command to the next
+pub fn load_path(path: &str) -> Result<MazeFile, MazeError> {
+    match path {
+        "-" => read(&read_path(path)?),
+        path => load(Path::new(path)),
+    }
+}
+
+// The bytes of `path`, or all of standard input for `-`
+pub fn read_path(path: &str) -> Result<Vec<u8>, MazeError> {
+    if path != "-" {
+        return Ok(fs::read(path)?);
+    }
+    let mut bytes = vec![];
+    io::stdin().lock().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::metadata::Metadata;
+    use crate::render::raster::{ImageFormat, Overlays, RasterRenderer, Style};
+    use crate::render::text::TextRenderer;
+    use crate::render::{Renderer, Theme};
+    use crate::{gen_maze_with, Env};
+
+    fn maze() -> MazeFile {
+        let mut env = Env::new(9, 7);
+        gen_maze_with(&mut env, &mut StdRng::seed_from_u64(4));
+        MazeFile::new(env)
+    }
+
+    fn image(format: ImageFormat, env: &Env) -> Vec<u8> {
+        let renderer = RasterRenderer { format, style: Style::Blocky, scale: 3, supersample: 1, overlays: Overlays::default(), metadata: Metadata::default() };
+        let mut bytes = vec![];
+        format.write(&mut bytes, &renderer.rasterize(env, &Theme::default())).expect("writing to memory");
+        bytes
+    }
+
+    fn passages(env: &Env) -> Vec<(usize, usize, usize, usize)> {
+        env.walls().map(|wall| (wall.start.row, wall.start.col, wall.target.row, wall.target.col)).collect()
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        let maze = maze();
+        let mut files: Vec<(&str, Vec<u8>)> = vec![];
+        for (name, write) in [
+            ("maze", mazefile::write as fn(&mut dyn std::io::Write, &MazeFile) -> Result<(), MazeError>),
+            ("json", |out, maze| json::write(out, maze, None)),
+            ("dot", dot::write),
+            ("txt", |out, maze| TextRenderer { color: false }.render(&maze.env, &Theme::default(), out)),
+        ] {
+            let mut bytes = vec![];
+            write(&mut bytes, &maze).expect("writing to memory");
+            files.push((name, bytes));
+        }
+        for (name, format) in [("png", ImageFormat::Png), ("qoi", ImageFormat::Qoi), ("tga", ImageFormat::Tga { rle: true }), ("bmp", ImageFormat::Bmp), ("ppm", ImageFormat::Ppm)] {
+            files.push((name, image(format, &maze.env)));
+        }
+        for (name, bytes) in files {
+            let read = read(&bytes).unwrap_or_else(|err| panic!("{}: {}", name, err));
+            assert_eq!((read.env.width, read.env.height), (maze.env.width, maze.env.height), "{}", name);
+            assert_eq!(passages(&read.env), passages(&maze.env), "{}", name);
+        }
+    }
+
+    #[test]
+    fn truncated_images_are_errors() {
+        let env = maze().env;
+        // Bytes after the pixels that the readers do not need: the last IDAT CRC and the IEND chunk
+        // of a PNG, and the footer of a TGA
+        for (format, trailer) in [(ImageFormat::Png, 16), (ImageFormat::Bmp, 0), (ImageFormat::Tga { rle: false }, 26), (ImageFormat::Tga { rle: true }, 26)] {
+            let bytes = image(format, &env);
+            for len in [20, bytes.len() / 2, bytes.len() - trailer - 1] {
+                assert!(read(&bytes[..len]).is_err(), "{} of {} bytes", len, bytes.len());
+            }
+        }
+    }
+
+    #[test]
+    fn forged_image_sizes_are_errors() {
+        let env = maze().env;
+        // Width and height fields of each header, set to the largest size the format allows
+        let (png, bmp, tga) = (0x7FFF_FFFFu32.to_be_bytes(), i32::MAX.to_le_bytes(), u16::MAX.to_le_bytes());
+        let forged = [
+            (ImageFormat::Png, [(16, &png[..]), (20, &png[..])]),
+            (ImageFormat::Bmp, [(18, &bmp[..]), (22, &bmp[..])]),
+            (ImageFormat::Tga { rle: false }, [(12, &tga[..]), (14, &tga[..])]),
+            (ImageFormat::Tga { rle: true }, [(12, &tga[..]), (14, &tga[..])]),
+        ];
+        for (format, fields) in forged {
+            let mut bytes = image(format, &env);
+            for (at, value) in fields {
+                bytes[at..at + value.len()].copy_from_slice(value);
+            }
+            assert!(read(&bytes).is_err());
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// Deflate (RFC 1951) decompression inside a zlib (RFC 1950) wrapper, enough to read PNG image data
+// written by any encoder
+
+use crate::error::MazeError;
+
+// Longest Huffman code deflate allows
+const MAX_BITS: usize = 15;
+// Base lengths and extra bits of length codes 257..285
+pub(crate) const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+pub(crate) const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+// Base distances and extra bits of distance codes 0..29
+pub(crate) const DIST_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+pub(crate) const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// Order code length code lengths are stored in by dynamic blocks
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn corrupt(message: &str) -> MazeError {
+    MazeError::InvalidData(format!("corrupt compressed data: {}", message))
+}
+
+// Reads bits least significant first, as deflate packs them
+struct Bits<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl Bits<'_> {
+    fn bit(&mut self) -> Result<u32, MazeError> {
+        let byte = *self.bytes.get(self.pos).ok_or_else(|| corrupt("unexpected end of stream"))?;
+        let value = (byte as u32 >> self.bit) & 1;
+        self.bit += 1;
+        if self.bit == 8 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn bits(&mut self, count: u8) -> Result<u32, MazeError> {
+        let mut value = 0;
+        for i in 0..count {
+            value |= self.bit()? << i;
+        }
+        Ok(value)
+    }
+
+    // Skips to the next byte boundary, as stored blocks start on one
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+// Canonical Huffman code: how many codes there are of each length, and the symbols sorted by code
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    // One bit at a time: codes of each length are consecutive, so the code is found once it falls
+    // within the range of its length
+    fn decode(&self, bits: &mut Bits) -> Result<u16, MazeError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..=MAX_BITS {
+            code |= bits.bit()? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(corrupt("invalid Huffman code"))
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), MazeError> {
+    let literals = bits.bits(5)? as usize + 257;
+    let distances = bits.bits(5)? as usize + 1;
+    let code_lengths = bits.bits(4)? as usize + 4;
+    let mut lengths = [0u8; 19];
+    for &i in CODE_LENGTH_ORDER.iter().take(code_lengths) {
+        lengths[i] = bits.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&lengths);
+
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (value, repeat) = match code_length_code.decode(bits)? {
+            len @ 0..=15 => (len as u8, 1),
+            16 => (*lengths.last().ok_or_else(|| corrupt("repeat with no previous length"))?, 3 + bits.bits(2)?),
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literals + distances {
+        return Err(corrupt("code lengths overrun"));
+    }
+    Ok((Huffman::new(&lengths[..literals]), Huffman::new(&lengths[literals..])))
+}
+
+// Decompresses a raw deflate stream
+pub fn inflate(bytes: &[u8]) -> Result<Vec<u8>, MazeError> {
+    let mut out = vec![];
+    let mut bits = Bits { bytes, pos: 0, bit: 0 };
+    loop {
+        let last = bits.bit()? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.align();
+                let header = bytes.get(bits.pos..bits.pos + 4).ok_or_else(|| corrupt("truncated stored block"))?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if len != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err(corrupt("stored block length mismatch"));
+                }
+                let start = bits.pos + 4;
+                out.extend_from_slice(bytes.get(start..start + len).ok_or_else(|| corrupt("truncated stored block"))?);
+                bits.pos = start + len;
+            },
+            kind @ (1 | 2) => {
+                let (literal, distance) = if kind == 1 { fixed_codes() } else { dynamic_codes(&mut bits)? };
+                loop {
+                    let symbol = literal.decode(&mut bits)? as usize;
+                    match symbol {
+                        0..=255 => out.push(symbol as u8),
+                        256 => break,
+                        257..=285 => {
+                            let len = LENGTH_BASE[symbol - 257] as usize + bits.bits(LENGTH_EXTRA[symbol - 257])? as usize;
+                            let code = distance.decode(&mut bits)? as usize;
+                            let (&base, &extra) = DIST_BASE.get(code).zip(DIST_EXTRA.get(code)).ok_or_else(|| corrupt("invalid distance code"))?;
+                            let dist = base as usize + bits.bits(extra)? as usize;
+                            if dist > out.len() {
+                                return Err(corrupt("distance reaches before the start"));
+                            }
+                            // Copies may overlap what they produce, so go byte by byte
+                            let from = out.len() - dist;
+                            for i in 0..len {
+                                out.push(out[from + i]);
+                            }
+                        },
+                        _ => return Err(corrupt("invalid length code")),
+                    }
+                }
+            },
+            _ => return Err(corrupt("invalid block type")),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+// Decompresses a zlib stream; the checksum is not verified, PNG chunks carry their own CRCs
+pub fn zlib_decompress(bytes: &[u8]) -> Result<Vec<u8>, MazeError> {
+    match bytes {
+        [cmf, flg, rest @ ..] if cmf & 0x0F == 8 && ((*cmf as u16) << 8 | *flg as u16).is_multiple_of(31) && flg & 0x20 == 0 => inflate(rest),
+        _ => Err(corrupt("not a zlib stream")),
+    }
+}
@@ -0,0 +1,28 @@
+use std::io::{self, Write};
+
+use crate::draw::Canvas;
+use crate::{base64, png};
+
+// Rough size in bytes of `write_iterm2`'s output for a `width`x`height` canvas
+pub fn estimated_size(width: usize, height: usize) -> usize {
+    64 + base64::encoded_len(png::encoded_size(width, height))
+}
+
+// Displays the canvas through iTerm2's inline image escape sequence: a single OSC 1337 `File`
+// command carrying the base64 encoded PNG, shown at the cursor at its pixel size
+pub fn write_iterm2(out: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+    let mut encoded = vec![];
+    png::write_png(&mut encoded, canvas)?;
+    let header = format!(
+        "\x1b]1337;File=inline=1;size={};width={}px;height={}px;preserveAspectRatio=1:",
+        encoded.len(),
+        canvas.width(),
+        canvas.height()
+    );
+    let mut bytes = header.into_bytes();
+    bytes.extend_from_slice(base64::encode(&encoded).as_bytes());
+    // Terminated by BEL, then a newline to move below the image
+    bytes.extend_from_slice(b"\x07\n");
+    out.write_all(&bytes)?;
+    out.flush()
+}
@@ -0,0 +1,319 @@
+// JSON export of a maze for games and scripts, on one line:
+//
+//   {"width":3,"height":2,"start":[0,0],"goal":[1,2],"passages":[[0,0,0,1],...],"excluded":[],
+//    "tags":[{"row":0,"col":1,"tag":"key"}]}
+//
+// Every passage joins two cells given as row, column, row, column; `excluded` lists the cells left
+// out of the maze (see `Env::exclude`). `tags` is only there when tags are given: free-form text
+// on cells (see `CellData`), read from a file of `row,col,text` lines for `--tags`. Mazes saved by
+// `maze solve` also have `"solution":[[r,c],...]`, the path from start to goal.
+//
+// `read` loads the same JSON back, ignoring any tags and keys it does not know.
+
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+
+use crate::celldata::CellData;
+use crate::error::MazeError;
+use crate::mazefile::MazeFile;
+use crate::render::{Renderer, Theme};
+use crate::{Cell, Env};
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub const EXTENSION: &str = "json";
+
+pub fn write(out: &mut dyn Write, maze: &MazeFile, tags: Option<&CellData<String>>) -> Result<(), MazeError> {
+    write_parts(out, &maze.env, maze.start, maze.goal, &maze.solution, tags)
+}
+
+fn write_parts(out: &mut dyn Write, env: &Env, start: Cell, goal: Cell, solution: &[Cell], tags: Option<&CellData<String>>) -> Result<(), MazeError> {
+    if let Some(tags) = tags.filter(|tags| (tags.width(), tags.height()) != (env.width, env.height)) {
+        return Err(MazeError::SizeMismatch { first: (env.width, env.height), second: (tags.width(), tags.height()) });
+    }
+    let passages: Vec<String> = env.walls().map(|w| format!("[{},{},{},{}]", w.start.row, w.start.col, w.target.row, w.target.col)).collect();
+    let excluded: Vec<String> = env.cells().filter(|&cell| env.is_excluded(cell)).map(|cell| format!("[{},{}]", cell.row, cell.col)).collect();
+    write!(
+        out,
+        "{{\"width\":{},\"height\":{},\"start\":[{},{}],\"goal\":[{},{}],\"passages\":[{}],\"excluded\":[{}]",
+        env.width, env.height, start.row, start.col, goal.row, goal.col, passages.join(","), excluded.join(",")
+    )?;
+    if !solution.is_empty() {
+        let cells: Vec<String> = solution.iter().map(|cell| format!("[{},{}]", cell.row, cell.col)).collect();
+        write!(out, ",\"solution\":[{}]", cells.join(","))?;
+    }
+    if let Some(tags) = tags {
+        let tags: Vec<String> = tags.iter().map(|(cell, tag)| format!("{{\"row\":{},\"col\":{},\"tag\":\"{}\"}}", cell.row, cell.col, escape(tag))).collect();
+        write!(out, ",\"tags\":[{}]", tags.join(","))?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+// Tags for a `width` x `height` maze from `row,col,text` lines; the text runs to the end of the
+// line and may hold commas. Blank lines and lines starting with `#` are skipped, and a later tag
+// on the same cell replaces an earlier one.
+pub fn parse_tags(text: &str, width: usize, height: usize) -> Result<CellData<String>, MazeError> {
+    let mut tags = CellData::new(width, height);
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let mut parts = line.splitn(3, ',');
+        let (row, col, tag) = (parts.next(), parts.next(), parts.next());
+        let (cell, tag) = match (row.and_then(|r| r.trim().parse().ok()), col.and_then(|c| c.trim().parse().ok()), tag) {
+            (Some(row), Some(col), Some(tag)) => (Cell { row, col }, tag),
+            _ => return Err(MazeError::InvalidData(format!("line {}: expected `row,col,text`", number + 1))),
+        };
+        if cell.row >= height || cell.col >= width {
+            return Err(MazeError::InvalidData(format!("line {}: cell ({}, {}) is outside the {}x{} maze", number + 1, cell.row, cell.col, width, height)));
+        }
+        tags.insert(cell, tag.to_string());
+    }
+    Ok(tags)
+}
+
+// The tags in the file at `path` for `env`
+pub fn read_tags(path: &str, env: &Env) -> Result<CellData<String>, MazeError> {
+    let text = fs::read_to_string(path).map_err(|err| MazeError::from(err).in_file(path))?;
+    parse_tags(&text, env.width, env.height).map_err(|err| err.in_file(path))
+}
+
+// JSON with the top-left cell as the start and the bottom-right one as the goal, and `tags` if
+// there are any
+#[derive(Default)]
+pub struct JsonRenderer {
+    pub tags: Option<CellData<String>>,
+}
+
+impl Renderer for JsonRenderer {
+    fn render(&self, env: &Env, _theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        write_parts(out, env, Cell { row: 0, col: 0 }, Cell { row: env.height - 1, col: env.width - 1 }, &[], self.tags.as_ref())
+    }
+}
+
+enum Value {
+    Null,
+    Bool,
+    // Kept as written, so integers of any size survive
+    Number(String),
+    String,
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+fn invalid(message: &str) -> MazeError {
+    MazeError::InvalidData(format!("invalid maze JSON: {}", message))
+}
+
+// A recursive-descent parser for just enough JSON to read mazes back
+struct Parser<'a> {
+    text: &'a str,
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn skip_space(&mut self) {
+        let rest = &self.text[self.at..];
+        self.at += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.text[self.at..].chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), MazeError> {
+        if self.peek() != Some(c) {
+            return Err(invalid(&format!("expected `{}` at byte {}", c, self.at)));
+        }
+        self.at += c.len_utf8();
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Value, MazeError> {
+        match self.peek() {
+            Some('{') => {
+                self.at += 1;
+                let mut fields = vec![];
+                if self.peek() == Some('}') {
+                    self.at += 1;
+                    return Ok(Value::Object(fields));
+                }
+                loop {
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    fields.push((key, self.value()?));
+                    if self.peek() == Some(',') {
+                        self.at += 1;
+                        continue;
+                    }
+                    self.expect('}')?;
+                    return Ok(Value::Object(fields));
+                }
+            },
+            Some('[') => {
+                self.at += 1;
+                let mut items = vec![];
+                if self.peek() == Some(']') {
+                    self.at += 1;
+                    return Ok(Value::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    if self.peek() == Some(',') {
+                        self.at += 1;
+                        continue;
+                    }
+                    self.expect(']')?;
+                    return Ok(Value::Array(items));
+                }
+            },
+            Some('"') => self.string().map(|_| Value::String),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let rest = &self.text[self.at..];
+                let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.'))).unwrap_or(rest.len());
+                self.at += len;
+                Ok(Value::Number(rest[..len].to_string()))
+            },
+            _ => {
+                let rest = &self.text[self.at..];
+                let (word, value) = [("true", Value::Bool), ("false", Value::Bool), ("null", Value::Null)]
+                    .into_iter()
+                    .find(|(word, _)| rest.starts_with(word))
+                    .ok_or_else(|| invalid(&format!("unexpected input at byte {}", self.at)))?;
+                self.at += word.len();
+                Ok(value)
+            },
+        }
+    }
+
+    fn string(&mut self) -> Result<String, MazeError> {
+        self.expect('"')?;
+        let mut out = String::new();
+        let mut chars = self.text[self.at..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.at += i + 1;
+                    return Ok(out);
+                },
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                        let code = u32::from_str_radix(&hex, 16).map_err(|_| invalid("bad `\\u` escape"))?;
+                        out.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    },
+                    Some(c @ ('"' | '\\' | '/')) => out.push(c),
+                    _ => return Err(invalid("bad escape in a string")),
+                },
+                c => out.push(c),
+            }
+        }
+        Err(invalid("unterminated string"))
+    }
+}
+
+fn number(value: Option<&Value>, what: &str) -> Result<usize, MazeError> {
+    match value {
+        Some(Value::Number(text)) => text.parse().map_err(|_| invalid(&format!("`{}` is not a whole number", what))),
+        _ => Err(invalid(&format!("missing number `{}`", what))),
+    }
+}
+
+// The numbers of an array like `[r,c]`, which has to hold `N` of them
+fn numbers<const N: usize>(value: &Value, what: &str) -> Result<[usize; N], MazeError> {
+    let Value::Array(items) = value else {
+        return Err(invalid(&format!("`{}` entries must be arrays", what)));
+    };
+    let numbers: Vec<usize> = items.iter().map(|item| number(Some(item), what)).collect::<Result<_, _>>()?;
+    numbers.try_into().map_err(|_| invalid(&format!("`{}` entries must have {} numbers", what, N)))
+}
+
+fn list<'a>(value: Option<&'a Value>, what: &str) -> Result<&'a [Value], MazeError> {
+    match value {
+        Some(Value::Array(items)) => Ok(items),
+        None => Ok(&[]),
+        _ => Err(invalid(&format!("`{}` must be an array", what))),
+    }
+}
+
+pub fn read(mut input: impl Read) -> Result<MazeFile, MazeError> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let mut parser = Parser { text: &text, at: 0 };
+    let Value::Object(fields) = parser.value()? else {
+        return Err(invalid("expected an object"));
+    };
+    if parser.peek().is_some() {
+        return Err(invalid(&format!("unexpected input after the maze at byte {}", parser.at)));
+    }
+    let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+    let (width, height) = (number(field("width"), "width")?, number(field("height"), "height")?);
+    let inside = |[row, col]: [usize; 2]| (row < height && col < width).then_some(Cell { row, col });
+    let cell = |name: &str| -> Result<Cell, MazeError> {
+        let value = field(name).ok_or_else(|| invalid(&format!("missing `{}`", name)))?;
+        inside(numbers(value, name)?).ok_or_else(|| invalid(&format!("`{}` is outside the maze", name)))
+    };
+    let mut maze = MazeFile::new(Env::try_new(width, height)?);
+    (maze.start, maze.goal) = (cell("start")?, cell("goal")?);
+    for value in list(field("excluded"), "excluded")? {
+        let excluded = inside(numbers(value, "excluded")?).ok_or_else(|| invalid("an excluded cell is outside the maze"))?;
+        maze.env.exclude(excluded);
+    }
+    for value in list(field("passages"), "passages")? {
+        let [r1, c1, r2, c2] = numbers(value, "passages")?;
+        match (inside([r1, c1]), inside([r2, c2])) {
+            (Some(a), Some(b)) if r1.abs_diff(r2) + c1.abs_diff(c2) == 1 => maze.env.carve(a, b),
+            _ => return Err(invalid(&format!("passage ({}, {})-({}, {}) does not join neighbouring cells of the maze", r1, c1, r2, c2))),
+        }
+    }
+    for value in list(field("solution"), "solution")? {
+        let next = inside(numbers(value, "solution")?).ok_or_else(|| invalid("a solution cell is outside the maze"))?;
+        if maze.solution.last().is_some_and(|&last| last.row.abs_diff(next.row) + last.col.abs_diff(next.col) != 1 || !maze.env.has_passage(last, next)) {
+            return Err(invalid("the solution goes through a wall"));
+        }
+        maze.solution.push(next);
+    }
+    if !maze.solution.is_empty() && (maze.solution.first() != Some(&maze.start) || maze.solution.last() != Some(&maze.goal)) {
+        return Err(invalid("the solution does not lead from start to goal"));
+    }
+    Ok(maze)
+}
+
+pub fn load(path: &Path) -> Result<MazeFile, MazeError> {
+    read(BufReader::new(File::open(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_oversized_mazes() {
+        for (width, height) in [(4_000_000_000usize, 4_000_000_000usize), (1 << 20, 1 << 20), (0, 5)] {
+            let text = format!(r#"{{"width":{},"height":{},"start":[0,0],"goal":[0,0],"passages":[]}}"#, width, height);
+            assert!(matches!(read(text.as_bytes()), Err(MazeError::InvalidSize { .. })), "{}x{}", width, height);
+        }
+    }
+}
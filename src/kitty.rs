@@ -0,0 +1,36 @@
+use std::io::{self, Write};
+
+use crate::draw::Canvas;
+use crate::{base64, png};
+
+// Largest base64 payload the protocol allows in a single escape sequence
+const CHUNK_SIZE: usize = 4096;
+
+// Rough size in bytes of `write_kitty`'s output for a `width`x`height` canvas
+pub fn estimated_size(width: usize, height: usize) -> usize {
+    let payload = base64::encoded_len(png::encoded_size(width, height));
+    payload + (payload.div_ceil(CHUNK_SIZE) * 16)
+}
+
+// Displays the canvas through the kitty graphics protocol: the image is sent as a PNG, base64
+// encoded and split over as many escape sequences as needed, and shown at the cursor as soon as
+// the last one arrives
+pub fn write_kitty(out: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+    let mut encoded = vec![];
+    png::write_png(&mut encoded, canvas)?;
+    let payload = base64::encode(&encoded);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(CHUNK_SIZE).collect();
+    let mut bytes = Vec::with_capacity(estimated_size(canvas.width(), canvas.height()));
+    for (i, chunk) in chunks.iter().enumerate() {
+        // `f=100` marks PNG data and `a=T` transmits and displays it; `m` says whether more follows
+        let more = (i + 1 < chunks.len()) as u8;
+        let control = if i == 0 { format!("f=100,a=T,m={}", more) } else { format!("m={}", more) };
+        bytes.extend_from_slice(format!("\x1b_G{};", control).as_bytes());
+        bytes.extend_from_slice(chunk);
+        bytes.extend_from_slice(b"\x1b\\");
+    }
+    // Move below the image so the prompt doesn't overwrite it
+    bytes.push(b'\n');
+    out.write_all(&bytes)?;
+    out.flush()
+}
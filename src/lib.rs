@@ -0,0 +1,74 @@
+pub mod analysis;
+pub mod animation;
+pub mod apng;
+pub mod ascii;
+pub mod base64;
+pub mod batch;
+pub mod bench;
+pub mod book;
+pub mod bmp;
+pub mod checkpoint;
+pub mod cube;
+pub mod cylinder;
+pub mod config;
+pub mod deflate;
+pub mod determinism;
+pub mod difficulty;
+pub mod dot;
+pub mod draw;
+#[cfg(feature = "native")]
+pub mod edit;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixed;
+pub mod font;
+#[cfg(feature = "gui")]
+pub mod gui;
+pub mod import;
+pub mod inflate;
+pub mod iterm2;
+pub mod json;
+pub mod kitty;
+pub mod manifest;
+pub mod mazefile;
+pub mod openings;
+pub mod metadata;
+pub mod parallel;
+pub mod pdf;
+#[cfg(feature = "native")]
+pub mod play;
+pub mod png;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod ppm;
+pub mod pursuit;
+pub mod qoi;
+pub mod race;
+pub mod refine;
+pub mod render;
+pub mod scan;
+pub mod search;
+#[cfg(feature = "native")]
+pub mod serve;
+pub mod shape;
+pub mod sixel;
+pub mod survey;
+pub mod template;
+pub mod textmask;
+pub mod tga;
+pub mod trace;
+pub mod validate;
+#[cfg(feature = "native")]
+pub mod video;
+pub mod voronoi;
+#[cfg(feature = "native")]
+pub mod walk;
+#[cfg(feature = "native")]
+pub mod watch;
+pub mod widths;
+pub mod word;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// The engine (`Env`, generators, solvers) lives in `maze-core` so it can build without `std`
+pub use maze_core::*;
@@ -1,280 +1,2385 @@
-use rand::prelude::SliceRandom;
+use maze::render::raster::{self, Corner, ImageFormat, Overlays, RasterRenderer, Style};
+use maze::render::mmap::MmapPpmRenderer;
+use maze::render::pdf::PdfRenderer;
+use maze::render::stream::StreamingPpmRenderer;
+use maze::render::svg::SvgRenderer;
+use maze::render::text::TextRenderer;
+use maze::render::braille::BrailleRenderer;
+use maze::render::field::{self, DistanceFieldRenderer, FieldFormat};
+use maze::solve::Solver;
+use maze::race::{self, Racer};
+use maze::pursuit::{self, Outcome, MINOTAUR_SPEED};
+use maze::book::BookMaze;
+use maze::render::sheet::Sheet;
+use maze::render::tiles::Tiling;
+use maze::dot::{self, DotRenderer};
+use maze::import;
+use maze::mazefile::{self, MazeFile, MazeFileRenderer};
+use maze::openings::{self, Placement};
+use maze::json::{self, JsonRenderer};
+use maze::metadata::Metadata;
+use maze::render::{diff, image_size, parse_color, Renderer, Theme, TRANSPARENT_COLOR};
+use maze::parallel::gen_maze_parallel;
+use maze::progress::{Progress, ProgressBar};
+use maze::difficulty::{self, Difficulty};
+use maze::template::{self, Fields};
+use maze::trace::{self, Trace};
+use maze::checkpoint;
+use maze::manifest;
+use maze::search::{self, Constraint};
+use maze::survey;
+use maze::config::{self, Config};
+use maze::error::MazeError;
+use maze::deadends::{self, DeadEnds};
+use maze::fixed::{self, Fixed};
+use maze::shape::{self, Shape};
+use maze::word;
+use maze::textmask;
+use maze::sparse;
+use maze::widths::{self, Widths};
+use maze::refine;
+use maze::cave;
+use maze::unicursal::{self, Curve};
+use maze::graph::Algorithm;
+use maze::voronoi;
+use maze::cube::{self, Layout};
+use maze::cylinder;
+use maze::animation::{self, Animation, Capture, Step};
+use maze::apng::ApngEncoder;
+use maze::video::VideoEncoder;
+use maze::{analysis, batch, bench, bmp, book, determinism, edit, gen_maze_progress, iterm2, kitty, play, png, ppm, qoi, serve, sixel, tga, validate, walk, watch, Cell, Env, MAX_CELLS, MAZE_SIZE};
 
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use std::env;
 use std::fs::{self, File};
 use std::path::Path;
-use std::io::{self, Write};
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::process;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Default)]
-struct Stack<T: Default + Copy + Clone> {
-    items: Vec<T>,
+#[derive(Copy, Clone, PartialEq)]
+enum Command {
+    // Generate a maze, or load one with `--from`, and save it as an image (or any other format).
+    // `gen` is the same restricted to the maze file formats.
+    Render,
+    // Find the path through the maze given with `--from` and save the maze with it
+    Solve,
+    // Explore the generated maze in a first-person terminal view
+    Walk,
+    // Move a marker through a top-down view of the maze in the terminal
+    Play,
+    // Animate generation and solving in a window (needs the `gui` feature)
+    Gui,
+    // Time every generator and renderer and print a comparison table
+    Bench,
+    // Answer HTTP requests for mazes
+    Serve,
+    // Toggle walls and move start/goal in the terminal, saving a `.maze` file
+    Edit,
+    // Write a PDF puzzle book with a solutions section
+    Book,
+    // Print difficulty metrics of a maze as JSON
+    Analyze,
+    // Try seeds until a maze meets every `--require` constraint
+    Search,
+    // Check that a `.maze` file holds a perfect maze
+    Validate,
+    // Reconnect the regions of a `.maze` file and save the result
+    Repair,
+    // Compare two `.maze` files and render the passages that differ
+    Diff,
+    // Rebuild a maze from a generation trace and render or animate it
+    Replay,
+    // Print the generation metadata embedded in a rendered image
+    Info,
+    // Run several solvers and random walkers through the maze step for step and rank them
+    Race,
+    // Simulate a minotaur chasing a player who walks the shortest way out
+    Chase,
+    // Generate the mazes of a job manifest on a pool of threads, reporting on every job
+    Batch,
+    // Carve `--count` mazes with each generator from the same seeds and report how their solution
+    // lengths and dead ends are spread
+    Survey,
+    // Save a loaded maze in another format, keeping its start, goal and solution where the format can;
+    // pictures show the solution only with `--solve`, so they scan back to the same maze
+    Convert,
 }
 
-impl<T: Default + Copy + Clone> Stack<T> {
-    pub fn push(&mut self, val: T) {
-        self.items.push(val);
-    }
+#[derive(Copy, Clone, PartialEq)]
+enum Format {
+    Image(ImageFormat),
+    Svg,
+    Text,
+    // Text with a braille dot per block, for mazes too big for `Text` on one screen
+    Braille,
+    // Steps from the top-left cell to every cell, for analysis in other tools
+    Field(FieldFormat),
+    // The `.maze` file format, which can be loaded back with `--input`
+    Maze,
+    // A Graphviz graph of cells and passages, which can also be loaded back
+    Dot,
+    // Cells and passages for games and scripts, optionally with `--tags` on cells
+    Json,
+    Pdf,
+}
+
+impl Format {
+    const ALL: [Format; 15] = [
+        Format::Image(ImageFormat::Ppm),
+        Format::Image(ImageFormat::Png),
+        Format::Image(ImageFormat::Qoi),
+        Format::Image(ImageFormat::Bmp),
+        Format::Image(ImageFormat::Tga { rle: false }),
+        Format::Image(ImageFormat::Sixel),
+        Format::Svg,
+        Format::Text,
+        Format::Braille,
+        Format::Field(FieldFormat::Csv),
+        Format::Field(FieldFormat::Npy),
+        Format::Maze,
+        Format::Dot,
+        Format::Json,
+        Format::Pdf,
+    ];
 
-    pub fn pop(&mut self) -> Option<T> {
-        self.items.pop()
+    fn name(&self) -> &'static str {
+        match self {
+            Format::Image(ImageFormat::Ppm) => "ppm",
+            Format::Image(ImageFormat::Png) => "png",
+            Format::Image(ImageFormat::Qoi) => "qoi",
+            Format::Image(ImageFormat::Bmp) => "bmp",
+            Format::Image(ImageFormat::Tga { .. }) => "tga",
+            Format::Image(ImageFormat::Sixel) => "sixel",
+            Format::Image(ImageFormat::Kitty) => "kitty",
+            Format::Image(ImageFormat::Iterm2) => "iterm2",
+            Format::Svg => "svg",
+            Format::Text => "txt",
+            Format::Braille => "braille",
+            Format::Field(FieldFormat::Csv) => "csv",
+            Format::Field(FieldFormat::Npy) => "npy",
+            Format::Maze => mazefile::EXTENSION,
+            Format::Dot => "dot",
+            Format::Json => "json",
+            Format::Pdf => "pdf",
+        }
     }
 
-    pub fn len(&self) -> usize {
-        self.items.len()
+    // `name()` of one of `ALL`, or another common spelling or extension of it
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "six" => Some(Format::Image(ImageFormat::Sixel)),
+            "text" => Some(Format::Text),
+            "gv" => Some(Format::Dot),
+            name => Format::ALL.into_iter().find(|format| format.name() == name),
+        }
     }
 }
 
-#[derive(Default, Copy, Clone)]
-struct Cell {
-    pub row: usize,
-    pub col: usize,
-    pub visited: bool,
+struct Options {
+    command: Command,
+    // Maze width and height, in cells
+    size: (usize, usize),
+    // Seed for the generator; a random maze is generated every run when not given
+    seed: Option<u64>,
+    // Load this `.maze` file instead of generating a maze, or `-` to read any format from stdin
+    input: Option<String>,
+    // Generate regions of the maze on this many threads, or with `--count`, this many mazes at once
+    threads: Option<usize>,
+    // Generate this many mazes, seeded from `seed`, into files named after `output`
+    count: Option<usize>,
+    // Preset for size, loops and solution length; an explicit `--size` still wins
+    difficulty: Option<Difficulty>,
+    // Output format; inferred from the output file extension when not given
+    format: Option<Format>,
+    style: Style,
+    scale: usize,
+    supersample: usize,
+    overlays: Overlays,
+    // Leave open space fully transparent instead of filling it with `OPEN_COLOR`
+    transparent: bool,
+    // Run-length encode TGA output
+    rle: bool,
+    // Write PPM output row by row instead of rasterizing the whole image first
+    stream: bool,
+    // Rasterize PPM output straight into a memory-mapped output file
+    mmap: bool,
+    // Split raster output into a grid of separate image files
+    tiles: Option<Tiling>,
+    // Lay several mazes out on a single image instead of rendering one
+    sheet: Option<Sheet>,
+    // Caption under every maze of a sheet; `{index}` and `{seed}` are filled in
+    caption: String,
+    // Title page text of a `book`
+    title: String,
+    // Solver animated by `gui`
+    solver: Solver,
+    // Contestants of a `race`
+    racers: Vec<Racer>,
+    // Generators compared by `survey`
+    generators: Vec<manifest::Generator>,
+    // Image `survey` draws its solution-length histograms to
+    histogram: Option<String>,
+    // Let a minotaur chase the player in `play`
+    minotaur: bool,
+    // Maze sizes timed by `bench`
+    bench_sizes: Vec<usize>,
+    // Address `serve` listens on
+    listen: String,
+    // Print the estimated cost of the run instead of doing it
+    dry_run: bool,
+    // Generate the maze several times with the same seed and fail if the runs differ
+    verify_determinism: bool,
+    // Record every generator step into this trace file
+    trace: Option<String>,
+    // Save the generator's progress to this file as it carves, and carry on from it if it is there
+    checkpoint: Option<String>,
+    // Visited cells between two checkpoints
+    checkpoint_every: usize,
+    // Number or share of dead ends every generated maze is braided or re-carved to
+    dead_ends: Option<DeadEnds>,
+    // How the start and goal of a generated maze are picked and cut through its outer wall, and the
+    // fewest steps between them
+    openings: Option<Placement>,
+    min_path: usize,
+    // Passages and walls every generated maze is carved around
+    fixed: Option<Fixed>,
+    // Word the solution of every generated maze spells
+    word: Option<String>,
+    // File of `row,col,text` lines tagging cells in the JSON export
+    tags: Option<String>,
+    // Picture or line drawing the solution of every generated maze traces
+    shape: Option<Shape>,
+    // Share of the cells every generated maze keeps, the rest left solid
+    density: Option<f32>,
+    // Text every generated maze is carved inside the letters of, the rest left solid
+    mask_text: Option<String>,
+    // Corridors every generated maze redraws at double width
+    wide: Option<Widths>,
+    // Share of the cells of every generated maze refined into sub-mazes, and their cells per side
+    refine: Option<f32>,
+    refine_factor: usize,
+    // Carve caves instead of mazes, from this share of rock smoothed this many times
+    cave: bool,
+    cave_fill: f32,
+    cave_steps: usize,
+    // Carve a single passage along this space-filling curve instead of a maze
+    curve: Option<Curve>,
+    // Carve over a Voronoi diagram of `size` sites instead of the square grid, with this algorithm
+    voronoi: bool,
+    graph_algorithm: Algorithm,
+    // Carve over the surface of a cube with `size` x `size` faces instead, drawn in this layout
+    cube: bool,
+    cube_layout: Layout,
+    // Carve around a cylinder `size` cells around and tall instead, whose columns wrap, or around
+    // a Möbius strip, whose columns wrap upside down
+    cylinder: bool,
+    mobius: bool,
+    // Play a `replay` back in a window instead of rendering the maze
+    animate: bool,
+    // Write the generation and solve animation to this animated PNG, or through `ffmpeg` to this
+    // video file, instead of rendering the maze
+    apng: Option<String>,
+    video: Option<String>,
+    // Most frames an animation file is split into, and how many are shown per second
+    frames: usize,
+    fps: usize,
+    // Include whole-maze statistics in the `analyze` report
+    stats: bool,
+    // The two `.maze` files `diff` compares
+    files: Vec<String>,
+    // Constraints a `search` result has to meet, and how many seeds it tries
+    constraints: Vec<Constraint>,
+    attempts: usize,
+    // Draw progress bars for generation and rendering on stderr
+    progress: bool,
+    // Keep running and write a freshly generated maze to the output on every key press
+    watch: bool,
+    // Output path, or `-` for stdout; may contain `template` placeholders
+    output: String,
+    // Draw the image inline in the terminal in this format instead of writing a file
+    display: Option<ImageFormat>,
+    // Theme colors replacing the defaults
+    solid_color: Option<u32>,
+    open_color: Option<u32>,
+    // Replace existing output files instead of refusing to
+    force: bool,
+    // Write to the first free `out-001.ppm`, `out-002.ppm`, ... instead of the output name itself
+    auto_number: bool,
+    // When the run started, for `{timestamp}` in output names
+    started: u64,
 }
 
-impl Cell {
-    pub fn ind(&self) -> usize {
-        self.row * MAZE_SIZE + self.col
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            command: Command::Render,
+            size: (MAZE_SIZE, MAZE_SIZE),
+            seed: None,
+            input: None,
+            threads: None,
+            count: None,
+            difficulty: None,
+            format: None,
+            style: Style::Blocky,
+            scale: 1,
+            supersample: 1,
+            overlays: Overlays::default(),
+            transparent: false,
+            rle: false,
+            stream: false,
+            mmap: false,
+            tiles: None,
+            sheet: None,
+            caption: String::from(DEFAULT_CAPTION),
+            title: String::from("Mazes"),
+            solver: Solver::Bfs,
+            minotaur: false,
+            racers: Solver::ALL.into_iter().map(Racer::Solver).collect(),
+            generators: Algorithm::ALL.into_iter().map(manifest::Generator::Tree).collect(),
+            histogram: None,
+            bench_sizes: bench::DEFAULT_SIZES.to_vec(),
+            listen: String::from("127.0.0.1:8080"),
+            progress: false,
+            watch: false,
+            dry_run: false,
+            verify_determinism: false,
+            trace: None,
+            checkpoint: None,
+            checkpoint_every: DEFAULT_CHECKPOINT_CELLS,
+            dead_ends: None,
+            openings: None,
+            min_path: 0,
+            fixed: None,
+            word: None,
+            tags: None,
+            shape: None,
+            density: None,
+            mask_text: None,
+            wide: None,
+            refine: None,
+            refine_factor: refine::DEFAULT_FACTOR,
+            cave: false,
+            cave_fill: cave::DEFAULT_FILL,
+            cave_steps: cave::DEFAULT_STEPS,
+            curve: None,
+            voronoi: false,
+            graph_algorithm: Algorithm::Dfs,
+            cube: false,
+            cube_layout: Layout::Net,
+            cylinder: false,
+            mobius: false,
+            animate: false,
+            apng: None,
+            video: None,
+            frames: DEFAULT_FRAMES,
+            fps: DEFAULT_FPS,
+            stats: false,
+            files: vec![],
+            constraints: vec![],
+            attempts: DEFAULT_ATTEMPTS,
+            output: String::from("out.ppm"),
+            display: None,
+            solid_color: None,
+            open_color: None,
+            force: false,
+            auto_number: false,
+            started: template::timestamp(),
+        }
     }
 }
 
-#[derive(Copy, Clone, PartialEq)]
-enum NeighborDir {
-    Center,
-    North,
-    South,
-    West,
-    East,
+impl Options {
+    fn format(&self) -> Format {
+        self.format_of(&self.output)
+    }
+
+    // `--format`, or else the format the extension of `output` names
+    fn format_of(&self, output: &str) -> Format {
+        self.format.unwrap_or_else(|| {
+            let ext = Path::new(output).extension().and_then(|e| e.to_str()).unwrap_or("");
+            Format::from_name(ext).unwrap_or(Format::Image(ImageFormat::Ppm))
+        })
+    }
 }
 
-#[derive(Default)]
-enum WallKind {
-    #[default]
-    Vertical,
-    Horizontal,
+// Pixels each tile shares with its neighbours unless `--tile-overlap` says otherwise
+const DEFAULT_TILE_OVERLAP: usize = 16;
+const DEFAULT_SHEET_MARGIN: usize = 32;
+const DEFAULT_CAPTION: &str = "#{index}";
+const DEFAULT_BOOK_MAZES: usize = 10;
+// Seeds `search` tries before giving up
+const DEFAULT_ATTEMPTS: usize = 10_000;
+// Animation files are split into at most this many frames, played at this rate
+const DEFAULT_FRAMES: usize = 300;
+const DEFAULT_FPS: usize = 30;
+// The finished maze stays on screen this long before an animation loops
+const FINAL_FRAME_MS: u16 = 2000;
+// Cells carved between two `--checkpoint` saves unless `--checkpoint-every` says otherwise
+const DEFAULT_CHECKPOINT_CELLS: usize = 1 << 24;
+
+// What `--help` prints: the commands, then the options grouped by what they apply to
+const HELP: &str = "\
+Usage: maze [COMMAND] [OPTIONS]
+
+Commands:
+  render                      Generate a maze, or load one with `--from`, and save it (the default)
+  gen                         Generate a maze and save it as a `.maze`, DOT or JSON file
+  solve                       Find the path through the maze given with `--from` and save it
+  walk                        Explore the maze in a first-person terminal view
+  play                        Move a marker through a top-down view of the maze in the terminal
+  gui                         Animate generation and solving in a window (needs the `gui` feature)
+  edit                        Toggle walls and move start/goal in the terminal, saving a `.maze` file
+  book                        Write a PDF puzzle book with a solutions section
+  analyze                     Print difficulty metrics of a maze as JSON
+  search                      Try seeds until a maze meets every `--require` constraint
+  validate                    Check that the maze given with `--from` is perfect
+  repair                      Reconnect the regions of the maze given with `--from` and save it
+  diff A.maze B.maze          Render the passages that differ between two mazes
+  replay                      Rebuild a maze from a `--trace` file and render or animate it
+  info FILE...                Print the generation metadata embedded in rendered images
+  race                        Run solvers and random walkers through the maze and rank them
+  chase                       Simulate a minotaur chasing a player who walks the shortest way out
+  batch MANIFEST...           Generate the mazes of job manifests on a pool of threads
+  convert FILE --to FORMAT    Save a maze in another format
+  survey                      Compare how the solutions and dead ends of each generator are spread
+  bench                       Time every generator and renderer
+  serve                       Answer HTTP requests for mazes
+
+Input and output:
+  --input, --from FILE|-      Load a maze (.maze, .json, .dot, .txt or an image) instead of generating one
+  -o, --out, --output FILE|-  Where to save; `{seed}`, `{index}`, `{timestamp}` are filled in
+  --format FORMAT             ppm, png, qoi, bmp, tga, sixel, svg, txt, braille, csv, npy, maze, dot,
+                              json or pdf; taken from the output extension by default
+  --display sixel|kitty|iterm2
+                              Draw the image in the terminal instead of saving it
+  --force                     Replace existing output files
+  --auto-number               Save to the first free `out-001.ppm`, `out-002.ppm`, ...
+  --count N                   Generate N mazes, seeded one after another
+  --dry-run                   Print the estimated cost of the run instead of doing it
+  --watch                     Save a new maze on every key press
+  --progress                  Draw progress bars on stderr
+  --stream                    Write PPM output row by row
+  --mmap                      Rasterize PPM output straight into a memory-mapped file
+  --tags FILE                 Tag cells in the JSON export with the `row,col,text` lines of FILE
+  --config FILE               Read default options from FILE instead of `maze.toml`
+  --profile NAME              Also apply the `[NAME]` section of the config file
+  -h, --help                  Print this help
+
+Generation:
+  --size N|WxH                Maze size in cells
+  --seed N                    Generator seed; random when not given
+  --difficulty easy|medium|hard|extreme
+                              Preset size, algorithm, loops and solution length
+  --algorithm dfs|kruskal|prim
+                              Spanning tree algorithm
+  --threads N                 Carve regions of the maze, or `--count` mazes, on N threads
+  --openings corners|opposite-edges|random|longest
+                              Where the start and goal are cut through the outer wall
+  --min-path N                Fewest steps between the start and goal
+  --dead-ends N|P%            Braid or re-carve the maze to this many dead ends
+  --fixed FILE                Carve around the passages and walls in FILE
+  --word TEXT                 Make the solution spell TEXT
+  --shape FILE.png|FILE.txt   Make the solution trace a picture or line drawing
+  --mask-text TEXT            Carve the maze inside the letters of TEXT
+  --density F                 Keep this share of the cells, leaving the rest solid
+  --wide solution|F           Widen the solution, or this share of the corridors
+  --refine F                  Refine this share of the cells into sub-mazes
+  --refine-factor N           Cells per side of a sub-maze
+  --cave                      Carve a cave instead of a maze
+  --cave-fill F               Share of rock the cave starts from
+  --cave-steps N              Times the cave is smoothed
+  --curve hilbert|serpentine  Carve a single passage along a space-filling curve
+  --voronoi                   Carve over a Voronoi diagram instead of the square grid
+  --cube                      Carve over the surface of a cube
+  --cube-layout net|strip     How the faces of the cube are laid out
+  --cylinder                  Carve around a cylinder whose columns wrap
+  --mobius                    Carve around a Möbius strip
+  --trace FILE.trace          Record every generator step
+  --checkpoint FILE           Save progress while carving, and resume from FILE if it is there
+  --checkpoint-every N        Cells carved between two checkpoints
+  --verify-determinism        Generate twice with the same seed and fail if the mazes differ
+
+Rendering:
+  --style blocky|rounded|isometric
+  --scale N                   Pixels per block
+  --supersample N             Draw N times larger and scale down, smoothing edges
+  --solid-color HEX           Wall color, like `#32a852` or `#32a852ff`
+  --open-color HEX            Passage color
+  --transparent               Leave passages transparent (PNG, QOI, TGA and SVG)
+  --rle                       Run-length encode TGA output
+  --solve                     Draw the solution
+  --solution-gradient         Draw the solution along a color gradient
+  --longest-path              Draw the longest path through the maze
+  --chokepoints N             Bar passages that are the only way into N or more cells
+  --compare                   Overlay the cells and paths every solver explores
+  --visit-order               Color cells by the order the generator visited them
+  --bands N                   Color cells in bands of N steps from the start
+  --label TEXT                Stamp TEXT in a corner of the image
+  --label-corner tl|tr|bl|br  Corner of the label
+  --debug                     Label every cell and outline the carve tree
+  --tiles CxR                 Split the image into a grid of separate files
+  --tile-overlap PX           Pixels tiles share with their neighbours
+  --sheet CxR                 Lay several mazes out on one image
+  --sheet-margin PX           Space around the mazes of a sheet
+  --caption TEXT              Caption under every maze of a sheet; `{index}` and `{seed}` are filled in
+
+Animation:
+  --animate                   Play a `replay` back in a window
+  --apng FILE.png             Save the generation and solve as an animated PNG
+  --video FILE.mp4            Save the generation and solve as a video, through `ffmpeg`
+  --frames N                  Most frames an animation is split into
+  --fps N                     Frames per second
+
+Commands' own options:
+  solve, gui --solver bfs|a*|wall-follower|tremaux
+                              Solver to run, or animate
+  play --minotaur             Let a minotaur chase the player
+  book --title TEXT           Title page text
+  analyze --stats             Include whole-maze statistics
+  search --require METRIC>=N  Constraint the maze has to meet; also `<=`, `>`, `<` and `=`
+  search --attempts N         Seeds to try before giving up
+  race --racers NAME,NAME,... Two or more of bfs, a*, wall-follower, tremaux and walker
+  survey --algorithms NAME,NAME,...
+                              Generators to compare
+  survey --histogram FILE.png Draw the solution length histograms
+  convert --to FORMAT         Output format, also naming the default output file
+  bench --sizes N,N,...       Maze sizes to time
+  serve --listen ADDR:PORT    Address to listen on
+";
+
+fn usage() -> ! {
+    eprintln!("Usage: maze [COMMAND] [OPTIONS]; run `maze --help` for the commands and options");
+    process::exit(1);
 }
 
-#[derive(Default)]
-struct Wall {
-    start: Cell,
-    target: Cell,
-    kind: WallKind
+// Reports a failure past argument parsing and exits
+macro_rules! fail {
+    ($($arg:tt)*) => {{
+        eprintln!("ERROR: {}", format_args!($($arg)*));
+        process::exit(1)
+    }};
 }
 
-const MAZE_SIZE: usize = 10;
+fn parse_factor(flag: &str, value: Option<String>) -> usize {
+    match value.and_then(|v| v.parse::<usize>().ok()) {
+        Some(n) if n >= 1 => n,
+        _ => {
+            eprintln!("ERROR: `{}` expects a positive integer", flag);
+            usage();
+        }
+    }
+}
+
+// Accepts either `N` for a square grid or `WxH`
+fn parse_dims(flag: &str, value: Option<String>) -> (usize, usize) {
+    let size = value.and_then(|v| {
+        let (w, h) = v.split_once('x').unwrap_or((&v, &v));
+        Some((w.parse::<usize>().ok()?, h.parse::<usize>().ok()?))
+    });
+    match size {
+        Some((w, h)) if w >= 1 && h >= 1 => (w, h),
+        _ => {
+            eprintln!("ERROR: `{}` expects `N` or `WxH` with positive integers", flag);
+            usage();
+        }
+    }
+}
+
+// Options a config file may set; the rest pick files or profiles, or only make sense on the
+// command line
+const CONFIG_OPTIONS: &[&str] = &[
+    "format", "style", "size", "difficulty", "seed", "threads", "count", "scale", "supersample", "visit-order", "bands",
+    "solve", "solution-gradient", "longest-path", "compare", "chokepoints", "label", "label-corner", "debug",
+    "transparent", "rle", "display", "stream", "mmap", "tiles", "tile-overlap", "sheet", "sheet-margin", "caption",
+    "title", "progress", "watch", "dry-run", "force", "solid-color", "open-color", "auto-number", "verify-determinism",
+    "trace", "checkpoint", "checkpoint-every", "dead-ends", "openings", "min-path", "fixed", "word", "tags", "shape",
+    "mask-text", "density", "wide", "refine", "refine-factor", "cave", "cave-fill", "cave-steps", "curve", "voronoi",
+    "cube", "cylinder", "mobius", "cube-layout", "algorithm", "animate", "apng", "video", "frames", "fps", "stats",
+    "require", "attempts", "minotaur", "racers", "algorithms", "histogram", "solver", "sizes", "listen", "input", "from",
+    "to", "out", "output",
+];
 
-#[derive(Default)]
-struct Env {
-    grid: [[Cell; MAZE_SIZE]; MAZE_SIZE],
-    removed_walls: Vec<Wall>,
+// Inserts the options from the config file (`--config FILE`, or `maze.toml` if there is one) and
+// the profile picked with `--profile` right after the command, so anything given on the command
+// line overrides them
+fn with_config(mut args: Vec<String>) -> Vec<String> {
+    let mut take = |flag: &str| {
+        let i = args.iter().position(|a| a == flag)?;
+        if i + 1 >= args.len() {
+            eprintln!("ERROR: `{}` expects a value", flag);
+            usage();
+        }
+        Some(args.drain(i..i + 2).nth(1).expect("flag and value"))
+    };
+    let (path, profile) = (take("--config"), take("--profile"));
+    let path = match path {
+        Some(path) => path,
+        None if Path::new(config::DEFAULT_PATH).exists() => String::from(config::DEFAULT_PATH),
+        None if profile.is_some() => {
+            eprintln!("ERROR: `--profile` needs a config file (`{}` or `--config FILE`)", config::DEFAULT_PATH);
+            usage();
+        },
+        None => return args,
+    };
+    let inserted = Config::load(Path::new(&path), CONFIG_OPTIONS).and_then(|config| config.args(profile.as_deref()));
+    let inserted = match inserted {
+        Ok(inserted) => inserted,
+        Err(err) => fail!("{}: {}", path, err),
+    };
+    let at = usize::from(args.first().is_some_and(|a| !a.starts_with('-')));
+    args.splice(at..at, inserted);
+    args
 }
 
-impl Env {
-    fn init() -> Self {
-        let mut this = Self::default();
-        for r in 0..MAZE_SIZE {
-            for c in 0..MAZE_SIZE {
-                this.grid[r][c] = Cell {
-                    row: r,
-                    col: c,
-                    visited: false,
+fn parse_args() -> Options {
+    let mut opts = Options::default();
+    let mut label_corner = Corner::BottomRight;
+    let mut tile_overlap = DEFAULT_TILE_OVERLAP;
+    let mut output_given = false;
+    let mut size_given = false;
+    let mut sheet_margin = DEFAULT_SHEET_MARGIN;
+    let mut args = with_config(env::args().skip(1).collect()).into_iter().peekable();
+    // Whether the command is spelled out, rather than the default `render`
+    let mut named = true;
+    let mut gen = false;
+    // `--to` of `convert`, which also names the extension of the default output
+    let mut to = None;
+    match args.peek().map(String::as_str) {
+        Some("render") => opts.command = Command::Render,
+        Some("gen") => gen = true,
+        Some("solve") => opts.command = Command::Solve,
+        Some("walk") => opts.command = Command::Walk,
+        Some("play") => opts.command = Command::Play,
+        Some("gui") => opts.command = Command::Gui,
+        Some("bench") => opts.command = Command::Bench,
+        Some("serve") => opts.command = Command::Serve,
+        Some("edit") => opts.command = Command::Edit,
+        Some("book") => opts.command = Command::Book,
+        Some("analyze") => opts.command = Command::Analyze,
+        Some("search") => opts.command = Command::Search,
+        Some("validate") => opts.command = Command::Validate,
+        Some("repair") => opts.command = Command::Repair,
+        Some("diff") => opts.command = Command::Diff,
+        Some("replay") => opts.command = Command::Replay,
+        Some("info") => opts.command = Command::Info,
+        Some("race") => opts.command = Command::Race,
+        Some("chase") => opts.command = Command::Chase,
+        Some("batch") => opts.command = Command::Batch,
+        Some("convert") => opts.command = Command::Convert,
+        Some("survey") => opts.command = Command::Survey,
+        _ => named = false,
+    }
+    if named {
+        args.next();
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => opts.format = match args.next().as_deref().and_then(Format::from_name) {
+                Some(format) => Some(format),
+                None => {
+                    let names: Vec<&str> = Format::ALL.iter().map(|f| f.name()).collect();
+                    eprintln!("ERROR: `--format` expects one of {}", names.join(", "));
+                    usage();
+                }
+            },
+            "--style" => opts.style = match args.next().as_deref().and_then(Style::from_name) {
+                Some(style) => style,
+                None => {
+                    eprintln!("ERROR: `--style` expects `blocky`, `rounded` or `isometric`");
+                    usage();
+                }
+            },
+            "--size" => {
+                opts.size = parse_dims(&arg, args.next());
+                if opts.size.0.checked_mul(opts.size.1).is_none_or(|cells| cells > MAX_CELLS) {
+                    eprintln!("ERROR: `--size` allows at most {} cells", MAX_CELLS);
+                    usage();
+                }
+                size_given = true;
+            },
+            "--difficulty" => opts.difficulty = match args.next().as_deref().and_then(Difficulty::from_name) {
+                Some(difficulty) => Some(difficulty),
+                None => {
+                    eprintln!("ERROR: `--difficulty` expects `easy`, `medium`, `hard` or `extreme`");
+                    usage();
+                }
+            },
+            "--seed" => opts.seed = match args.next().and_then(|v| v.parse::<u64>().ok()) {
+                Some(seed) => Some(seed),
+                None => {
+                    eprintln!("ERROR: `--seed` expects a non-negative integer");
+                    usage();
+                }
+            },
+            "--threads" => opts.threads = Some(parse_factor(&arg, args.next())),
+            "--count" => opts.count = Some(parse_factor(&arg, args.next())),
+            "--scale" => opts.scale = parse_factor(&arg, args.next()),
+            "--supersample" => opts.supersample = parse_factor(&arg, args.next()),
+            "--visit-order" => opts.overlays.visit_order = true,
+            "--bands" => opts.overlays.bands = Some(parse_factor(&arg, args.next())),
+            "--solve" => opts.overlays.solve = true,
+            "--solution-gradient" => {
+                opts.overlays.solve = true;
+                opts.overlays.solution_gradient = true;
+            },
+            "--longest-path" => opts.overlays.longest_path = true,
+            "--compare" => opts.overlays.compare = true,
+            "--chokepoints" => opts.overlays.chokepoints = Some(parse_factor(&arg, args.next())),
+            "--label" => match args.next() {
+                Some(text) => opts.overlays.label = Some((text, label_corner)),
+                None => usage(),
+            },
+            "--label-corner" => {
+                label_corner = match args.next().as_deref() {
+                    Some("tl") => Corner::TopLeft,
+                    Some("tr") => Corner::TopRight,
+                    Some("bl") => Corner::BottomLeft,
+                    Some("br") => Corner::BottomRight,
+                    _ => {
+                        eprintln!("ERROR: `--label-corner` expects `tl`, `tr`, `bl` or `br`");
+                        usage();
+                    }
+                };
+                if let Some((_, corner)) = opts.overlays.label.as_mut() {
+                    *corner = label_corner;
+                }
+            },
+            "--debug" => opts.overlays.debug = true,
+            "--transparent" => opts.transparent = true,
+            "--rle" => opts.rle = true,
+            "--display" => {
+                opts.display = match args.next().as_deref() {
+                    Some("sixel") => Some(ImageFormat::Sixel),
+                    Some("kitty") => Some(ImageFormat::Kitty),
+                    Some("iterm2") => Some(ImageFormat::Iterm2),
+                    _ => {
+                        eprintln!("ERROR: `--display` expects `sixel`, `kitty` or `iterm2`");
+                        usage();
+                    }
                 };
+            },
+            "--stream" => opts.stream = true,
+            "--mmap" => opts.mmap = true,
+            "--tiles" => {
+                let (cols, rows) = parse_dims(&arg, args.next());
+                let overlap = opts.tiles.map_or(DEFAULT_TILE_OVERLAP, |t| t.overlap);
+                opts.tiles = Some(Tiling { cols, rows, overlap });
+            },
+            "--tile-overlap" => match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                Some(overlap) => tile_overlap = overlap,
+                None => {
+                    eprintln!("ERROR: `--tile-overlap` expects a non-negative integer");
+                    usage();
+                }
+            },
+            "--sheet" => {
+                let (cols, rows) = parse_dims(&arg, args.next());
+                opts.sheet = Some(Sheet { cols, rows, margin: DEFAULT_SHEET_MARGIN });
+            },
+            "--sheet-margin" => match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                Some(margin) => sheet_margin = margin,
+                None => {
+                    eprintln!("ERROR: `--sheet-margin` expects a non-negative integer");
+                    usage();
+                }
+            },
+            "--caption" => match args.next() {
+                Some(text) => opts.caption = text,
+                None => usage(),
+            },
+            "--title" => match args.next() {
+                Some(text) => opts.title = text,
+                None => usage(),
+            },
+            "--progress" => opts.progress = true,
+            "--watch" => opts.watch = true,
+            "--dry-run" => opts.dry_run = true,
+            "--force" => opts.force = true,
+            "--solid-color" | "--open-color" => match args.next().as_deref().and_then(parse_color) {
+                Some(color) if arg == "--solid-color" => opts.solid_color = Some(color),
+                Some(color) => opts.open_color = Some(color),
+                None => {
+                    eprintln!("ERROR: `{}` expects a color like `#32a852` or `#32a852ff`", arg);
+                    usage();
+                }
+            },
+            "--auto-number" => opts.auto_number = true,
+            "--verify-determinism" => opts.verify_determinism = true,
+            "--trace" => match args.next() {
+                Some(path) => opts.trace = Some(path),
+                None => usage(),
+            },
+            "--checkpoint" => match args.next() {
+                Some(path) => opts.checkpoint = Some(path),
+                None => usage(),
+            },
+            "--checkpoint-every" => opts.checkpoint_every = parse_factor(&arg, args.next()),
+            "--dead-ends" => opts.dead_ends = match args.next().as_deref().and_then(DeadEnds::parse) {
+                Some(dead_ends) => Some(dead_ends),
+                None => {
+                    eprintln!("ERROR: `--dead-ends` expects a count like `40` or a percentage of the cells like `25%`");
+                    usage();
+                }
+            },
+            "--openings" => opts.openings = match args.next().as_deref().and_then(Placement::from_name) {
+                Some(placement) => Some(placement),
+                None => {
+                    eprintln!("ERROR: `--openings` expects `corners`, `opposite-edges`, `random` or `longest`");
+                    usage();
+                }
+            },
+            "--min-path" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(steps) => opts.min_path = steps,
+                None => {
+                    eprintln!("ERROR: `--min-path` expects a number of steps");
+                    usage();
+                }
+            },
+            "--fixed" => match args.next() {
+                Some(path) => match Fixed::load(Path::new(&path)) {
+                    Ok(fixed) => opts.fixed = Some(fixed),
+                    Err(err) => fail!("Failed to load fixed passages and walls from `{}`: {}", path, err),
+                },
+                None => usage(),
+            },
+            "--word" => match args.next() {
+                Some(word) => opts.word = Some(word),
+                None => usage(),
+            },
+            "--tags" => match args.next() {
+                Some(path) => opts.tags = Some(path),
+                None => usage(),
+            },
+            "--shape" => match args.next() {
+                Some(path) => match Shape::load(Path::new(&path)) {
+                    Ok(shape) => opts.shape = Some(shape),
+                    Err(err) => fail!("Failed to load shape from `{}`: {}", path, err),
+                },
+                None => usage(),
+            },
+            "--mask-text" => match args.next() {
+                Some(text) => opts.mask_text = Some(text),
+                None => usage(),
+            },
+            "--density" => match args.next().and_then(|d| d.parse::<f32>().ok()) {
+                Some(density) if density > 0.0 && density <= 1.0 => opts.density = Some(density),
+                _ => {
+                    eprintln!("ERROR: `--density` expects the share of cells to keep, above 0 and at most 1, like `0.6`");
+                    usage();
+                }
+            },
+            "--wide" => match args.next().as_deref().and_then(Widths::parse) {
+                Some(wide) => opts.wide = Some(wide),
+                None => {
+                    eprintln!("ERROR: `--wide` expects `solution` or the share of corridors to widen, above 0 and at most 1, like `0.3`");
+                    usage();
+                }
+            },
+            "--refine" => match args.next().and_then(|r| r.parse::<f32>().ok()) {
+                Some(share) if (0.0..=1.0).contains(&share) => opts.refine = Some(share),
+                _ => {
+                    eprintln!("ERROR: `--refine` expects the share of cells to refine, from 0 to 1, like `0.3`");
+                    usage();
+                }
+            },
+            "--refine-factor" => opts.refine_factor = parse_factor(&arg, args.next()),
+            "--cave" => opts.cave = true,
+            "--cave-fill" => match args.next().and_then(|f| f.parse::<f32>().ok()) {
+                Some(fill) if (0.0..1.0).contains(&fill) => opts.cave_fill = fill,
+                _ => {
+                    eprintln!("ERROR: `--cave-fill` expects the share of rock to start from, at least 0 and below 1, like `0.45`");
+                    usage();
+                }
+            },
+            "--cave-steps" => match args.next().and_then(|n| n.parse::<usize>().ok()) {
+                Some(steps) => opts.cave_steps = steps,
+                None => {
+                    eprintln!("ERROR: `--cave-steps` expects a non-negative integer");
+                    usage();
+                }
+            },
+            "--curve" => match args.next().as_deref().and_then(Curve::from_name) {
+                Some(curve) => opts.curve = Some(curve),
+                None => {
+                    let names: Vec<&str> = Curve::ALL.iter().map(|c| c.name()).collect();
+                    eprintln!("ERROR: `--curve` expects one of {}", names.join(", "));
+                    usage();
+                }
+            },
+            "--voronoi" => opts.voronoi = true,
+            "--cube" => opts.cube = true,
+            "--cylinder" => opts.cylinder = true,
+            "--mobius" => opts.mobius = true,
+            "--cube-layout" => match args.next().as_deref().and_then(Layout::from_name) {
+                Some(layout) => opts.cube_layout = layout,
+                None => {
+                    eprintln!("ERROR: `--cube-layout` expects `net` or `strip`");
+                    usage();
+                }
+            },
+            "--algorithm" => match args.next().as_deref().and_then(Algorithm::from_name) {
+                Some(algorithm) => opts.graph_algorithm = algorithm,
+                None => {
+                    let names: Vec<&str> = Algorithm::ALL.iter().map(|a| a.name()).collect();
+                    eprintln!("ERROR: `--algorithm` expects one of {}", names.join(", "));
+                    usage();
+                }
+            },
+            "--animate" => opts.animate = true,
+            "--apng" => match args.next() {
+                Some(path) => opts.apng = Some(path),
+                None => usage(),
+            },
+            "--video" => match args.next() {
+                Some(path) => opts.video = Some(path),
+                None => usage(),
+            },
+            "--frames" => opts.frames = parse_factor(&arg, args.next()),
+            "--fps" => opts.fps = parse_factor(&arg, args.next()),
+            "--stats" => opts.stats = true,
+            "--require" => match args.next().as_deref().map(Constraint::parse) {
+                Some(Some(constraint)) => opts.constraints.push(constraint),
+                _ => {
+                    let metrics: Vec<&str> = search::Metric::ALL.iter().map(|m| m.name()).collect();
+                    eprintln!("ERROR: `--require` expects `METRIC>=N`, `<=`, `>`, `<` or `=`, with METRIC one of {}", metrics.join(", "));
+                    usage();
+                }
+            },
+            "--attempts" => opts.attempts = parse_factor(&arg, args.next()),
+            "--minotaur" => opts.minotaur = true,
+            "--racers" => {
+                let racers: Option<Vec<Racer>> = args.next().and_then(|list| list.split(',').map(|name| Racer::from_name(name.trim())).collect());
+                match racers {
+                    Some(racers) if racers.len() >= 2 && opts.command == Command::Race => opts.racers = racers,
+                    _ => {
+                        eprintln!("ERROR: `race --racers` expects two or more of `bfs`, `a*`, `wall-follower`, `tremaux` and `walker`, separated by commas");
+                        usage();
+                    }
+                }
+            },
+            "--algorithms" => {
+                let generators: Option<Vec<manifest::Generator>> = args.next().and_then(|list| list.split(',').map(|name| manifest::Generator::from_name(name.trim())).collect());
+                match generators {
+                    Some(generators) if !generators.is_empty() && opts.command == Command::Survey => opts.generators = generators,
+                    _ => {
+                        eprintln!("ERROR: `survey --algorithms` expects one or more of `dfs`, `kruskal`, `prim`, `cave`, `hilbert` and `serpentine`, separated by commas");
+                        usage();
+                    }
+                }
+            },
+            "--histogram" => match args.next() {
+                Some(path) if opts.command == Command::Survey => opts.histogram = Some(path),
+                _ => {
+                    eprintln!("ERROR: `survey --histogram` expects an image file");
+                    usage();
+                }
+            },
+            "--solver" => opts.solver = match args.next().as_deref().and_then(Solver::from_name) {
+                Some(solver) => solver,
+                None => {
+                    eprintln!("ERROR: `--solver` expects `bfs`, `a*`, `wall-follower` or `tremaux`");
+                    usage();
+                }
+            },
+            "--sizes" => {
+                let sizes = args.next().unwrap_or_default();
+                opts.bench_sizes = sizes.split(',').map(|n| parse_factor(&arg, Some(n.to_string()))).collect();
+            },
+            "--listen" => match args.next() {
+                Some(addr) => opts.listen = addr,
+                None => usage(),
+            },
+            "--input" | "--from" => match args.next() {
+                Some(path) => opts.input = Some(path),
+                None => usage(),
+            },
+            "--to" => match args.next() {
+                Some(name) if Format::from_name(&name).is_some() => to = Some(name),
+                _ => {
+                    let names: Vec<&str> = Format::ALL.iter().map(|f| f.name()).collect();
+                    eprintln!("ERROR: `--to` expects one of {}", names.join(", "));
+                    usage();
+                }
+            },
+            "-h" | "--help" => {
+                print!("{}", HELP);
+                process::exit(0);
+            },
+            "-o" | "--out" | "--output" => match args.next() {
+                Some(path) => {
+                    opts.output = path;
+                    output_given = true;
+                },
+                None => usage(),
+            },
+            path if matches!(opts.command, Command::Diff | Command::Info | Command::Batch) && (path == "-" || !path.starts_with('-')) => opts.files.push(arg),
+            path if opts.command == Command::Convert && opts.input.is_none() && (path == "-" || !path.starts_with('-')) => opts.input = Some(arg),
+            _ => {
+                eprintln!("ERROR: Unknown argument `{}`", arg);
+                usage();
             }
         }
-        this.removed_walls = vec![];
-        this
     }
+    if let Some(tiles) = opts.tiles.as_mut() {
+        tiles.overlap = tile_overlap;
+    }
+    if let Some(sheet) = opts.sheet.as_mut() {
+        sheet.margin = sheet_margin;
+    }
+    if let Some(display) = opts.display {
+        if output_given || opts.format.is_some() {
+            eprintln!("ERROR: `--display` writes to the terminal and cannot be combined with `--output` or `--format`");
+            usage();
+        }
+        opts.format = Some(Format::Image(display));
+        opts.output = String::from("-");
+    }
+    if let Some(difficulty) = opts.difficulty {
+        if !size_given {
+            let size = difficulty.preset().size;
+            opts.size = (size, size);
+        }
+        if opts.input.is_some() || region_threads(&opts) {
+            eprintln!("ERROR: `--difficulty` generates its own mazes and cannot be combined with `--input` or region `--threads`");
+            usage();
+        }
+    }
+    if opts.command == Command::Edit {
+        // Edits are saved back to the loaded file unless told otherwise
+        if !output_given {
+            opts.output = opts.input.clone().unwrap_or_else(|| format!("out.{}", mazefile::EXTENSION));
+        }
+        if opts.format() != Format::Maze || opts.output == "-" {
+            eprintln!("ERROR: `edit` saves `.maze` files; pass `-o FILE.maze`");
+            usage();
+        }
+    }
+    if opts.command == Command::Diff {
+        if !output_given {
+            opts.output = String::from("diff.png");
+        }
+        if opts.files.len() != 2 || opts.files.iter().all(|path| path == "-") || !matches!(opts.format(), Format::Image(_)) {
+            eprintln!("ERROR: `diff` compares two `.maze` files, at most one of them from standard input, and renders a ppm/png image");
+            usage();
+        }
+    }
+    // Repairs are saved back to the loaded file unless told otherwise
+    if opts.command == Command::Repair && !output_given {
+        opts.output = opts.input.clone().unwrap_or_default();
+        opts.force = !opts.auto_number;
+    }
+    if opts.watch && (opts.command != Command::Render || opts.input.is_some() || opts.output == "-") {
+        eprintln!("ERROR: `--watch` regenerates a file output and cannot be combined with subcommands or `--input`");
+        usage();
+    }
+    if matches!(opts.command, Command::Analyze | Command::Survey) && !output_given {
+        opts.output = String::from("-");
+    }
+    if opts.command == Command::Search {
+        if opts.constraints.is_empty() || opts.input.is_some() || opts.dry_run || opts.output == "-" {
+            eprintln!("ERROR: `search` needs at least one `--require`, cannot be combined with `--input` or `--dry-run` and saves the winner to a file");
+            usage();
+        }
+        // The winning maze is only saved when asked for
+        if !output_given {
+            opts.output.clear();
+        }
+    }
+    if gen || opts.command == Command::Solve {
+        // Standard output has no extension to tell the format by
+        if opts.format.is_none() && (opts.output == "-" || (opts.command == Command::Solve && !output_given)) {
+            opts.format = Some(Format::Maze);
+        }
+    }
+    if gen {
+        if !output_given {
+            opts.output = format!("out.{}", mazefile::EXTENSION);
+        }
+        if opts.input.is_some() || !matches!(opts.format(), Format::Maze | Format::Dot | Format::Json) {
+            eprintln!("ERROR: `gen` saves new mazes as `.maze`, DOT or JSON files; use `render` for images");
+            usage();
+        }
+    }
+    if opts.command == Command::Solve {
+        if !output_given {
+            opts.output = String::from("-");
+        }
+        if opts.input.is_none() || !matches!(opts.format(), Format::Maze | Format::Json) {
+            eprintln!("ERROR: `solve` finds the path through the maze given with `--from` and saves it as a `.maze` or JSON file");
+            usage();
+        }
+    }
+    if opts.command == Command::Convert {
+        let Some(input) = opts.input.as_deref() else {
+            eprintln!("ERROR: `convert` needs the maze to convert, as `convert FILE` or `--from FILE`");
+            usage();
+        };
+        if to.is_some() && opts.format.is_some() {
+            eprintln!("ERROR: `--to` and `--format` both name the output format; give one of them");
+            usage();
+        }
+        if let Some(name) = to.as_deref() {
+            opts.format = Format::from_name(name);
+        }
+        // `in.maze --to svg` is saved as `in.svg`
+        if !output_given {
+            opts.output = match (input, to.as_deref()) {
+                ("-", _) => String::from("-"),
+                (input, Some(name)) => Path::new(input).with_extension(name).to_string_lossy().into_owned(),
+                _ => {
+                    eprintln!("ERROR: `convert` needs `--to FORMAT` or an output file to take the format from");
+                    usage();
+                }
+            };
+        }
+        if opts.output == "-" && opts.format.is_none() {
+            eprintln!("ERROR: `convert` to standard output needs `--to FORMAT`");
+            usage();
+        }
+        if opts.output == input && input != "-" {
+            eprintln!("ERROR: `convert` would overwrite `{}` with itself; pass `-o FILE`", input);
+            usage();
+        }
+    }
+    if to.is_some() && opts.command != Command::Convert {
+        eprintln!("ERROR: `--to` is the output format of `convert`; use `--format` otherwise");
+        usage();
+    }
+    if matches!(opts.command, Command::Validate | Command::Repair) && opts.input.is_none() {
+        eprintln!("ERROR: `validate` and `repair` work on the maze given with `--input`");
+        usage();
+    }
+    if opts.trace.is_some() {
+        let generates = matches!(opts.command, Command::Render | Command::Walk | Command::Play | Command::Gui | Command::Edit | Command::Analyze | Command::Race | Command::Chase);
+        if !generates || opts.input.is_some() || opts.threads.is_some() || opts.difficulty.is_some() || opts.count.is_some() || opts.watch || opts.sheet.is_some() {
+            eprintln!("ERROR: `--trace` records a single maze from the serial generator and cannot be combined with `--input`, `--threads`, `--difficulty`, `--count`, `--watch` or `--sheet`");
+            usage();
+        }
+    }
+    if let Some(shape) = &opts.shape {
+        if opts.fixed.is_some() || opts.word.is_some() {
+            eprintln!("ERROR: `--shape` fixes the solution path itself and cannot be combined with `--fixed` or `--word`");
+            usage();
+        }
+        match shape::fixed(shape, opts.size.0, opts.size.1) {
+            Ok(fixed) => opts.fixed = Some(fixed),
+            Err(err) => fail!("Cannot trace the shape: {}", err),
+        }
+    }
+    if let Some(text) = opts.word.as_deref() {
+        if opts.fixed.is_some() {
+            eprintln!("ERROR: `--word` fixes the solution path itself and cannot be combined with `--fixed`");
+            usage();
+        }
+        // Grow the default size to fit the word; an explicit `--size` has to fit it already
+        if !size_given {
+            let (width, height) = word::min_size(text);
+            opts.size = (opts.size.0.max(width + 2), opts.size.1.max(height + 4));
+        }
+        match word::fixed(text, opts.size.0, opts.size.1) {
+            Ok(fixed) => opts.fixed = Some(fixed),
+            Err(err) => fail!("Cannot spell the word: {}", err),
+        }
+    }
+    if let Some(fixed) = &opts.fixed {
+        if opts.input.is_some() || opts.difficulty.is_some() || opts.trace.is_some() || region_threads(&opts) {
+            eprintln!("ERROR: `--fixed`, `--word` and `--shape` guide the serial generator and cannot be combined with `--input`, `--difficulty`, `--trace` or region `--threads`");
+            usage();
+        }
+        if let Err(err) = fixed.check(opts.size.0, opts.size.1) {
+            fail!("Cannot carve a {}x{} maze around the fixed passages and walls: {}", opts.size.0, opts.size.1, err);
+        }
+    }
+    if let Some(text) = opts.mask_text.as_deref() {
+        let other = opts.trace.is_some() || opts.checkpoint.is_some() || opts.fixed.is_some() || opts.density.is_some() || opts.cave || opts.curve.is_some();
+        if opts.input.is_some() || opts.difficulty.is_some() || other || region_threads(&opts) {
+            eprintln!("ERROR: `--mask-text` leaves cells out before the serial generator runs and cannot be combined with `--input`, `--difficulty`, `--trace`, `--checkpoint`, `--fixed`, `--word`, `--shape`, `--density`, `--cave`, `--curve` or region `--threads`");
+            usage();
+        }
+        // Grow the default size to fit the text; an explicit `--size` has to fit it already
+        if !size_given {
+            let (width, height) = textmask::min_size(text);
+            opts.size = (opts.size.0.max(width), opts.size.1.max(height));
+        }
+        if let Err(err) = textmask::mask(&mut Env::new(opts.size.0, opts.size.1), text) {
+            fail!("Cannot mask the maze with the text: {}", err);
+        }
+    }
+    if opts.density.is_some() && (opts.input.is_some() || opts.difficulty.is_some() || opts.trace.is_some() || opts.fixed.is_some() || region_threads(&opts)) {
+        eprintln!("ERROR: `--density` leaves cells out before the serial generator runs and cannot be combined with `--input`, `--difficulty`, `--trace`, `--fixed`, `--word`, `--shape` or region `--threads`");
+        usage();
+    }
+    if (opts.cave || opts.curve.is_some()) && (opts.input.is_some() || opts.difficulty.is_some() || opts.trace.is_some() || opts.fixed.is_some() || opts.density.is_some() || (opts.cave && opts.curve.is_some()) || region_threads(&opts)) {
+        eprintln!("ERROR: `--cave` and `--curve` replace the maze generator and cannot be combined with each other, `--input`, `--difficulty`, `--trace`, `--fixed`, `--word`, `--shape`, `--density` or region `--threads`");
+        usage();
+    }
+    if opts.checkpoint.is_some() {
+        let generates = matches!(opts.command, Command::Render | Command::Walk | Command::Play | Command::Gui | Command::Edit | Command::Analyze | Command::Race | Command::Chase);
+        let other = opts.trace.is_some() || opts.fixed.is_some() || opts.density.is_some() || opts.cave || opts.curve.is_some();
+        if !generates || other || opts.input.is_some() || opts.threads.is_some() || opts.difficulty.is_some() || opts.count.is_some() || opts.watch || opts.sheet.is_some() {
+            eprintln!("ERROR: `--checkpoint` saves a single maze from the serial generator and cannot be combined with `--input`, `--threads`, `--difficulty`, `--count`, `--watch`, `--sheet`, `--trace`, `--fixed`, `--word`, `--shape`, `--density`, `--cave` or `--curve`");
+            usage();
+        }
+    } else if opts.checkpoint_every != DEFAULT_CHECKPOINT_CELLS {
+        eprintln!("ERROR: `--checkpoint-every` only applies with `--checkpoint`");
+        usage();
+    }
+    if opts.dead_ends.is_some() {
+        let other = opts.trace.is_some() || opts.fixed.is_some() || opts.curve.is_some() || opts.voronoi || opts.cube || opts.cylinder || opts.mobius;
+        if opts.input.is_some() || opts.difficulty.is_some() || other {
+            eprintln!("ERROR: `--dead-ends` reworks generated grid mazes and cannot be combined with `--input`, `--difficulty`, `--trace`, `--fixed`, `--word`, `--shape`, `--curve` or the graph layouts");
+            usage();
+        }
+    }
+    if let Some(curve) = opts.curve.filter(|curve| !curve.fits(opts.size.0, opts.size.1)) {
+        eprintln!("ERROR: the {} curve only covers square grids with a side that is a power of two, like `--size 32`", curve.name());
+        usage();
+    }
+    let graph_layouts = [opts.voronoi, opts.cube, opts.cylinder, opts.mobius].into_iter().filter(|&layout| layout).count();
+    if graph_layouts > 0 {
+        let grid_only = opts.input.is_some() || opts.difficulty.is_some() || opts.trace.is_some() || opts.checkpoint.is_some() || opts.fixed.is_some() || opts.density.is_some() || opts.mask_text.is_some() || opts.cave || opts.curve.is_some() || opts.wide.is_some() || opts.refine.is_some() || graph_layouts > 1;
+        let batch = opts.count.is_some() || opts.watch || opts.sheet.is_some() || opts.tiles.is_some() || opts.apng.is_some() || opts.video.is_some();
+        let overlays = &opts.overlays;
+        let grid_drawing = opts.style != Style::Blocky || overlays.visit_order || overlays.bands.is_some() || overlays.solution_gradient || overlays.longest_path || overlays.chokepoints.is_some() || overlays.compare || overlays.debug || overlays.label.is_some();
+        if opts.command != Command::Render || grid_only || batch || grid_drawing || !matches!(opts.format(), Format::Image(_) | Format::Svg) {
+            eprintln!("ERROR: `--voronoi`, `--cube`, `--cylinder` and `--mobius` render a single maze as an image (or SVG for `--voronoi`), optionally with `--solve`, and cannot be combined with each other, subcommands, other generators or layouts, styles, other overlays, `--count`, `--watch`, `--sheet`, `--tiles` or animations");
+            usage();
+        }
+        if opts.cube && (opts.size.0 != opts.size.1 || opts.format() == Format::Svg) {
+            eprintln!("ERROR: `--cube` needs square faces (`--size N`) and an image format");
+            usage();
+        }
+        if (opts.cylinder || opts.mobius) && opts.format() == Format::Svg {
+            eprintln!("ERROR: `--cylinder` and `--mobius` need an image format");
+            usage();
+        }
+    } else if opts.graph_algorithm != Algorithm::Dfs {
+        let generator = opts.input.is_some() || opts.difficulty.is_some() || opts.trace.is_some() || opts.checkpoint.is_some() || opts.fixed.is_some() || opts.density.is_some() || opts.mask_text.is_some() || opts.cave || opts.curve.is_some() || region_threads(&opts);
+        let steps = opts.overlays.visit_order || opts.apng.is_some() || opts.video.is_some() || opts.verify_determinism;
+        if generator || steps {
+            eprintln!("ERROR: `--algorithm kruskal|prim` carves whole grids or graph layouts in one go and cannot be combined with `--input`, `--difficulty`, `--trace`, `--checkpoint`, `--fixed`, `--word`, `--shape`, `--mask-text`, `--density`, `--cave`, `--curve`, region `--threads`, `--visit-order`, animations or `--verify-determinism`");
+            usage();
+        }
+    }
+    if opts.cube_layout != Layout::Net && !opts.cube {
+        eprintln!("ERROR: `--cube-layout` only applies to `--cube` mazes");
+        usage();
+    }
+    if opts.tags.is_some() && opts.format() != Format::Json {
+        eprintln!("ERROR: `--tags` only applies to `--format json`");
+        usage();
+    }
+    if (opts.wide.is_some() || opts.refine.is_some()) && (opts.input.is_some() || opts.trace.is_some() || (opts.wide.is_some() && opts.refine.is_some())) {
+        eprintln!("ERROR: `--wide` and `--refine` redraw generated mazes, one at a time, and cannot be combined with `--input` or `--trace`");
+        usage();
+    }
+    if opts.command == Command::Replay && opts.input.is_none() {
+        eprintln!("ERROR: `replay` plays back the trace given with `--input`");
+        usage();
+    }
+    if opts.minotaur && opts.command != Command::Play {
+        eprintln!("ERROR: `--minotaur` is an option of `play`; `chase` runs the scripted version");
+        usage();
+    }
+    if opts.animate && opts.command != Command::Replay {
+        eprintln!("ERROR: `--animate` is an option of `replay`");
+        usage();
+    }
+    if opts.apng.is_some() || opts.video.is_some() {
+        let generated = opts.command == Command::Render && opts.input.is_none();
+        let other = opts.count.is_some() || opts.watch || opts.sheet.is_some() || opts.tiles.is_some() || opts.animate || opts.dry_run;
+        if !(generated || matches!(opts.command, Command::Replay | Command::Race | Command::Chase)) || other || (opts.apng.is_some() && opts.video.is_some()) {
+            eprintln!("ERROR: `--apng` and `--video` animate a generated maze, a `replay`, a `race` or a `chase`, one at a time, and cannot be combined with `--count`, `--watch`, `--sheet`, `--tiles`, `--animate` or `--dry-run`");
+            usage();
+        }
+        if opts.frames < 2 || opts.fps > 1000 {
+            eprintln!("ERROR: an animation needs at least 2 `--frames` and at most 1000 `--fps`");
+            usage();
+        }
+    }
+    if opts.verify_determinism && (opts.command != Command::Render || opts.input.is_some() || opts.difficulty.is_some()) {
+        eprintln!("ERROR: `--verify-determinism` checks the generators and cannot be combined with subcommands, `--input` or `--difficulty`");
+        usage();
+    }
+    if opts.stats && opts.command != Command::Analyze {
+        eprintln!("ERROR: `--stats` is an option of `analyze`");
+        usage();
+    }
+    if opts.command == Command::Book {
+        if !output_given {
+            opts.output = String::from("book.pdf");
+        }
+        if opts.format() != Format::Pdf || opts.input.is_some() || opts.watch {
+            eprintln!("ERROR: `book` writes a PDF (`-o FILE.pdf`) and cannot be combined with `--input` or `--watch`");
+            usage();
+        }
+    }
+    let writes_files = matches!(opts.command, Command::Render | Command::Book) && opts.output != "-";
+    if opts.count.is_some() && (!(writes_files || opts.command == Command::Survey) || opts.input.is_some() || opts.watch) {
+        eprintln!("ERROR: `--count` writes files and cannot be combined with subcommands, `--input` or `--watch`");
+        usage();
+    }
+    if opts.overlays.visit_order && opts.overlays.bands.is_some() {
+        eprintln!("ERROR: `--visit-order` and `--bands` both color the cells; pick one");
+        usage();
+    }
+    // Replayed traces keep the visit order; loaded `.maze` files do not
+    if opts.input.is_some() && opts.command != Command::Replay && opts.overlays.visit_order {
+        eprintln!("ERROR: `--visit-order` needs a generated maze or a trace; `.maze` files have no visit order");
+        usage();
+    }
+    let raster = matches!(opts.format(), Format::Image(_));
+    if opts.sheet.is_some() {
+        let other = opts.command != Command::Render || opts.input.is_some() || opts.watch || opts.count.is_some();
+        if !raster || other || opts.tiles.is_some() || opts.stream || opts.mmap {
+            eprintln!("ERROR: `--sheet` renders one ppm/png image and cannot be combined with subcommands, `--input`, `--watch`, `--count`, `--tiles`, `--stream` or `--mmap`");
+            usage();
+        }
+    }
+    if opts.tiles.is_some() && (!raster || opts.stream || opts.mmap || opts.output == "-") {
+        eprintln!("ERROR: `--tiles` needs ppm/png file output and cannot be combined with `--stream` or `--mmap`");
+        usage();
+    }
+    // `analyze` lists the `--chokepoints` instead of drawing them
+    let drawn = opts.command != Command::Analyze;
+    if drawn && (opts.overlays.any_top_down() || opts.overlays.label.is_some() || opts.style != Style::Blocky) && !raster && !opts.voronoi && !opts.cube && !opts.cylinder && !opts.mobius {
+        eprintln!("ERROR: styles and overlays are only available for image (ppm/png) output");
+        usage();
+    }
+    if opts.overlays.any_top_down() && opts.style == Style::Isometric {
+        eprintln!("ERROR: `--debug`, `--visit-order`, `--bands`, `--solve`, `--longest-path`, `--chokepoints` and `--compare` only work with top-down styles");
+        usage();
+    }
+    if opts.stream || opts.mmap {
+        let plain = !opts.overlays.any_top_down() && opts.overlays.label.is_none();
+        if opts.format() != Format::Image(ImageFormat::Ppm) || opts.style != Style::Blocky || opts.supersample != 1 || !plain {
+            eprintln!("ERROR: `--stream` and `--mmap` only support plain blocky PPM output without supersampling or overlays");
+            usage();
+        }
+    }
+    // A path length alone keeps the corners and checks them
+    if opts.min_path > 0 && opts.openings.is_none() {
+        opts.openings = Some(Placement::Corners);
+    }
+    if opts.openings.is_some() {
+        let shown = match opts.format() {
+            Format::Image(_) => opts.style != Style::Isometric && !opts.stream && !opts.mmap,
+            format => matches!(format, Format::Maze | Format::Dot | Format::Json),
+        };
+        let other = opts.voronoi || opts.cube || opts.cylinder || opts.mobius || opts.apng.is_some() || opts.video.is_some();
+        if opts.command != Command::Render || opts.input.is_some() || opts.count.is_some() || opts.watch || opts.sheet.is_some() || other || !shown {
+            eprintln!("ERROR: `--openings` and `--min-path` place the start and goal of a single generated maze saved as a top-down image, `.maze`, DOT or JSON file");
+            usage();
+        }
+    }
+    if opts.mmap && (opts.stream || opts.output == "-") {
+        eprintln!("ERROR: `--mmap` needs a file output and cannot be combined with `--stream`");
+        usage();
+    }
+    // Every image format but PPM and BMP has an alpha channel, as do animated PNGs but not videos
+    let alpha = if opts.apng.is_some() || opts.video.is_some() {
+        opts.apng.is_some()
+    } else {
+        match opts.format() {
+            Format::Image(format) => !matches!(format, ImageFormat::Ppm | ImageFormat::Bmp),
+            format => format == Format::Svg,
+        }
+    };
+    if opts.transparent && !alpha {
+        eprintln!("ERROR: `--transparent` requires PNG, QOI, TGA, SVG, APNG or terminal (`--display`) output (e.g. `--output out.png`)");
+        usage();
+    }
+    if opts.rle {
+        if !matches!(opts.format(), Format::Image(ImageFormat::Tga { .. })) {
+            eprintln!("ERROR: `--rle` requires TGA output (e.g. `--output out.tga`)");
+            usage();
+        }
+        opts.format = Some(Format::Image(ImageFormat::Tga { rle: true }));
+    }
+    if opts.force && opts.auto_number {
+        eprintln!("ERROR: `--force` and `--auto-number` cannot be combined");
+        usage();
+    }
+    if opts.output.contains("{index}") && (opts.count.is_none() || opts.command != Command::Render) {
+        eprintln!("ERROR: `{{index}}` in the output name needs a `--count` batch");
+        usage();
+    }
+    let loads = opts.input.is_some() || opts.command == Command::Diff;
+    if loads && (opts.output.contains("{seed}") || opts.output.contains("{algo}")) {
+        eprintln!("ERROR: `{{seed}}` and `{{algo}}` in the output name are unknown for loaded mazes");
+        usage();
+    }
+    if opts.command == Command::Info && opts.files.len() != 1 {
+        eprintln!("ERROR: `info` reads the metadata of one ppm/png/svg file");
+        usage();
+    }
+    if opts.command == Command::Batch {
+        let layouts = opts.voronoi || opts.cube || opts.cylinder || opts.mobius;
+        let generator = opts.difficulty.is_some() || opts.cave || opts.curve.is_some() || opts.trace.is_some() || opts.checkpoint.is_some() || opts.fixed.is_some() || opts.density.is_some() || opts.mask_text.is_some() || opts.wide.is_some() || opts.refine.is_some() || opts.graph_algorithm != Algorithm::Dfs || layouts;
+        let other = opts.input.is_some() || opts.count.is_some() || opts.watch || opts.sheet.is_some() || opts.apng.is_some() || opts.video.is_some() || opts.dry_run;
+        if opts.files.len() != 1 || size_given || output_given || opts.format.is_some() || generator || other {
+            eprintln!("ERROR: `batch` runs the jobs of one manifest file, which gives the size, seed, algorithm and outputs of every maze, and cannot be combined with `--size`, `--output`, `--format`, `--input`, `--count`, `--watch`, `--sheet`, `--dry-run`, animations or other generators");
+            usage();
+        }
+    }
+    if opts.command == Command::Survey {
+        let layouts = opts.voronoi || opts.cube || opts.cylinder || opts.mobius;
+        let generator = opts.difficulty.is_some() || opts.cave || opts.curve.is_some() || opts.trace.is_some() || opts.checkpoint.is_some() || opts.fixed.is_some() || opts.density.is_some() || opts.mask_text.is_some() || opts.dead_ends.is_some() || opts.wide.is_some() || opts.refine.is_some() || opts.graph_algorithm != Algorithm::Dfs || layouts;
+        let other = opts.input.is_some() || opts.watch || opts.sheet.is_some() || opts.apng.is_some() || opts.video.is_some() || opts.dry_run;
+        let report = (opts.output == "-" && opts.format.is_none()) || matches!(opts.format(), Format::Field(FieldFormat::Csv) | Format::Json);
+        if !report || generator || other {
+            eprintln!("ERROR: `survey` writes a CSV or JSON report of the mazes it carves with `--algorithms` and cannot be combined with `--input`, `--watch`, `--sheet`, `--dry-run`, animations or other generators");
+            usage();
+        }
+        if opts.histogram.as_deref().is_some_and(|path| histogram_format(path).is_none()) {
+            eprintln!("ERROR: `--histogram` saves an image such as `hist.png`");
+            usage();
+        }
+        if let Some(curve) = opts.generators.iter().find_map(|g| match g { manifest::Generator::Curve(curve) if !curve.fits(opts.size.0, opts.size.1) => Some(curve), _ => None }) {
+            eprintln!("ERROR: A {} curve cannot fill a {}x{} maze", curve.name(), opts.size.0, opts.size.1);
+            usage();
+        }
+    }
+    // Generated mazes always get an explicit seed, so it can be recorded in the output and used in
+    // its name
+    if opts.seed.is_none() {
+        opts.seed = Some(rand::random());
+    }
+    opts
 }
 
-fn in_bound(val: i32, low: i32, high: i32) -> bool {
-    (val >= low) && (val < high)
+#[cfg(feature = "gui")]
+fn run_gui(env: &Env, solver: Solver, trace: Option<&Trace>) {
+    if let Err(err) = maze::gui::run(env, solver, trace) {
+        fail!("Window error: {}", err);
+    }
 }
 
-fn unvisited_neighbors(grid: &[[Cell; MAZE_SIZE]; MAZE_SIZE], row: usize, col: usize) -> NeighborDir {
-    let mut rng = rand::thread_rng();
-    let mut directions = [
-        NeighborDir::North,
-        NeighborDir::South,
-        NeighborDir::East,
-        NeighborDir::West,
-    ];
-    // Shuffle the order in which neighboring cells are 'checked'
-    directions.shuffle(&mut rng);
-    let mut new_row;
-    let mut new_col;
-
-    for el in directions.iter() {
-        // Reset values for the next direction
-        new_row = row as i32;
-        new_col = col as i32;
-
-        match el {
-            NeighborDir::North => new_row -= 1,
-            NeighborDir::South => new_row += 1,
-            NeighborDir::West => new_col -= 1,
-            NeighborDir::East => new_col += 1,
-            NeighborDir::Center => unreachable!(),
-        };
+#[cfg(not(feature = "gui"))]
+fn run_gui(_env: &Env, _solver: Solver, _trace: Option<&Trace>) {
+    eprintln!("ERROR: `gui` needs maze to be built with `--features gui`");
+    process::exit(1);
+}
 
-        if in_bound(new_row, 0, MAZE_SIZE as i32) &&
-            in_bound(new_col, 0, MAZE_SIZE as i32) &&
-            !grid[new_row as usize][new_col as usize].visited {
-            return *el;
-        }
+fn raster_renderer(opts: &Options, format: ImageFormat) -> RasterRenderer {
+    RasterRenderer {
+        format,
+        style: opts.style,
+        scale: opts.scale,
+        supersample: opts.supersample,
+        overlays: opts.overlays.clone(),
+        metadata: Metadata::default(),
     }
-    return NeighborDir::Center;
 }
 
-fn remove_wall(walls: &mut Vec<Wall>, start: Cell, target: Cell) {
-    assert!(start.ind() != target.ind());
-    let row_diff = (start.row as i32) - (target.row as i32);
-    let col_diff = (start.col as i32) - (target.col as i32);
-    let kind = if row_diff != 0 {
-        WallKind::Vertical
-        // Original: WallKind::Horizontal
-    } else {
-        // Original: WallKind::Vertical
-        WallKind::Horizontal
+// Prints what each `--compare` solver explored to standard error, keeping it out of image output
+fn report_comparison(opts: &Options, env: &Env) {
+    if !opts.overlays.compare {
+        return;
+    }
+    let (start, goal) = opts.overlays.endpoints(env);
+    for (solver, explored, path_len) in raster::solver_comparison(env, start, goal) {
+        eprintln!("{:>14}: explored {:>3} cells, path length {}", solver.name(), explored, path_len);
+    }
+}
+
+fn human_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", size, UNITS[unit]) }
+}
+
+// Prints the image dimensions, peak memory and output size the requested run would need
+fn dry_run(opts: &Options) {
+    let (width, height) = opts.size;
+    let maze = Env::footprint(width, height);
+    // Expanded block map used by the text, SVG and walk views
+    let blocks = ((2 * height) + 1) * ((2 * width) + 1 + 24);
+    let encoded = |format: ImageFormat, w: usize, h: usize| match format {
+        ImageFormat::Ppm => ppm::encoded_size(w, h),
+        ImageFormat::Png => png::encoded_size(w, h),
+        ImageFormat::Qoi => qoi::encoded_size(w, h),
+        ImageFormat::Bmp => bmp::encoded_size(w, h),
+        ImageFormat::Tga { rle } => tga::encoded_size(w, h, rle),
+        ImageFormat::Sixel => sixel::estimated_size(w, h),
+        ImageFormat::Kitty => kitty::estimated_size(w, h),
+        ImageFormat::Iterm2 => iterm2::estimated_size(w, h),
     };
-    if row_diff > 0 || col_diff > 0 {
-        walls.push(Wall { target, start, kind });
-    } else if row_diff < 0 || col_diff < 0 {
-        walls.push(Wall { start, target, kind });
-    }
-}
-
-fn gen_maze(env: &mut Env) {
-    // Initial random row and col
-    let mut row = rand::random::<usize>() % MAZE_SIZE;
-    let mut col = rand::random::<usize>() % MAZE_SIZE;
-    let mut current = env.grid[row][col];
-    // Mark current cell as visited
-    env.grid[row][col].visited = true;
-    
-    // Initialize a separate stack
-    let mut stack = Stack::<Cell>::default();
-    // Push random initial cell to the stack
-    stack.push(current);
 
-    while stack.len() > 0 {
-        // Pop cell from the stack
-        current = stack.pop().unwrap();
-        // Update `row` and `col` to the current cell's
-        row = current.row;
-        col = current.col;
-        // Get the direction of a random unvisited neighbor
-        let unvisited = unvisited_neighbors(&env.grid, row, col);
-        // If unvisited neighbor is center that means all of the current cell's neighbors are visited
-        if unvisited == NeighborDir::Center { continue; }
-        // Push current cell to the stack
-        stack.push(current);
+    println!("maze:   {}x{} cells ({})", width, height, human_bytes(maze));
+    let (memory, output) = match (opts.command, opts.format()) {
+        (Command::Walk | Command::Play, _) => (blocks, None),
+        (Command::Gui, _) => (image_size(width) * image_size(height) * 4, None),
+        (_, Format::Image(format)) => {
+            let renderer = raster_renderer(opts, format);
+            let (w, h) = if opts.stream || opts.mmap {
+                (image_size(width) * opts.scale, image_size(height) * opts.scale)
+            } else {
+                renderer.image_dims(width, height)
+            };
+            println!("image:  {}x{} px", w, h);
+            if let Some(sheet) = opts.sheet {
+                let (page_w, page_h) = sheet.page_dims(w, h);
+                println!("sheet:  {} mazes of {}x{} px on a {}x{} px page", sheet.count(), w, h, page_w, page_h);
+                let memory = renderer.peak_bytes(width, height) + (sheet.count() * w * h * 4) + (page_w * page_h * 4);
+                (memory, Some(encoded(format, page_w, page_h)))
+            } else if opts.stream || opts.mmap {
+                // One pixel row plus the block column of every logical x
+                (w * 3 + image_size(width) * 8, Some(ppm::encoded_size(w, h)))
+            } else if let Some(tiling) = opts.tiles {
+                let (tile_w, tile_h) = (w.div_ceil(tiling.cols), h.div_ceil(tiling.rows));
+                let (full_w, full_h) = (tile_w + 2 * tiling.overlap, tile_h + 2 * tiling.overlap);
+                let total = encoded(format, full_w, full_h) * tiling.cols * tiling.rows;
+                println!("tiles:  {}x{} of up to {}x{} px", tiling.cols, tiling.rows, full_w, full_h);
+                (renderer.peak_bytes(width, height) + full_w * full_h * 4, Some(total))
+            } else {
+                (renderer.peak_bytes(width, height), Some(encoded(format, w, h)))
+            }
+        },
+        (_, Format::Svg) => {
+            // One `<line>` per standing wall slot, at most ~48 bytes each
+            let walls = (height * (width + 1)) + (width * (height + 1)) - ((width * height) - 1);
+            (blocks, Some(400 + walls * 48))
+        },
+        // Up to three bytes per glyph, three glyphs per cell plus a post or wall between cells
+        (_, Format::Text) => (blocks, Some(((2 * height) + 1) * ((((4 * width) + 1) * 3) + 1))),
+        // Three bytes per glyph, one glyph per 2x4 blocks
+        (_, Format::Braille) => (blocks, Some((height + 1).div_ceil(2) * ((width + 1) * 3 + 1))),
+        // Up to seven digits and a comma per cell
+        (_, Format::Field(FieldFormat::Csv)) => (width * height * 8, Some(height * width * 8)),
+        // The header plus one `int32` per cell
+        (_, Format::Field(FieldFormat::Npy)) => (width * height * 8, Some(field::npy_header(width, height).len() + width * height * 4)),
+        // A short header plus one hex digit per cell and a newline per row
+        (_, Format::Maze) => (0, Some(64 + height * (width + 1))),
+        // Up to ~32 bytes per passage line, one passage per cell but the first
+        (_, Format::Dot) => (0, Some(200 + width * height * 32)),
+        // Up to ~24 bytes per passage, one passage per cell but the first, plus the tags
+        (_, Format::Json) => (0, Some(200 + width * height * 24 + opts.tags.as_ref().and_then(|path| fs::metadata(path).ok()).map_or(0, |m| 2 * m.len() as usize))),
+        // One line segment of up to ~40 bytes per standing wall slot, per page the maze is on
+        (command, Format::Pdf) => {
+            let walls = (height * (width + 1)) + (width * (height + 1)) - ((width * height) - 1);
+            let mazes = if command == Command::Book { opts.count.unwrap_or(DEFAULT_BOOK_MAZES) } else { 1 };
+            // Books print every maze twice, the second time with a solution of up to one point per cell
+            let per_maze = if command == Command::Book { (2 * walls * 40) + (width * height * 16) } else { walls * 40 };
+            (blocks + per_maze, Some(2048 + mazes * per_maze))
+        },
+    };
+    println!("memory: ~{} peak", human_bytes(maze + memory));
+    match output {
+        Some(bytes) => println!("output: ~{} ({})", human_bytes(bytes), opts.output),
+        None => println!("output: none"),
+    }
+}
 
-        let mut target_row = row;
-        let mut target_col = col;
-        match unvisited {
-            NeighborDir::North => target_row -= 1,
-            NeighborDir::South => target_row += 1,
-            NeighborDir::West => target_col -= 1,
-            NeighborDir::East => target_col += 1,
-            NeighborDir::Center => unreachable!(),
-        }
-        let target = env.grid[target_row][target_col];
-        // Remove wall between current and target cell
-        remove_wall(&mut env.removed_walls, current, target);
-        // Mark target cell as visited
-        env.grid[target_row][target_col].visited = true;
-        stack.push(target);
+// Existing files are only replaced with `--force`
+fn check_overwrite(opts: &Options, filename: &str) -> io::Result<()> {
+    if filename != "-" && !opts.force && Path::new(filename).exists() {
+        let message = "file already exists; pass `--force` to overwrite it or `--auto-number` to pick a new name";
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, message));
     }
+    Ok(())
 }
 
-const SOLID_COLOR: u32 = 0x32A852;
-const OPEN_COLOR: u32 = 0x0;
-// const OPEN_COLOR: u32 = 0x2856A1;
+// The image format `survey --histogram` saves in, named by the file's extension whatever the
+// report's `--format` is
+fn histogram_format(path: &str) -> Option<ImageFormat> {
+    match Path::new(path).extension().and_then(|e| e.to_str()).and_then(Format::from_name) {
+        Some(Format::Image(format)) => Some(format),
+        _ => None,
+    }
+}
 
-const OPEN_PATH_SIZE: u32 = 10;
-const BORDER_THICKNESS: u32 = 1;
+// Opens the output destination, replacing an existing file only with `--force`
+fn open_output(opts: &Options, filename: &str) -> Result<Box<dyn Write>, io::Error> {
+    if filename == "-" {
+        return Ok(Box::new(BufWriter::new(io::stdout().lock())));
+    }
+    check_overwrite(opts, filename)?;
+    if Path::exists(Path::new(filename)) {
+        fs::remove_file(filename)?;
+    }
+    Ok(Box::new(BufWriter::new(File::create(filename)?)))
+}
 
-const IMG_SIZE: usize = (MAZE_SIZE * OPEN_PATH_SIZE as usize) + ((MAZE_SIZE+1) * BORDER_THICKNESS as usize);
+// `out.ppm` becomes the first of `out-001.ppm`, `out-002.ppm`, ... that does not exist yet
+fn auto_number(output: &str) -> String {
+    let path = Path::new(output);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("out");
+    (1..)
+        .map(|n| {
+            let name = match path.extension().and_then(|e| e.to_str()) {
+                Some(ext) => format!("{}-{:03}.{}", stem, n, ext),
+                None => format!("{}-{:03}", stem, n),
+            };
+            path.with_file_name(name).to_string_lossy().into_owned()
+        })
+        .find(|name| !Path::new(name).exists())
+        .expect("some number is free")
+}
 
-fn fill_rect(pixels: &mut [[u32; IMG_SIZE]; IMG_SIZE], rx: u32, ry: u32, rw: u32, rh: u32, color: u32) {
-    assert!(rx + rw <= IMG_SIZE as u32);
-    assert!(ry + rh <= IMG_SIZE as u32);
+// Whether `--threads` carves a single maze in parallel regions. Only single mazes from `generate`
+// are; batches, sheets, books, searches and surveys use their threads for separate mazes.
+fn region_threads(opts: &Options) -> bool {
+    let single = opts.count.is_none() && opts.sheet.is_none() && !matches!(opts.command, Command::Book | Command::Search | Command::Batch | Command::Survey);
+    opts.threads.is_some() && single
+}
 
-    for y in ry..(ry + rh) {
-        for x in rx..(rx + rw) {
-            pixels[y as usize][x as usize] = color;
-        }
+// Generator of the mazes this run produces, for the metadata and `{algo}`
+fn algorithm(opts: &Options) -> &'static str {
+    if opts.cave {
+        "cave"
+    } else if let Some(curve) = opts.curve {
+        curve.name()
+    } else if opts.voronoi || opts.cube || opts.cylinder || opts.mobius || opts.graph_algorithm != Algorithm::Dfs {
+        opts.graph_algorithm.name()
+    } else if let Some(difficulty) = opts.difficulty {
+        difficulty.preset().algorithm.name()
+    } else if region_threads(opts) {
+        "parallel-dfs"
+    } else {
+        "dfs"
+    }
+}
+
+// `opts.output` with its placeholders filled in for a `width`x`height` maze
+fn output_name(opts: &Options, width: usize, height: usize, seed: Option<u64>, index: Option<(usize, usize)>) -> String {
+    let fields = Fields { algo: algorithm(opts), width, height, seed, timestamp: opts.started, index };
+    let name = match index {
+        Some(_) => batch::output_path(&opts.output, &fields),
+        None => fields.expand(&opts.output),
+    };
+    if opts.auto_number && name != "-" { auto_number(&name) } else { name }
+}
+
+fn progress_bar(enabled: bool, label: &str) -> Box<dyn Progress> {
+    if enabled { Box::new(ProgressBar::new(label)) } else { Box::new(()) }
+}
+
+// `out.png` becomes `out_r0_c1.png` for the tile in row 0, column 1
+fn tile_path(output: &str, row: usize, col: usize) -> String {
+    let path = Path::new(output);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("out");
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}_r{}_c{}.{}", stem, row, col, ext),
+        None => format!("{}_r{}_c{}", stem, row, col),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn load_maze(path: &str) -> MazeFile {
+    match import::load_path(path) {
+        Ok(maze) => maze,
+        Err(err) => fail!("Failed to load maze from `{}`: {}", path, err),
+    }
+}
+
+// Maze with an explicit seed, as used for every maze of a batch, sheet or book
+fn seeded_maze(opts: &Options, seed: u64) -> Env {
+    if let Some(difficulty) = opts.difficulty {
+        return redraw(opts, difficulty::generate(difficulty.preset(), opts.size.0, opts.size.1, seed).0, Some(seed));
+    }
+    let mut env = Env::new(opts.size.0, opts.size.1);
+    if opts.cave {
+        cave::gen_cave(&mut env, &mut StdRng::seed_from_u64(seed), opts.cave_fill, opts.cave_steps);
+        return redraw(opts, env, Some(seed));
+    }
+    if let Some(curve) = opts.curve {
+        unicursal::gen_curve(&mut env, curve);
+        return redraw(opts, env, Some(seed));
     }
+    if let Some(text) = opts.mask_text.as_deref() {
+        textmask::mask(&mut env, text).expect("checked when parsing the arguments");
+    }
+    match (&opts.fixed, opts.density) {
+        (Some(fixed), _) => fixed::gen_maze_fixed(&mut env, &mut StdRng::seed_from_u64(seed), fixed, &mut ()).expect("checked when parsing the arguments"),
+        (None, Some(density)) => gen_maze_sparse(&mut env, &mut StdRng::seed_from_u64(seed), density, &mut ()).expect("`()` never cancels"),
+        (None, None) => manifest::Generator::Tree(opts.graph_algorithm).carve(&mut env, &mut StdRng::seed_from_u64(seed)).expect("grids are carved along their own edges"),
+    }
+    redraw(opts, env, Some(seed))
 }
 
-fn draw_maze(env: &Env, pixels: &mut [[u32; IMG_SIZE]; IMG_SIZE]) {
-    let mut y;
-    let mut x;
-    
-    for r in 0..(MAZE_SIZE as u32) {
-        for c in 0..=(MAZE_SIZE as u32) {
-            x = (c * OPEN_PATH_SIZE) + (c * BORDER_THICKNESS);
-            y = (r * OPEN_PATH_SIZE) + (r * BORDER_THICKNESS);
-            fill_rect(pixels, x, y, BORDER_THICKNESS, OPEN_PATH_SIZE + (2*BORDER_THICKNESS), SOLID_COLOR);
+// `env` braided or re-carved to its `--dead-ends`, then redrawn at a finer resolution, with its
+// `--wide` corridors at double width or its `--refine` cells as sub-mazes, or as it is without either
+fn redraw(opts: &Options, mut env: Env, seed: Option<u64>) -> Env {
+    if let Some(dead_ends) = opts.dead_ends {
+        let target = dead_ends.target(&env);
+        let reached = match seed {
+            Some(seed) => deadends::tune_dead_ends(&mut env, &mut StdRng::seed_from_u64(seed), target),
+            None => deadends::tune_dead_ends(&mut env, &mut rand::thread_rng(), target),
+        };
+        if reached != target {
+            fail!("Cannot give the {}x{} maze {} dead ends; the closest it got is {}", env.width, env.height, target, reached);
         }
     }
+    match (opts.wide, opts.refine, seed) {
+        (Some(widths), _, Some(seed)) => widths::widen(&env, &widths::wide_cells(&env, widths, &mut StdRng::seed_from_u64(seed))),
+        (Some(widths), _, None) => widths::widen(&env, &widths::wide_cells(&env, widths, &mut rand::thread_rng())),
+        (None, Some(share), Some(seed)) => refine::refine(&env, share, opts.refine_factor, &mut StdRng::seed_from_u64(seed)),
+        (None, Some(share), None) => refine::refine(&env, share, opts.refine_factor, &mut rand::thread_rng()),
+        (None, None, _) => env,
+    }
+}
+
+// Leaves all but `density` of the cells out of `env`, always keeping the start and goal, and
+// carves the rest
+fn gen_maze_sparse(env: &mut Env, rng: &mut impl rand::Rng, density: f32, progress: &mut dyn Progress) -> Result<(), MazeError> {
+    let keep = [Cell { row: 0, col: 0 }, Cell { row: env.height - 1, col: env.width - 1 }];
+    sparse::exclude_cells(env, rng, density, &keep)?;
+    gen_maze_progress(env, rng, progress)
+}
 
-    for r in 0..=(MAZE_SIZE as u32) {
-        for c in 0..(MAZE_SIZE as u32) {
-            x = (c * OPEN_PATH_SIZE) + (c * BORDER_THICKNESS);
-            y = (r * OPEN_PATH_SIZE) + (r * BORDER_THICKNESS);
-            fill_rect(pixels, x, y, OPEN_PATH_SIZE + (2*BORDER_THICKNESS), BORDER_THICKNESS, SOLID_COLOR);
+fn generate(opts: &Options, seed: Option<u64>) -> Env {
+    if opts.difficulty.is_some() || opts.cave || opts.curve.is_some() {
+        return seeded_maze(opts, seed.unwrap_or_else(rand::random));
+    }
+    let mut env = Env::new(opts.size.0, opts.size.1);
+    let mut progress = progress_bar(opts.progress, "generating");
+    if let Some(path) = opts.trace.as_deref() {
+        let traced = match seed {
+            Some(seed) => trace::gen_maze_traced(&mut env, &mut StdRng::seed_from_u64(seed), progress.as_mut()),
+            None => trace::gen_maze_traced(&mut env, &mut rand::thread_rng(), progress.as_mut()),
+        };
+        let result = open_output(opts, path).map_err(MazeError::from).and_then(|mut out| {
+            traced.expect("progress bars never cancel").write(&mut out)?;
+            Ok(out.flush()?)
+        });
+        if let Err(err) = result {
+            fail!("Failed to save trace to `{}`: {}", path, err);
+        }
+        return env;
+    }
+    if let Some(path) = opts.checkpoint.as_deref() {
+        // An existing checkpoint is carried on from, whatever the seed
+        let carved = if Path::new(path).exists() {
+            let checkpoint = match checkpoint::load(path) {
+                Ok(checkpoint) => checkpoint,
+                Err(err) => fail!("Failed to load checkpoint from `{}`: {}", path, err),
+            };
+            let size = (checkpoint.env.width, checkpoint.env.height);
+            if size != opts.size {
+                fail!("The checkpoint in `{}` is for a {}x{} maze, not {}x{}", path, size.0, size.1, opts.size.0, opts.size.1);
+            }
+            checkpoint::resume(checkpoint, path, opts.checkpoint_every, progress.as_mut())
+        } else {
+            let seed = seed.unwrap_or_else(rand::random);
+            checkpoint::generate(&mut env, seed, path, opts.checkpoint_every, progress.as_mut()).map(|_| env)
+        };
+        match carved {
+            Ok(env) => return redraw(opts, env, seed),
+            Err(err) => fail!("Failed to save checkpoint to `{}`: {}", path, err),
         }
     }
+    if let Some(text) = opts.mask_text.as_deref() {
+        textmask::mask(&mut env, text).expect("checked when parsing the arguments");
+    }
+    let generated = match (seed, opts.threads, &opts.fixed, opts.density) {
+        (Some(seed), _, Some(fixed), _) => fixed::gen_maze_fixed(&mut env, &mut StdRng::seed_from_u64(seed), fixed, progress.as_mut()),
+        (None, _, Some(fixed), _) => fixed::gen_maze_fixed(&mut env, &mut rand::thread_rng(), fixed, progress.as_mut()),
+        (Some(seed), _, None, Some(density)) => gen_maze_sparse(&mut env, &mut StdRng::seed_from_u64(seed), density, progress.as_mut()),
+        (None, _, None, Some(density)) => gen_maze_sparse(&mut env, &mut rand::thread_rng(), density, progress.as_mut()),
+        (Some(seed), _, None, None) if opts.graph_algorithm != Algorithm::Dfs => manifest::Generator::Tree(opts.graph_algorithm).carve(&mut env, &mut StdRng::seed_from_u64(seed)),
+        (None, _, None, None) if opts.graph_algorithm != Algorithm::Dfs => manifest::Generator::Tree(opts.graph_algorithm).carve(&mut env, &mut rand::thread_rng()),
+        (seed, Some(threads), None, None) => gen_maze_parallel(&mut env, seed.unwrap_or_else(rand::random), threads, progress.as_mut()),
+        (Some(seed), None, None, None) => gen_maze_progress(&mut env, &mut StdRng::seed_from_u64(seed), progress.as_mut()),
+        (None, None, None, None) => gen_maze_progress(&mut env, &mut rand::thread_rng(), progress.as_mut()),
+    };
+    generated.expect("progress bars never cancel");
+    redraw(opts, env, seed)
+}
 
-    
-    for wall in env.removed_walls.iter() {
-        match wall.kind {
-            WallKind::Vertical => {
-                fill_rect(pixels,
-                    ((wall.target.col as u32) * OPEN_PATH_SIZE) + ((wall.target.col as u32) * BORDER_THICKNESS),
-                    ((wall.target.row as u32) * OPEN_PATH_SIZE) + ((wall.target.row as u32) * BORDER_THICKNESS) + BORDER_THICKNESS,
-                    BORDER_THICKNESS, OPEN_PATH_SIZE, OPEN_COLOR
-                );
-            },
-            WallKind::Horizontal => {
-                fill_rect(pixels,
-                    ((wall.target.col as u32) * OPEN_PATH_SIZE) + ((wall.target.col as u32) * BORDER_THICKNESS) + BORDER_THICKNESS,
-                    ((wall.target.row as u32) * OPEN_PATH_SIZE) + ((wall.target.row as u32) * BORDER_THICKNESS),
-                    OPEN_PATH_SIZE, BORDER_THICKNESS, OPEN_COLOR
-                );
-            },
+// Writes an animation of `steps` played over `animation` to the `--apng` or `--video` file
+fn save_animation(opts: &Options, animation: Animation, steps: Vec<Step>) -> Result<(), MazeError> {
+    let mut capture = Capture::new(animation, steps, opts.frames);
+    let (count, delay) = (capture.frame_count(), (1000 / opts.fps) as u16);
+    if let Some(path) = opts.video.as_deref() {
+        check_overwrite(opts, path)?;
+        let mut video = VideoEncoder::new(path, capture.width(), capture.height(), opts.fps)?;
+        let hold = (FINAL_FRAME_MS as usize * opts.fps).div_ceil(1000).max(1);
+        let mut shown = 0;
+        while let Some(canvas) = capture.next_frame() {
+            shown += 1;
+            video.add_frame(canvas, if shown == count { hold } else { 1 })?;
         }
+        video.finish()?;
+        return Ok(());
     }
-   
+
+    let path = opts.apng.as_deref().expect("an animation file was asked for");
+    let mut out = open_output(opts, path)?;
+    let mut encoder = ApngEncoder::new(&mut out, capture.width(), capture.height(), count)?;
+    let mut shown = 0;
+    while let Some(canvas) = capture.next_frame() {
+        shown += 1;
+        encoder.add_frame(canvas, if shown == count { FINAL_FRAME_MS } else { delay })?;
+    }
+    encoder.finish()?;
+    Ok(())
 }
 
-fn save_as_ppm(pixels: &[[u32; IMG_SIZE]; IMG_SIZE], filename: &str) -> Result<(), io::Error> {
-    if Path::exists(Path::new(filename)) {
-        fs::remove_file(filename)?;
+fn theme(opts: &Options) -> Theme {
+    let mut theme = Theme::default();
+    theme.solid_color = opts.solid_color.unwrap_or(theme.solid_color);
+    theme.open_color = opts.open_color.unwrap_or(theme.open_color);
+    if opts.transparent {
+        theme.open_color = TRANSPARENT_COLOR;
+    }
+    theme
+}
+
+// Renders the mazes of a sheet like a `--count` batch, on up to `--threads` at once, and writes the
+// page to the output
+fn save_sheet(opts: &Options, sheet: Sheet, format: ImageFormat) -> Result<(), MazeError> {
+    let master = opts.seed.unwrap_or_else(rand::random);
+    let threads = opts.threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let renderer = raster_renderer(opts, format);
+    let mazes = Mutex::new(Vec::with_capacity(sheet.count()));
+    let mut progress = progress_bar(opts.progress, "mazes");
+    batch::run(sheet.count(), master, threads, progress.as_mut(), |index, seed| {
+        let env = seeded_maze(opts, seed);
+        let caption = opts.caption.replace("{index}", &index.to_string()).replace("{seed}", &seed.to_string());
+        let canvas = renderer.rasterize(&env, &theme(opts));
+        mazes.lock().expect("no job panics while holding the lock").push((index, env, canvas, caption));
+        Ok(())
+    })?;
+    let mut mazes = mazes.into_inner().expect("no job panicked while holding the lock");
+    mazes.sort_by_key(|(index, ..)| *index);
+    for (_, env, ..) in mazes.iter() {
+        report_comparison(opts, env);
     }
-    let mut file = File::create(filename)?;
+    let mazes: Vec<_> = mazes.into_iter().map(|(_, _, canvas, caption)| (canvas, caption)).collect();
+    let mut out = open_output(opts, &opts.output)?;
+    format.write(&mut out, &sheet.compose(&mazes))?;
+    Ok(out.flush()?)
+}
 
-    write!(&mut file, "P6\n{} {} 255\n", IMG_SIZE, IMG_SIZE)?;
-    for y in 0..IMG_SIZE {
-        for x in 0..IMG_SIZE {
-            let pixel = pixels[y][x];
-            // Color HEX code format: 0xRRGGBB
-            let color_components = [
-                ((pixel >> 8*2) & 0xFF) as u8, //     0xRR & 0xFF
-                ((pixel >> 8*1) & 0xFF) as u8, //   0x__GG & 0xFF
-                ((pixel >> 8*0) & 0xFF) as u8, // 0x____BB & 0xFF
-            ];
-            file.write(&color_components)?;
+// Generates the mazes of a book like a `--count` batch and writes the PDF to the output
+fn save_book(opts: &Options) -> Result<(), MazeError> {
+    let count = opts.count.unwrap_or(DEFAULT_BOOK_MAZES);
+    let master = opts.seed.unwrap_or_else(rand::random);
+    let threads = opts.threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let mazes = Mutex::new(Vec::with_capacity(count));
+    let mut progress = progress_bar(opts.progress, "mazes");
+    batch::run(count, master, threads, progress.as_mut(), |index, seed| {
+        let env = seeded_maze(opts, seed);
+        mazes.lock().expect("no job panics while holding the lock").push((index, BookMaze { env, seed }));
+        Ok(())
+    })?;
+    let mut mazes = mazes.into_inner().expect("no job panicked while holding the lock");
+    mazes.sort_by_key(|(index, _)| *index);
+    let mazes: Vec<BookMaze> = mazes.into_iter().map(|(_, maze)| maze).collect();
+    let mut out = open_output(opts, &opts.output)?;
+    book::write_book(&mut out, &opts.title, &mazes)?;
+    Ok(out.flush()?)
+}
+
+// Writes `env` to `output` in the requested format, with progress bars if `show_progress`. Image
+// and SVG output records `seed` (`None` for loaded mazes) and the generator in its metadata; `--curve`
+// mazes do not depend on the seed, so theirs is left out.
+fn save(opts: &Options, env: &Env, output: &str, seed: Option<u64>, show_progress: bool) -> Result<(), MazeError> {
+    let generated = opts.input.is_none() || opts.command == Command::Replay;
+    let seed = seed.filter(|_| opts.curve.is_none());
+    save_with(opts, env, output, Metadata::for_maze(env, seed, generated.then(|| algorithm(opts))), show_progress)
+}
+
+// `save` with the given image and SVG metadata, in the format `output` calls for
+fn save_with(opts: &Options, env: &Env, output: &str, metadata: Metadata, show_progress: bool) -> Result<(), MazeError> {
+    let theme = theme(opts);
+    report_comparison(opts, env);
+    if opts.mmap {
+        let renderer = MmapPpmRenderer { scale: opts.scale, metadata };
+        let mut progress = progress_bar(show_progress, "rendering");
+        check_overwrite(opts, output)?;
+        return renderer.render_to_file(env, &theme, Path::new(output), progress.as_mut());
+    }
+    if let (Some(tiling), Format::Image(format)) = (opts.tiles, opts.format_of(output)) {
+        let renderer = raster_renderer(opts, format);
+        for tile in tiling.split(&renderer.rasterize(env, &theme))? {
+            let path = tile_path(output, tile.row, tile.col);
+            let result = open_output(opts, &path).and_then(|mut out| {
+                format.write_with(&mut out, &tile.canvas, &metadata)?;
+                out.flush()
+            });
+            result.map_err(|err| MazeError::from(err).in_file(&path))?;
         }
+        return Ok(());
     }
-    Ok(())
+    let renderer: Box<dyn Renderer> = match opts.format_of(output) {
+        Format::Image(ImageFormat::Ppm) if opts.stream => Box::new(StreamingPpmRenderer { scale: opts.scale, metadata }),
+        Format::Image(format) => Box::new(RasterRenderer { metadata, ..raster_renderer(opts, format) }),
+        Format::Svg => Box::new(SvgRenderer { metadata, ..SvgRenderer::default() }),
+        // Colors only for a terminal; piped output stays plain text
+        Format::Text => Box::new(TextRenderer { color: output == "-" && io::stdout().is_terminal() }),
+        Format::Braille => Box::new(BrailleRenderer { color: output == "-" && io::stdout().is_terminal() }),
+        Format::Field(format) => Box::new(DistanceFieldRenderer { format }),
+        Format::Maze => Box::new(MazeFileRenderer),
+        Format::Dot => Box::new(DotRenderer),
+        Format::Json => Box::new(JsonRenderer { tags: opts.tags.as_deref().map(|path| json::read_tags(path, env)).transpose()? }),
+        Format::Pdf => Box::new(PdfRenderer),
+    };
+    let mut out = open_output(opts, output)?;
+    renderer.render_with_progress(env, &theme, &mut out, progress_bar(show_progress, "rendering").as_mut())?;
+    Ok(out.flush()?)
+}
+
+// Saves a loaded maze to `opts.output`. `.maze`, DOT and JSON output keep its start and goal, and
+// `.maze` and JSON its solution; other formats are rendered like a generated maze.
+fn save_loaded(opts: &Options, maze: &MazeFile) -> Result<(), MazeError> {
+    let format = opts.format();
+    if !matches!(format, Format::Maze | Format::Dot | Format::Json) {
+        return save(opts, &maze.env, &opts.output, None, opts.progress);
+    }
+    let mut out = open_output(opts, &opts.output)?;
+    match format {
+        Format::Maze => mazefile::write(&mut out, maze)?,
+        Format::Dot => dot::write(&mut out, maze)?,
+        _ => json::write(&mut out, maze, opts.tags.as_deref().map(|path| json::read_tags(path, &maze.env)).transpose()?.as_ref())?,
+    }
+    Ok(out.flush()?)
+}
+
+// Writes the `--watch` maze for `seed` and returns the status line. Seeds are always explicit
+// here so every maze can be reproduced from the printed line.
+fn watch_step(opts: &Options, seed: u64) -> Result<String, MazeError> {
+    let env = generate(opts, Some(seed));
+    let output = output_name(opts, opts.size.0, opts.size.1, Some(seed), None);
+    save(opts, &env, &output, Some(seed), opts.progress)?;
+    Ok(format!("wrote `{}` (seed {})", output, seed))
 }
 
 fn main() {
-    let mut env = Env::init();
-    gen_maze(&mut env);
-    let mut pixels = [[0u32; IMG_SIZE]; IMG_SIZE];
-    draw_maze(&env, &mut pixels);
-    if let Err(_) = save_as_ppm(&pixels, "out.ppm") {
-        panic!("ERROR: Failed to save maze as ppm");
+    let mut opts = parse_args();
+    if opts.command == Command::Bench {
+        if let Err(err) = bench::run(&opts.bench_sizes) {
+            fail!("Benchmark failed: {}", err);
+        }
+        return;
+    }
+    if opts.command == Command::Serve {
+        if let Err(err) = serve::serve(&opts.listen) {
+            fail!("Failed to serve on `{}`: {}", opts.listen, err);
+        }
+        return;
+    }
+    if opts.verify_determinism {
+        let seed = opts.seed.unwrap_or_else(rand::random);
+        // Thread counts that split the regions differently, plus whatever was asked for
+        let available = thread::available_parallelism().map_or(1, |n| n.get());
+        let mut thread_counts = vec![1, 2, available];
+        thread_counts.extend(opts.threads);
+        thread_counts.sort_unstable();
+        thread_counts.dedup();
+        let (w, h) = opts.size;
+        match determinism::verify(w, h, seed, &thread_counts) {
+            Ok(runs) => println!("deterministic: {} runs of the {}x{} maze with seed {} agree", runs, w, h, seed),
+            Err(mismatch) => {
+                eprintln!("ERROR: Generation is not deterministic for the {}x{} maze with seed {}: {}", w, h, seed, mismatch);
+                process::exit(1);
+            },
+        }
+        return;
+    }
+    if opts.command == Command::Search {
+        let master = opts.seed.unwrap_or_else(rand::random);
+        let threads = opts.threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+        let mut progress = progress_bar(opts.progress, "seeds");
+        let found = search::search(opts.attempts, master, threads, progress.as_mut(), |seed| {
+            search::check(&seeded_maze(&opts, seed), &opts.constraints)
+        });
+        let Some(found) = found else {
+            eprintln!("ERROR: None of {} seeds (master seed {}) meets the constraints", opts.attempts, master);
+            process::exit(1);
+        };
+        println!("{{\"seed\":{},\"attempt\":{},\"analysis\":{}}}", found.seed, found.attempt, found.report.to_json());
+        if !opts.output.is_empty() {
+            let output = output_name(&opts, opts.size.0, opts.size.1, Some(found.seed), None);
+            if let Err(err) = save(&opts, &seeded_maze(&opts, found.seed), &output, Some(found.seed), false) {
+                fail!("Failed to save maze to `{}`: {}", output, err);
+            }
+        }
+        return;
+    }
+    if let (Command::Solve, Some(input)) = (opts.command, opts.input.as_deref()) {
+        let mut maze = load_maze(input);
+        match opts.solver.solve(&maze.env, maze.start, maze.goal) {
+            Some(solution) => maze.solution = solution.path,
+            None => fail!("`{}` has no path from its start to its goal", input),
+        }
+        let result = open_output(&opts, &opts.output).map_err(MazeError::from).and_then(|mut out| {
+            match opts.format() {
+                Format::Json => json::write(&mut out, &maze, None)?,
+                _ => mazefile::write(&mut out, &maze)?,
+            }
+            Ok(out.flush()?)
+        });
+        if let Err(err) = result {
+            fail!("Failed to save maze to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if let (Command::Validate, Some(input)) = (opts.command, opts.input.as_deref()) {
+        // Files that do not even parse are reported like any other problem
+        let problems = match import::load_path(input) {
+            Ok(maze) => validate::validate_file(&maze).iter().map(|p| p.to_string()).collect(),
+            Err(MazeError::InvalidData(message)) => vec![message],
+            Err(err) => fail!("Failed to read `{}`: {}", input, err),
+        };
+        if problems.is_empty() {
+            println!("{}: ok, a perfect maze", input);
+            return;
+        }
+        for problem in problems.iter() {
+            println!("{}: {}", input, problem);
+        }
+        process::exit(1);
+    }
+    if opts.command == Command::Batch {
+        let path = &opts.files[0];
+        let jobs = match import::read_path(path).and_then(|bytes| manifest::parse(&String::from_utf8_lossy(&bytes))) {
+            Ok(jobs) => jobs,
+            Err(err) => fail!("Failed to load manifest `{}`: {}", path, err),
+        };
+        let master = opts.seed.expect("set when parsing the arguments");
+        let threads = opts.threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+        let started = Instant::now();
+        let (mut done, mut failed) = (0, 0);
+        let run = |index: usize| -> Result<(u64, Duration), MazeError> {
+            let (job, started) = (&jobs[index - 1], Instant::now());
+            let seed = job.seed.unwrap_or_else(|| batch::maze_seed(master, job.line));
+            let env = job.carve(seed)?;
+            let metadata = Metadata::for_maze(&env, job.generator.is_random().then_some(seed), Some(job.generator.name()));
+            for output in job.outputs.iter() {
+                save_with(&opts, &env, output, metadata.clone(), false).map_err(|err| err.in_file(output))?;
+            }
+            Ok((seed, started.elapsed()))
+        };
+        batch::run_all(jobs.len(), threads, run, |index, result| {
+            let job = &jobs[index - 1];
+            done += 1;
+            match result {
+                Ok((seed, elapsed)) => {
+                    let size = format!("{}x{} {}, seed {}", job.width, job.height, job.generator.name(), seed);
+                    eprintln!("[{}/{}] line {}: {}: {} ({} ms)", done, jobs.len(), job.line, size, job.outputs.join(" "), elapsed.as_millis());
+                },
+                Err(err) => {
+                    failed += 1;
+                    eprintln!("[{}/{}] line {}: FAILED: {}", done, jobs.len(), job.line, err);
+                },
+            }
+        });
+        eprintln!("{} jobs: {} saved, {} failed in {:.2} s", jobs.len(), jobs.len() - failed, failed, started.elapsed().as_secs_f64());
+        if failed > 0 {
+            process::exit(1);
+        }
+        return;
+    }
+    if opts.command == Command::Survey {
+        let (count, master) = (opts.count.unwrap_or(survey::DEFAULT_COUNT), opts.seed.expect("set when parsing the arguments"));
+        let threads = opts.threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+        let mut progress = progress_bar(opts.progress, "mazes");
+        let report = match survey::run(&opts.generators, opts.size.0, opts.size.1, count, master, threads, progress.as_mut()) {
+            Ok(report) => report,
+            Err(err) => fail!("Survey failed: {}", err),
+        };
+        let result = open_output(&opts, &opts.output).and_then(|mut out| {
+            match opts.format() {
+                Format::Json => out.write_all(report.to_json().as_bytes())?,
+                _ => out.write_all(report.to_csv().as_bytes())?,
+            }
+            out.flush()
+        });
+        if let Err(err) = result {
+            fail!("Failed to save report to `{}`: {}", opts.output, err);
+        }
+        if let Some(path) = opts.histogram.as_deref() {
+            let result = open_output(&opts, path).and_then(|mut out| {
+                if let Some(format) = histogram_format(path) {
+                    format.write(&mut out, &report.histogram())?;
+                }
+                out.flush()
+            });
+            if let Err(err) = result {
+                fail!("Failed to save histogram to `{}`: {}", path, err);
+            }
+        }
+        return;
+    }
+    if opts.command == Command::Info {
+        let path = &opts.files[0];
+        let metadata = match import::read_path(path).and_then(|bytes| Metadata::read(&bytes)) {
+            Ok(metadata) => metadata,
+            Err(err) => fail!("Failed to read `{}`: {}", path, err),
+        };
+        if metadata.is_empty() {
+            eprintln!("{}: no maze metadata", path);
+            process::exit(1);
+        }
+        for (key, value) in metadata.entries.iter() {
+            println!("{}: {}", key, value);
+        }
+        return;
+    }
+    if let (Command::Replay, Some(input)) = (opts.command, opts.input.as_deref()) {
+        let trace = match import::read_path(input).and_then(|bytes| Trace::read(&mut bytes.as_slice())) {
+            Ok(trace) => trace,
+            Err(err) => fail!("Failed to load trace from `{}`: {}", input, err),
+        };
+        let env = trace.replay();
+        opts.output = output_name(&opts, env.width, env.height, None, None);
+        if opts.animate {
+            run_gui(&env, opts.solver, Some(&trace));
+        } else if let Some(path) = opts.apng.as_deref().or(opts.video.as_deref()) {
+            let (steps, _) = animation::steps(&env, opts.solver, Some(&trace));
+            if let Err(err) = save_animation(&opts, Animation::new(&env, opts.scale, theme(&opts)), steps) {
+                fail!("Failed to save animation to `{}`: {}", path, err);
+            }
+        } else if let Err(err) = save(&opts, &env, &opts.output, None, opts.progress) {
+            fail!("Failed to save maze to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if let (Command::Diff, Format::Image(format)) = (opts.command, opts.format()) {
+        let (a, b) = (load_maze(&opts.files[0]), load_maze(&opts.files[1]));
+        if (a.env.width, a.env.height) != (b.env.width, b.env.height) {
+            eprintln!("ERROR: `{}` is {}x{} but `{}` is {}x{}", opts.files[0], a.env.width, a.env.height, opts.files[1], b.env.width, b.env.height);
+            process::exit(1);
+        }
+        let changes = diff::diff(&a.env, &b.env).expect("sizes were checked");
+        opts.output = output_name(&opts, a.env.width, a.env.height, None, None);
+        println!("{} passages in common, {} only in `{}`, {} only in `{}`", changes.common, changes.only_a.len(), opts.files[0], changes.only_b.len(), opts.files[1]);
+        for (walls, name) in [(&changes.only_a, &opts.files[0]), (&changes.only_b, &opts.files[1])] {
+            for wall in walls.iter() {
+                println!("  only in `{}`: ({}, {}) - ({}, {})", name, wall.start.row, wall.start.col, wall.target.row, wall.target.col);
+            }
+        }
+        let canvas = diff::draw_diff(&a.env, &b.env, &theme(&opts)).expect("sizes were checked").upscale(opts.scale);
+        let result = open_output(&opts, &opts.output).and_then(|mut out| {
+            format.write(&mut out, &canvas)?;
+            out.flush()
+        });
+        if let Err(err) = result {
+            fail!("Failed to save diff to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if opts.voronoi {
+        let seed = opts.seed.unwrap_or_else(rand::random);
+        let maze = voronoi::generate(opts.size.0, opts.size.1, opts.graph_algorithm, &mut StdRng::seed_from_u64(seed));
+        opts.output = output_name(&opts, opts.size.0, opts.size.1, Some(seed), None);
+        let result = open_output(&opts, &opts.output).map_err(MazeError::from).and_then(|mut out| {
+            match opts.format() {
+                Format::Image(format) => format.write(&mut out, &maze.render(&theme(&opts), opts.scale, opts.overlays.solve))?,
+                _ => maze.write_svg(&mut out, &theme(&opts), opts.overlays.solve)?,
+            }
+            Ok(out.flush()?)
+        });
+        if let Err(err) = result {
+            fail!("Failed to save maze to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if opts.cube || opts.cylinder || opts.mobius {
+        let seed = opts.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let canvas = if opts.cube {
+            cube::generate(opts.size.0, opts.graph_algorithm, &mut rng).render(opts.cube_layout, &theme(&opts), opts.scale, opts.overlays.solve)
+        } else {
+            cylinder::generate(opts.size.0, opts.size.1, opts.mobius, opts.graph_algorithm, &mut rng).render(&theme(&opts), opts.scale, opts.overlays.solve)
+        };
+        opts.output = output_name(&opts, opts.size.0, opts.size.1, Some(seed), None);
+        let result = open_output(&opts, &opts.output).and_then(|mut out| {
+            if let Format::Image(format) = opts.format() {
+                format.write(&mut out, &canvas)?;
+            }
+            out.flush()
+        });
+        if let Err(err) = result {
+            fail!("Failed to save maze to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    let loaded = opts.input.as_deref().map(load_maze);
+    if let Some(maze) = loaded.as_ref() {
+        opts.size = (maze.env.width, maze.env.height);
+        // Mazes saved by `solve` are drawn with their solution, as with `--solve`
+        opts.overlays.solve |= opts.command == Command::Render && !maze.solution.is_empty();
+    }
+    // Batches and `--watch` name every maze separately
+    let batch = opts.count.is_some() && opts.command != Command::Book;
+    if !batch && !opts.watch {
+        opts.output = output_name(&opts, opts.size.0, opts.size.1, opts.seed, None);
+    }
+    if opts.dry_run {
+        dry_run(&opts);
+        return;
+    }
+    if let (Some(sheet), Format::Image(format)) = (opts.sheet, opts.format()) {
+        if let Err(err) = save_sheet(&opts, sheet, format) {
+            fail!("Failed to save sheet to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if opts.command == Command::Book {
+        if let Err(err) = save_book(&opts) {
+            fail!("Failed to save book to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if let Some(count) = opts.count {
+        // Each maze is generated on a single thread; the threads work on different mazes instead
+        let master = opts.seed.unwrap_or_else(rand::random);
+        let threads = opts.threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+        let mut progress = progress_bar(opts.progress, "mazes");
+        let result = batch::run(count, master, threads, progress.as_mut(), |index, seed| {
+            let env = seeded_maze(&opts, seed);
+            let path = output_name(&opts, opts.size.0, opts.size.1, Some(seed), Some((index, count)));
+            save(&opts, &env, &path, Some(seed), false).map_err(|err| err.in_file(&path))
+        });
+        match result {
+            Ok(()) => eprintln!("wrote {} mazes (master seed {})", count, master),
+            Err(err) => fail!("Batch failed: {}", err),
+        }
+        return;
+    }
+    if opts.watch {
+        let seed = opts.seed.unwrap_or_else(rand::random);
+        let result = watch_step(&opts, seed).map(|status| eprintln!("{}", status)).and_then(|_| {
+            // Later mazes replace the file the first one wrote
+            opts.force = true;
+            Ok(watch::watch(|| Ok(watch_step(&opts, rand::random())?))?)
+        });
+        if let Err(err) = result {
+            fail!("Failed to save maze to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    let mut maze = loaded.unwrap_or_else(|| MazeFile::new(generate(&opts, opts.seed)));
+    if opts.command == Command::Repair {
+        let changes = validate::repair(&mut maze.env);
+        for change in changes.iter() {
+            eprintln!("{}", change);
+        }
+        if changes.is_empty() {
+            eprintln!("nothing to repair");
+        }
+        if let Err(err) = save_loaded(&opts, &maze) {
+            fail!("Failed to save maze to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if opts.command == Command::Convert {
+        if let Err(err) = save_loaded(&opts, &maze) {
+            fail!("Failed to save maze to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if opts.command == Command::Edit {
+        if let Err(err) = edit::edit(maze, &opts.output) {
+            fail!("Terminal error: {}", err);
+        }
+        return;
+    }
+    if opts.command == Command::Analyze {
+        let mut report = analysis::analyze(&maze.env, maze.start, maze.goal);
+        if opts.stats {
+            report.stats = Some(analysis::stats(&maze.env));
+        }
+        if let Some(min_cut_off) = opts.overlays.chokepoints {
+            report.chokepoints = Some(analysis::chokepoints(&maze.env, min_cut_off));
+        }
+        let result = open_output(&opts, &opts.output).and_then(|mut out| {
+            writeln!(out, "{}", report.to_json())?;
+            out.flush()
+        });
+        if let Err(err) = result {
+            fail!("Failed to save analysis to `{}`: {}", opts.output, err);
+        }
+        return;
+    }
+    if let Some(placement) = opts.openings {
+        // Seeded like the maze, so a seed always gives the same openings too
+        let mut rng = opts.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        match openings::place(&maze.env, placement, opts.min_path, &mut rng) {
+            Ok(openings) => {
+                [(maze.start, _), (maze.goal, _)] = openings;
+                opts.overlays.openings = Some(openings);
+            },
+            Err(err) => fail!("Failed to place openings: {}", err),
+        }
+    }
+    let (start, goal) = (maze.start, maze.goal);
+    let env = maze.env;
+    if opts.command == Command::Gui {
+        run_gui(&env, opts.solver, None);
+        return;
+    }
+    if opts.command == Command::Walk || opts.command == Command::Play {
+        let result = if opts.command == Command::Walk { walk::walk(&env) } else { play::play(&env, opts.minotaur) };
+        if let Err(err) = result {
+            fail!("Terminal error: {}", err);
+        }
+        return;
+    }
+    if opts.command == Command::Race {
+        let lanes = race::run(&env, &opts.racers, opts.seed.unwrap_or_default());
+        let mut standings: Vec<&race::Lane> = lanes.iter().collect();
+        standings.sort_by_key(|lane| lane.finish().unwrap_or(usize::MAX));
+        for (place, lane) in standings.iter().enumerate() {
+            match lane.finish() {
+                Some(ticks) => println!("{}. {}: reached the goal in {} steps", place + 1, lane.name, ticks),
+                None => println!("-  {}: gave up after {} steps", lane.name, lane.explored.len()),
+            }
+        }
+        if let Some(path) = opts.apng.as_deref().or(opts.video.as_deref()) {
+            if let Err(err) = save_animation(&opts, Animation::carved(&env, opts.scale, theme(&opts)), race::steps(&lanes)) {
+                fail!("Failed to save animation to `{}`: {}", path, err);
+            }
+        }
+        return;
+    }
+    if opts.command == Command::Chase {
+        let (steps, chase) = pursuit::scripted(&env, MINOTAUR_SPEED);
+        match chase.outcome {
+            Outcome::Caught => println!("The minotaur caught the player at ({}, {}) after {} turns", chase.player.row, chase.player.col, chase.turns),
+            _ => println!("The player escaped in {} turns", chase.turns),
+        }
+        println!("The minotaur ran {} A* searches and patched its route on the other moves", chase.minotaur.searches);
+        if let Some(path) = opts.apng.as_deref().or(opts.video.as_deref()) {
+            if let Err(err) = save_animation(&opts, Animation::carved(&env, opts.scale, theme(&opts)), steps) {
+                fail!("Failed to save animation to `{}`: {}", path, err);
+            }
+        }
+        return;
+    }
+    if let Some(path) = opts.apng.as_deref().or(opts.video.as_deref()) {
+        let (steps, _) = animation::steps(&env, opts.solver, None);
+        if let Err(err) = save_animation(&opts, Animation::new(&env, opts.scale, theme(&opts)), steps) {
+            fail!("Failed to save animation to `{}`: {}", path, err);
+        }
+        return;
+    }
+    let seed = opts.seed.filter(|_| opts.input.is_none());
+    let result = match opts.format() {
+        // Maze files record where the openings went
+        Format::Maze | Format::Dot | Format::Json if opts.openings.is_some() => save_loaded(&opts, &MazeFile { start, goal, ..MazeFile::new(env) }),
+        _ => save(&opts, &env, &opts.output, seed, opts.progress),
+    };
+    if let Err(err) = result {
+        fail!("Failed to save maze to `{}`: {}", opts.output, err);
+    }
+    // The maze is saved, so the checkpoint it was carved from is not needed any more
+    if let Some(path) = opts.checkpoint.as_deref() {
+        if let Err(err) = fs::remove_file(path) {
+            fail!("Failed to remove checkpoint `{}`: {}", path, err);
+        }
     }
 }
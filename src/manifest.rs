@@ -0,0 +1,140 @@
+// Job manifests for `maze batch`: one maze per line with its size, seed, algorithm and the files to
+// save it to, so one run can turn out thousands of assets:
+//
+//   # size  seed  algorithm  outputs
+//   20x10   7     dfs        posters/a.png posters/a.maze
+//   32      -     hilbert    posters/b.svg
+//
+// The size is `N` or `WxH`, and a seed of `-` is derived from the batch seed and the line number.
+// Every output is saved in the format its extension names. Blank lines and lines starting with `#`
+// are skipped.
+
+use std::fs;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cave;
+use crate::error::MazeError;
+use crate::graph::{self, Algorithm, Graph};
+use crate::unicursal::{self, Curve};
+use crate::{gen_maze_with, Env};
+
+// How a job carves its maze
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Generator {
+    // A spanning tree of the grid; `dfs` carves the same maze as `--seed`
+    Tree(Algorithm),
+    // `--cave` with the default fill and smoothing
+    Cave,
+    Curve(Curve),
+}
+
+impl Generator {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cave" => Some(Generator::Cave),
+            name => Algorithm::from_name(name).map(Generator::Tree).or_else(|| Curve::from_name(name).map(Generator::Curve)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Generator::Tree(algorithm) => algorithm.name(),
+            Generator::Cave => "cave",
+            Generator::Curve(curve) => curve.name(),
+        }
+    }
+
+    // Whether the seed changes the maze; a curve always fills the grid the same way
+    pub fn is_random(&self) -> bool {
+        !matches!(self, Generator::Curve(_))
+    }
+
+    // Carves `env`, which should have every wall standing
+    pub fn carve(&self, env: &mut Env, rng: &mut impl Rng) -> Result<(), MazeError> {
+        match self {
+            Generator::Tree(Algorithm::Dfs) => gen_maze_with(env, rng),
+            Generator::Tree(algorithm) => graph::carve_grid(env, &Graph::grid(env.width, env.height).spanning_tree(*algorithm, rng))?,
+            Generator::Cave => cave::gen_cave(env, rng, cave::DEFAULT_FILL, cave::DEFAULT_STEPS),
+            Generator::Curve(curve) => unicursal::gen_curve(env, *curve),
+        }
+        Ok(())
+    }
+}
+
+pub struct Job {
+    // Line of the manifest the job is on, counting from 1
+    pub line: usize,
+    pub width: usize,
+    pub height: usize,
+    // `None` for `-`
+    pub seed: Option<u64>,
+    pub generator: Generator,
+    pub outputs: Vec<String>,
+}
+
+impl Job {
+    // The job's maze, carved from `seed`
+    pub fn carve(&self, seed: u64) -> Result<Env, MazeError> {
+        let mut env = Env::try_new(self.width, self.height)?;
+        self.generator.carve(&mut env, &mut StdRng::seed_from_u64(seed))?;
+        Ok(env)
+    }
+}
+
+fn invalid(line: usize, message: &str) -> MazeError {
+    MazeError::InvalidData(format!("line {}: {}", line, message))
+}
+
+fn parse_size(text: &str) -> Option<(usize, usize)> {
+    let (w, h) = text.split_once('x').unwrap_or((text, text));
+    Some((w.parse().ok()?, h.parse().ok()?)).filter(|&(w, h)| w >= 1 && h >= 1)
+}
+
+pub fn parse(text: &str) -> Result<Vec<Job>, MazeError> {
+    let mut jobs = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [size, seed, algorithm, outputs @ ..] = fields.as_slice() else {
+            return Err(invalid(number, "expected `SIZE SEED ALGORITHM OUTPUT...`"));
+        };
+        let Some((width, height)) = parse_size(size) else {
+            return Err(invalid(number, &format!("`{}` is not a size; expected `N` or `WxH` with positive integers", size)));
+        };
+        let seed = match *seed {
+            "-" => None,
+            seed => Some(seed.parse().map_err(|_| invalid(number, &format!("`{}` is not a seed; expected a number or `-`", seed)))?),
+        };
+        let Some(generator) = Generator::from_name(algorithm) else {
+            let mut names: Vec<&str> = Algorithm::ALL.iter().map(|a| a.name()).collect();
+            names.push("cave");
+            names.extend(Curve::ALL.iter().map(|c| c.name()));
+            return Err(invalid(number, &format!("unknown algorithm `{}`; expected one of {}", algorithm, names.join(", "))));
+        };
+        if let Generator::Curve(curve) = generator {
+            if !curve.fits(width, height) {
+                return Err(invalid(number, &format!("the {} curve only covers square grids with a side that is a power of two", curve.name())));
+            }
+        }
+        if outputs.is_empty() {
+            return Err(invalid(number, "no outputs"));
+        }
+        if outputs.contains(&"-") {
+            return Err(invalid(number, "jobs run side by side, so they cannot share standard output"));
+        }
+        let outputs = outputs.iter().map(|output| output.to_string()).collect();
+        jobs.push(Job { line: number, width, height, seed, generator, outputs });
+    }
+    Ok(jobs)
+}
+
+pub fn load(path: &Path) -> Result<Vec<Job>, MazeError> {
+    parse(&fs::read_to_string(path)?)
+}
@@ -0,0 +1,203 @@
+// The `.maze` file format: a small text format that stores a maze exactly, so it can be edited,
+// re-rendered or solved later without regenerating it.
+//
+//   maze 1
+//   size 8 6
+//   start 0 0
+//   goal 5 7
+//   6ce4...
+//
+// `size` is width then height, `start`/`goal` are row then column, and every following line is
+// one row of the maze with one hex digit per cell holding the `OPEN_*` bits of its open sides, or
+// `.` for a cell left out of the maze. Mazes saved by `maze solve` go on with `solution N` and the
+// N cells of the path from start to goal, one `row column` per line; readers that stop after the
+// rows still read them as plain mazes.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::error::MazeError;
+use crate::render::{Renderer, Theme};
+use crate::transform::Transform;
+use crate::{Cell, Env, OPEN_EAST, OPEN_NORTH, OPEN_SOUTH, OPEN_WEST};
+
+pub const EXTENSION: &str = "maze";
+const MAGIC: &str = "maze 1";
+// Character of an excluded cell, and what it reads as: no open sides, but not a hex digit either
+const EXCLUDED_CHAR: char = '.';
+const EXCLUDED: u8 = 0x10;
+
+// A maze together with the cells a solver should connect
+pub struct MazeFile {
+    pub env: Env,
+    pub start: Cell,
+    pub goal: Cell,
+    // Cells from start to goal, both included, if the file has a solution; empty otherwise
+    pub solution: Vec<Cell>,
+}
+
+impl MazeFile {
+    // Start in the top-left cell and finish in the bottom-right one, like the rest of the crate
+    pub fn new(env: Env) -> Self {
+        let goal = Cell { row: env.height - 1, col: env.width - 1 };
+        Self { env, start: Cell { row: 0, col: 0 }, goal, solution: vec![] }
+    }
+
+    // The maze turned or flipped by `transform`, with the start and goal moved along with it
+    pub fn transformed(&self, transform: Transform) -> Self {
+        let (width, height) = (self.env.width, self.env.height);
+        let moved = |cell: Cell| transform.cell(cell, width, height);
+        Self { env: self.env.transformed(transform), start: moved(self.start), goal: moved(self.goal), solution: self.solution.iter().map(|&cell| moved(cell)).collect() }
+    }
+}
+
+fn invalid(line: usize, message: &str) -> MazeError {
+    MazeError::InvalidData(format!("line {}: {}", line, message))
+}
+
+// The two numbers after `key` on header line `number`
+fn header(line: Option<(usize, String)>, number: usize, key: &str) -> Result<(usize, usize), MazeError> {
+    let (_, text) = line.ok_or_else(|| invalid(number, &format!("expected `{} A B`, found the end of the file", key)))?;
+    let mut words = text.split_whitespace();
+    let values = (words.next() == Some(key))
+        .then(|| Some((words.next()?.parse().ok()?, words.next()?.parse().ok()?)))
+        .flatten();
+    values.filter(|_| words.next().is_none()).ok_or_else(|| invalid(number, &format!("expected `{} A B`", key)))
+}
+
+pub fn read(input: impl BufRead) -> Result<MazeFile, MazeError> {
+    let mut lines = input.lines().enumerate().map(|(i, line)| line.map(|l| (i + 1, l)));
+    let mut next = || lines.next().transpose();
+
+    match next()? {
+        Some((_, line)) if line.trim_end() == MAGIC => {},
+        _ => return Err(invalid(1, &format!("not a maze file (expected `{}`)", MAGIC))),
+    }
+    let (width, height) = header(next()?, 2, "size")?;
+    let mut env = Env::try_new(width, height).map_err(|err| invalid(2, &err.to_string()))?;
+    let inside = |(row, col): (usize, usize)| row < height && col < width;
+    let start = header(next()?, 3, "start").and_then(|c| if inside(c) { Ok(c) } else { Err(invalid(3, "start is outside the maze")) })?;
+    let goal = header(next()?, 4, "goal").and_then(|c| if inside(c) { Ok(c) } else { Err(invalid(4, "goal is outside the maze")) })?;
+
+    let mut sides = vec![0u8; width * height];
+    for row in 0..height {
+        let (number, line) = next()?.ok_or_else(|| invalid(5 + row, "missing maze row"))?;
+        let digits: Vec<u8> = line.trim_end().chars()
+            .map(|c| if c == EXCLUDED_CHAR { Some(EXCLUDED) } else { c.to_digit(16).map(|d| d as u8) })
+            .collect::<Option<_>>()
+            .ok_or_else(|| invalid(number, "rows must only contain hex digits and `.`"))?;
+        if digits.len() != width {
+            return Err(invalid(number, &format!("expected {} cells, found {}", width, digits.len())));
+        }
+        sides[row * width..(row + 1) * width].copy_from_slice(&digits);
+    }
+    for cell in env.cells().collect::<Vec<_>>() {
+        let (number, open) = (5 + cell.row, sides[env.ind(cell)]);
+        let east = Cell { row: cell.row, col: cell.col + 1 };
+        let south = Cell { row: cell.row + 1, col: cell.col };
+        let outward = (cell.row == 0 && open & OPEN_NORTH != 0)
+            || (cell.col == 0 && open & OPEN_WEST != 0)
+            || (east.col == width && open & OPEN_EAST != 0)
+            || (south.row == height && open & OPEN_SOUTH != 0);
+        let mismatch = (east.col < width && (open & OPEN_EAST != 0) != (sides[env.ind(east)] & OPEN_WEST != 0))
+            || (south.row < height && (open & OPEN_SOUTH != 0) != (sides[env.ind(south)] & OPEN_NORTH != 0));
+        if outward || mismatch {
+            let message = format!("cell ({}, {}) disagrees with its neighbours or opens onto the border", cell.row, cell.col);
+            return Err(invalid(number, &message));
+        }
+        if open == EXCLUDED {
+            env.exclude(cell);
+        }
+        if open & OPEN_EAST != 0 {
+            env.carve(cell, east);
+        }
+        if open & OPEN_SOUTH != 0 {
+            env.carve(cell, south);
+        }
+    }
+    let (start, goal) = (Cell { row: start.0, col: start.1 }, Cell { row: goal.0, col: goal.1 });
+    let mut solution: Vec<Cell> = vec![];
+    if let Some((number, line)) = next()?.filter(|(_, line)| line.starts_with("solution")) {
+        let mut words = line.split_whitespace().skip(1);
+        let count = match (words.next().and_then(|n| n.parse::<usize>().ok()), words.next()) {
+            (Some(count), None) if (1..=env.cell_count()).contains(&count) => count,
+            _ => return Err(invalid(number, "expected `solution N` with N from 1 to the number of cells")),
+        };
+        for i in 1..=count {
+            let (number, line) = next()?.ok_or_else(|| invalid(number + i, "missing solution cell"))?;
+            let mut words = line.split_whitespace().map(|w| w.parse::<usize>().ok());
+            let (Some(Some(row)), Some(Some(col)), None) = (words.next(), words.next(), words.next()) else {
+                return Err(invalid(number, "expected `row column`"));
+            };
+            let cell = Cell { row, col };
+            if !inside((row, col)) || solution.last().is_some_and(|&last| last.row.abs_diff(row) + last.col.abs_diff(col) != 1 || !env.has_passage(last, cell)) {
+                return Err(invalid(number, "the solution leaves the maze or goes through a wall"));
+            }
+            solution.push(cell);
+        }
+        if solution.first() != Some(&start) || solution.last() != Some(&goal) {
+            return Err(invalid(number, "the solution does not lead from start to goal"));
+        }
+    }
+    Ok(MazeFile { env, start, goal, solution })
+}
+
+pub fn load(path: &Path) -> Result<MazeFile, MazeError> {
+    read(BufReader::new(File::open(path)?))
+}
+
+pub fn write(out: &mut dyn Write, maze: &MazeFile) -> Result<(), MazeError> {
+    write_parts(out, &maze.env, maze.start, maze.goal)?;
+    if !maze.solution.is_empty() {
+        writeln!(out, "solution {}", maze.solution.len())?;
+        for cell in maze.solution.iter() {
+            writeln!(out, "{} {}", cell.row, cell.col)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_parts(out: &mut dyn Write, env: &Env, start: Cell, goal: Cell) -> Result<(), MazeError> {
+    writeln!(out, "{}", MAGIC)?;
+    writeln!(out, "size {} {}", env.width, env.height)?;
+    writeln!(out, "start {} {}", start.row, start.col)?;
+    writeln!(out, "goal {} {}", goal.row, goal.col)?;
+    let mut line = String::with_capacity(env.width + 1);
+    for row in 0..env.height {
+        line.clear();
+        for col in 0..env.width {
+            let cell = Cell { row, col };
+            line.push(if env.is_excluded(cell) {
+                EXCLUDED_CHAR
+            } else {
+                char::from_digit(env.open_sides(cell) as u32, 16).expect("four side bits")
+            });
+        }
+        line.push('\n');
+        out.write_all(line.as_bytes())?;
+    }
+    Ok(())
+}
+
+// Saves plain mazes (default start and goal) through the common renderer interface
+pub struct MazeFileRenderer;
+
+impl Renderer for MazeFileRenderer {
+    fn render(&self, env: &Env, _theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        write_parts(out, env, Cell { row: 0, col: 0 }, Cell { row: env.height - 1, col: env.width - 1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_header_lines_name_the_line() {
+        for (text, line) in [("maze 1\n", 2), ("maze 1\nsize 3 3\n", 3), ("maze 1\nsize 3 3\nstart 0 0\n", 4)] {
+            let err = read(text.as_bytes()).err().expect("a truncated header");
+            assert!(err.to_string().starts_with(&format!("line {}: ", line)), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// Generation details embedded in rendered images, so a file can always be traced back to the seed
+// that produced it: PPM comment lines, PNG `tEXt` chunks and an SVG `<metadata>` element, each
+// holding `Key: value` entries.
+
+use crate::error::MazeError;
+use crate::Env;
+
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    // Key-value pairs in the order they are written
+    pub entries: Vec<(String, String)>,
+}
+
+impl Metadata {
+    // Details of `env`: the crate version, and the generator and seed when they are known
+    pub fn for_maze(env: &Env, seed: Option<u64>, algorithm: Option<&str>) -> Self {
+        let mut entries = vec![(String::from("Software"), format!("maze {}", env!("CARGO_PKG_VERSION")))];
+        if let Some(algorithm) = algorithm {
+            entries.push((String::from("Algorithm"), algorithm.to_string()));
+        }
+        entries.push((String::from("Width"), env.width.to_string()));
+        entries.push((String::from("Height"), env.height.to_string()));
+        if let Some(seed) = seed {
+            entries.push((String::from("Seed"), seed.to_string()));
+        }
+        Self { entries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // The metadata of a PPM, PNG or SVG file, which is empty if the file has none
+    pub fn read(bytes: &[u8]) -> Result<Metadata, MazeError> {
+        if bytes.starts_with(PNG_SIGNATURE) {
+            return read_png(bytes);
+        }
+        if bytes.starts_with(b"P6") || bytes.starts_with(b"P3") {
+            return Ok(read_ppm(bytes));
+        }
+        let text = String::from_utf8_lossy(bytes);
+        if text.contains("<svg") {
+            return Ok(read_svg(&text));
+        }
+        Err(MazeError::InvalidData(String::from("not a PPM, PNG or SVG file")))
+    }
+}
+
+// `Key: value` into a pair
+fn entry(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim();
+    (!key.is_empty() && !key.contains(char::is_whitespace)).then(|| (key.to_string(), value.trim().to_string()))
+}
+
+// Comment lines right after the magic number
+fn read_ppm(bytes: &[u8]) -> Metadata {
+    let entries = bytes
+        .split(|b| *b == b'\n')
+        .skip(1)
+        .map_while(|line| line.strip_prefix(b"#"))
+        .filter_map(|line| entry(&String::from_utf8_lossy(line)))
+        .collect();
+    Metadata { entries }
+}
+
+// Every `tEXt` chunk before the image data ends
+fn read_png(bytes: &[u8]) -> Result<Metadata, MazeError> {
+    let truncated = || MazeError::InvalidData(String::from("PNG file ends in the middle of a chunk"));
+    let mut entries = vec![];
+    let mut rest = &bytes[PNG_SIGNATURE.len()..];
+    while rest.len() >= 8 {
+        let len = u32::from_be_bytes(rest[..4].try_into().expect("four bytes")) as usize;
+        let kind = &rest[4..8];
+        let data = rest.get(8..8 + len).ok_or_else(truncated)?;
+        if kind == b"tEXt" {
+            if let Some(split) = data.iter().position(|b| *b == 0) {
+                // Both halves are Latin-1, which maps byte for byte onto the first 256 code points
+                let latin1 = |bytes: &[u8]| bytes.iter().map(|b| *b as char).collect::<String>();
+                entries.push((latin1(&data[..split]), latin1(&data[split + 1..])));
+            }
+        }
+        if kind == b"IEND" {
+            break;
+        }
+        rest = rest.get(8 + len + 4..).ok_or_else(truncated)?;
+    }
+    Ok(Metadata { entries })
+}
+
+// Lines of the first `<metadata>` element
+fn read_svg(text: &str) -> Metadata {
+    let body = text.split_once("<metadata>").and_then(|(_, rest)| rest.split_once("</metadata>")).map_or("", |(body, _)| body);
+    let unescape = |s: String| s.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
+    let entries = body.lines().filter_map(entry).map(|(k, v)| (k, unescape(v))).collect();
+    Metadata { entries }
+}
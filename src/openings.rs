@@ -0,0 +1,155 @@
+// Where the entrance and exit of a maze go: a cell on the border for each, with the side the
+// opening is cut through. Every strategy can be asked for a pair at least `min_length` steps apart
+// along the maze's passages, so a random pair never ends up two cells from each other.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::analysis;
+use crate::error::MazeError;
+use crate::stitch::Edge;
+use crate::{Cell, Env};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Placement {
+    // Top-left to bottom-right, or top-right to bottom-left when that pair is too close
+    Corners,
+    // Across the maze between the two sides farthest apart: west to east, or north to south for
+    // mazes taller than they are wide
+    OppositeEdges,
+    // Any two border cells
+    Random,
+    // The two border cells farthest apart along the passages
+    Longest,
+}
+
+impl Placement {
+    pub const ALL: [Placement; 4] = [Placement::Corners, Placement::OppositeEdges, Placement::Random, Placement::Longest];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Placement::Corners => "corners",
+            Placement::OppositeEdges => "opposite-edges",
+            Placement::Random => "random",
+            Placement::Longest => "longest",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Placement::ALL.into_iter().find(|p| p.name() == name)
+    }
+}
+
+// A border cell and the side of the maze its opening is cut through
+pub type Opening = (Cell, Edge);
+
+// The side of the maze `cell` opens through: west or east for cells in the first or last column,
+// north or south otherwise
+pub fn side(env: &Env, cell: Cell) -> Edge {
+    if cell.col == 0 {
+        Edge::West
+    } else if cell.col + 1 == env.width {
+        Edge::East
+    } else if cell.row == 0 {
+        Edge::North
+    } else {
+        Edge::South
+    }
+}
+
+// Cells of the maze along `edge`, left out ones skipped
+fn along(env: &Env, edge: Edge) -> Vec<Cell> {
+    let cells: Vec<Cell> = match edge {
+        Edge::North => (0..env.width).map(|col| Cell { row: 0, col }).collect(),
+        Edge::South => (0..env.width).map(|col| Cell { row: env.height - 1, col }).collect(),
+        Edge::West => (0..env.height).map(|row| Cell { row, col: 0 }).collect(),
+        Edge::East => (0..env.height).map(|row| Cell { row, col: env.width - 1 }).collect(),
+    };
+    cells.into_iter().filter(|&cell| !env.is_excluded(cell)).collect()
+}
+
+// Every cell on the border once, clockwise from the top-left corner
+fn border(env: &Env) -> Vec<Cell> {
+    let (right, bottom) = (env.width - 1, env.height - 1);
+    let north = (0..env.width).map(|col| Cell { row: 0, col });
+    let east = (1..env.height).map(|row| Cell { row, col: right });
+    let south = (0..right).rev().filter(|_| bottom > 0).map(|col| Cell { row: bottom, col });
+    let west = (1..bottom).rev().filter(|_| right > 0).map(|row| Cell { row, col: 0 });
+    north.chain(east).chain(south).chain(west).filter(|&cell| !env.is_excluded(cell)).collect()
+}
+
+fn too_close(placement: Placement, min_length: usize, best: Option<usize>) -> MazeError {
+    let found = match best {
+        Some(best) => format!("the farthest apart are {} steps", best),
+        None => String::from("no two border cells are connected"),
+    };
+    MazeError::InvalidData(format!("no {} openings are at least {} steps apart; {}", placement.name(), min_length, found))
+}
+
+// Picks the start and goal openings of `env` by `placement`, at least `min_length` steps apart.
+// The random strategies draw from `rng`; `corners` and `longest` always give the same pair.
+pub fn place(env: &Env, placement: Placement, min_length: usize, rng: &mut impl Rng) -> Result<[Opening; 2], MazeError> {
+    let opening = |cell: Cell| (cell, side(env, cell));
+    let distance = |a: Cell, b: Cell| analysis::distances(env, a)[env.ind(b)];
+    let mut best = None;
+    match placement {
+        Placement::Corners => {
+            let (right, bottom) = (env.width - 1, env.height - 1);
+            let pairs = [((0, 0), (bottom, right)), ((0, right), (bottom, 0))];
+            for ((r1, c1), (r2, c2)) in pairs {
+                let (start, goal) = (Cell { row: r1, col: c1 }, Cell { row: r2, col: c2 });
+                if env.is_excluded(start) || env.is_excluded(goal) {
+                    continue;
+                }
+                let Some(d) = distance(start, goal) else { continue };
+                if d >= min_length && start != goal {
+                    return Ok([opening(start), opening(goal)]);
+                }
+                best = best.max(Some(d));
+            }
+            Err(too_close(placement, min_length, best))
+        },
+        Placement::OppositeEdges | Placement::Random => {
+            // The sides the openings go through, when they are fixed
+            let edges = match placement {
+                Placement::OppositeEdges if env.height > env.width => Some((Edge::North, Edge::South)),
+                Placement::OppositeEdges => Some((Edge::West, Edge::East)),
+                _ => None,
+            };
+            let (mut starts, goals) = match edges {
+                Some((from, to)) => (along(env, from), along(env, to)),
+                None => (border(env), border(env)),
+            };
+            starts.shuffle(rng);
+            for start in starts {
+                let distances = analysis::distances(env, start);
+                let reached = |goal: &Cell| distances[env.ind(*goal)].filter(|_| *goal != start);
+                best = best.max(goals.iter().filter_map(reached).max());
+                let far: Vec<Cell> = goals.iter().copied().filter(|goal| reached(goal).is_some_and(|d| d >= min_length)).collect();
+                if let Some(&goal) = far.choose(rng) {
+                    return Ok(match edges {
+                        Some((from, to)) => [(start, from), (goal, to)],
+                        None => [opening(start), opening(goal)],
+                    });
+                }
+            }
+            Err(too_close(placement, min_length, best))
+        },
+        Placement::Longest => {
+            // Two sweeps, like `analysis::longest_path` but between border cells only: the border
+            // cell farthest from any other is one end and the one farthest from that the other
+            let cells = border(env);
+            let farthest = |from: Cell| {
+                let distances = analysis::distances(env, from);
+                cells.iter().filter_map(|&cell| distances[env.ind(cell)].map(|d| (d, cell))).max_by_key(|&(d, _)| d)
+            };
+            let first = *cells.first().ok_or_else(|| too_close(placement, min_length, None))?;
+            let (_, start) = farthest(first).expect("a cell reaches itself");
+            let (d, goal) = farthest(start).expect("a cell reaches itself");
+            if d < min_length || start == goal {
+                return Err(too_close(placement, min_length, Some(d)));
+            }
+            Ok([opening(start), opening(goal)])
+        },
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::MazeError;
+use crate::progress::{CancelToken, Progress};
+use crate::{gen_maze_progress, gen_maze_with, Cell, Env, WallKind};
+
+// Width and height of a region, in cells. The region layout only depends on the maze size, never
+// on the thread count, so the same seed always produces the same maze.
+pub const REGION_SIZE: usize = 32;
+
+// Longest the calling thread waits for a finished region before reporting progress again
+const PROGRESS_POLL: Duration = Duration::from_millis(50);
+
+// Independent RNG stream for region `index`; stream 0 is reserved for stitching
+fn region_rng(seed: u64, index: usize) -> StdRng {
+    StdRng::seed_from_u64(seed ^ (index as u64).wrapping_mul(0x9E3779B97F4A7C15))
+}
+
+// Generate a perfect maze by carving `REGION_SIZE` regions on up to `threads` threads and then
+// joining neighbouring regions with single seeded boundary carves. `progress` is polled with the
+// number of cells in finished regions while the workers run; cancelling it stops every worker
+// and leaves `env` uncarved.
+pub fn gen_maze_parallel(env: &mut Env, seed: u64, threads: usize, progress: &mut dyn Progress) -> Result<(), MazeError> {
+    let region_cols = env.width.div_ceil(REGION_SIZE);
+    let region_rows = env.height.div_ceil(REGION_SIZE);
+    let region_count = region_cols * region_rows;
+    let (maze_width, maze_height) = (env.width, env.height);
+    // Origin (row, col) and size (width, height) of every region
+    let bounds = |index: usize| {
+        let (row, col) = ((index / region_cols) * REGION_SIZE, (index % region_cols) * REGION_SIZE);
+        let size = (REGION_SIZE.min(maze_width - col), REGION_SIZE.min(maze_height - row));
+        (Cell { row, col }, size)
+    };
+
+    let threads = threads.clamp(1, region_count);
+    let total = env.cell_count();
+    let stop = &CancelToken::default();
+    let mut regions = thread::scope(|scope| -> Result<Vec<(usize, Env)>, MazeError> {
+        // Finished regions are sent back as soon as they are carved
+        let (sender, receiver) = mpsc::channel();
+        let workers: Vec<_> = (0..threads)
+            .map(|t| {
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    for index in (t..region_count).step_by(threads) {
+                        let (_, (width, height)) = bounds(index);
+                        let mut region = Env::new(width, height);
+                        gen_maze_progress(&mut region, &mut region_rng(seed, index + 1), &mut stop.clone())?;
+                        if sender.send((index, region)).is_err() {
+                            break;
+                        }
+                    }
+                    Ok::<(), MazeError>(())
+                })
+            })
+            .collect();
+        drop(sender);
+
+        let (mut regions, mut carved) = (vec![], 0);
+        loop {
+            match receiver.recv_timeout(PROGRESS_POLL) {
+                Ok((index, region)) => {
+                    carved += region.cell_count();
+                    regions.push((index, region));
+                },
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if progress.checkpoint(carved, total).is_err() {
+                stop.cancel();
+            }
+        }
+        for worker in workers {
+            worker.join().expect("region worker panicked")?;
+        }
+        Ok(regions)
+    })?;
+    regions.sort_by_key(|(index, _)| *index);
+
+    for (index, region) in regions.iter() {
+        let (origin, _) = bounds(*index);
+        let to_global = |cell: Cell| Cell { row: origin.row + cell.row, col: origin.col + cell.col };
+        for wall in region.walls() {
+            env.carve(to_global(wall.start), to_global(wall.target));
+        }
+        for &ind in region.visit_order.iter() {
+            let cell = to_global(region.cell(ind as usize));
+            env.mark_visited(cell);
+            env.visit_order.push(env.ind(cell) as u32);
+        }
+    }
+
+    // The regions themselves form a grid; a spanning tree over it says which neighbouring
+    // regions get joined, and each join opens one random cell pair along their shared edge
+    let mut rng = region_rng(seed, 0);
+    let mut layout = Env::new(region_cols, region_rows);
+    gen_maze_with(&mut layout, &mut rng);
+    for join in layout.walls() {
+        let (origin, (width, height)) = bounds(layout.ind(join.start));
+        let (a, b) = match join.kind {
+            WallKind::Vertical => {
+                let row = origin.row + rng.gen_range(0..height);
+                let col = origin.col + width - 1;
+                (Cell { row, col }, Cell { row, col: col + 1 })
+            },
+            WallKind::Horizontal => {
+                let row = origin.row + height - 1;
+                let col = origin.col + rng.gen_range(0..width);
+                (Cell { row, col }, Cell { row: row + 1, col })
+            },
+        };
+        env.carve(a, b);
+    }
+    progress.update(total, total);
+    Ok(())
+}
+
+// `gen_maze_parallel` with the seed drawn from `rng`. Every region needs a stream of its own that
+// does not depend on which thread carves it, so `rng` only picks the seed they are derived from.
+pub fn gen_maze_parallel_with(env: &mut Env, rng: &mut impl Rng, threads: usize, progress: &mut dyn Progress) -> Result<(), MazeError> {
+    gen_maze_parallel(env, rng.gen(), threads, progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_count_does_not_change_the_maze() {
+        // Uneven so the last row and column of regions are partial
+        let (width, height) = ((3 * REGION_SIZE) + 5, (2 * REGION_SIZE) + 7);
+        let mazes: Vec<Env> = [1, 2, 8]
+            .into_iter()
+            .map(|threads| {
+                let mut env = Env::new(width, height);
+                gen_maze_parallel(&mut env, 42, threads, &mut ()).expect("`()` never cancels");
+                env
+            })
+            .collect();
+        let passages = |env: &Env| env.walls().map(|wall| (wall.start, wall.target)).collect::<Vec<_>>();
+        for env in mazes.iter() {
+            assert!(env.is_perfect());
+            assert_eq!(passages(env), passages(&mazes[0]));
+        }
+    }
+}
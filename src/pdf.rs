@@ -0,0 +1,105 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+// A4 portrait, in PDF points (1/72 inch)
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+// Resource names of the two standard fonts every page can use; standard fonts need no embedding
+pub const FONT_REGULAR: &str = "F1";
+pub const FONT_BOLD: &str = "F2";
+
+// Minimal PDF writer: uncompressed page content streams over a fixed page size and two fonts.
+// Content uses PDF coordinates, with the origin in the bottom-left corner of the page.
+#[derive(Default)]
+pub struct Pdf {
+    pages: Vec<String>,
+}
+
+// `r`, `g` and `b` operands for a 0xRRGGBBAA color; PDF has no alpha without extra objects
+pub fn rgb(color: u32) -> String {
+    let channel = |shift: u32| ((color >> shift) & 0xFF) as f32 / 255.0;
+    format!("{:.3} {:.3} {:.3}", channel(24), channel(16), channel(8))
+}
+
+// Width of `text` set in Helvetica at `size` points, close enough to center short titles.
+// Helvetica averages a little over half an em per character.
+pub fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * 0.55
+}
+
+// Content stream operators that show `text` with its baseline starting at (`x`, `y`)
+pub fn text(x: f32, y: f32, size: f32, font: &str, color: u32, text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            },
+            // Standard fonts only cover Latin-1 through the default encoding
+            ch if ch.is_ascii() && !ch.is_ascii_control() => escaped.push(ch),
+            _ => escaped.push('?'),
+        }
+    }
+    format!("BT /{} {:.1} Tf {} rg {:.2} {:.2} Td ({}) Tj ET\n", font, size, rgb(color), x, y, escaped)
+}
+
+// `text`, horizontally centered on the page
+pub fn centered_text(y: f32, size: f32, font: &str, color: u32, line: &str) -> String {
+    text((PAGE_WIDTH - text_width(line, size)) / 2.0, y, size, font, color, line)
+}
+
+impl Pdf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_page(&mut self, content: String) {
+        self.pages.push(content);
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn write(&self, out: &mut dyn Write) -> io::Result<()> {
+        // Objects: 1 catalog, 2 page tree, 3-4 fonts, then a page and its content stream per page
+        let page_id = |i: usize| 5 + (2 * i);
+        let mut objects = vec![
+            String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+            {
+                let mut kids = String::new();
+                for i in 0..self.pages.len() {
+                    let _ = write!(kids, "{} 0 R ", page_id(i));
+                }
+                format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.trim_end(), self.pages.len())
+            },
+            String::from("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>"),
+            String::from("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold >>"),
+        ];
+        for (i, content) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /{} 3 0 R /{} 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT, FONT_REGULAR, FONT_BOLD, page_id(i) + 1
+            ));
+            objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+        }
+
+        // The binary comment marks the file as binary for transfer tools
+        let mut bytes = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(bytes.len());
+            bytes.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+        }
+        let xref = bytes.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(trailer, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref);
+        bytes.extend_from_slice(trailer.as_bytes());
+        out.write_all(&bytes)
+    }
+}
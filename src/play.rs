@@ -0,0 +1,119 @@
+use std::io::{self, Write};
+use std::time::Instant;
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute, queue,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+
+use crate::pursuit::{Outcome, Pursuit, MINOTAUR_SPEED};
+use crate::render::SOLID_COLOR;
+use crate::{Cell, Env};
+
+// Every block of the block map is drawn this many terminal columns wide, so cells look square
+pub(crate) const BLOCK_WIDTH: usize = 2;
+const PLAYER_COLOR: Color = Color::Yellow;
+const EXIT_COLOR: Color = Color::Red;
+const MINOTAUR_COLOR: Color = Color::Magenta;
+
+pub(crate) fn wall_color() -> Color {
+    Color::Rgb { r: (SOLID_COLOR >> 24) as u8, g: (SOLID_COLOR >> 16) as u8, b: (SOLID_COLOR >> 8) as u8 }
+}
+
+// First block row/column to draw so that `target` stays centered when the maze is larger than
+// the view
+pub(crate) fn scroll(target: usize, view: usize, total: usize) -> usize {
+    if total <= view { 0 } else { target.saturating_sub(view / 2).min(total - view) }
+}
+
+fn draw(out: &mut impl Write, env: &Env, blocks: &[Vec<bool>], player: Cell, exit: Cell, minotaur: Option<Cell>, steps: usize) -> io::Result<()> {
+    let (cols, rows) = terminal::size().unwrap_or((80, 24));
+    let (block_rows, block_cols) = env.block_dims();
+    let view_rows = (rows as usize).saturating_sub(1).max(1).min(block_rows);
+    let view_cols = (cols as usize / BLOCK_WIDTH).max(1).min(block_cols);
+    let (player_i, player_j) = ((2 * player.row) + 1, (2 * player.col) + 1);
+    let (exit_i, exit_j) = ((2 * exit.row) + 1, (2 * exit.col) + 1);
+    let minotaur = minotaur.map(|m| ((2 * m.row) + 1, (2 * m.col) + 1));
+    let top = scroll(player_i, view_rows, block_rows);
+    let left = scroll(player_j, view_cols, block_cols);
+
+    queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+    for (y, (i, row)) in blocks.iter().enumerate().skip(top).take(view_rows).enumerate() {
+        queue!(out, cursor::MoveTo(0, y as u16))?;
+        for (j, &solid) in row.iter().enumerate().skip(left).take(view_cols) {
+            let (color, glyph) = if minotaur == Some((i, j)) {
+                (MINOTAUR_COLOR, "MM")
+            } else if (i, j) == (player_i, player_j) {
+                (PLAYER_COLOR, "@@")
+            } else if (i, j) == (exit_i, exit_j) {
+                (EXIT_COLOR, "[]")
+            } else if solid {
+                (wall_color(), "██")
+            } else {
+                (Color::Reset, "  ")
+            };
+            queue!(out, SetForegroundColor(color), Print(glyph))?;
+        }
+    }
+    let status = format!("Arrows/WASD: move  Q: quit   cell ({}, {})  steps {}", player.row, player.col, steps);
+    queue!(out, ResetColor, cursor::MoveTo(0, view_rows as u16), Print(status))?;
+    out.flush()
+}
+
+// Top-down play through the maze: the player starts in the top-left cell and has to reach the
+// exit in the bottom-right one. Moves into walls are ignored. With `minotaur`, a minotaur starting
+// in the middle of the maze takes `MINOTAUR_SPEED` steps towards the player after every move.
+pub fn play(env: &Env, minotaur: bool) -> io::Result<()> {
+    let blocks = env.blocks();
+    let exit = Cell { row: env.height - 1, col: env.width - 1 };
+    let mut player = Cell { row: 0, col: 0 };
+    let mut steps = 0;
+    let mut pursuit = minotaur.then(|| Pursuit::new(env, MINOTAUR_SPEED));
+    let mut out = io::stdout();
+    let start = Instant::now();
+
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = (|| -> io::Result<Outcome> {
+        loop {
+            draw(&mut out, env, &blocks, player, exit, pursuit.as_ref().map(|p| p.minotaur.position), steps)?;
+            if player == exit {
+                return Ok(Outcome::Escaped);
+            }
+            if pursuit.as_ref().is_some_and(|p| p.outcome == Outcome::Caught) {
+                return Ok(Outcome::Caught);
+            }
+            let Event::Key(key) = event::read()? else { continue };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let target = match key.code {
+                KeyCode::Char('w') | KeyCode::Up if player.row > 0 => Cell { row: player.row - 1, ..player },
+                KeyCode::Char('s') | KeyCode::Down if player.row + 1 < env.height => Cell { row: player.row + 1, ..player },
+                KeyCode::Char('a') | KeyCode::Left if player.col > 0 => Cell { col: player.col - 1, ..player },
+                KeyCode::Char('d') | KeyCode::Right if player.col + 1 < env.width => Cell { col: player.col + 1, ..player },
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(Outcome::Running),
+                _ => continue,
+            };
+            if env.has_passage(player, target) {
+                player = target;
+                steps += 1;
+                if let Some(pursuit) = pursuit.as_mut() {
+                    pursuit.turn(env, target);
+                }
+            }
+        }
+    })();
+    execute!(out, ResetColor, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    match result? {
+        Outcome::Escaped => println!("You reached the exit in {} steps and {:.1}s!", steps, start.elapsed().as_secs_f64()),
+        Outcome::Caught => println!("The minotaur caught you after {} steps!", steps),
+        Outcome::Running => {},
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use std::io::{self, Write};
+
+use crate::deflate;
+use crate::draw::Canvas;
+use crate::error::MazeError;
+use crate::inflate;
+use crate::metadata::Metadata;
+
+pub(crate) fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xEDB88320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *entry = c;
+    }
+    table
+}
+
+fn crc32(table: &[u32; 256], bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+    for b in bytes {
+        crc = table[((crc ^ *b as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFFFFFF
+}
+
+pub(crate) fn write_chunk(out: &mut dyn Write, table: &[u32; 256], kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    let mut body = Vec::with_capacity(data.len() + 4);
+    body.extend_from_slice(kind);
+    body.extend_from_slice(data);
+    out.write_all(&body)?;
+    out.write_all(&crc32(table, &body).to_be_bytes())
+}
+
+// Largest size in bytes of `write_png`'s output for a `width`x`height` canvas, if none of it
+// compresses; maze images come out far smaller
+pub fn encoded_size(width: usize, height: usize) -> usize {
+    let raw = height * ((width * 4) + 1);
+    let zlib = deflate::max_zlib_size(raw);
+    // Signature, then IHDR, IDAT and IEND each with 12 bytes of length/type/CRC
+    8 + (12 + 13) + (12 + zlib) + 12
+}
+
+pub fn write_png(out: &mut dyn Write, canvas: &Canvas) -> Result<(), io::Error> {
+    write_png_with(out, canvas, &Metadata::default())
+}
+
+// Pixels are in 0xRRGGBBAA format; the image is written as 8-bit RGBA, with a `tEXt` chunk per
+// metadata entry
+pub fn write_png_with(out: &mut dyn Write, canvas: &Canvas, metadata: &Metadata) -> Result<(), io::Error> {
+    let (width, height) = (canvas.width(), canvas.height());
+    if width == 0 || height == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("a PNG cannot be {}x{} px", width, height)));
+    }
+    let table = crc32_table();
+    out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'])?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // Bit depth 8, color type 6 (RGBA), default compression/filter, no interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    write_chunk(out, &table, b"IHDR", &header)?;
+    for (key, value) in metadata.entries.iter() {
+        // Keywords are 1-79 Latin-1 characters and neither half may contain a NUL
+        let latin1 = |s: &str| s.chars().map(|c| if c == '\0' || c as u32 > 0xFF { b'?' } else { c as u8 }).collect::<Vec<u8>>();
+        let mut text = latin1(key);
+        text.truncate(79);
+        text.push(0);
+        text.extend(latin1(value));
+        write_chunk(out, &table, b"tEXt", &text)?;
+    }
+
+    let mut raw = Vec::with_capacity(height * (width * 4 + 1));
+    for row in canvas.pixels().chunks(width) {
+        // Filter type 0 (None) for every scanline
+        raw.push(0);
+        for pixel in row {
+            raw.extend_from_slice(&pixel.to_be_bytes());
+        }
+    }
+    write_chunk(out, &table, b"IDAT", &deflate::zlib_compress(&raw))?;
+    write_chunk(out, &table, b"IEND", &[])?;
+    out.flush()
+}
+
+fn invalid(message: &str) -> MazeError {
+    MazeError::InvalidData(format!("unsupported or broken PNG: {}", message))
+}
+
+// Raw scanlines with their filters undone; `bpp` is the filter's byte distance to the pixel on the
+// left
+fn unfilter(data: &[u8], stride: usize, height: usize, bpp: usize) -> Result<Vec<u8>, MazeError> {
+    let mut out = vec![0u8; stride * height];
+    for y in 0..height {
+        let line = data.get(y * (stride + 1)..(y + 1) * (stride + 1)).ok_or_else(|| invalid("image data is too short"))?;
+        let (filter, line) = (line[0], &line[1..]);
+        let (above, row) = out.split_at_mut(y * stride);
+        let above = if y > 0 { &above[(y - 1) * stride..] } else { &[][..] };
+        let row = &mut row[..stride];
+        for x in 0..stride {
+            let a = if x >= bpp { row[x - bpp] as i16 } else { 0 };
+            let b = above.get(x).map_or(0, |&b| b as i16);
+            let c = if x >= bpp { above.get(x - bpp).map_or(0, |&c| c as i16) } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => (a + b) / 2,
+                4 => {
+                    let p = a + b - c;
+                    let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+                    if pa <= pb && pa <= pc { a } else if pb <= pc { b } else { c }
+                },
+                _ => return Err(invalid("unknown scanline filter")),
+            };
+            row[x] = line[x].wrapping_add(predicted as u8);
+        }
+    }
+    Ok(out)
+}
+
+// Decodes a non-interlaced PNG of any color type and bit depth into 0xRRGGBBAA pixels. 16-bit
+// samples keep their high byte.
+pub fn read_png(bytes: &[u8]) -> Result<Canvas, MazeError> {
+    let mut rest = bytes.strip_prefix(b"\x89PNG\r\n\x1a\n".as_slice()).ok_or_else(|| invalid("missing signature"))?;
+    let (mut header, mut palette, mut transparency, mut data) = (None, vec![], vec![], vec![]);
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[..4].try_into().expect("four bytes")) as usize;
+        let (kind, body) = (&rest[4..8], rest[8..].get(..len).ok_or_else(|| invalid("truncated chunk"))?);
+        match kind {
+            b"IHDR" if len == 13 => header = Some(body.to_vec()),
+            b"PLTE" => palette = body.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"tRNS" => transparency = body.to_vec(),
+            b"IDAT" => data.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {},
+        }
+        rest = rest.get(8 + len + 4..).unwrap_or(&[]);
+    }
+    let header = header.ok_or_else(|| invalid("missing IHDR"))?;
+    let width = u32::from_be_bytes(header[0..4].try_into().expect("four bytes")) as usize;
+    let height = u32::from_be_bytes(header[4..8].try_into().expect("four bytes")) as usize;
+    let (depth, color_type, interlace) = (header[8] as usize, header[9], header[12]);
+    if interlace != 0 {
+        return Err(invalid("interlaced images are not supported"));
+    }
+    let channels = match color_type {
+        0 | 3 => 1,
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => return Err(invalid("unknown color type")),
+    };
+    if ![1, 2, 4, 8, 16].contains(&depth) || (depth < 8 && !matches!(color_type, 0 | 3)) {
+        return Err(invalid("unsupported bit depth"));
+    }
+    let data = inflate::zlib_decompress(&data)?;
+    // Every scanline is a filter byte and `stride` bytes of samples. Checked before `unfilter`
+    // allocates the image, so forged dimensions cannot overflow it or ask for more than the data has.
+    let stride = (width as u64 * (channels * depth) as u64).div_ceil(8);
+    if width == 0 || height == 0 || (stride + 1).saturating_mul(height as u64) > data.len() as u64 {
+        return Err(invalid("image data is too short for its dimensions"));
+    }
+    let stride = stride as usize;
+    let raw = unfilter(&data, stride, height, (channels * depth / 8).max(1))?;
+
+    // Sample `i` of a row, scaled to 8 bits
+    let sample = |row: &[u8], i: usize| -> u8 {
+        match depth {
+            16 => row[2 * i],
+            8 => row[i],
+            _ => {
+                let (per_byte, max) = (8 / depth, (1 << depth) - 1);
+                let value = (row[i / per_byte] >> (8 - depth * (1 + i % per_byte))) as usize & max;
+                if color_type == 3 { value as u8 } else { (value * 255 / max) as u8 }
+            },
+        }
+    };
+    let mut canvas = Canvas::new(width, height, 0);
+    for (y, row) in raw.chunks(stride.max(1)).take(height).enumerate() {
+        for x in 0..width {
+            let s = |c: usize| sample(row, (x * channels) + c);
+            let [r, g, b, a] = match color_type {
+                0 => [s(0), s(0), s(0), 0xFF],
+                2 => [s(0), s(1), s(2), 0xFF],
+                3 => {
+                    let index = s(0) as usize;
+                    let [r, g, b] = *palette.get(index).ok_or_else(|| invalid("palette index out of range"))?;
+                    [r, g, b, transparency.get(index).copied().unwrap_or(0xFF)]
+                },
+                4 => [s(0), s(0), s(0), s(1)],
+                _ => [s(0), s(1), s(2), s(3)],
+            };
+            canvas.set(x, y, u32::from_be_bytes([r, g, b, a]));
+        }
+    }
+    Ok(canvas)
+}
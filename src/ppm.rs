@@ -0,0 +1,92 @@
+use std::io::{self, Write};
+
+use crate::draw::Canvas;
+use crate::error::MazeError;
+use crate::metadata::Metadata;
+
+// Binary PPM header for a `width`x`height` image, with a `# Key: value` comment line per
+// metadata entry
+pub fn header(width: usize, height: usize, metadata: &Metadata) -> String {
+    let mut header = String::from("P6\n");
+    for (key, value) in metadata.entries.iter() {
+        header.push_str(&format!("# {}: {}\n", key, value.replace('\n', " ")));
+    }
+    header + &format!("{} {} 255\n", width, height)
+}
+
+// Exact size in bytes of `write_ppm`'s output for a `width`x`height` canvas
+pub fn encoded_size(width: usize, height: usize) -> usize {
+    header(width, height, &Metadata::default()).len() + (width * height * 3)
+}
+
+pub fn write_ppm(out: &mut dyn Write, canvas: &Canvas) -> Result<(), io::Error> {
+    write_ppm_with(out, canvas, &Metadata::default())
+}
+
+// Pixels are in 0xRRGGBBAA format; PPM has no alpha channel so it is dropped.
+// Each row is converted into one byte buffer and written with a single call, so wrap `out` in a
+// `BufWriter` when it is a file or socket.
+pub fn write_ppm_with(out: &mut dyn Write, canvas: &Canvas, metadata: &Metadata) -> Result<(), io::Error> {
+    out.write_all(header(canvas.width(), canvas.height(), metadata).as_bytes())?;
+    let mut row = Vec::with_capacity(canvas.width() * 3);
+    for pixels in canvas.pixels().chunks(canvas.width().max(1)) {
+        row.clear();
+        for pixel in pixels {
+            row.extend_from_slice(&[
+                ((pixel >> 24) & 0xFF) as u8, //     0xRR & 0xFF
+                ((pixel >> 16) & 0xFF) as u8, //   0x__GG & 0xFF
+                ((pixel >> 8) & 0xFF) as u8,  // 0x____BB & 0xFF
+            ]);
+        }
+        out.write_all(&row)?;
+    }
+    Ok(())
+}
+
+// Decodes a binary PPM (`P6`) or PGM (`P5`) into opaque 0xRRGGBBAA pixels. 16-bit samples keep
+// their high byte.
+pub fn read_ppm(bytes: &[u8]) -> Result<Canvas, MazeError> {
+    let invalid = |message: &str| MazeError::InvalidData(format!("unsupported or broken PPM: {}", message));
+    let channels = match bytes.get(..2) {
+        Some(b"P6") => 3,
+        Some(b"P5") => 1,
+        _ => return Err(invalid("expected a `P6` or `P5` header")),
+    };
+    // Width, height and maximum value, separated by whitespace and `#` comments
+    let (mut fields, mut pos) = (vec![], 2);
+    while fields.len() < 3 {
+        match bytes.get(pos) {
+            Some(b'#') => pos += bytes[pos..].iter().position(|&b| b == b'\n').unwrap_or(bytes.len() - pos),
+            Some(b) if b.is_ascii_whitespace() => pos += 1,
+            Some(b) if b.is_ascii_digit() => {
+                let len = bytes[pos..].iter().take_while(|b| b.is_ascii_digit()).count();
+                fields.push(String::from_utf8_lossy(&bytes[pos..pos + len]).parse::<usize>().map_err(|_| invalid("header number out of range"))?);
+                pos += len;
+            },
+            _ => return Err(invalid("malformed header")),
+        }
+    }
+    let (width, height, max) = (fields[0], fields[1], fields[2]);
+    if max == 0 || max > 0xFFFF {
+        return Err(invalid("maximum value must be 1-65535"));
+    }
+    // A single whitespace byte separates the header from the samples
+    let samples = bytes.get(pos + 1..).unwrap_or(&[]);
+    let size = if max > 0xFF { 2 } else { 1 };
+    if samples.len() < width * height * channels * size {
+        return Err(invalid("pixel data is too short"));
+    }
+    let sample = |i: usize| {
+        let value = if size == 2 { u16::from_be_bytes([samples[2 * i], samples[(2 * i) + 1]]) as usize } else { samples[i] as usize };
+        (value * 255 / max) as u8
+    };
+    let mut canvas = Canvas::new(width, height, 0);
+    for y in 0..height {
+        for x in 0..width {
+            let i = ((y * width) + x) * channels;
+            let (r, g, b) = if channels == 3 { (sample(i), sample(i + 1), sample(i + 2)) } else { (sample(i), sample(i), sample(i)) };
+            canvas.set(x, y, u32::from_be_bytes([r, g, b, 0xFF]));
+        }
+    }
+    Ok(canvas)
+}
@@ -0,0 +1,140 @@
+// A minotaur chasing a player through the maze. The minotaur is a `Chaser`: an A* pursuer that
+// keeps its route between moves and patches it as the target moves, so a chase over many turns
+// costs a handful of searches rather than one per step.
+
+use std::collections::VecDeque;
+
+use crate::animation::Step;
+use crate::solve::Solver;
+use crate::{Cell, Env};
+
+// Steps the minotaur takes for every step of the player
+pub const MINOTAUR_SPEED: usize = 2;
+const PLAYER_TINT: u32 = 0xF2D13A90;
+const MINOTAUR_TINT: u32 = 0xC040C090;
+
+pub struct Chaser {
+    pub position: Cell,
+    // Cells after `position` up to the target the route leads to
+    route: VecDeque<Cell>,
+    // Full A* searches run so far
+    pub searches: usize,
+}
+
+impl Chaser {
+    pub fn new(position: Cell) -> Self {
+        Chaser { position, route: VecDeque::new(), searches: 0 }
+    }
+
+    // Brings the route up to date with `target`. A target that moved back along the route or one
+    // open cell past its end only trims or extends it, which keeps it shortest in a perfect maze;
+    // any other move costs a new search.
+    fn retarget(&mut self, env: &Env, target: Cell) {
+        let end = self.route.back().copied().unwrap_or(self.position);
+        if end == target {
+            return;
+        }
+        if target == self.position {
+            self.route.clear();
+        } else if let Some(pos) = self.route.iter().position(|&c| c == target) {
+            self.route.truncate(pos + 1);
+        } else if env.neighbors(end).any(|n| n == target) {
+            self.route.push_back(target);
+        } else {
+            self.searches += 1;
+            let route = Solver::AStar.solve(env, self.position, target).map_or(vec![], |s| s.path);
+            self.route = route.into_iter().skip(1).collect();
+        }
+    }
+
+    // Moves one cell towards `target`, staying put if it cannot be reached
+    pub fn step(&mut self, env: &Env, target: Cell) -> Cell {
+        self.retarget(env, target);
+        if let Some(next) = self.route.pop_front() {
+            self.position = next;
+        }
+        self.position
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Running,
+    // The player reached the exit
+    Escaped,
+    // The minotaur reached the player
+    Caught,
+}
+
+pub struct Pursuit {
+    pub player: Cell,
+    pub exit: Cell,
+    pub minotaur: Chaser,
+    // Minotaur steps per turn
+    pub speed: usize,
+    pub turns: usize,
+    pub outcome: Outcome,
+    // Every cell the minotaur stepped into, in order
+    pub trail: Vec<Cell>,
+}
+
+impl Pursuit {
+    // The player starts in the top-left cell with the exit in the bottom-right one, and the
+    // minotaur starts in the middle of the maze
+    pub fn new(env: &Env, speed: usize) -> Self {
+        Pursuit {
+            player: Cell { row: 0, col: 0 },
+            exit: Cell { row: env.height - 1, col: env.width - 1 },
+            minotaur: Chaser::new(Cell { row: env.height / 2, col: env.width / 2 }),
+            speed,
+            turns: 0,
+            outcome: Outcome::Running,
+            trail: vec![],
+        }
+    }
+
+    // Plays one turn: the player moves to the open neighbour `to`, then the minotaur takes its
+    // steps. Moves into walls, and moves once the chase is over, are ignored.
+    pub fn turn(&mut self, env: &Env, to: Cell) -> Outcome {
+        if self.outcome != Outcome::Running || !env.neighbors(self.player).any(|n| n == to) {
+            return self.outcome;
+        }
+        self.player = to;
+        self.turns += 1;
+        if self.player == self.minotaur.position {
+            self.outcome = Outcome::Caught;
+        } else if self.player == self.exit {
+            self.outcome = Outcome::Escaped;
+        } else {
+            for _ in 0..self.speed {
+                let from = self.minotaur.position;
+                if self.minotaur.step(env, self.player) != from {
+                    self.trail.push(self.minotaur.position);
+                }
+                if self.minotaur.position == self.player {
+                    self.outcome = Outcome::Caught;
+                    break;
+                }
+            }
+        }
+        self.outcome
+    }
+}
+
+// A scripted chase where the player walks the shortest path to the exit whatever the minotaur
+// does, as animation steps tinting the cells each of them moves into
+pub fn scripted(env: &Env, speed: usize) -> (Vec<Step>, Pursuit) {
+    let mut pursuit = Pursuit::new(env, speed);
+    let route = Solver::Bfs.solve(env, pursuit.player, pursuit.exit).map_or(vec![], |s| s.path);
+    let mut steps = vec![Step::Visit(pursuit.player, PLAYER_TINT), Step::Visit(pursuit.minotaur.position, MINOTAUR_TINT)];
+    for &cell in route.iter().skip(1) {
+        let moved = pursuit.trail.len();
+        let outcome = pursuit.turn(env, cell);
+        steps.push(Step::Visit(cell, PLAYER_TINT));
+        steps.extend(pursuit.trail[moved..].iter().map(|&c| Step::Visit(c, MINOTAUR_TINT)));
+        if outcome != Outcome::Running {
+            break;
+        }
+    }
+    (steps, pursuit)
+}
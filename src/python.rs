@@ -0,0 +1,117 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::metadata::Metadata;
+use crate::render::raster::{ImageFormat, Overlays, RasterRenderer, Style};
+use crate::render::Theme;
+use crate::solve::Solver;
+use crate::{gen_maze, gen_maze_with, png, Cell, Env};
+
+// A generated maze, as seen from Python
+#[pyclass(name = "Maze")]
+pub struct PyMaze {
+    env: Env,
+}
+
+#[pymethods]
+impl PyMaze {
+    #[getter]
+    fn width(&self) -> usize {
+        self.env.width
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.env.height
+    }
+
+    // One byte per cell in row-major order holding the `OPEN_*` bits of its open sides
+    fn cells(&self) -> Vec<u8> {
+        self.env.cells().map(|cell| self.env.open_sides(cell)).collect()
+    }
+
+    fn has_passage(&self, a: (usize, usize), b: (usize, usize)) -> PyResult<bool> {
+        let (a, b) = (self.cell(a)?, self.cell(b)?);
+        if a.row.abs_diff(b.row) + a.col.abs_diff(b.col) != 1 {
+            return Err(PyValueError::new_err("cells are not neighbours"));
+        }
+        Ok(self.env.has_passage(a, b))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Maze(width={}, height={})", self.env.width, self.env.height)
+    }
+}
+
+impl PyMaze {
+    fn cell(&self, (row, col): (usize, usize)) -> PyResult<Cell> {
+        if row >= self.env.height || col >= self.env.width {
+            return Err(PyValueError::new_err(format!("cell ({}, {}) is outside the maze", row, col)));
+        }
+        Ok(Cell { row, col })
+    }
+}
+
+// generate(width, height, seed=None) -> Maze; the same seed gives the same maze as `maze --seed`
+#[pyfunction]
+#[pyo3(signature = (width, height, seed=None))]
+fn generate(width: usize, height: usize, seed: Option<u64>) -> PyResult<PyMaze> {
+    let mut env = Env::try_new(width, height).map_err(|err| PyValueError::new_err(err.to_string()))?;
+    match seed {
+        Some(seed) => gen_maze_with(&mut env, &mut StdRng::seed_from_u64(seed)),
+        None => gen_maze(&mut env),
+    }
+    Ok(PyMaze { env })
+}
+
+// solve(maze, solver="bfs", start=None, goal=None) -> [(row, col), ...] from the top-left to the
+// bottom-right cell unless `start`/`goal` say otherwise
+#[pyfunction]
+#[pyo3(signature = (maze, solver="bfs", start=None, goal=None))]
+fn solve(maze: &PyMaze, solver: &str, start: Option<(usize, usize)>, goal: Option<(usize, usize)>) -> PyResult<Vec<(usize, usize)>> {
+    let solver = Solver::from_name(solver)
+        .ok_or_else(|| PyValueError::new_err("solver must be `bfs`, `a*`, `wall-follower` or `tremaux`"))?;
+    let start = maze.cell(start.unwrap_or((0, 0)))?;
+    let goal = maze.cell(goal.unwrap_or((maze.env.height - 1, maze.env.width - 1)))?;
+    let solution = solver.solve(&maze.env, start, goal);
+    Ok(solution.map_or(vec![], |s| s.path.iter().map(|c| (c.row, c.col)).collect()))
+}
+
+// render_png_bytes(maze, scale=1, style="blocky", solve=False) -> bytes of a PNG image
+#[pyfunction]
+#[pyo3(signature = (maze, scale=1, style="blocky", solve=false))]
+fn render_png_bytes<'py>(py: Python<'py>, maze: &PyMaze, scale: usize, style: &str, solve: bool) -> PyResult<Bound<'py, PyBytes>> {
+    let style = Style::from_name(style)
+        .ok_or_else(|| PyValueError::new_err("style must be `blocky`, `rounded` or `isometric`"))?;
+    if solve && style == Style::Isometric {
+        return Err(PyValueError::new_err("the solution overlay only works with top-down styles"));
+    }
+    let renderer = RasterRenderer {
+        format: ImageFormat::Png,
+        style,
+        scale: scale.max(1),
+        supersample: 1,
+        overlays: Overlays { solve, ..Overlays::default() },
+        metadata: Metadata::default(),
+    };
+    let canvas = renderer.rasterize(&maze.env, &Theme::default());
+    let mut bytes = vec![];
+    png::write_png(&mut bytes, &canvas)?;
+    Ok(PyBytes::new(py, &bytes))
+}
+
+#[pymodule]
+fn maze(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyMaze>()?;
+    m.add_function(wrap_pyfunction!(generate, m)?)?;
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(render_png_bytes, m)?)?;
+    m.add("OPEN_NORTH", crate::OPEN_NORTH)?;
+    m.add("OPEN_EAST", crate::OPEN_EAST)?;
+    m.add("OPEN_SOUTH", crate::OPEN_SOUTH)?;
+    m.add("OPEN_WEST", crate::OPEN_WEST)?;
+    Ok(())
+}
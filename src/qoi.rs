@@ -0,0 +1,146 @@
+use std::io::{self, Write};
+
+use crate::draw::Canvas;
+use crate::error::MazeError;
+
+// "Quite OK Image" format: runs of the previous pixel, references into a 64-entry table of recently
+// seen pixels and small channel differences, which shrink flat-colored mazes to a few bytes per row.
+// See https://qoiformat.org/qoi-specification.pdf
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xC0;
+const OP_RGB: u8 = 0xFE;
+const OP_RGBA: u8 = 0xFF;
+// Longest run a single `OP_RUN` can encode
+const MAX_RUN: u8 = 62;
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+// Largest possible size in bytes of `write_qoi`'s output for a `width`x`height` canvas, with every
+// pixel stored as a full `OP_RGBA`
+pub fn encoded_size(width: usize, height: usize) -> usize {
+    14 + (width * height * 5) + END_MARKER.len()
+}
+
+// Pixels are in 0xRRGGBBAA format and are written as RGBA with sRGB color space
+pub fn write_qoi(out: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(1024);
+    bytes.extend_from_slice(b"qoif");
+    bytes.extend_from_slice(&(canvas.width() as u32).to_be_bytes());
+    bytes.extend_from_slice(&(canvas.height() as u32).to_be_bytes());
+    // Four channels, sRGB with linear alpha
+    bytes.extend_from_slice(&[4, 0]);
+
+    let mut seen = [[0u8; 4]; 64];
+    let mut prev = [0, 0, 0, 255];
+    let mut run = 0;
+    for pixel in canvas.pixels() {
+        let px = pixel.to_be_bytes();
+        if px == prev {
+            run += 1;
+            if run == MAX_RUN {
+                bytes.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+            continue;
+        }
+        if run > 0 {
+            bytes.push(OP_RUN | (run - 1));
+            run = 0;
+        }
+
+        let [r, g, b, a] = px;
+        let hash = ((r as usize * 3) + (g as usize * 5) + (b as usize * 7) + (a as usize * 11)) % 64;
+        if seen[hash] == px {
+            bytes.push(OP_INDEX | hash as u8);
+        } else if a == prev[3] {
+            let (dr, dg, db) = (r.wrapping_sub(prev[0]) as i8, g.wrapping_sub(prev[1]) as i8, b.wrapping_sub(prev[2]) as i8);
+            let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+            if (-2..=1).contains(&dr) && (-2..=1).contains(&dg) && (-2..=1).contains(&db) {
+                bytes.push(OP_DIFF | (((dr + 2) as u8) << 4) | (((dg + 2) as u8) << 2) | (db + 2) as u8);
+            } else if (-32..=31).contains(&dg) && (-8..=7).contains(&dr_dg) && (-8..=7).contains(&db_dg) {
+                bytes.extend_from_slice(&[OP_LUMA | (dg + 32) as u8, (((dr_dg + 8) as u8) << 4) | (db_dg + 8) as u8]);
+            } else {
+                bytes.extend_from_slice(&[OP_RGB, r, g, b]);
+            }
+        } else {
+            bytes.extend_from_slice(&[OP_RGBA, r, g, b, a]);
+        }
+        seen[hash] = px;
+        prev = px;
+    }
+    if run > 0 {
+        bytes.push(OP_RUN | (run - 1));
+    }
+    bytes.extend_from_slice(&END_MARKER);
+    out.write_all(&bytes)?;
+    out.flush()
+}
+
+// Decodes a QOI image into 0xRRGGBBAA pixels. Three-channel images are read as opaque.
+pub fn read_qoi(bytes: &[u8]) -> Result<Canvas, MazeError> {
+    let invalid = |message: &str| MazeError::InvalidData(format!("unsupported or broken QOI: {}", message));
+    if bytes.len() < 14 || !bytes.starts_with(b"qoif") {
+        return Err(invalid("missing header"));
+    }
+    let u32_at = |i: usize| u32::from_be_bytes(bytes[i..i + 4].try_into().expect("four bytes")) as usize;
+    let (width, height) = (u32_at(4), u32_at(8));
+    // A byte codes at most `MAX_RUN` pixels, which bounds the size a well-formed file can claim
+    if width == 0 || height == 0 || width.saturating_mul(height) > bytes.len().saturating_mul(MAX_RUN as usize) {
+        return Err(invalid("unsupported dimensions"));
+    }
+
+    let mut canvas = Canvas::new(width, height, 0);
+    let mut seen = [[0u8; 4]; 64];
+    let mut px = [0, 0, 0, 255];
+    let (mut i, mut run) = (14, 0);
+    for y in 0..height {
+        for x in 0..width {
+            if run > 0 {
+                run -= 1;
+            } else {
+                let byte = *bytes.get(i).ok_or_else(|| invalid("pixel data is too short"))?;
+                let operand = |n: usize| bytes.get(i + 1..i + 1 + n).ok_or_else(|| invalid("pixel data is too short"));
+                match byte {
+                    OP_RGB => {
+                        px[..3].copy_from_slice(operand(3)?);
+                        i += 4;
+                    },
+                    OP_RGBA => {
+                        px.copy_from_slice(operand(4)?);
+                        i += 5;
+                    },
+                    _ => match byte & 0xC0 {
+                        OP_INDEX => {
+                            px = seen[byte as usize];
+                            i += 1;
+                        },
+                        OP_DIFF => {
+                            px[0] = px[0].wrapping_add((byte >> 4) & 0x03).wrapping_sub(2);
+                            px[1] = px[1].wrapping_add((byte >> 2) & 0x03).wrapping_sub(2);
+                            px[2] = px[2].wrapping_add(byte & 0x03).wrapping_sub(2);
+                            i += 1;
+                        },
+                        OP_LUMA => {
+                            let next = operand(1)?[0];
+                            let dg = (byte & 0x3F).wrapping_sub(32);
+                            px[0] = px[0].wrapping_add(dg).wrapping_add(next >> 4).wrapping_sub(8);
+                            px[1] = px[1].wrapping_add(dg);
+                            px[2] = px[2].wrapping_add(dg).wrapping_add(next & 0x0F).wrapping_sub(8);
+                            i += 2;
+                        },
+                        _ => {
+                            run = byte & 0x3F;
+                            i += 1;
+                        },
+                    },
+                }
+                let [r, g, b, a] = px;
+                seen[((r as usize * 3) + (g as usize * 5) + (b as usize * 7) + (a as usize * 11)) % 64] = px;
+            }
+            canvas.set(x, y, u32::from_be_bytes(px));
+        }
+    }
+    Ok(canvas)
+}
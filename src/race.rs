@@ -0,0 +1,115 @@
+// Races several solvers, and random walkers, through the same maze one step at a time: on every
+// tick each racer moves one step along its explored trace, so the racer that explores the fewest
+// cells before reaching the goal wins.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::animation::Step;
+use crate::solve::{Solution, Solver};
+use crate::{batch, Cell, Env};
+
+// Trail tints of the racers, in order, repeating when there are more racers
+const COLORS: [u32; 6] = [0xE0404070, 0x40A0F070, 0xF0C03070, 0x60C06070, 0xC060E070, 0x40E0E070];
+// Random walkers give up after this many steps per cell
+const WALK_STEPS_PER_CELL: usize = 100;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Racer {
+    Solver(Solver),
+    // Steps to a random open neighbour every tick
+    Walker,
+}
+
+impl Racer {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "walker" => Some(Racer::Walker),
+            _ => Solver::from_name(name).map(Racer::Solver),
+        }
+    }
+}
+
+pub struct Lane {
+    pub name: String,
+    pub color: u32,
+    // Cells in the order the racer reached them
+    pub explored: Vec<Cell>,
+    // Path from start to goal, or `None` if the racer never got there
+    pub path: Option<Vec<Cell>>,
+}
+
+impl Lane {
+    // Ticks the racer needed to reach the goal
+    pub fn finish(&self) -> Option<usize> {
+        self.path.as_ref().map(|_| self.explored.len())
+    }
+}
+
+// Random walk from `start` until it reaches `goal` or runs out of steps. The path is the walk
+// with every loop erased.
+fn random_walk(env: &Env, start: Cell, goal: Cell, rng: &mut impl Rng) -> Solution {
+    let passages = env.passages();
+    let (mut ind, mut explored, mut path) = (env.ind(start), vec![start], vec![env.ind(start)]);
+    for _ in 0..(WALK_STEPS_PER_CELL * env.cell_count()) {
+        if ind == env.ind(goal) || passages[ind].is_empty() {
+            break;
+        }
+        ind = passages[ind][rng.gen_range(0..passages[ind].len())];
+        explored.push(env.cell(ind));
+        if let Some(pos) = path.iter().position(|&i| i == ind) {
+            path.truncate(pos + 1);
+        } else {
+            path.push(ind);
+        }
+    }
+    let path = if ind == env.ind(goal) { path.into_iter().map(|i| env.cell(i)).collect() } else { vec![] };
+    Solution { path, explored }
+}
+
+// Runs every racer from the top-left to the bottom-right cell. Walker `i` is seeded with the
+// `i`th batch seed of `seed`, so a race can be rerun.
+pub fn run(env: &Env, racers: &[Racer], seed: u64) -> Vec<Lane> {
+    let (start, goal) = (Cell { row: 0, col: 0 }, Cell { row: env.height - 1, col: env.width - 1 });
+    let walkers = racers.iter().filter(|r| **r == Racer::Walker).count();
+    let mut walker = 0;
+    racers
+        .iter()
+        .enumerate()
+        .map(|(i, racer)| {
+            let (name, solution) = match racer {
+                Racer::Solver(solver) => (solver.name().to_string(), solver.solve(env, start, goal)),
+                Racer::Walker => {
+                    walker += 1;
+                    let mut rng = StdRng::seed_from_u64(batch::maze_seed(seed, walker - 1));
+                    let name = if walkers > 1 { format!("walker {}", walker) } else { String::from("walker") };
+                    (name, Some(random_walk(env, start, goal, &mut rng)))
+                },
+            };
+            let (explored, path) = match solution {
+                Some(solution) => (solution.explored, Some(solution.path).filter(|p| !p.is_empty())),
+                None => (vec![], None),
+            };
+            Lane { name, color: COLORS[i % COLORS.len()], explored, path }
+        })
+        .collect()
+}
+
+// Animation of the race: every tick moves each racer still running one cell, and each racer's
+// path is drawn in its color once it finishes
+pub fn steps(lanes: &[Lane]) -> Vec<Step> {
+    let ticks = lanes.iter().map(|l| l.explored.len()).max().unwrap_or(0);
+    let mut steps = vec![];
+    for tick in 0..ticks {
+        for lane in lanes {
+            if let Some(&cell) = lane.explored.get(tick) {
+                steps.push(Step::Visit(cell, lane.color));
+            }
+            if lane.finish() == Some(tick + 1) {
+                let path = lane.path.as_deref().unwrap_or_default();
+                steps.extend(path.windows(2).map(|pair| Step::Path(pair[0], pair[1], lane.color | 0xFF)));
+            }
+        }
+    }
+    steps
+}
@@ -0,0 +1,75 @@
+// Mazes at two scales at once. A coarse maze is carved first and then redrawn with every cell
+// becoming a `factor` x `factor` block of fine cells. Most blocks stay one open room, so they read
+// as the coarse cell they were, and coarse passages between two rooms open their whole shared
+// side. A share of the blocks is refined into a small maze of its own instead, and a coarse
+// passage into one of those opens a single fine cell, at the same randomly picked offset along
+// both sides of the boundary. Refined blocks are perfect mazes; rooms are loops.
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::{gen_maze_with, Cell, Env};
+
+// Sub-cells per side of a refined block when none is asked for
+pub const DEFAULT_FACTOR: usize = 3;
+
+// `env` redrawn at `factor` times the resolution with about `share` of its cells, picked at
+// random, refined into sub-mazes; see the top of the file. The visit order carries over block by
+// block, with refined blocks in the order their own mazes were carved.
+pub fn refine(env: &Env, share: f32, factor: usize, rng: &mut impl Rng) -> Env {
+    let mut cells: Vec<Cell> = env.cells().filter(|&cell| !env.is_excluded(cell)).collect();
+    cells.shuffle(rng);
+    let mut refined = vec![false; env.cell_count()];
+    for cell in cells.iter().take((cells.len() as f32 * share.clamp(0.0, 1.0)).round() as usize) {
+        refined[env.ind(*cell)] = true;
+    }
+
+    let mut out = Env::new(factor * env.width, factor * env.height);
+    let fine = |cell: Cell, row: usize, col: usize| Cell { row: (factor * cell.row) + row, col: (factor * cell.col) + col };
+    let mut visits = vec![vec![]; env.cell_count()];
+    for cell in env.cells() {
+        let block: Vec<(usize, usize)> = (0..factor).flat_map(|row| (0..factor).map(move |col| (row, col))).collect();
+        if env.is_excluded(cell) {
+            for &(row, col) in block.iter() {
+                out.exclude(fine(cell, row, col));
+            }
+        } else if refined[env.ind(cell)] {
+            let mut sub = Env::new(factor, factor);
+            gen_maze_with(&mut sub, rng);
+            for wall in sub.walls() {
+                out.carve(fine(cell, wall.start.row, wall.start.col), fine(cell, wall.target.row, wall.target.col));
+            }
+            visits[env.ind(cell)] = sub.visit_order.iter().map(|&ind| sub.cell(ind as usize)).map(|c| fine(cell, c.row, c.col)).collect();
+        } else {
+            for &(row, col) in block.iter() {
+                if col + 1 < factor {
+                    out.carve(fine(cell, row, col), fine(cell, row, col + 1));
+                }
+                if row + 1 < factor {
+                    out.carve(fine(cell, row, col), fine(cell, row + 1, col));
+                }
+            }
+            visits[env.ind(cell)] = block.iter().map(|&(row, col)| fine(cell, row, col)).collect();
+        }
+    }
+
+    // Coarse passages: the whole side between two rooms, one matching offset otherwise
+    for wall in env.walls() {
+        let (a, b) = (wall.start, wall.target);
+        let offsets: Vec<usize> = if refined[env.ind(a)] || refined[env.ind(b)] { vec![rng.gen_range(0..factor)] } else { (0..factor).collect() };
+        for offset in offsets {
+            if a.row == b.row {
+                out.carve(fine(a, offset, factor - 1), fine(b, offset, 0));
+            } else {
+                out.carve(fine(a, factor - 1, offset), fine(b, 0, offset));
+            }
+        }
+    }
+
+    for &ind in env.visit_order.iter() {
+        for &sub in visits[ind as usize].iter() {
+            out.visit_order.push(out.ind(sub) as u32);
+        }
+    }
+    out
+}
@@ -0,0 +1,49 @@
+use std::io::Write;
+
+use crate::error::MazeError;
+use crate::Env;
+
+use super::{Renderer, Theme};
+
+// Dense text output: every block of the block map becomes one braille dot, so a character holds
+// 2x4 blocks and a 200x200 maze fits in 201x101 characters
+pub struct BrailleRenderer {
+    // Wrap the dots in 24-bit ANSI color escapes using the theme's wall color
+    pub color: bool,
+}
+
+// Bit of the dot at column `x`, row `y` of a braille cell, which Unicode numbers 1-8 down the left
+// column then the right, with the bottom row added last
+const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+impl Renderer for BrailleRenderer {
+    fn render(&self, env: &Env, theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        let blocks = env.blocks();
+        let (rows, cols) = env.block_dims();
+        let (start, end) = if self.color {
+            let c = theme.solid_color;
+            (format!("\x1b[38;2;{};{};{}m", c >> 24, (c >> 16) & 0xFF, (c >> 8) & 0xFF), String::from("\x1b[0m"))
+        } else {
+            (String::new(), String::new())
+        };
+
+        for top in (0..rows).step_by(4) {
+            let mut line = start.clone();
+            for left in (0..cols).step_by(2) {
+                let mut bits = 0;
+                for (dy, row) in DOTS.iter().enumerate() {
+                    for (dx, bit) in row.iter().enumerate() {
+                        let (i, j) = (top + dy, left + dx);
+                        if i < rows && j < cols && blocks[i][j] {
+                            bits |= bit;
+                        }
+                    }
+                }
+                line.push(char::from_u32(0x2800 + bits).expect("braille patterns are valid chars"));
+            }
+            line.push_str(&end);
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use crate::draw::{fill_rect, Canvas};
+use crate::error::MazeError;
+use crate::{Env, Wall};
+
+use super::raster::draw_maze;
+use super::{cell_center, image_size, Theme};
+
+// Passages only the first maze has are drawn in this color, passages only the second has in the other
+pub const ONLY_A_COLOR: u32 = 0xE04040FF;
+pub const ONLY_B_COLOR: u32 = 0x40A0F0FF;
+// Width of the bars marking differences, in logical pixels
+const MARK_THICKNESS: usize = 4;
+
+// Passages that differ between two mazes of the same size
+pub struct MazeDiff {
+    pub only_a: Vec<Wall>,
+    pub only_b: Vec<Wall>,
+    // Passages open in both
+    pub common: usize,
+}
+
+impl MazeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_a.is_empty() && self.only_b.is_empty()
+    }
+}
+
+// Only mazes of the same size can be compared
+pub fn diff(a: &Env, b: &Env) -> Result<MazeDiff, MazeError> {
+    if (a.width, a.height) != (b.width, b.height) {
+        return Err(MazeError::SizeMismatch { first: (a.width, a.height), second: (b.width, b.height) });
+    }
+    let only_a: Vec<Wall> = a.walls().filter(|w| !b.has_passage(w.start, w.target)).collect();
+    let only_b = b.walls().filter(|w| !a.has_passage(w.start, w.target)).collect();
+    let common = a.walls().count() - only_a.len();
+    Ok(MazeDiff { only_a, only_b, common })
+}
+
+// Blocky image of the passages both mazes share, with a bar across every wall slot that only
+// one of them has open, colored `ONLY_A_COLOR` or `ONLY_B_COLOR`. Drawn at one pixel per
+// logical pixel; upscale the result for bigger images.
+pub fn draw_diff(a: &Env, b: &Env, theme: &Theme) -> Result<Canvas, MazeError> {
+    let changes = diff(a, b)?;
+    let mut shared = Env::new(a.width, a.height);
+    for wall in a.walls().filter(|w| b.has_passage(w.start, w.target)) {
+        shared.carve(wall.start, wall.target);
+    }
+    let mut canvas = Canvas::new(image_size(a.width), image_size(a.height), theme.open_color);
+    draw_maze(&shared, &mut canvas, theme);
+
+    let half = (MARK_THICKNESS / 2) as f32;
+    let marks = changes.only_a.iter().map(|w| (w, ONLY_A_COLOR)).chain(changes.only_b.iter().map(|w| (w, ONLY_B_COLOR)));
+    for (wall, color) in marks {
+        let (sx, sy) = cell_center(&wall.start);
+        let (tx, ty) = cell_center(&wall.target);
+        let (x, y) = ((sx.min(tx) - half) as usize, (sy.min(ty) - half) as usize);
+        let (w, h) = ((tx - sx).abs() as usize + MARK_THICKNESS, (ty - sy).abs() as usize + MARK_THICKNESS);
+        fill_rect(&mut canvas, x, y, w, h, color);
+    }
+    Ok(canvas)
+}
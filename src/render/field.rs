@@ -0,0 +1,58 @@
+use std::io::Write;
+
+use crate::analysis::distances;
+use crate::error::MazeError;
+use crate::{Cell, Env};
+
+use super::{Renderer, Theme};
+
+// Written for cells the top-left cell cannot reach
+const UNREACHABLE: i32 = -1;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum FieldFormat {
+    // One line per maze row, comma-separated
+    Csv,
+    // NumPy array of `int32` with shape (height, width)
+    Npy,
+}
+
+// The distance field of the maze: how many steps every cell is from the top-left one, laid out
+// as a grid of cells, for analysis outside this crate
+pub struct DistanceFieldRenderer {
+    pub format: FieldFormat,
+}
+
+// NPY version 1.0 header, padded with spaces so the data starts on a 64-byte boundary
+pub fn npy_header(width: usize, height: usize) -> Vec<u8> {
+    let mut dict = format!("{{'descr': '<i4', 'fortran_order': False, 'shape': ({}, {}), }}", height, width);
+    // Magic, version and header length take 10 bytes, and the header ends with a newline
+    while (10 + dict.len() + 1) % 64 != 0 {
+        dict.push(' ');
+    }
+    dict.push('\n');
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header
+}
+
+impl Renderer for DistanceFieldRenderer {
+    fn render(&self, env: &Env, _theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        let field: Vec<i32> = distances(env, Cell { row: 0, col: 0 }).into_iter().map(|d| d.map_or(UNREACHABLE, |d| d as i32)).collect();
+        match self.format {
+            FieldFormat::Csv => {
+                for row in field.chunks(env.width) {
+                    let line: Vec<String> = row.iter().map(|d| d.to_string()).collect();
+                    writeln!(out, "{}", line.join(","))?;
+                }
+            },
+            FieldFormat::Npy => {
+                let mut bytes = npy_header(env.width, env.height);
+                bytes.extend(field.iter().flat_map(|d| d.to_le_bytes()));
+                out.write_all(&bytes)?;
+            },
+        }
+        Ok(())
+    }
+}
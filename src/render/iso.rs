@@ -0,0 +1,60 @@
+use crate::draw::{fill_convex_polygon, shade, Canvas};
+use crate::Env;
+
+use super::{Theme, OPEN_PATH_SIZE};
+
+// Half the width of a block's diamond at `unit` pixels per logical pixel; the diamond is twice
+// as wide as it is tall
+fn tile_size(unit: usize) -> usize {
+    (OPEN_PATH_SIZE as usize / 2) * unit
+}
+
+// Canvas size of an isometric render of a maze with a `rows`x`cols` block map
+pub fn canvas_size(rows: usize, cols: usize, unit: usize) -> (usize, usize) {
+    let tile = tile_size(unit);
+    // The footprint is a diamond: columns run down-right and rows run down-left
+    ((rows + cols) * tile, ((rows + cols) * tile / 2) + (2 * tile))
+}
+
+// Renders the maze in an isometric projection with walls extruded into shaded blocks
+pub fn draw_maze_isometric(env: &Env, unit: usize, theme: &Theme) -> Canvas {
+    let blocks = env.blocks();
+    let tile = tile_size(unit);
+    let wall_height = 2 * tile;
+    let (rows, cols) = env.block_dims();
+    let (width, height) = canvas_size(rows, cols, unit);
+    let mut canvas = Canvas::new(width, height, theme.open_color);
+
+    let (t, h) = (tile as f32, wall_height as f32);
+    let floor = shade(theme.solid_color, 0.3);
+    let (top, left, right) = (theme.solid_color, shade(theme.solid_color, 0.75), shade(theme.solid_color, 0.5));
+
+    // Painter's algorithm: blocks further from the viewer (smaller row + col) are drawn first
+    for depth in 0..(rows + cols - 1) {
+        for (r, row) in blocks.iter().enumerate() {
+            if depth < r || depth - r >= cols {
+                continue;
+            }
+            let c = depth - r;
+            // Top vertex of the block's footprint diamond
+            let x = (c as f32 - r as f32) * t + (rows as f32 * t);
+            let y = (depth as f32 * t / 2.0) + h;
+            let diamond = |lift: f32| [
+                (x, y - lift),
+                (x + t, y + (t / 2.0) - lift),
+                (x, y + t - lift),
+                (x - t, y + (t / 2.0) - lift),
+            ];
+            if !row[c] {
+                fill_convex_polygon(&mut canvas, &diamond(0.0), floor);
+                continue;
+            }
+            let [_, g_right, g_bottom, g_left] = diamond(0.0);
+            let [_, t_right, t_bottom, t_left] = diamond(h);
+            fill_convex_polygon(&mut canvas, &[t_left, t_bottom, g_bottom, g_left], left);
+            fill_convex_polygon(&mut canvas, &[t_bottom, t_right, g_right, g_bottom], right);
+            fill_convex_polygon(&mut canvas, &diamond(h), top);
+        }
+    }
+    canvas
+}
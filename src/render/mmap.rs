@@ -0,0 +1,66 @@
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use memmap2::MmapMut;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::error::MazeError;
+use crate::metadata::Metadata;
+use crate::ppm;
+use crate::progress::Progress;
+use crate::Env;
+
+use super::stream::RowRaster;
+use super::Theme;
+
+// Rows rasterized per band; bands run in parallel when the `rayon` feature is enabled
+const BAND_ROWS: usize = 64;
+// Bands filled between two progress updates
+const BANDS_PER_UPDATE: usize = 16;
+
+// Blocky PPM output rasterized directly into a memory-mapped file. Nothing the size of the image
+// is ever allocated; the OS pages the file in and out as bands are filled.
+pub struct MmapPpmRenderer {
+    // Integer nearest-neighbor upscale factor
+    pub scale: usize,
+    // Written into the header as comment lines
+    pub metadata: Metadata,
+}
+
+impl MmapPpmRenderer {
+    // Needs a real file to map, so this cannot implement `Renderer` (which writes to a stream).
+    // Progress is reported in pixel rows filled; on cancellation the file is left partially filled.
+    pub fn render_to_file(&self, env: &Env, theme: &Theme, path: &Path, progress: &mut dyn Progress) -> Result<(), MazeError> {
+        let raster = RowRaster::new(env, theme, self.scale);
+        let (width, height) = raster.size();
+        let header = ppm::header(width, height, &self.metadata);
+        let row_bytes = width * 3;
+
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+        file.set_len((header.len() + row_bytes * height) as u64)?;
+        // SAFETY: the file was just created and truncated by us; nothing else should map or
+        // resize it while the render runs
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[..header.len()].copy_from_slice(header.as_bytes());
+
+        let band_bytes = (row_bytes * BAND_ROWS).max(1);
+        let body = &mut map[header.len()..];
+        for (group, bands) in body.chunks_mut(band_bytes * BANDS_PER_UPDATE).enumerate() {
+            let first_band = group * BANDS_PER_UPDATE;
+            let band = |(i, rows): (usize, &mut [u8])| {
+                for (j, row) in rows.chunks_exact_mut(row_bytes.max(1)).enumerate() {
+                    raster.fill(((first_band + i) * BAND_ROWS) + j, row);
+                }
+            };
+            #[cfg(feature = "rayon")]
+            bands.par_chunks_mut(band_bytes).enumerate().for_each(band);
+            #[cfg(not(feature = "rayon"))]
+            bands.chunks_mut(band_bytes).enumerate().for_each(band);
+            progress.checkpoint(((first_band + BANDS_PER_UPDATE) * BAND_ROWS).min(height), height)?;
+        }
+        progress.update(height, height);
+        map.flush()?;
+        Ok(())
+    }
+}
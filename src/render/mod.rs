@@ -0,0 +1,81 @@
+use std::io::Write;
+
+use crate::error::MazeError;
+use crate::progress::Progress;
+use crate::{Cell, Env};
+
+pub mod braille;
+pub mod diff;
+pub mod field;
+pub mod iso;
+#[cfg(feature = "native")]
+pub mod mmap;
+pub mod pdf;
+pub mod raster;
+pub mod sheet;
+pub mod stream;
+pub mod svg;
+pub mod text;
+pub mod tiles;
+
+// Color HEX code format: 0xRRGGBBAA
+pub const SOLID_COLOR: u32 = 0x32A852FF;
+pub const OPEN_COLOR: u32 = 0x000000FF;
+// pub const OPEN_COLOR: u32 = 0x2856A1FF;
+pub const TRANSPARENT_COLOR: u32 = 0x00000000;
+
+pub const OPEN_PATH_SIZE: u32 = 10;
+pub const BORDER_THICKNESS: u32 = 1;
+
+// Length in logical pixels of an image side spanning `cells` cells
+pub fn image_size(cells: usize) -> usize {
+    (cells * OPEN_PATH_SIZE as usize) + ((cells + 1) * BORDER_THICKNESS as usize)
+}
+
+// Backend-independent description of how a maze should look
+pub struct Theme {
+    pub solid_color: u32,
+    pub open_color: u32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self { solid_color: SOLID_COLOR, open_color: OPEN_COLOR }
+    }
+}
+
+// `#RRGGBB` or `#RRGGBBAA` (the `#` is optional) as 0xRRGGBBAA; six digits are fully opaque
+pub fn parse_color(text: &str) -> Option<u32> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    match hex.len() {
+        6 => Some((value << 8) | 0xFF),
+        8 => Some(value),
+        _ => None,
+    }
+}
+
+// An output backend: turns a maze and a theme into bytes (an image file, markup, terminal text...)
+pub trait Renderer {
+    fn render(&self, env: &Env, theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError>;
+
+    // Like `render`, reporting progress along the way. Cancelling `progress` stops the render with
+    // `MazeError::Cancelled`, leaving partial output behind. Backends without finer-grained
+    // reporting only check for cancellation before they start.
+    fn render_with_progress(&self, env: &Env, theme: &Theme, out: &mut dyn Write, progress: &mut dyn Progress) -> Result<(), MazeError> {
+        progress.checkpoint(0, 1)?;
+        self.render(env, theme, out)?;
+        progress.update(1, 1);
+        Ok(())
+    }
+}
+
+// Center of a cell's open area, in logical (1x) pixels
+pub fn cell_center(cell: &Cell) -> (f32, f32) {
+    let pitch = (OPEN_PATH_SIZE + BORDER_THICKNESS) as f32;
+    let offset = BORDER_THICKNESS as f32 + (OPEN_PATH_SIZE as f32 / 2.0);
+    ((cell.col as f32 * pitch) + offset, (cell.row as f32 * pitch) + offset)
+}
@@ -0,0 +1,73 @@
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::error::MazeError;
+use crate::pdf::{self, Pdf, PAGE_HEIGHT, PAGE_WIDTH};
+use crate::{Cell, Env};
+
+use super::{Renderer, Theme};
+
+// Blank space around a maze drawn on its own page, in points
+const PAGE_MARGIN: f32 = 40.0;
+const SOLUTION_COLOR: u32 = 0xE04040FF;
+
+// Part of the `w`x`h` point box with its top-left corner at (`x`, `y`) that `draw_maze` fills:
+// the largest box with square cells, centered
+pub fn maze_box(env: &Env, (x, y, w, h): (f32, f32, f32, f32)) -> (f32, f32, f32, f32) {
+    let cell = (w / env.width as f32).min(h / env.height as f32);
+    let (maze_w, maze_h) = (cell * env.width as f32, cell * env.height as f32);
+    (x + ((w - maze_w) / 2.0), y - ((h - maze_h) / 2.0), maze_w, maze_h)
+}
+
+// Content stream drawing `env` as large as fits in the `w`x`h` point box whose top-left corner is
+// at (`x`, `y`) (page coordinates, y up), centered in it. `solution` is drawn on top as a line
+// through the cell centers.
+pub fn draw_maze(env: &Env, theme: &Theme, area: (f32, f32, f32, f32), solution: Option<&[Cell]>) -> String {
+    let (left, top, maze_w, maze_h) = maze_box(env, area);
+    let cell = maze_w / env.width as f32;
+    // Block map corner (`i`, `j`) in page coordinates
+    let point = |i: usize, j: usize| (left + (j as f32 * cell / 2.0), top - (i as f32 * cell / 2.0));
+
+    let mut out = String::new();
+    let _ = writeln!(out, "q {} rg {:.2} {:.2} {:.2} {:.2} re f", pdf::rgb(theme.open_color), left, top - maze_h, maze_w, maze_h);
+    let _ = writeln!(out, "{} RG {:.2} w 2 J", pdf::rgb(theme.solid_color), (cell / 8.0).max(0.5));
+    let (rows, cols) = env.block_dims();
+    for i in 0..rows {
+        for j in 0..cols {
+            // Wall slots run between the posts on either side of them, like in the SVG output
+            let (from, to) = match (i % 2, j % 2) {
+                (0, 1) => ((i, j - 1), (i, j + 1)),
+                (1, 0) => ((i - 1, j), (i + 1, j)),
+                _ => continue,
+            };
+            if env.is_solid_block(i, j) {
+                let ((x1, y1), (x2, y2)) = (point(from.0, from.1), point(to.0, to.1));
+                let _ = writeln!(out, "{:.2} {:.2} m {:.2} {:.2} l", x1, y1, x2, y2);
+            }
+        }
+    }
+    out.push_str("S\n");
+    if let Some(path) = solution {
+        let _ = writeln!(out, "{} RG {:.2} w 1 J 1 j", pdf::rgb(SOLUTION_COLOR), (cell / 3.0).max(0.5));
+        for (n, c) in path.iter().enumerate() {
+            let (px, py) = point((2 * c.row) + 1, (2 * c.col) + 1);
+            let _ = writeln!(out, "{:.2} {:.2} {}", px, py, if n == 0 { "m" } else { "l" });
+        }
+        out.push_str("S\n");
+    }
+    out.push_str("Q\n");
+    out
+}
+
+// Single-page PDF with the maze centered on an A4 page
+pub struct PdfRenderer;
+
+impl Renderer for PdfRenderer {
+    fn render(&self, env: &Env, theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        let mut doc = Pdf::new();
+        let area = (PAGE_MARGIN, PAGE_HEIGHT - PAGE_MARGIN, PAGE_WIDTH - (2.0 * PAGE_MARGIN), PAGE_HEIGHT - (2.0 * PAGE_MARGIN));
+        doc.add_page(draw_maze(env, theme, area, None));
+        doc.write(out)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,530 @@
+use std::io::{self, Write};
+
+use crate::draw::{blend, draw_line, draw_polyline, fill_circle, fill_rect, fill_rounded_rect, lerp_color, Canvas};
+use crate::error::MazeError;
+use crate::metadata::Metadata;
+use crate::openings::Opening;
+use crate::solve::Solver;
+use crate::stitch::Edge;
+use crate::progress::Progress;
+use crate::analysis::Bridge;
+use crate::{analysis, bmp, font, iterm2, kitty, png, ppm, qoi, sixel, tga, Cell, Env, WallKind};
+
+use super::{cell_center, image_size, iso, Renderer, Theme, BORDER_THICKNESS, OPEN_PATH_SIZE};
+
+const VISIT_FIRST_COLOR: u32 = 0x2856A1FF;
+const VISIT_LAST_COLOR: u32 = 0xF2D13AFF;
+const LABEL_TEXT_COLOR: u32 = 0xFFFFFFFF;
+const LABEL_BACKGROUND_COLOR: u32 = 0x000000C0;
+const SOLUTION_COLOR: u32 = 0xE04040FF;
+const SOLUTION_START_COLOR: u32 = 0x40C0F0FF;
+const SOLUTION_GOAL_COLOR: u32 = 0xF04090FF;
+const LONGEST_PATH_COLOR: u32 = 0xF0A030FF;
+const CHOKEPOINT_COLOR: u32 = 0xF0E040FF;
+// Far enough apart in lightness that the bands stay apart in grayscale print
+const BAND_NEAR_COLOR: u32 = 0xF4E8C8FF;
+const BAND_FAR_COLOR: u32 = 0x2E2550FF;
+// Width of the solution path, in logical pixels
+const SOLUTION_THICKNESS: usize = 4;
+// Explored-cell tint and path color for each solver in `Solver::ALL`
+const COMPARE_COLORS: [u32; 4] = [0xE0404060, 0x40A0F060, 0xF0C03060, 0x60C06060];
+const DEBUG_TREE_COLOR: u32 = 0xD03030FF;
+const DEBUG_TEXT_COLOR: u32 = 0xF0F0F0FF;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Qoi,
+    Bmp,
+    // Targa, optionally run-length encoded
+    Tga { rle: bool },
+    // Terminal graphics, for display rather than saving
+    Sixel,
+    Kitty,
+    Iterm2,
+}
+
+impl ImageFormat {
+    pub fn write(&self, out: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+        self.write_with(out, canvas, &Metadata::default())
+    }
+
+    pub fn write_with(&self, out: &mut dyn Write, canvas: &Canvas, metadata: &Metadata) -> io::Result<()> {
+        match self {
+            ImageFormat::Ppm => ppm::write_ppm_with(out, canvas, metadata),
+            ImageFormat::Png => png::write_png_with(out, canvas, metadata),
+            // QOI, BMP and TGA have no place for metadata
+            ImageFormat::Qoi => qoi::write_qoi(out, canvas),
+            ImageFormat::Bmp => bmp::write_bmp(out, canvas),
+            ImageFormat::Tga { rle } => tga::write_tga(out, canvas, *rle),
+            // Terminals have nowhere to keep metadata
+            ImageFormat::Sixel => sixel::write_sixel(out, canvas),
+            ImageFormat::Kitty => kitty::write_kitty(out, canvas),
+            ImageFormat::Iterm2 => iterm2::write_iterm2(out, canvas),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Style {
+    // Walls drawn as axis-aligned rectangles
+    Blocky,
+    // Corridors drawn as smooth round-capped strokes
+    Rounded,
+    // Walls extruded into shaded blocks seen from an isometric viewpoint
+    Isometric,
+}
+
+impl Style {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "blocky" => Style::Blocky,
+            "rounded" => Style::Rounded,
+            "isometric" => Style::Isometric,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+// Draws walls as axis-aligned rectangles at one pixel per logical pixel
+pub(crate) fn draw_maze(env: &Env, canvas: &mut Canvas, theme: &Theme) {
+    let (open, border) = (OPEN_PATH_SIZE as usize, BORDER_THICKNESS as usize);
+    let pitch = open + border;
+
+    for r in 0..env.height {
+        for c in 0..=env.width {
+            fill_rect(canvas, c * pitch, r * pitch, border, open + (2 * border), theme.solid_color);
+        }
+    }
+
+    for r in 0..=env.height {
+        for c in 0..env.width {
+            fill_rect(canvas, c * pitch, r * pitch, open + (2 * border), border, theme.solid_color);
+        }
+    }
+
+    for wall in env.walls() {
+        let (x, y) = (wall.target.col * pitch, wall.target.row * pitch);
+        match wall.kind {
+            WallKind::Vertical => fill_rect(canvas, x, y + border, border, open, theme.open_color),
+            WallKind::Horizontal => fill_rect(canvas, x + border, y, open, border, theme.open_color),
+        }
+    }
+
+    // Posts with no wall left to hold up, inside rooms and wide corridors
+    for r in 1..env.height {
+        for c in 1..env.width {
+            if !env.is_solid_block(2 * r, 2 * c) {
+                fill_rect(canvas, c * pitch, r * pitch, border, border, theme.open_color);
+            }
+        }
+    }
+
+    for cell in env.cells().filter(|&cell| env.is_excluded(cell)) {
+        fill_rect(canvas, cell.col * pitch, cell.row * pitch, open + (2 * border), open + (2 * border), theme.solid_color);
+    }
+}
+
+// Draws corridors as round-capped strokes between cell centers, rasterized directly at
+// `unit` pixels per logical pixel so supersampling can smooth the curves
+fn draw_maze_rounded(env: &Env, unit: usize, theme: &Theme) -> Canvas {
+    let mut canvas = Canvas::new(image_size(env.width) * unit, image_size(env.height) * unit, theme.solid_color);
+    let u = unit as f32;
+    let radius = (OPEN_PATH_SIZE as f32 / 2.0) * u;
+
+    for cell in env.cells().filter(|&cell| !env.is_excluded(cell)) {
+        let (cx, cy) = cell_center(&cell);
+        fill_circle(&mut canvas, cx * u, cy * u, radius, theme.open_color);
+    }
+
+    for wall in env.walls() {
+        let (sx, sy) = cell_center(&wall.start);
+        let (tx, ty) = cell_center(&wall.target);
+        fill_rounded_rect(&mut canvas,
+            (sx.min(tx) * u) - radius, (sy.min(ty) * u) - radius,
+            ((tx - sx).abs() * u) + (2.0 * radius), ((ty - sy).abs() * u) + (2.0 * radius),
+            radius, theme.open_color
+        );
+    }
+    canvas
+}
+
+// Cuts each opening through the outer wall next to its cell, as wide as a corridor
+fn draw_openings(env: &Env, canvas: &mut Canvas, unit: usize, openings: &[Opening], open_color: u32) {
+    let (open, border) = (OPEN_PATH_SIZE as usize * unit, BORDER_THICKNESS as usize * unit);
+    let pitch = open + border;
+    for &(cell, edge) in openings {
+        let (x, y) = (cell.col * pitch, cell.row * pitch);
+        match edge {
+            Edge::North => fill_rect(canvas, x + border, 0, open, border, open_color),
+            Edge::South => fill_rect(canvas, x + border, env.height * pitch, open, border, open_color),
+            Edge::West => fill_rect(canvas, 0, y + border, border, open, open_color),
+            Edge::East => fill_rect(canvas, env.width * pitch, y + border, border, open, open_color),
+        }
+    }
+}
+
+// Draws a bar like a door across each bridge, over the wall slot it passes through and a logical
+// pixel of the corridor on either side
+fn draw_chokepoints(bridges: &[Bridge], canvas: &mut Canvas, unit: usize) {
+    let (open, border) = (OPEN_PATH_SIZE as usize, BORDER_THICKNESS as usize);
+    let pitch = open + border;
+    for bridge in bridges {
+        let (a, b) = (bridge.a, bridge.b);
+        let (row, col) = (a.row.max(b.row), a.col.max(b.col));
+        let (x, y, w, h) = if a.row == b.row {
+            ((col * pitch) - 1, (row * pitch) + border, border + 2, open)
+        } else {
+            ((col * pitch) + border, (row * pitch) - 1, open, border + 2)
+        };
+        fill_rect(canvas, x * unit, y * unit, w * unit, h * unit, CHOKEPOINT_COLOR);
+    }
+}
+
+// Recolors the open pixels of every cell along a gradient from `VISIT_FIRST_COLOR` (first cell
+// the generator visited) to `VISIT_LAST_COLOR` (last one). Cells are painted in visit order over
+// a box that includes their surrounding wall slots, so an opening takes the later cell's color.
+fn draw_visit_order(env: &Env, canvas: &mut Canvas, unit: usize, open_color: u32) {
+    let pitch = (OPEN_PATH_SIZE + BORDER_THICKNESS) as usize * unit;
+    let span = pitch + (BORDER_THICKNESS as usize * unit);
+    let last = env.visit_order.len().saturating_sub(1).max(1) as f32;
+    for (i, cell) in env.visit_order.iter().map(|&ind| env.cell(ind as usize)).enumerate() {
+        let color = lerp_color(VISIT_FIRST_COLOR, VISIT_LAST_COLOR, i as f32 / last);
+        let (x0, y0) = (cell.col * pitch, cell.row * pitch);
+        for y in y0..(y0 + span) {
+            for x in x0..(x0 + span) {
+                if canvas.get(x, y) == Some(open_color) {
+                    canvas.set(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+// Recolors the open pixels of every cell reachable from `start` by its distance band: cells fewer
+// than `steps` steps away get `BAND_NEAR_COLOR`, the farthest band `BAND_FAR_COLOR`, and the bands
+// between them evenly spaced shades. Boxes overlap like in `draw_visit_order`.
+fn draw_bands(env: &Env, canvas: &mut Canvas, unit: usize, start: Cell, steps: usize, open_color: u32) {
+    let pitch = (OPEN_PATH_SIZE + BORDER_THICKNESS) as usize * unit;
+    let span = pitch + (BORDER_THICKNESS as usize * unit);
+    let distances = analysis::distances(env, start);
+    let last = (distances.iter().flatten().max().unwrap_or(&0) / steps).max(1) as f32;
+    for (i, distance) in distances.iter().enumerate() {
+        let Some(distance) = distance else { continue };
+        let color = lerp_color(BAND_NEAR_COLOR, BAND_FAR_COLOR, (distance / steps) as f32 / last);
+        let cell = env.cell(i);
+        let (x0, y0) = (cell.col * pitch, cell.row * pitch);
+        for y in y0..(y0 + span) {
+            for x in x0..(x0 + span) {
+                if canvas.get(x, y) == Some(open_color) {
+                    canvas.set(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+// Draws `path` as a thick `color` line through the cell centers. With `gradient`, every segment is
+// colored by its position along the path, from `SOLUTION_START_COLOR` to `SOLUTION_GOAL_COLOR`.
+fn draw_solution(path: &[Cell], canvas: &mut Canvas, unit: usize, color: u32, gradient: bool) {
+    let half = SOLUTION_THICKNESS as f32 / 2.0;
+    let last = path.len().saturating_sub(1).max(1) as f32;
+    let color_at = |i: usize| if gradient {
+        lerp_color(SOLUTION_START_COLOR, SOLUTION_GOAL_COLOR, i as f32 / last)
+    } else {
+        color
+    };
+    let u = unit as f32;
+    let at = |cell: &Cell| {
+        let (x, y) = cell_center(cell);
+        (x * u, y * u)
+    };
+
+    if let Some(cell) = path.first() {
+        draw_polyline(canvas, &[at(cell)], 2.0 * half * u, color_at(0));
+    }
+    for (i, pair) in path.windows(2).enumerate() {
+        draw_line(canvas, at(&pair[0]), at(&pair[1]), 2.0 * half * u, color_at(i + 1));
+    }
+}
+
+// Runs every solver from `start` to `goal` and overlays their explored cells as translucent tints
+// plus their final paths, each slightly offset so they stay visible
+fn draw_solver_comparison(env: &Env, canvas: &mut Canvas, unit: usize, start: Cell, goal: Cell) {
+    let pitch = (OPEN_PATH_SIZE + BORDER_THICKNESS) as usize;
+    let inner = OPEN_PATH_SIZE as usize * unit;
+    let thickness = unit;
+
+    for (i, (solver, tint)) in Solver::ALL.iter().zip(COMPARE_COLORS).enumerate() {
+        let Some(solution) = solver.solve(env, start, goal) else { continue };
+        let mut seen = vec![false; env.cell_count()];
+        for cell in solution.explored.iter() {
+            if std::mem::replace(&mut seen[env.ind(*cell)], true) {
+                continue;
+            }
+            let x0 = ((cell.col * pitch) + BORDER_THICKNESS as usize) * unit;
+            let y0 = ((cell.row * pitch) + BORDER_THICKNESS as usize) * unit;
+            for y in y0..(y0 + inner) {
+                for x in x0..(x0 + inner) {
+                    if let Some(under) = canvas.get(x, y) {
+                        canvas.set(x, y, blend(under, tint));
+                    }
+                }
+            }
+        }
+
+        let offset = (i as f32 - 1.0) * 2.0;
+        let color = tint | 0xFF;
+        for pair in solution.path.windows(2) {
+            let (sx, sy) = cell_center(&pair[0]);
+            let (tx, ty) = cell_center(&pair[1]);
+            let x = ((sx.min(tx) + offset) as usize) * unit;
+            let y = ((sy.min(ty) + offset) as usize) * unit;
+            let w = ((tx - sx).abs() as usize * unit) + thickness;
+            let h = ((ty - sy).abs() as usize * unit) + thickness;
+            fill_rect(canvas, x, y, w, h, color);
+        }
+    }
+}
+
+// Runs every solver `--compare` overlays from `start` to `goal` and returns, for each one that
+// reaches the goal, the number of distinct cells it explored and the length of its path
+pub fn solver_comparison(env: &Env, start: Cell, goal: Cell) -> Vec<(Solver, usize, usize)> {
+    Solver::ALL.iter().filter_map(|solver| {
+        let solution = solver.solve(env, start, goal)?;
+        let mut seen = vec![false; env.cell_count()];
+        let explored = solution.explored.iter().filter(|cell| !std::mem::replace(&mut seen[env.ind(**cell)], true)).count();
+        Some((*solver, explored, solution.path.len()))
+    }).collect()
+}
+
+// Overlays the carve tree (one segment per removed wall, joining the two cell centers) and each
+// cell's `ind()` on a top-down image rendered at `unit` pixels per logical pixel
+fn draw_debug_overlay(env: &Env, canvas: &mut Canvas, unit: usize) {
+    for wall in env.walls() {
+        let (sx, sy) = cell_center(&wall.start);
+        let (tx, ty) = cell_center(&wall.target);
+        let (x, y) = (sx.min(tx) as usize, sy.min(ty) as usize);
+        let (w, h) = ((tx - sx).abs() as usize + 1, (ty - sy).abs() as usize + 1);
+        fill_rect(canvas, x * unit, y * unit, w * unit, h * unit, DEBUG_TREE_COLOR);
+    }
+
+    let pitch = (OPEN_PATH_SIZE + BORDER_THICKNESS) as usize;
+    for cell in env.cells() {
+        let label = env.ind(cell).to_string();
+        // Centered horizontally, along the top edge of the cell
+        let pad = (OPEN_PATH_SIZE as usize).saturating_sub(font::text_width(&label)) / 2;
+        let x = (cell.col * pitch) + BORDER_THICKNESS as usize + pad;
+        let y = (cell.row * pitch) + BORDER_THICKNESS as usize;
+        font::draw_text(canvas, x * unit, y * unit, &label, unit, DEBUG_TEXT_COLOR);
+    }
+}
+
+// Draws `text` on a translucent backing box in one corner of an image rendered at `unit` pixels
+// per logical pixel
+fn draw_label(canvas: &mut Canvas, unit: usize, text: &str, corner: Corner) {
+    let (width, height) = (canvas.width(), canvas.height());
+    let pad = unit;
+    let box_w = (font::text_width(text) * unit) + (2 * pad);
+    let box_h = (font::GLYPH_HEIGHT * unit) + (2 * pad);
+    let x = match corner {
+        Corner::TopLeft | Corner::BottomLeft => pad,
+        Corner::TopRight | Corner::BottomRight => width.saturating_sub(box_w + pad),
+    };
+    let y = match corner {
+        Corner::TopLeft | Corner::TopRight => pad,
+        Corner::BottomLeft | Corner::BottomRight => height.saturating_sub(box_h + pad),
+    };
+    for py in y..(y + box_h).min(height) {
+        for px in x..(x + box_w).min(width) {
+            if let Some(under) = canvas.get(px, py) {
+                canvas.set(px, py, blend(under, LABEL_BACKGROUND_COLOR));
+            }
+        }
+    }
+    font::draw_text(canvas, x + pad, y + pad, text, unit, LABEL_TEXT_COLOR);
+}
+
+// Extra layers drawn on top of a top-down render
+#[derive(Clone, Default)]
+pub struct Overlays {
+    // Color cells along a gradient by the order the generator visited them
+    pub visit_order: bool,
+    // Color cells in bands of this many steps from the start, light to dark, like the contours of
+    // a map
+    pub bands: Option<usize>,
+    // Gaps in the outer wall at the start and goal (see `openings::place`), which `solve` then
+    // draws the path between instead of the corners
+    pub openings: Option<[Opening; 2]>,
+    // Draw the path from the top-left to the bottom-right cell
+    pub solve: bool,
+    // Color the solution from start to goal along a gradient
+    pub solution_gradient: bool,
+    // Draw the longest path through the maze, between the hardest start and goal pair
+    pub longest_path: bool,
+    // Bar the passages that are the only way into at least this many cells, see
+    // `analysis::chokepoints`
+    pub chokepoints: Option<usize>,
+    // Overlay the explored cells and paths of every solver
+    pub compare: bool,
+    // Label every cell with its index and outline the carve tree
+    pub debug: bool,
+    // Text (title, seed, date, ...) stamped in a corner of the image
+    pub label: Option<(String, Corner)>,
+}
+
+impl Overlays {
+    pub fn any_top_down(&self) -> bool {
+        self.visit_order || self.bands.is_some() || self.solve || self.longest_path || self.chokepoints.is_some() || self.compare || self.debug
+    }
+
+    // Cells the solving overlays run between: the openings, or the top-left and bottom-right corners
+    pub fn endpoints(&self, env: &Env) -> (Cell, Cell) {
+        match self.openings {
+            Some([(start, _), (goal, _)]) => (start, goal),
+            None => (Cell { row: 0, col: 0 }, Cell { row: env.height - 1, col: env.width - 1 }),
+        }
+    }
+}
+
+pub struct RasterRenderer {
+    pub format: ImageFormat,
+    pub style: Style,
+    // Integer nearest-neighbor upscale factor applied to the final image
+    pub scale: usize,
+    // Supersampling factor; the image is rasterized this many times larger and averaged back down
+    pub supersample: usize,
+    pub overlays: Overlays,
+    // Written into the image file
+    pub metadata: Metadata,
+}
+
+// Rough bytes per cell a solver holds: adjacency lists, came-from/cost tables and the explored trace
+const SOLVER_BYTES_PER_CELL: usize = 96;
+
+impl RasterRenderer {
+    // Pixel size of the final image for a `width`x`height` maze
+    pub fn image_dims(&self, width: usize, height: usize) -> (usize, usize) {
+        let factor = self.scale * self.supersample;
+        let (w, h) = match self.style {
+            Style::Blocky | Style::Rounded => (image_size(width) * factor, image_size(height) * factor),
+            Style::Isometric => iso::canvas_size((2 * height) + 1, (2 * width) + 1, factor),
+        };
+        (w / self.supersample, h / self.supersample)
+    }
+
+    // Upper bound on the heap bytes `render` holds at once for a `width`x`height` maze, not
+    // counting the maze itself
+    pub fn peak_bytes(&self, width: usize, height: usize) -> usize {
+        let (w, h) = self.image_dims(width, height);
+        let ss = self.supersample;
+        let mut bytes = (w * ss) * (h * ss) * 4;
+        if self.style == Style::Blocky {
+            // Logical-resolution canvas that gets upscaled
+            bytes += image_size(width) * image_size(height) * 4;
+        }
+        if ss > 1 {
+            bytes += w * h * 4;
+        }
+        if self.style == Style::Isometric {
+            // Expanded block map
+            bytes += ((2 * height) + 1) * ((2 * width) + 1);
+        }
+        if self.overlays.solve || self.overlays.bands.is_some() || self.overlays.longest_path || self.overlays.chokepoints.is_some() || self.overlays.compare {
+            bytes += width * height * SOLVER_BYTES_PER_CELL;
+        }
+        if matches!(self.format, ImageFormat::Png | ImageFormat::Kitty | ImageFormat::Iterm2) {
+            // Filtered scanlines plus their zlib-wrapped copy
+            bytes += 2 * h * ((w * 4) + 1);
+        }
+        if self.format == ImageFormat::Kitty {
+            bytes += kitty::estimated_size(w, h);
+        }
+        if self.format == ImageFormat::Iterm2 {
+            bytes += iterm2::estimated_size(w, h);
+        }
+        if self.format == ImageFormat::Qoi {
+            bytes += qoi::encoded_size(w, h);
+        }
+        if self.format == ImageFormat::Sixel {
+            // Palette index of every pixel plus the encoded bands
+            bytes += (w * h * 2) + sixel::estimated_size(w, h);
+        }
+        bytes
+    }
+
+    // Rasterizes the maze with every overlay applied
+    pub fn rasterize(&self, env: &Env, theme: &Theme) -> Canvas {
+        // Rasterize at `scale * supersample` and average back down by `supersample`
+        let factor = self.scale * self.supersample;
+        let mut hi_res = match self.style {
+            Style::Blocky => {
+                let mut canvas = Canvas::new(image_size(env.width), image_size(env.height), theme.open_color);
+                draw_maze(env, &mut canvas, theme);
+                canvas.upscale(factor)
+            },
+            Style::Rounded => draw_maze_rounded(env, factor, theme),
+            Style::Isometric => iso::draw_maze_isometric(env, factor, theme),
+        };
+
+        let overlays = &self.overlays;
+        if let Some(openings) = overlays.openings.as_ref().filter(|_| self.style != Style::Isometric) {
+            draw_openings(env, &mut hi_res, factor, openings, theme.open_color);
+        }
+        if overlays.visit_order {
+            draw_visit_order(env, &mut hi_res, factor, theme.open_color);
+        }
+        let (start, goal) = overlays.endpoints(env);
+        if let Some(steps) = overlays.bands {
+            draw_bands(env, &mut hi_res, factor, start, steps, theme.open_color);
+        }
+        // Loaded mazes may have no way from the start to the goal; those are drawn without one
+        if let Some(solution) = overlays.solve.then(|| Solver::Bfs.solve(env, start, goal)).flatten() {
+            draw_solution(&solution.path, &mut hi_res, factor, SOLUTION_COLOR, overlays.solution_gradient);
+        }
+        if overlays.longest_path {
+            draw_solution(&analysis::longest_path(env), &mut hi_res, factor, LONGEST_PATH_COLOR, false);
+        }
+        if let Some(min_cut_off) = overlays.chokepoints {
+            draw_chokepoints(&analysis::chokepoints(env, min_cut_off), &mut hi_res, factor);
+        }
+        if overlays.compare {
+            draw_solver_comparison(env, &mut hi_res, factor, start, goal);
+        }
+        if overlays.debug {
+            draw_debug_overlay(env, &mut hi_res, factor);
+        }
+        if let Some((text, corner)) = &overlays.label {
+            draw_label(&mut hi_res, factor, text, *corner);
+        }
+
+        // Without supersampling the image is already at its final size, as `peak_bytes` counts it
+        if self.supersample == 1 { hi_res } else { hi_res.downsample(self.supersample) }
+    }
+}
+
+impl Renderer for RasterRenderer {
+    fn render(&self, env: &Env, theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        self.format.write_with(out, &self.rasterize(env, theme), &self.metadata)?;
+        Ok(())
+    }
+
+    // Reports two coarse stages: rasterizing, then encoding
+    fn render_with_progress(&self, env: &Env, theme: &Theme, out: &mut dyn Write, progress: &mut dyn Progress) -> Result<(), MazeError> {
+        progress.checkpoint(0, 2)?;
+        let canvas = self.rasterize(env, theme);
+        progress.checkpoint(1, 2)?;
+        self.format.write_with(out, &canvas, &self.metadata)?;
+        progress.update(2, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use crate::draw::Canvas;
+use crate::font::{self, GLYPH_HEIGHT};
+
+// Sheets are meant for paper, so they get a white page with black captions whatever the theme
+pub const PAGE_COLOR: u32 = 0xFFFFFFFF;
+const CAPTION_COLOR: u32 = 0x000000FF;
+
+// Several mazes laid out in a grid on one page, e.g. for worksheets
+#[derive(Copy, Clone)]
+pub struct Sheet {
+    pub cols: usize,
+    pub rows: usize,
+    // Pixels around the page edge and between neighbouring slots
+    pub margin: usize,
+}
+
+impl Sheet {
+    pub fn count(&self) -> usize {
+        self.cols * self.rows
+    }
+
+    // Font pixel size of captions under a `maze_w` pixel wide maze
+    fn caption_unit(maze_w: usize) -> usize {
+        (maze_w / 64).max(2)
+    }
+
+    // Height of the caption strip under every maze, including the gap above it
+    fn caption_height(maze_w: usize) -> usize {
+        (GLYPH_HEIGHT + 2) * Self::caption_unit(maze_w)
+    }
+
+    // Size of the page for mazes rendered at `maze_w`x`maze_h` pixels
+    pub fn page_dims(&self, maze_w: usize, maze_h: usize) -> (usize, usize) {
+        let slot_h = maze_h + Self::caption_height(maze_w);
+        ((self.cols * maze_w) + ((self.cols + 1) * self.margin), (self.rows * slot_h) + ((self.rows + 1) * self.margin))
+    }
+
+    // Place `mazes` (image and caption) in row-major order; mazes past `count` are ignored.
+    // Every slot is as large as the largest image, and images and captions are centered in it.
+    pub fn compose(&self, mazes: &[(Canvas, String)]) -> Canvas {
+        let maze_w = mazes.iter().map(|(canvas, _)| canvas.width()).max().unwrap_or(0);
+        let maze_h = mazes.iter().map(|(canvas, _)| canvas.height()).max().unwrap_or(0);
+        let (page_w, page_h) = self.page_dims(maze_w, maze_h);
+        let (unit, slot_h) = (Self::caption_unit(maze_w), maze_h + Self::caption_height(maze_w));
+        let mut page = Canvas::new(page_w, page_h, PAGE_COLOR);
+        for (i, (canvas, caption)) in mazes.iter().take(self.count()).enumerate() {
+            let x = self.margin + ((i % self.cols) * (maze_w + self.margin));
+            let y = self.margin + ((i / self.cols) * (slot_h + self.margin));
+            page.blit(canvas, x + ((maze_w - canvas.width()) / 2), y + ((maze_h - canvas.height()) / 2));
+            let text_x = x + (maze_w.saturating_sub(font::text_width(caption) * unit) / 2);
+            font::draw_text(&mut page, text_x, y + maze_h + (2 * unit), caption, unit, CAPTION_COLOR);
+        }
+        page
+    }
+}
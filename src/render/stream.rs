@@ -0,0 +1,83 @@
+use std::io::Write;
+
+use crate::error::MazeError;
+use crate::metadata::Metadata;
+use crate::ppm;
+use crate::progress::Progress;
+use crate::Env;
+
+use super::{image_size, Renderer, Theme, BORDER_THICKNESS, OPEN_PATH_SIZE};
+
+// Blocky PPM output that rasterizes and writes one pixel row at a time, so memory use is bounded
+// by the image width rather than its area
+pub struct StreamingPpmRenderer {
+    // Integer nearest-neighbor upscale factor
+    pub scale: usize,
+    // Written into the header as comment lines
+    pub metadata: Metadata,
+}
+
+// Maps a logical pixel coordinate to its index in the block map (even = wall slot, odd = cell)
+fn block_index(p: usize) -> usize {
+    let pitch = (OPEN_PATH_SIZE + BORDER_THICKNESS) as usize;
+    let (cell, offset) = (p / pitch, p % pitch);
+    if offset < BORDER_THICKNESS as usize { 2 * cell } else { (2 * cell) + 1 }
+}
+
+// Rasterizes single blocky RGB pixel rows straight from the wall bits, without a canvas
+pub(super) struct RowRaster<'a> {
+    env: &'a Env,
+    scale: usize,
+    solid: [u8; 3],
+    open: [u8; 3],
+    // Block column of every logical x, computed once and shared by all rows
+    columns: Vec<usize>,
+}
+
+impl<'a> RowRaster<'a> {
+    pub(super) fn new(env: &'a Env, theme: &Theme, scale: usize) -> Self {
+        let rgb = |color: u32| [(color >> 24) as u8, (color >> 16) as u8, (color >> 8) as u8];
+        let (_, block_cols) = env.block_dims();
+        let columns = (0..image_size(env.width)).map(|x| block_index(x).min(block_cols - 1)).collect();
+        Self { env, scale, solid: rgb(theme.solid_color), open: rgb(theme.open_color), columns }
+    }
+
+    // Output size in pixels
+    pub(super) fn size(&self) -> (usize, usize) {
+        (image_size(self.env.width) * self.scale, image_size(self.env.height) * self.scale)
+    }
+
+    // Fill `row` (3 bytes per pixel) with output pixel row `y`
+    pub(super) fn fill(&self, y: usize, row: &mut [u8]) {
+        let (block_rows, _) = self.env.block_dims();
+        let i = block_index(y / self.scale).min(block_rows - 1);
+        for (x, pixel) in row.chunks_exact_mut(3).enumerate() {
+            let j = self.columns[x / self.scale];
+            pixel.copy_from_slice(if self.env.is_solid_block(i, j) { &self.solid } else { &self.open });
+        }
+    }
+}
+
+impl Renderer for StreamingPpmRenderer {
+    fn render(&self, env: &Env, theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        self.render_with_progress(env, theme, out, &mut ())
+    }
+
+    // Reports progress in pixel rows written
+    fn render_with_progress(&self, env: &Env, theme: &Theme, out: &mut dyn Write, progress: &mut dyn Progress) -> Result<(), MazeError> {
+        let raster = RowRaster::new(env, theme, self.scale);
+        let (width, height) = raster.size();
+        let mut row = vec![0u8; width * 3];
+
+        out.write_all(ppm::header(width, height, &self.metadata).as_bytes())?;
+        for y in 0..height {
+            // Rows within the same scaled pixel are identical; only rebuild on the first one
+            if y % self.scale == 0 {
+                raster.fill(y, &mut row);
+            }
+            out.write_all(&row)?;
+            progress.checkpoint(y + 1, height)?;
+        }
+        Ok(())
+    }
+}
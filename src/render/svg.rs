@@ -0,0 +1,74 @@
+use std::io::Write;
+
+use crate::error::MazeError;
+use crate::metadata::Metadata;
+use crate::Env;
+
+use super::{Renderer, Theme};
+
+// Vector output: one stroked line per wall on top of a background rectangle
+pub struct SvgRenderer {
+    // Distance between neighbouring wall lines, in SVG user units
+    pub cell_size: u32,
+    pub wall_width: u32,
+    // Written into a `<metadata>` element
+    pub metadata: Metadata,
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        Self { cell_size: 16, wall_width: 2, metadata: Metadata::default() }
+    }
+}
+
+// SVG has no 0xRRGGBBAA notation, so the alpha is split out into a separate opacity
+fn svg_color(color: u32) -> (String, f32) {
+    (format!("#{:06x}", color >> 8), (color & 0xFF) as f32 / 255.0)
+}
+
+impl Renderer for SvgRenderer {
+    fn render(&self, env: &Env, theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        let blocks = env.blocks();
+        let cell = self.cell_size as usize;
+        let margin = self.wall_width as usize;
+        let (width, height) = ((env.width * cell) + (2 * margin), (env.height * cell) + (2 * margin));
+        let (open, open_opacity) = svg_color(theme.open_color);
+        let (solid, solid_opacity) = svg_color(theme.solid_color);
+
+        writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#)?;
+        if !self.metadata.is_empty() {
+            writeln!(out, "<metadata>")?;
+            for (key, value) in self.metadata.entries.iter() {
+                writeln!(out, "{}: {}", key, value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('\n', " "))?;
+            }
+            writeln!(out, "</metadata>")?;
+        }
+        writeln!(out, r#"<rect width="100%" height="100%" fill="{open}" fill-opacity="{open_opacity}"/>"#)?;
+        writeln!(
+            out,
+            r#"<g stroke="{solid}" stroke-opacity="{solid_opacity}" stroke-width="{}" stroke-linecap="square" transform="translate({margin} {margin})">"#,
+            self.wall_width
+        )?;
+        for (i, row) in blocks.iter().enumerate() {
+            for (j, solid) in row.iter().enumerate() {
+                if !solid {
+                    continue;
+                }
+                // Wall slots sit between two posts; posts themselves are covered by the line caps
+                let (x1, y1, x2, y2) = match (i % 2, j % 2) {
+                    (0, 1) => (j / 2, i / 2, (j / 2) + 1, i / 2),
+                    (1, 0) => (j / 2, i / 2, j / 2, (i / 2) + 1),
+                    _ => continue,
+                };
+                writeln!(
+                    out,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+                    x1 * cell, y1 * cell, x2 * cell, y2 * cell
+                )?;
+            }
+        }
+        writeln!(out, "</g>")?;
+        writeln!(out, "</svg>")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::io::Write;
+
+use crate::error::MazeError;
+use crate::Env;
+
+use super::{Renderer, Theme};
+
+// Box-drawing text output, for terminals or plain `.txt` files
+pub struct TextRenderer {
+    // Wrap walls in 24-bit ANSI color escapes using the theme's wall color
+    pub color: bool,
+}
+
+// Junction glyph for a wall post, indexed by the bitmask up | down << 1 | left << 2 | right << 3
+const JUNCTIONS: [char; 16] = [
+    ' ', '╵', '╷', '│', '╴', '┘', '┐', '┤',
+    '╶', '└', '┌', '├', '─', '┴', '┬', '┼',
+];
+
+// An excluded cell, see `Env::exclude`
+pub(crate) const EXCLUDED_CELL: &str = "███";
+
+impl Renderer for TextRenderer {
+    fn render(&self, env: &Env, theme: &Theme, out: &mut dyn Write) -> Result<(), MazeError> {
+        let blocks = env.blocks();
+        let (rows, cols) = env.block_dims();
+        let solid = |i: usize, j: usize| i < rows && j < cols && blocks[i][j];
+        // Between excluded cells only, so filled in along with them
+        let cell = |i: usize, j: usize| i % 2 == 1 && j % 2 == 1 && solid(i, j);
+        let buried = |i: usize, j: usize| match (i % 2, j % 2) {
+            (0, 0) => i > 0 && j > 0 && cell(i - 1, j - 1) && cell(i - 1, j + 1) && cell(i + 1, j - 1) && cell(i + 1, j + 1),
+            (0, _) => i > 0 && cell(i - 1, j) && cell(i + 1, j),
+            _ => j > 0 && cell(i, j - 1) && cell(i, j + 1),
+        };
+        let (start, end) = if self.color {
+            let c = theme.solid_color;
+            (format!("\x1b[38;2;{};{};{}m", c >> 24, (c >> 16) & 0xFF, (c >> 8) & 0xFF), String::from("\x1b[0m"))
+        } else {
+            (String::new(), String::new())
+        };
+
+        for i in 0..rows {
+            let mut line = start.clone();
+            for j in 0..cols {
+                match (i % 2, j % 2) {
+                    (0, 0) if buried(i, j) => line.push('█'),
+                    // Post: joins whichever wall slots around it are still standing
+                    (0, 0) => {
+                        let mask = (i > 0 && solid(i - 1, j)) as usize
+                            | (solid(i + 1, j) as usize) << 1
+                            | ((j > 0 && solid(i, j - 1)) as usize) << 2
+                            | (solid(i, j + 1) as usize) << 3;
+                        line.push(JUNCTIONS[mask]);
+                    },
+                    // Horizontal wall slot, as wide as a cell
+                    (0, 1) if buried(i, j) => line.push_str(EXCLUDED_CELL),
+                    (0, 1) => line.push_str(if solid(i, j) { "───" } else { "   " }),
+                    // Vertical wall slot
+                    (1, 0) if buried(i, j) => line.push('█'),
+                    (1, 0) => line.push(if solid(i, j) { '│' } else { ' ' }),
+                    // Cell, filled in if it is left out of the maze
+                    _ => line.push_str(if solid(i, j) { EXCLUDED_CELL } else { "   " }),
+                }
+            }
+            line.push_str(&end);
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::draw::{fill_rect, Canvas};
+use crate::error::MazeError;
+
+// Color of the registration marks drawn in the overlap between tiles
+pub const MARK_COLOR: u32 = 0xE03C3CFF;
+
+// Splits one large image into a grid of printable pages
+#[derive(Copy, Clone)]
+pub struct Tiling {
+    pub cols: usize,
+    pub rows: usize,
+    // Pixels every tile extends past its share of the image on each side
+    pub overlap: usize,
+}
+
+pub struct Tile {
+    pub row: usize,
+    pub col: usize,
+    pub canvas: Canvas,
+}
+
+// Draw a crosshair centered on (`x`, `y`)
+fn draw_mark(canvas: &mut Canvas, x: usize, y: usize, arm: usize) {
+    let thickness = (arm / 8).max(1);
+    let half = thickness / 2;
+    fill_rect(canvas, x.saturating_sub(arm), y.saturating_sub(half), 2 * arm + 1, thickness, MARK_COLOR);
+    fill_rect(canvas, x.saturating_sub(half), y.saturating_sub(arm), thickness, 2 * arm + 1, MARK_COLOR);
+}
+
+impl Tiling {
+    // Cut `canvas` into `rows`x`cols` tiles in row-major order. Each tile keeps `overlap` extra
+    // pixels around its share, and crosshairs mark the share's corners so neighbouring tiles can
+    // be lined up after printing. Shares differ by at most a pixel, so every tile gets one as long
+    // as the image has at least `cols`x`rows` pixels.
+    pub fn split(&self, canvas: &Canvas) -> Result<Vec<Tile>, MazeError> {
+        let (width, height) = (canvas.width(), canvas.height());
+        if self.cols == 0 || self.rows == 0 || self.cols > width || self.rows > height {
+            return Err(MazeError::InvalidData(format!("a {}x{} px image cannot be cut into {}x{} tiles", width, height, self.cols, self.rows)));
+        }
+        let x_at = |col: usize| col * width / self.cols;
+        let y_at = |row: usize| row * height / self.rows;
+        let mut tiles = vec![];
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let (x, y) = (x_at(col).saturating_sub(self.overlap), y_at(row).saturating_sub(self.overlap));
+                let (x_end, y_end) = (x_at(col + 1) + self.overlap, y_at(row + 1) + self.overlap);
+                let mut tile = canvas.crop(x, y, x_end - x, y_end - y);
+                if self.overlap > 0 {
+                    for (cx, cy) in [(col, row), (col + 1, row), (col, row + 1), (col + 1, row + 1)] {
+                        let (mx, my) = (x_at(cx), y_at(cy));
+                        // Marks are placed in image coordinates, so overlapping tiles share them
+                        if mx >= x && my >= y {
+                            draw_mark(&mut tile, mx - x, my - y, self.overlap / 2);
+                        }
+                    }
+                }
+                tiles.push(Tile { row, col, canvas: tile });
+            }
+        }
+        Ok(tiles)
+    }
+}
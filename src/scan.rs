@@ -0,0 +1,308 @@
+// Reads mazes back from pictures: renders from this crate or other programs, screenshots and
+// scans of printed mazes. The image is split into two classes of pixels with Otsu's threshold and
+// either can be the walls, so a grid is fitted to both and the one with the thinner walls wins.
+// The grid pitch follows from the frame thickness and the widths of the corridors, checked against
+// the wall posts, and every wall slot is sampled at its middle. When the frame has gaps, the cells
+// behind the first and last become the start and goal.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::bmp::read_bmp;
+use crate::draw::{blend, Canvas};
+use crate::error::MazeError;
+use crate::mazefile::MazeFile;
+use crate::png::read_png;
+use crate::ppm::read_ppm;
+use crate::qoi::read_qoi;
+use crate::tga::{is_tga, read_tga};
+use crate::{Cell, Env};
+
+pub const EXTENSIONS: [&str; 6] = ["png", "ppm", "pgm", "bmp", "qoi", "tga"];
+
+fn invalid(message: &str) -> MazeError {
+    MazeError::InvalidData(format!("cannot read a maze from the image: {}", message))
+}
+
+// Decodes a PNG, PPM/PGM, BMP, QOI or TGA file, whichever its first bytes announce
+pub fn decode(bytes: &[u8]) -> Result<Canvas, MazeError> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => read_png(bytes),
+        [b'P', b'5' | b'6', ..] => read_ppm(bytes),
+        [b'B', b'M', ..] => read_bmp(bytes),
+        [b'q', b'o', b'i', b'f', ..] => read_qoi(bytes),
+        _ if is_tga(bytes) => read_tga(bytes),
+        _ => Err(MazeError::InvalidData(String::from("not a PNG, PPM, PGM, BMP, QOI or TGA image"))),
+    }
+}
+
+// Otsu's method: the luminance that best separates the histogram into two classes
+fn otsu(histogram: &[usize; 256]) -> u8 {
+    let total: usize = histogram.iter().sum();
+    let sum: f64 = histogram.iter().enumerate().map(|(i, &n)| (i * n) as f64).sum();
+    let (mut below, mut below_sum, mut best, mut threshold) = (0usize, 0f64, -1f64, 0u8);
+    for (i, &n) in histogram.iter().enumerate() {
+        below += n;
+        below_sum += (i * n) as f64;
+        let above = total - below;
+        if below == 0 || above == 0 {
+            continue;
+        }
+        let (mean_below, mean_above) = (below_sum / below as f64, (sum - below_sum) / above as f64);
+        let between = below as f64 * above as f64 * (mean_below - mean_above).powi(2);
+        if between > best {
+            (best, threshold) = (between, i as u8);
+        }
+    }
+    threshold
+}
+
+// Pixels at or below the Otsu threshold of their luminance, and whether most of the image's edge
+// is such dark pixels
+pub(crate) fn threshold(canvas: &Canvas) -> Result<(Vec<bool>, bool), MazeError> {
+    let (width, height) = (canvas.width(), canvas.height());
+    // Translucent pixels are seen against white, like paper
+    let luma: Vec<u8> = canvas.pixels().iter().map(|&p| {
+        let c = blend(0xFFFFFFFF, p);
+        (((((c >> 24) & 0xFF) * 54) + (((c >> 16) & 0xFF) * 183) + (((c >> 8) & 0xFF) * 19)) >> 8) as u8
+    }).collect();
+    let mut histogram = [0usize; 256];
+    for &l in luma.iter() {
+        histogram[l as usize] += 1;
+    }
+    let threshold = otsu(&histogram);
+    let dark: Vec<bool> = luma.iter().map(|&l| l <= threshold).collect();
+    if dark.iter().all(|&d| d) || !dark.iter().any(|&d| d) {
+        return Err(invalid("the image has a single color"));
+    }
+    let ring = (0..width).flat_map(|x| [(x, 0), (x, height - 1)]).chain((0..height).flat_map(|y| [(0, y), (width - 1, y)]));
+    let (dark_count, count) = ring.fold((0, 0), |(d, n), (x, y)| (d + dark[(y * width) + x] as usize, n + 1));
+    Ok((dark, 2 * dark_count >= count))
+}
+
+// Wall pixels of the image, `true` for walls
+struct Mask {
+    width: usize,
+    height: usize,
+    walls: Vec<bool>,
+}
+
+impl Mask {
+    fn wall(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.walls[(y * self.width) + x]
+    }
+
+    // The frame around the maze, as (left, top, right, bottom) inclusive: the outermost rows and
+    // columns with at least half as many wall pixels as the fullest one, so specks of dirt outside
+    // it do not count
+    fn bounds(&self) -> (usize, usize, usize, usize) {
+        let (mut rows, mut columns) = (vec![0; self.height], vec![0; self.width]);
+        for (i, _) in self.walls.iter().enumerate().filter(|(_, &w)| w) {
+            rows[i / self.width] += 1;
+            columns[i % self.width] += 1;
+        }
+        let span = |counts: &[usize]| {
+            let max = counts.iter().copied().max().unwrap_or(0);
+            let full = |&count: &usize| 2 * count >= max;
+            (counts.iter().position(full).unwrap_or(0), counts.iter().rposition(full).unwrap_or(0))
+        };
+        let ((x0, x1), (y0, y1)) = (span(&columns), span(&rows));
+        (x0, y0, x1, y1)
+    }
+}
+
+// The grid along one axis: wall line `k` covers `origin + k * pitch` to that plus `thickness`
+#[derive(Copy, Clone, Debug)]
+struct Axis {
+    origin: f64,
+    pitch: f64,
+    thickness: f64,
+    cells: usize,
+}
+
+impl Axis {
+    // Span of wall line `k`
+    fn wall(&self, k: usize) -> (f64, f64) {
+        let start = self.origin + (k as f64 * self.pitch);
+        (start, start + self.thickness)
+    }
+
+    // Middle third of cell `k`, clear of the walls on either side
+    fn cell(&self, k: usize) -> (f64, f64) {
+        let (start, len) = (self.origin + (k as f64 * self.pitch) + self.thickness, self.pitch - self.thickness);
+        (start + (len / 3.0), start + (2.0 * len / 3.0))
+    }
+}
+
+fn median(mut values: Vec<usize>) -> Option<usize> {
+    values.sort_unstable();
+    values.get(values.len() / 2).copied()
+}
+
+// Gap widths for the candidate grids, counting every corridor and room; the narrowest corridor is
+// usually but not always the most common, so several are tried
+const CANDIDATE_GAPS: usize = 8;
+
+// What one axis of the maze shows: where its frame starts, how far it reaches and how thick its
+// walls are, and the grids that could explain the walls crossed in between, finest first
+struct Fit {
+    origin: usize,
+    span: usize,
+    thickness: usize,
+    grids: Vec<Axis>,
+}
+
+impl Fit {
+    fn grid(&self, cells: usize) -> Axis {
+        let (span, thickness) = ((self.span - self.thickness) as f64, self.thickness as f64);
+        Axis { origin: self.origin as f64, pitch: span / cells as f64, thickness, cells }
+    }
+
+    // The grid whose cells are as long as `pitch`, for an axis without walls of its own to go by
+    fn square(&self, pitch: f64) -> Axis {
+        self.grid((((self.span - self.thickness) as f64 / pitch).round() as usize).max(1))
+    }
+}
+
+// `lines` are sequences of wall flags running across the maze from one side of its frame to the
+// other. A corridor crosses no walls inside the frame and leaves no grids to choose from.
+fn fit_axis(origin: usize, lines: &[Vec<bool>]) -> Result<Fit, MazeError> {
+    let span = lines.first().map_or(0, |line| line.len());
+    // The frame is the run of wall every line starts with; lines running along a wall are too long
+    let thickness = median(lines.iter().map(|line| line.iter().take_while(|&&w| w).count()).collect()).unwrap_or(0);
+    if thickness == 0 || thickness * 2 >= span {
+        return Err(invalid("no frame around the maze"));
+    }
+    // Gaps between two walls span one or more cells
+    let mut counts = HashMap::new();
+    for line in lines {
+        let mut start = None;
+        for (i, pair) in line.windows(2).enumerate() {
+            match pair {
+                [true, false] => start = Some(i + 1),
+                [false, true] => {
+                    if let Some(start) = start.take() {
+                        *counts.entry(i + 1 - start).or_insert(0) += 1;
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+    let mut gaps: Vec<(usize, usize)> = counts.into_iter().collect();
+    gaps.sort_unstable_by_key(|&(gap, count)| (std::cmp::Reverse(count), gap));
+    // Whole pixel gaps and thicknesses are off by a fraction in scans, which adds up over many
+    // cells, so the neighbouring counts are tried too. Gaps as wide as the whole inside are no
+    // help, and the walls they do cross split it at least in two.
+    let mut cells: Vec<usize> = gaps.iter().take(CANDIDATE_GAPS).filter_map(|&(gap, _)| {
+        let cells = (span - thickness) as f64 / (gap + thickness) as f64;
+        (cells >= 1.5).then(|| (cells.floor() as usize).saturating_sub(1).max(2)..=cells.ceil() as usize + 1)
+    }).flatten().collect();
+    cells.sort_unstable_by(|a, b| b.cmp(a));
+    cells.dedup();
+    let mut fit = Fit { origin, span, thickness, grids: vec![] };
+    fit.grids = cells.into_iter().map(|cells| fit.grid(cells)).collect();
+    Ok(fit)
+}
+
+
+// Whether the rectangle spanning `x0..x1` by `y0..y1` is a wall: a thin line
+// drawn between two pixels only darkens both halfway, so a third of its pixels being walls is enough
+fn sample(mask: &Mask, (x0, x1): (f64, f64), (y0, y1): (f64, f64)) -> bool {
+    // Pixel `p` covers `p..p + 1`; every pixel the span touches counts
+    let pixels = |a: f64, b: f64| (a.floor().max(0.0) as usize, ((b.ceil() - 1.0).max(a.floor())).max(0.0) as usize);
+    let ((x0, x1), (y0, y1)) = (pixels(x0, x1), pixels(y0, y1));
+    let (mut walls, mut count) = (0, 0);
+    for py in y0..=y1 {
+        for px in x0..=x1 {
+            walls += mask.wall(px, py) as usize;
+            count += 1;
+        }
+    }
+    3 * walls >= count
+}
+
+// The grid the wall pixels of `mask` are laid out on, along x and y. Most posts of the grid inside
+// the frame must be walls.
+fn fit(mask: &Mask) -> Result<(Axis, Axis), MazeError> {
+    let (x0, y0, x1, y1) = mask.bounds();
+    let rows: Vec<Vec<bool>> = (y0..=y1).map(|y| (x0..=x1).map(|x| mask.wall(x, y)).collect()).collect();
+    let columns: Vec<Vec<bool>> = (x0..=x1).map(|x| (y0..=y1).map(|y| mask.wall(x, y)).collect()).collect();
+    let (xs, ys) = (fit_axis(x0, &rows)?, fit_axis(y0, &columns)?);
+    // A corridor's cells are taken to be square; with nothing to go by either way, the maze is a
+    // single row or column of them
+    let mut grids: Vec<(Axis, Axis)> = match (xs.grids.is_empty(), ys.grids.is_empty()) {
+        (false, false) => xs.grids.iter().flat_map(|&x| ys.grids.iter().map(move |&y| (x, y))).collect(),
+        (true, false) => ys.grids.iter().map(|&y| (xs.square(y.pitch), y)).collect(),
+        (false, true) => xs.grids.iter().map(|&x| (x, ys.square(x.pitch))).collect(),
+        (true, true) if xs.span <= ys.span => vec![(xs.grid(1), ys.square(xs.grid(1).pitch))],
+        (true, true) => vec![(xs.square(ys.grid(1).pitch), ys.grid(1))],
+    };
+    // Too fine a grid puts posts in the middle of cells, so the finest one that passes is it
+    grids.sort_by_key(|(x, y)| std::cmp::Reverse(x.cells * y.cells));
+    let standing = |(xs, ys): &(Axis, Axis)| {
+        // Posts on the frame stand whatever the grid, so only the ones inside count
+        let posts = (xs.cells - 1) * (ys.cells - 1);
+        let mut fallen = 0;
+        for (c, r) in (1..ys.cells).flat_map(|r| (1..xs.cells).map(move |c| (c, r))) {
+            fallen += !sample(mask, xs.wall(c), ys.wall(r)) as usize;
+            // Wrong grids are mostly given up on early
+            if 10 * fallen > posts {
+                return false;
+            }
+        }
+        true
+    };
+    grids.into_iter().find(standing).ok_or_else(|| invalid("no regular grid of walls found"))
+}
+
+pub fn read_canvas(canvas: &Canvas) -> Result<MazeFile, MazeError> {
+    let (dark, edge_dark) = threshold(canvas)?;
+    // Either class can be the walls: dark lines on paper, or light walls on a dark screen. Both
+    // can fit a grid, one as the other's inverse, but walls are thinner than the corridors between
+    // them, or as thick at most; on a tie the walls are what runs along the image edge.
+    let [first, second] = [edge_dark, !edge_dark].map(|walls_dark| {
+        let mask = Mask { width: canvas.width(), height: canvas.height(), walls: dark.iter().map(|&d| d == walls_dark).collect() };
+        fit(&mask).map(|grid| (mask, grid))
+    });
+    let thickness = |fit: &Result<(Mask, (Axis, Axis)), MazeError>| fit.as_ref().map_or(f64::INFINITY, |(_, (xs, ys))| (xs.thickness / xs.pitch).max(ys.thickness / ys.pitch));
+    let (mask, (xs, ys)) = if first.is_err() || thickness(&second) < thickness(&first) - 0.05 { second.or(first) } else { first }?;
+
+    let mut env = Env::try_new(xs.cells, ys.cells)?;
+    // Wall slots are sampled along the middle third of their length, across their thickness
+    let vertical = |r: usize, k: usize| sample(&mask, xs.wall(k), ys.cell(r));
+    let horizontal = |k: usize, c: usize| sample(&mask, xs.cell(c), ys.wall(k));
+    for cell in env.cells().collect::<Vec<_>>() {
+        if cell.col + 1 < xs.cells && !vertical(cell.row, cell.col + 1) {
+            env.carve(cell, Cell { row: cell.row, col: cell.col + 1 });
+        }
+        if cell.row + 1 < ys.cells && !horizontal(cell.row + 1, cell.col) {
+            env.carve(cell, Cell { row: cell.row + 1, col: cell.col });
+        }
+    }
+
+    // Openings in the frame: along the top, down the left and right sides, then along the bottom
+    let (width, height) = (xs.cells, ys.cells);
+    let top = (0..width).filter(|&c| !horizontal(0, c)).map(|col| Cell { row: 0, col });
+    let sides = (0..height).flat_map(|row| {
+        let left = (!vertical(row, 0)).then_some(Cell { row, col: 0 });
+        let right = (!vertical(row, width)).then_some(Cell { row, col: width - 1 });
+        [left, right].into_iter().flatten()
+    });
+    let bottom = (0..width).filter(|&c| !horizontal(height, c)).map(|col| Cell { row: height - 1, col });
+    let openings: Vec<Cell> = top.chain(sides).chain(bottom).collect();
+    let mut maze = MazeFile::new(env);
+    if let [first, .., last] = openings[..] {
+        (maze.start, maze.goal) = (first, last);
+    }
+    Ok(maze)
+}
+
+pub fn read(bytes: &[u8]) -> Result<MazeFile, MazeError> {
+    read_canvas(&decode(bytes)?)
+}
+
+pub fn load(path: &Path) -> Result<MazeFile, MazeError> {
+    read(&fs::read(path)?)
+}
@@ -0,0 +1,192 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+use crate::analysis::{self, Analysis};
+use crate::batch::maze_seed;
+use crate::progress::Progress;
+use crate::{Cell, Env};
+
+// Numbers of an `analyze --stats` report a search can constrain, named like their JSON keys
+#[derive(Copy, Clone, PartialEq)]
+pub enum Metric {
+    SolutionLength,
+    BranchesOnSolution,
+    DecisionPoints,
+    DeadEnds,
+    DecisionDensity,
+    Score,
+    Corridors,
+    AverageCorridorLength,
+    LongestStraightRun,
+    Diameter,
+    River,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 11] = [
+        Metric::SolutionLength,
+        Metric::BranchesOnSolution,
+        Metric::DecisionPoints,
+        Metric::DeadEnds,
+        Metric::DecisionDensity,
+        Metric::Score,
+        Metric::Corridors,
+        Metric::AverageCorridorLength,
+        Metric::LongestStraightRun,
+        Metric::Diameter,
+        Metric::River,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::SolutionLength => "solution_length",
+            Metric::BranchesOnSolution => "branches_on_solution",
+            Metric::DecisionPoints => "decision_points",
+            Metric::DeadEnds => "dead_ends",
+            Metric::DecisionDensity => "decision_density",
+            Metric::Score => "score",
+            Metric::Corridors => "corridors",
+            Metric::AverageCorridorLength => "average_corridor_length",
+            Metric::LongestStraightRun => "longest_straight_run",
+            Metric::Diameter => "diameter",
+            Metric::River => "river",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|metric| metric.name() == name)
+    }
+
+    // Whether the metric is part of `analysis::stats` rather than the plain analysis
+    fn needs_stats(&self) -> bool {
+        matches!(self, Metric::Corridors | Metric::AverageCorridorLength | Metric::LongestStraightRun | Metric::Diameter | Metric::River)
+    }
+
+    fn value(&self, report: &Analysis) -> f32 {
+        let stats = || report.stats.as_ref().expect("stats are computed when a constraint needs them");
+        match self {
+            Metric::SolutionLength => report.solution_length as f32,
+            Metric::BranchesOnSolution => report.branches_on_solution as f32,
+            Metric::DecisionPoints => report.decision_points as f32,
+            Metric::DeadEnds => report.dead_ends as f32,
+            Metric::DecisionDensity => report.decision_density,
+            Metric::Score => report.score,
+            Metric::Corridors => stats().corridors as f32,
+            Metric::AverageCorridorLength => stats().average_corridor_length,
+            Metric::LongestStraightRun => stats().longest_straight_run as f32,
+            Metric::Diameter => stats().diameter as f32,
+            Metric::River => stats().river,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Comparison {
+    AtLeast,
+    AtMost,
+    Greater,
+    Less,
+    Equal,
+}
+
+// Two-character operators come first so `>=` is not read as `>` followed by `=N`
+const COMPARISONS: [(&str, Comparison); 5] = [
+    (">=", Comparison::AtLeast),
+    ("<=", Comparison::AtMost),
+    (">", Comparison::Greater),
+    ("<", Comparison::Less),
+    ("=", Comparison::Equal),
+];
+
+// One requirement on a maze, like `solution_length>=200`
+#[derive(Copy, Clone)]
+pub struct Constraint {
+    metric: Metric,
+    comparison: Comparison,
+    value: f32,
+}
+
+impl Constraint {
+    // `METRIC>=N`, `METRIC<=N`, `METRIC>N`, `METRIC<N` or `METRIC=N`
+    pub fn parse(text: &str) -> Option<Self> {
+        COMPARISONS.iter().find_map(|&(op, comparison)| {
+            let (name, value) = text.split_once(op)?;
+            Some(Self { metric: Metric::from_name(name.trim())?, comparison, value: value.trim().parse().ok()? })
+        })
+    }
+
+    pub fn holds(&self, report: &Analysis) -> bool {
+        let value = self.metric.value(report);
+        match self.comparison {
+            Comparison::AtLeast => value >= self.value,
+            Comparison::AtMost => value <= self.value,
+            Comparison::Greater => value > self.value,
+            Comparison::Less => value < self.value,
+            Comparison::Equal => value == self.value,
+        }
+    }
+}
+
+// Analysis of `env` solved from the top-left to the bottom-right cell, if it meets every constraint
+pub fn check(env: &Env, constraints: &[Constraint]) -> Option<Analysis> {
+    let goal = Cell { row: env.height - 1, col: env.width - 1 };
+    let mut report = analysis::analyze(env, Cell { row: 0, col: 0 }, goal);
+    if constraints.iter().any(|c| c.metric.needs_stats()) {
+        report.stats = Some(analysis::stats(env));
+    }
+    constraints.iter().all(|c| c.holds(&report)).then_some(report)
+}
+
+pub struct Found {
+    // Attempt number, counted from 1 like the indices of a batch
+    pub attempt: usize,
+    pub seed: u64,
+    pub report: Analysis,
+}
+
+// Tries the seeds `maze_seed(master, 1)` through `maze_seed(master, attempts)` on up to `threads`
+// threads until `matches` accepts one. The lowest accepted attempt wins, whatever the thread
+// count, so a search is reproducible from its master seed. `progress` counts tried seeds.
+pub fn search<F>(attempts: usize, master: u64, threads: usize, progress: &mut dyn Progress, matches: F) -> Option<Found>
+where
+    F: Fn(u64) -> Option<Analysis> + Sync,
+{
+    let next = AtomicUsize::new(1);
+    let best = AtomicUsize::new(usize::MAX);
+    let found: Mutex<Option<Found>> = Mutex::new(None);
+    thread::scope(|scope| {
+        let (next, best, found, matches) = (&next, &best, &found, &matches);
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..threads.clamp(1, attempts.max(1)) {
+            let sender = sender.clone();
+            scope.spawn(move || loop {
+                let attempt = next.fetch_add(1, Ordering::Relaxed);
+                // Attempts after an accepted one can no longer win
+                if attempt > attempts || attempt > best.load(Ordering::Relaxed) {
+                    break;
+                }
+                let seed = maze_seed(master, attempt);
+                if let Some(report) = matches(seed) {
+                    best.fetch_min(attempt, Ordering::Relaxed);
+                    let mut found = found.lock().expect("no thread panics while holding the lock");
+                    if found.as_ref().is_none_or(|f| attempt < f.attempt) {
+                        *found = Some(Found { attempt, seed, report });
+                    }
+                }
+                if sender.send(()).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        progress.update(0, attempts);
+        for (done, ()) in receiver.iter().enumerate() {
+            progress.update(done + 1, attempts);
+        }
+        // A match ends the search early; close the bar anyway
+        progress.update(attempts, attempts);
+    });
+    found.into_inner().expect("no thread panics while holding the lock")
+}
@@ -0,0 +1,167 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::metadata::Metadata;
+use crate::render::raster::{ImageFormat, Overlays, RasterRenderer, Style};
+use crate::render::svg::SvgRenderer;
+use crate::render::text::TextRenderer;
+use crate::render::braille::BrailleRenderer;
+use crate::render::{Renderer, Theme};
+use crate::{gen_maze_with, Env, MAZE_SIZE};
+
+// Requests asking for more than this are refused so a single client cannot exhaust the server
+const MAX_CELLS: usize = 1 << 20;
+const MAX_PIXELS: usize = 1 << 26;
+// Longest request line or header accepted, in bytes
+const MAX_LINE: usize = 8192;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const HELP: &str = "\
+GET /maze?w=40&h=40&seed=7&format=svg
+
+  w, h     width and height in cells (default 10)
+  seed     generator seed; a random one is picked and returned in `X-Maze-Seed` when missing
+  format   png (default), ppm, svg, txt or braille
+  style    blocky (default), rounded or isometric; png/ppm only
+  scale    pixels per logical pixel (default 1); png/ppm only
+  solve    draw the solution when `1` or `true`; png/ppm only
+";
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    seed: Option<u64>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: &'static str, body: &str) -> Self {
+        Self { status, content_type: "text/plain; charset=utf-8", seed: None, body: body.as_bytes().to_vec() }
+    }
+}
+
+fn bad_request(message: &str) -> Response {
+    Response::text("400 Bad Request", &format!("{}\n", message))
+}
+
+fn parse_param<T: std::str::FromStr>(name: &str, value: Option<&str>, default: T) -> Result<T, Response> {
+    match value {
+        None => Ok(default),
+        Some(v) => v.parse().map_err(|_| bad_request(&format!("`{}` expects a non-negative integer", name))),
+    }
+}
+
+// Generates and renders the maze described by the query string of a `/maze` request
+fn maze_response(query: &str) -> Result<Response, Response> {
+    let param = |name: &str| {
+        query.split('&').filter_map(|pair| pair.split_once('=')).find(|(key, _)| *key == name).map(|(_, value)| value)
+    };
+    let width = parse_param("w", param("w"), MAZE_SIZE)?;
+    let height = parse_param("h", param("h"), MAZE_SIZE)?;
+    if width == 0 || height == 0 || width.saturating_mul(height) > MAX_CELLS {
+        return Err(bad_request(&format!("`w` and `h` must be positive with at most {} cells in total", MAX_CELLS)));
+    }
+    let seed = parse_param("seed", param("seed"), rand::random())?;
+    let scale = parse_param("scale", param("scale"), 1)?;
+    let solve = matches!(param("solve"), Some("1" | "true"));
+    let style = match param("style") {
+        None => Style::Blocky,
+        Some(name) => Style::from_name(name).ok_or_else(|| bad_request("`style` expects `blocky`, `rounded` or `isometric`"))?,
+    };
+
+    let mut env = Env::new(width, height);
+    gen_maze_with(&mut env, &mut StdRng::seed_from_u64(seed));
+    let metadata = Metadata::for_maze(&env, Some(seed), Some("dfs"));
+    let (renderer, content_type): (Box<dyn Renderer>, &str) = match param("format").unwrap_or("png") {
+        format @ ("png" | "ppm") => {
+            if solve && style == Style::Isometric {
+                return Err(bad_request("`solve` only works with top-down styles"));
+            }
+            let (format, content_type) = match format {
+                "png" => (ImageFormat::Png, "image/png"),
+                _ => (ImageFormat::Ppm, "image/x-portable-pixmap"),
+            };
+            let overlays = Overlays { solve, ..Overlays::default() };
+            let renderer = RasterRenderer { format, style, scale: scale.max(1), supersample: 1, overlays, metadata };
+            let (w, h) = renderer.image_dims(width, height);
+            if w.saturating_mul(h) > MAX_PIXELS {
+                return Err(bad_request(&format!("the image would be {}x{} px; lower `scale` or the maze size", w, h)));
+            }
+            (Box::new(renderer), content_type)
+        },
+        "svg" => (Box::new(SvgRenderer { metadata, ..SvgRenderer::default() }), "image/svg+xml"),
+        "txt" | "text" => (Box::new(TextRenderer { color: false }), "text/plain; charset=utf-8"),
+        "braille" => (Box::new(BrailleRenderer { color: false }), "text/plain; charset=utf-8"),
+        _ => return Err(bad_request("`format` expects `png`, `ppm`, `svg`, `txt` or `braille`")),
+    };
+
+    let mut body = vec![];
+    renderer
+        .render(&env, &Theme::default(), &mut body)
+        .map_err(|err| Response::text("500 Internal Server Error", &format!("{}\n", err)))?;
+    Ok(Response { status: "200 OK", content_type, seed: Some(seed), body })
+}
+
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.by_ref().take(MAX_LINE as u64).read_line(&mut line)?;
+    if !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "request line too long or truncated"));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn handle(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let request = read_line(&mut reader)?;
+    // Headers are not needed; skip them so the client sees its request fully read
+    while !read_line(&mut reader)?.is_empty() {}
+
+    let mut parts = request.split(' ');
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let response = match (method, path) {
+        ("GET", "/maze") => maze_response(query).unwrap_or_else(|err| err),
+        ("GET", "/") => Response::text("200 OK", HELP),
+        ("GET", _) => Response::text("404 Not Found", "not found; try GET /\n"),
+        _ => Response::text("405 Method Not Allowed", "only GET is supported\n"),
+    };
+    eprintln!("{} {} -> {}", method, target, response.status);
+
+    let mut out = &stream;
+    write!(out, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n", response.status, response.content_type, response.body.len())?;
+    if let Some(seed) = response.seed {
+        write!(out, "X-Maze-Seed: {}\r\n", seed)?;
+    }
+    out.write_all(b"\r\n")?;
+    out.write_all(&response.body)?;
+    out.flush()
+}
+
+// Serves mazes over HTTP on `addr` until the process is killed, one thread per connection.
+// `GET /` describes the query parameters of `GET /maze`.
+pub fn serve(addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("Serving mazes on http://{}/maze", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("ERROR: Failed to accept connection: {}", err);
+                continue;
+            },
+        };
+        thread::spawn(move || {
+            if let Err(err) = handle(stream) {
+                eprintln!("ERROR: Failed to answer request: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,349 @@
+// Mazes whose solution traces a shape: the dark part of a small picture, or a line drawing given
+// as a list of points. The shape is scaled to fit inside the maze, its path is fixed, and
+// `fixed::gen_maze_fixed` carves the rest around it, with leads from the top-left start to the
+// shape and from the shape to the bottom-right goal.
+//
+// A picture is traced the way `word` writes letters: every pixel is a 2x2 block of cells and the
+// path loops around a spanning tree of the pixels, so it fills the shape in. Parts of the picture
+// that do not touch are bridged first. A line drawing is followed step by step; where it crosses
+// or comes back to itself the path goes around the cells it already took.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use crate::draw::Canvas;
+use crate::error::MazeError;
+use crate::fixed::Fixed;
+use crate::scan;
+use crate::Cell;
+
+pub enum Shape {
+    // Pixels of a picture that belong to the shape, row by row
+    Bitmap { width: usize, height: usize, pixels: Vec<bool> },
+    // Points of a line drawing, x to the right and y down, joined in order
+    Polyline(Vec<(f64, f64)>),
+}
+
+fn invalid(message: &str) -> MazeError {
+    MazeError::InvalidData(String::from(message))
+}
+
+impl Shape {
+    // Pictures are recognised by `scan::EXTENSIONS`; anything else is read as a line drawing
+    pub fn load(path: &Path) -> Result<Self, MazeError> {
+        let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).unwrap_or_default();
+        if scan::EXTENSIONS.contains(&extension.as_str()) {
+            Self::from_canvas(&scan::decode(&fs::read(path)?)?)
+        } else {
+            Self::parse_polyline(&fs::read_to_string(path)?)
+        }
+    }
+
+    // The shape is whichever class of pixels does not make up the image border, so both dark
+    // drawings on white and light ones on black work
+    pub fn from_canvas(canvas: &Canvas) -> Result<Self, MazeError> {
+        let (dark, edge_dark) = scan::threshold(canvas)?;
+        Ok(Shape::Bitmap { width: canvas.width(), height: canvas.height(), pixels: dark.iter().map(|&d| d != edge_dark).collect() })
+    }
+
+    // One point per line as `x y` or `x,y`; `#` starts a comment
+    pub fn parse_polyline(text: &str) -> Result<Self, MazeError> {
+        let mut points = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let numbers: Vec<f64> = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()).map(|s| s.parse::<f64>()).collect::<Result<_, _>>()
+                .map_err(|_| MazeError::InvalidData(format!("line {}: expected a point as `x y`", i + 1)))?;
+            match numbers[..] {
+                [x, y] if x.is_finite() && y.is_finite() => points.push((x, y)),
+                _ => return Err(MazeError::InvalidData(format!("line {}: expected a point as `x y`", i + 1))),
+            }
+        }
+        Ok(Shape::Polyline(points))
+    }
+}
+
+// Cells of the loop around a depth-first spanning tree of the set `pixels` (`width` per row) that
+// are joined to `entry`, each pixel being a 2x2 block of cells. As (row, col) from the top-left
+// block, starting at the bottom-left cell of `entry` and ending at its bottom-right one, so
+// `entry` must have no pixel below it.
+pub(crate) fn loop_around(pixels: &[bool], width: usize, entry: (usize, usize)) -> Vec<(usize, usize)> {
+    let height = pixels.len() / width;
+    let set = |r: usize, c: usize| r < height && c < width && pixels[(r * width) + c];
+    // `down[i]`/`right[i]` link pixel `i` to the one below/to its right
+    let (mut down, mut right, mut seen) = (vec![false; pixels.len()], vec![false; pixels.len()], vec![false; pixels.len()]);
+    seen[(entry.0 * width) + entry.1] = true;
+    let mut stack = vec![entry];
+    while let Some((r, c)) = stack.pop() {
+        for (nr, nc) in [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)] {
+            if !set(nr, nc) || seen[(nr * width) + nc] {
+                continue;
+            }
+            seen[(nr * width) + nc] = true;
+            if nr != r {
+                down[(r.min(nr) * width) + c] = true;
+            } else {
+                right[(r * width) + c.min(nc)] = true;
+            }
+            stack.push((nr, nc));
+        }
+    }
+
+    // Every cell links to one cell across each of its pixel's sides: its neighbour in the same
+    // pixel or, where a tree edge leaves that side, the facing cell in the next pixel
+    let columns = 2 * width;
+    let mut links = vec![vec![]; 4 * pixels.len()];
+    let mut link = |a: (usize, usize), b: (usize, usize)| {
+        links[(a.0 * columns) + a.1].push(b);
+        links[(b.0 * columns) + b.1].push(a);
+    };
+    for i in (0..pixels.len()).filter(|&i| seen[i]) {
+        let (r, c) = (i / width, i % width);
+        let (top, left) = (2 * r, 2 * c);
+        let (bottom, right_col) = (top + 1, left + 1);
+        if !(r > 0 && down[i - width]) {
+            link((top, left), (top, right_col));
+        }
+        if down[i] {
+            link((bottom, left), (bottom + 1, left));
+            link((bottom, right_col), (bottom + 1, right_col));
+        } else {
+            link((bottom, left), (bottom, right_col));
+        }
+        if !(c > 0 && right[i - 1]) {
+            link((top, left), (bottom, left));
+        }
+        if right[i] {
+            link((top, right_col), (top, right_col + 1));
+            link((bottom, right_col), (bottom, right_col + 1));
+        } else {
+            link((top, right_col), (bottom, right_col));
+        }
+    }
+
+    // Walk the loop from the entry's bottom-left cell, away from its bottom-right one
+    let first = ((2 * entry.0) + 1, 2 * entry.1);
+    let last = (first.0, first.1 + 1);
+    let mut cells = vec![first];
+    let (mut prev, mut current) = (last, first);
+    while current != last {
+        let next = *links[(current.0 * columns) + current.1].iter().find(|&&n| n != prev).expect("every cell of the loop has two links");
+        cells.push(next);
+        (prev, current) = (current, next);
+    }
+    cells
+}
+
+// Breadth-first route from `from` to `to` through cells that are not `blocked`, `to` itself
+// excepted. The cells after `from`, up to and including `to`.
+fn route(width: usize, height: usize, blocked: &[bool], from: Cell, to: Cell) -> Option<Vec<Cell>> {
+    let index = |c: Cell| (c.row * width) + c.col;
+    let mut parent = vec![usize::MAX; width * height];
+    parent[index(from)] = index(from);
+    let mut queue = VecDeque::from([from]);
+    while let Some(cell) = queue.pop_front() {
+        if cell == to {
+            let mut cells = vec![];
+            let mut i = index(to);
+            while i != index(from) {
+                cells.push(Cell { row: i / width, col: i % width });
+                i = parent[i];
+            }
+            cells.reverse();
+            return Some(cells);
+        }
+        let (row, col) = (cell.row, cell.col);
+        let next = [(row.wrapping_sub(1), col), (row + 1, col), (row, col.wrapping_sub(1)), (row, col + 1)];
+        for next in next.into_iter().filter(|&(r, c)| r < height && c < width).map(|(row, col)| Cell { row, col }) {
+            if parent[index(next)] == usize::MAX && (!blocked[index(next)] || next == to) {
+                parent[index(next)] = index(cell);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+// Joins every part of the set `pixels` to the first one by setting the pixels along the shortest
+// gaps between them
+pub(crate) fn bridge(pixels: &mut [bool], width: usize) {
+    let height = pixels.len() / width;
+    let neighbours = move |i: usize| {
+        let (r, c) = (i / width, i % width);
+        [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)].into_iter().filter(move |&(r, c)| r < height && c < width).map(move |(r, c)| (r * width) + c)
+    };
+    let Some(first) = pixels.iter().position(|&p| p) else { return };
+    loop {
+        // The part joined so far
+        let mut joined = vec![false; pixels.len()];
+        joined[first] = true;
+        let mut stack = vec![first];
+        while let Some(i) = stack.pop() {
+            for n in neighbours(i) {
+                if pixels[n] && !joined[n] {
+                    joined[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        // Out from it to the nearest pixel of another part
+        let mut parent = vec![usize::MAX; pixels.len()];
+        let mut queue: VecDeque<usize> = (0..pixels.len()).filter(|&i| joined[i]).collect();
+        for &i in queue.iter() {
+            parent[i] = i;
+        }
+        let mut found = None;
+        while let Some(i) = queue.pop_front() {
+            if pixels[i] && !joined[i] {
+                found = Some(i);
+                break;
+            }
+            for n in neighbours(i) {
+                if parent[n] == usize::MAX {
+                    parent[n] = i;
+                    queue.push_back(n);
+                }
+            }
+        }
+        let Some(mut i) = found else { return };
+        while !joined[i] {
+            pixels[i] = true;
+            i = parent[i];
+        }
+    }
+}
+
+// The picture's shape cropped and scaled to fit `columns`x`rows` pixels, keeping its aspect
+fn fit_bitmap(width: usize, pixels: &[bool], columns: usize, rows: usize) -> Result<(Vec<bool>, usize), MazeError> {
+    let height = pixels.len() / width;
+    let set: Vec<usize> = (0..pixels.len()).filter(|&i| pixels[i]).collect();
+    if set.is_empty() {
+        return Err(invalid("the picture has no shape in it"));
+    }
+    let (x0, x1) = (set.iter().map(|&i| i % width).min().unwrap_or(0), set.iter().map(|&i| i % width).max().unwrap_or(0));
+    let (y0, y1) = (set.iter().map(|&i| i / width).min().unwrap_or(0), set.iter().map(|&i| i / width).max().unwrap_or(0));
+    let (w, h) = (x1 + 1 - x0, y1 + 1 - y0);
+    let scale = (columns as f64 / w as f64).min(rows as f64 / h as f64);
+    let (fw, fh) = (((w as f64 * scale).round() as usize).clamp(1, columns), ((h as f64 * scale).round() as usize).clamp(1, rows));
+    // A pixel belongs to the shape if any picture pixel under it does, so thin lines survive
+    // shrinking
+    let mut fitted = vec![false; fw * fh];
+    for (i, pixel) in fitted.iter_mut().enumerate() {
+        let (x, y) = (i % fw, i / fw);
+        let (sx0, sx1) = (x0 + (x * w / fw), x0 + ((x + 1) * w).div_ceil(fw));
+        let (sy0, sy1) = (y0 + (y * h / fh), y0 + ((y + 1) * h).div_ceil(fh));
+        *pixel = (sy0..sy1.min(height)).any(|sy| (sx0..sx1.min(width)).any(|sx| pixels[(sy * width) + sx]));
+    }
+    Ok((fitted, fw))
+}
+
+// Cells of a `from`-`to` line, `from` excluded, each a step up, down, left or right from the one
+// before and all as close to the line as they can be
+fn line(from: Cell, to: Cell) -> Vec<Cell> {
+    let (dx, dy) = (to.col as f64 - from.col as f64, to.row as f64 - from.row as f64);
+    let length = (dx * dx + dy * dy).sqrt();
+    let mut cells = vec![];
+    let mut current = from;
+    while current != to {
+        let horizontal = Cell { row: current.row, col: if to.col > current.col { current.col + 1 } else { current.col.saturating_sub(1) } };
+        let vertical = Cell { row: if to.row > current.row { current.row + 1 } else { current.row.saturating_sub(1) }, col: current.col };
+        // Distance of a cell from the line
+        let off = |c: Cell| ((c.col as f64 - from.col as f64) * dy - (c.row as f64 - from.row as f64) * dx).abs() / length;
+        current = match (current.col != to.col, current.row != to.row) {
+            (true, true) if off(vertical) < off(horizontal) => vertical,
+            (true, _) => horizontal,
+            _ => vertical,
+        };
+        cells.push(current);
+    }
+    cells
+}
+
+// The line drawing scaled to fit the cells inside a one-cell margin of a `width`x`height` maze,
+// followed as a path that never visits a cell twice
+fn follow(points: &[(f64, f64)], width: usize, height: usize) -> Result<Vec<Cell>, MazeError> {
+    let (xs, ys) = (points.iter().map(|p| p.0), points.iter().map(|p| p.1));
+    let (min_x, max_x) = (xs.clone().fold(f64::INFINITY, f64::min), xs.fold(f64::NEG_INFINITY, f64::max));
+    let (min_y, max_y) = (ys.clone().fold(f64::INFINITY, f64::min), ys.fold(f64::NEG_INFINITY, f64::max));
+    if points.len() < 2 || (max_x == min_x && max_y == min_y) {
+        return Err(invalid("the line drawing needs at least two different points"));
+    }
+    let (span_x, span_y) = ((width - 3) as f64, (height - 3) as f64);
+    let scale = (span_x / (max_x - min_x)).min(span_y / (max_y - min_y));
+    let (pad_x, pad_y) = ((span_x - (max_x - min_x) * scale) / 2.0, (span_y - (max_y - min_y) * scale) / 2.0);
+    let cells: Vec<Cell> = points.iter().map(|&(x, y)| Cell {
+        row: 1 + (((y - min_y) * scale) + pad_y).round() as usize,
+        col: 1 + (((x - min_x) * scale) + pad_x).round() as usize,
+    }).collect();
+
+    // The margin stays free for the leads to the start and goal
+    let mut blocked: Vec<bool> = (0..width * height).map(|i| i / width == 0 || i / width == height - 1 || i % width == 0 || i % width == width - 1).collect();
+    let mut path = vec![cells[0]];
+    blocked[(cells[0].row * width) + cells[0].col] = true;
+    for cell in cells.windows(2).flat_map(|pair| line(pair[0], pair[1])) {
+        if blocked[(cell.row * width) + cell.col] {
+            continue;
+        }
+        // Past cells taken before, the path goes around them to where the drawing continues
+        let last = *path.last().expect("starts with a cell");
+        let steps = if last.row.abs_diff(cell.row) + last.col.abs_diff(cell.col) == 1 { Some(vec![cell]) } else { route(width, height, &blocked, last, cell) };
+        let Some(steps) = steps else { continue };
+        for step in steps {
+            blocked[(step.row * width) + step.col] = true;
+            path.push(step);
+        }
+    }
+    Ok(path)
+}
+
+// `core` with leads from the top-left cell to its first cell and from its last cell to the
+// bottom-right one, trying it backwards if the first lead leaves no way through for the second
+fn lead_in_and_out(core: Vec<Cell>, width: usize, height: usize) -> Result<Vec<Cell>, MazeError> {
+    let (start, goal) = (Cell { row: 0, col: 0 }, Cell { row: height - 1, col: width - 1 });
+    for core in [core.clone(), core.into_iter().rev().collect()] {
+        let mut blocked = vec![false; width * height];
+        for cell in core.iter() {
+            blocked[(cell.row * width) + cell.col] = true;
+        }
+        let Some(lead) = route(width, height, &blocked, start, core[0]) else { continue };
+        for cell in lead.iter() {
+            blocked[(cell.row * width) + cell.col] = true;
+        }
+        if let Some(tail) = route(width, height, &blocked, *core.last().expect("never empty"), goal) {
+            return Ok([start].into_iter().chain(lead).chain(core.into_iter().skip(1)).chain(tail).collect());
+        }
+    }
+    Err(invalid("the shape leaves no way from the start past it to the goal"))
+}
+
+// The solution path of a `width`x`height` maze tracing `shape`, from the top-left cell to the
+// bottom-right one
+pub fn path(shape: &Shape, width: usize, height: usize) -> Result<Vec<Cell>, MazeError> {
+    if width < 5 || height < 5 {
+        return Err(invalid("tracing a shape needs a maze of at least 5x5"));
+    }
+    let core = match shape {
+        Shape::Bitmap { width: picture_width, pixels, .. } => {
+            // Every pixel takes 2x2 cells, inside a one-cell margin
+            let (mut fitted, columns) = fit_bitmap(*picture_width, pixels, (width - 2) / 2, (height - 2) / 2)?;
+            bridge(&mut fitted, columns);
+            let rows = fitted.len() / columns;
+            // Into the shape from below its lowest, leftmost pixel
+            let entry = (0..fitted.len()).rev().find(|&i| fitted[i]).map(|i| i / columns).expect("fitted shapes are never empty");
+            let entry = (entry, (0..columns).find(|&c| fitted[(entry * columns) + c]).expect("the row has a pixel"));
+            let (top, left) = (1 + ((height - 2 - (2 * rows)) / 2), 1 + ((width - 2 - (2 * columns)) / 2));
+            loop_around(&fitted, columns, entry).into_iter().map(|(r, c)| Cell { row: top + r, col: left + c }).collect()
+        },
+        Shape::Polyline(points) => follow(points, width, height)?,
+    };
+    lead_in_and_out(core, width, height)
+}
+
+// Passages to carve a maze around so that its solution traces `shape`; see `fixed::gen_maze_fixed`
+pub fn fixed(shape: &Shape, width: usize, height: usize) -> Result<Fixed, MazeError> {
+    let path = path(shape, width, height)?;
+    Ok(Fixed { passages: path.windows(2).map(|pair| (pair[0], pair[1])).collect(), walls: vec![] })
+}
@@ -0,0 +1,97 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use crate::draw::{blend, Canvas};
+
+// Palette entries terminals reliably support
+const MAX_COLORS: usize = 256;
+// Levels per channel of the color cube used when an image has more colors than the palette holds
+const CUBE_LEVELS: u32 = 6;
+
+// Rough size in bytes of `write_sixel`'s output for a `width`x`height` canvas of a plain maze: two
+// colors per six-pixel band, with at most one byte per column each
+pub fn estimated_size(width: usize, height: usize) -> usize {
+    64 + (2 * (width + 4) * height.div_ceil(6))
+}
+
+// Nearest color of the `CUBE_LEVELS`^3 cube
+fn cube(rgb: u32) -> u32 {
+    let step = 255 / (CUBE_LEVELS - 1);
+    let level = |shift: u32| ((((rgb >> shift) & 0xFF) + (step / 2)) / step) * step;
+    (level(16) << 16) | (level(8) << 8) | level(0)
+}
+
+// Palette of 0xRRGGBB colors and the palette index of every pixel, `None` where the pixel is fully
+// transparent. Translucent pixels are composited onto black first.
+fn quantize(canvas: &Canvas) -> (Vec<u32>, Vec<Option<u8>>) {
+    let rgb: Vec<Option<u32>> = canvas.pixels().iter().map(|&p| (p & 0xFF != 0).then(|| blend(0x000000FF, p) >> 8)).collect();
+    let distinct = rgb.iter().flatten().collect::<HashSet<_>>().len();
+    let reduce = |c: u32| if distinct > MAX_COLORS { cube(c) } else { c };
+
+    let (mut palette, mut index) = (vec![], HashMap::new());
+    let indices = rgb
+        .iter()
+        .map(|c| {
+            let c = reduce((*c)?);
+            Some(*index.entry(c).or_insert_with(|| {
+                palette.push(c);
+                (palette.len() - 1) as u8
+            }))
+        })
+        .collect();
+    (palette, indices)
+}
+
+// Encodes the canvas as a DEC sixel image: one palette register per color, then every six-pixel
+// band drawn one color at a time with run-length compression. Fully transparent pixels are left
+// unpainted so the terminal background shows through.
+pub fn write_sixel(out: &mut dyn Write, canvas: &Canvas) -> io::Result<()> {
+    let (width, height) = (canvas.width(), canvas.height());
+    let (palette, indices) = quantize(canvas);
+    let mut bytes = format!("\x1bP0;1;0q\"1;1;{};{}", width, height).into_bytes();
+    let percent = |c: u32, shift: u32| (((c >> shift) & 0xFF) * 100 + 127) / 255;
+    for (i, c) in palette.iter().enumerate() {
+        bytes.extend_from_slice(format!("#{};2;{};{};{}", i, percent(*c, 16), percent(*c, 8), percent(*c, 0)).as_bytes());
+    }
+
+    let run = |bytes: &mut Vec<u8>, glyph: u8, count: usize| {
+        if count > 3 {
+            bytes.extend_from_slice(format!("!{}", count).as_bytes());
+            bytes.push(glyph);
+        } else {
+            bytes.extend(std::iter::repeat_n(glyph, count));
+        }
+    };
+    for top in (0..height).step_by(6) {
+        let rows = 6.min(height - top);
+        let mut present = vec![false; palette.len()];
+        for i in indices[top * width..(top + rows) * width].iter().flatten() {
+            present[*i as usize] = true;
+        }
+        let mut first = true;
+        for color in (0..palette.len()).filter(|c| present[*c]) {
+            if !first {
+                // Back to the start of the band to overlay the next color
+                bytes.push(b'$');
+            }
+            first = false;
+            bytes.extend_from_slice(format!("#{}", color).as_bytes());
+            let (mut glyph, mut count) = (0u8, 0);
+            for x in 0..width {
+                let bits = (0..rows).filter(|dy| indices[(top + dy) * width + x] == Some(color as u8)).fold(0, |acc, dy| acc | (1 << dy));
+                let next = 63 + bits;
+                if next != glyph && count > 0 {
+                    run(&mut bytes, glyph, count);
+                    count = 0;
+                }
+                glyph = next;
+                count += 1;
+            }
+            run(&mut bytes, glyph, count);
+        }
+        bytes.push(b'-');
+    }
+    bytes.extend_from_slice(b"\x1b\\");
+    out.write_all(&bytes)?;
+    out.flush()
+}
@@ -0,0 +1,185 @@
+// Surveys of the generators: many mazes from each, carved from the same seeds, and how their
+// solution lengths and dead ends are spread, as CSV or JSON and as a histogram image, so generators
+// can be compared by the numbers rather than by eye.
+
+use std::sync::Mutex;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::analysis;
+use crate::batch;
+use crate::draw::{fill_rect, Canvas};
+use crate::error::MazeError;
+use crate::font::{self, GLYPH_HEIGHT};
+use crate::json;
+use crate::manifest::Generator;
+use crate::progress::Progress;
+use crate::{Cell, Env};
+
+// Mazes per generator when `--count` is not given
+pub const DEFAULT_COUNT: usize = 100;
+
+// How a set of values is spread
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub min: usize,
+    // 10th percentile
+    pub p10: usize,
+    pub median: usize,
+    pub mean: f64,
+    pub p90: usize,
+    pub max: usize,
+    pub stddev: f64,
+}
+
+impl Summary {
+    // Percentiles are the nearest rank; `values` must not be empty
+    pub fn of(values: &[usize]) -> Summary {
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        let rank = |p: usize| sorted[((p * sorted.len()).div_ceil(100)).clamp(1, sorted.len()) - 1];
+        let mean = sorted.iter().sum::<usize>() as f64 / sorted.len() as f64;
+        let variance = sorted.iter().map(|&v| (v as f64 - mean).powi(2)).sum::<f64>() / sorted.len() as f64;
+        Summary { min: sorted[0], p10: rank(10), median: rank(50), mean, p90: rank(90), max: sorted[sorted.len() - 1], stddev: variance.sqrt() }
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"min\":{},\"p10\":{},\"median\":{},\"mean\":{:.4},\"p90\":{},\"max\":{},\"stddev\":{:.4}}}",
+            self.min, self.p10, self.median, self.mean, self.p90, self.max, self.stddev
+        )
+    }
+}
+
+// The mazes of one generator, in seed order
+pub struct Sample {
+    pub generator: Generator,
+    // Cells on the shortest path from the top-left to the bottom-right cell, 0 where there is none,
+    // as in `analysis::analyze`
+    pub solution_lengths: Vec<usize>,
+    pub dead_ends: Vec<usize>,
+}
+
+pub struct Survey {
+    pub width: usize,
+    pub height: usize,
+    // Maze `i` of every generator is carved from `batch::maze_seed(master, i)`
+    pub master: u64,
+    pub samples: Vec<Sample>,
+}
+
+// Carves `count` `width` x `height` mazes with each of `generators` on up to `threads` threads and
+// measures them. `progress` counts finished mazes over all generators.
+pub fn run(generators: &[Generator], width: usize, height: usize, count: usize, master: u64, threads: usize, progress: &mut dyn Progress) -> Result<Survey, MazeError> {
+    let measured = Mutex::new(vec![(0, 0); generators.len() * count]);
+    let goal = Cell { row: height - 1, col: width - 1 };
+    batch::run(generators.len() * count, master, threads, progress, |index, _| {
+        // Jobs run generator by generator, and every generator gets the same seeds
+        let (generator, i) = ((index - 1) / count, ((index - 1) % count) + 1);
+        let mut env = Env::try_new(width, height)?;
+        generators[generator].carve(&mut env, &mut StdRng::seed_from_u64(batch::maze_seed(master, i)))?;
+        let solution_length = analysis::analyze(&env, Cell { row: 0, col: 0 }, goal).solution_length;
+        measured.lock().expect("no job panics while holding the lock")[index - 1] = (solution_length, env.dead_ends().count());
+        Ok(())
+    })?;
+    let measured = measured.into_inner().expect("no job panics while holding the lock");
+    let samples = generators
+        .iter()
+        .zip(measured.chunks(count))
+        .map(|(&generator, mazes)| Sample {
+            generator,
+            solution_lengths: mazes.iter().map(|&(length, _)| length).collect(),
+            dead_ends: mazes.iter().map(|&(_, dead_ends)| dead_ends).collect(),
+        })
+        .collect();
+    Ok(Survey { width, height, master, samples })
+}
+
+impl Survey {
+    // One row per generator and metric
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("algorithm,metric,mazes,min,p10,median,mean,p90,max,stddev\n");
+        for sample in self.samples.iter() {
+            for (metric, values) in [("solution_length", &sample.solution_lengths), ("dead_ends", &sample.dead_ends)] {
+                let s = Summary::of(values);
+                out += &format!(
+                    "{},{},{},{},{},{},{:.4},{},{},{:.4}\n",
+                    sample.generator.name(), metric, values.len(), s.min, s.p10, s.median, s.mean, s.p90, s.max, s.stddev
+                );
+            }
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let algorithms: Vec<String> = self
+            .samples
+            .iter()
+            .map(|sample| {
+                format!(
+                    "{{\"name\":\"{}\",\"mazes\":{},\"solution_length\":{},\"dead_ends\":{}}}",
+                    json::escape(sample.generator.name()),
+                    sample.solution_lengths.len(),
+                    Summary::of(&sample.solution_lengths).to_json(),
+                    Summary::of(&sample.dead_ends).to_json()
+                )
+            })
+            .collect();
+        format!("{{\"width\":{},\"height\":{},\"master_seed\":{},\"algorithms\":[{}]}}\n", self.width, self.height, self.master, algorithms.join(","))
+    }
+
+    // Solution-length histograms, one row per generator over the same bins so they line up, each
+    // labelled with the generator's name and mean
+    pub fn histogram(&self) -> Canvas {
+        let values = || self.samples.iter().flat_map(|sample| sample.solution_lengths.iter().copied());
+        let (lo, hi) = (values().min().unwrap_or(0), values().max().unwrap_or(0));
+        let bins = (hi - lo + 1).min(HISTOGRAM_BINS);
+        let bin = |v: usize| ((v - lo) * bins) / (hi - lo + 1);
+        let counts: Vec<Vec<usize>> = self
+            .samples
+            .iter()
+            .map(|sample| {
+                let mut counts = vec![0; bins];
+                for &v in sample.solution_lengths.iter() {
+                    counts[bin(v)] += 1;
+                }
+                counts
+            })
+            .collect();
+        let tallest = counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+        let label = GLYPH_HEIGHT + HISTOGRAM_PAD;
+        let row = label + HISTOGRAM_BAR_HEIGHT + HISTOGRAM_PAD;
+        let width = (2 * HISTOGRAM_PAD) + (bins * HISTOGRAM_BAR_WIDTH);
+        let height = HISTOGRAM_PAD + (self.samples.len() * row) + label;
+        let mut canvas = Canvas::new(width, height, HISTOGRAM_BACKGROUND);
+        for (i, (sample, counts)) in self.samples.iter().zip(counts.iter()).enumerate() {
+            let top = HISTOGRAM_PAD + (i * row);
+            let mean = Summary::of(&sample.solution_lengths).mean;
+            font::draw_text(&mut canvas, HISTOGRAM_PAD, top, &format!("{} mean {:.1}", sample.generator.name(), mean), 1, HISTOGRAM_TEXT);
+            let base = top + label + HISTOGRAM_BAR_HEIGHT;
+            let color = HISTOGRAM_COLORS[i % HISTOGRAM_COLORS.len()];
+            for (b, &count) in counts.iter().enumerate() {
+                let bar = (count * HISTOGRAM_BAR_HEIGHT).div_ceil(tallest);
+                fill_rect(&mut canvas, HISTOGRAM_PAD + (b * HISTOGRAM_BAR_WIDTH), base - bar, HISTOGRAM_BAR_WIDTH - 1, bar, color);
+            }
+            fill_rect(&mut canvas, HISTOGRAM_PAD, base, bins * HISTOGRAM_BAR_WIDTH, 1, HISTOGRAM_TEXT);
+        }
+        // The range the bins cover, under the last row
+        let (first, last) = (lo.to_string(), hi.to_string());
+        let y = height - GLYPH_HEIGHT - HISTOGRAM_PAD;
+        font::draw_text(&mut canvas, HISTOGRAM_PAD, y, &first, 1, HISTOGRAM_TEXT);
+        font::draw_text(&mut canvas, (width - HISTOGRAM_PAD).saturating_sub(font::text_width(&last)), y, &last, 1, HISTOGRAM_TEXT);
+        canvas.upscale(HISTOGRAM_SCALE)
+    }
+}
+
+const HISTOGRAM_BINS: usize = 24;
+const HISTOGRAM_BAR_WIDTH: usize = 6;
+const HISTOGRAM_BAR_HEIGHT: usize = 40;
+const HISTOGRAM_PAD: usize = 4;
+const HISTOGRAM_SCALE: usize = 3;
+const HISTOGRAM_BACKGROUND: u32 = 0xFFFFFFFF;
+const HISTOGRAM_TEXT: u32 = 0x202020FF;
+const HISTOGRAM_COLORS: [u32; 6] = [0x2856A1FF, 0xE04040FF, 0x32A852FF, 0xF0A030FF, 0x8040C0FF, 0x40A0C0FF];
@@ -0,0 +1,43 @@
+// Output filename templates. Placeholders in a template are replaced by the values of the maze
+// being saved, so `maze_{algo}_{w}x{h}_{seed}.png` becomes `maze_dfs_40x30_1234.png`:
+//
+//   {algo}        generator that carved the maze (`dfs`, or `parallel-dfs` with `--threads`)
+//   {w}, {h}      width and height in cells
+//   {seed}        seed the maze was generated from
+//   {timestamp}   seconds since the Unix epoch when the run started
+//   {index}       position in a batch, zero-padded to the width of the batch size
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Values for the placeholders; ones that are `None` are left in the name as they are
+pub struct Fields<'a> {
+    pub algo: &'a str,
+    pub width: usize,
+    pub height: usize,
+    pub seed: Option<u64>,
+    pub timestamp: u64,
+    // Index of the maze in a batch, and the batch size
+    pub index: Option<(usize, usize)>,
+}
+
+impl Fields<'_> {
+    pub fn expand(&self, template: &str) -> String {
+        let mut name = template
+            .replace("{algo}", self.algo)
+            .replace("{w}", &self.width.to_string())
+            .replace("{h}", &self.height.to_string())
+            .replace("{timestamp}", &self.timestamp.to_string());
+        if let Some(seed) = self.seed {
+            name = name.replace("{seed}", &seed.to_string());
+        }
+        if let Some((index, count)) = self.index {
+            name = name.replace("{index}", &format!("{:0width$}", index, width = count.to_string().len()));
+        }
+        name
+    }
+}
+
+// Seconds since the Unix epoch, or 0 if the clock is set before it
+pub fn timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
@@ -0,0 +1,66 @@
+// Mazes inside the letterforms of some text. The text is drawn with `font`'s glyphs, every glyph
+// pixel becomes a square block of cells and every cell outside the letters is left out of the maze
+// (see `Env::exclude`), so the corridors spell the text. Letters that do not touch, and pixels that
+// only meet at a corner, are bridged the way `shape` bridges a picture, and so are the top-left
+// start and bottom-right goal, so the region stays connected.
+
+use crate::draw::Canvas;
+use crate::error::MazeError;
+use crate::font::{self, GLYPH_HEIGHT};
+use crate::shape::bridge;
+use crate::{Cell, Env};
+
+// Fewest cells along each side of a glyph pixel, so corner-to-corner pixels stay readable once bridged
+const MIN_BLOCK: usize = 2;
+
+// Pixels of `text` in `font`'s glyphs, row by row, and the number per row
+fn rasterize(text: &str) -> Result<(Vec<bool>, usize), MazeError> {
+    if let Some(ch) = text.chars().find(|&ch| !font::can_draw(ch)) {
+        return Err(MazeError::InvalidData(format!("`{}` has no glyph in the built-in font", ch)));
+    }
+    let width = font::text_width(text);
+    if width == 0 {
+        return Err(MazeError::InvalidData(String::from("the text is empty")));
+    }
+    let mut canvas = Canvas::new(width, GLYPH_HEIGHT, 0);
+    font::draw_text(&mut canvas, 0, 0, text, 1, 1);
+    let pixels: Vec<bool> = (0..GLYPH_HEIGHT).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| canvas.get(x, y) == Some(1)).collect();
+    if !pixels.contains(&true) {
+        return Err(MazeError::InvalidData(String::from("the text has nothing to draw")));
+    }
+    Ok((pixels, width))
+}
+
+// Smallest maze `text` fits in, with a one-cell margin around it for the leads to the start and goal
+pub fn min_size(text: &str) -> (usize, usize) {
+    ((font::text_width(text) * MIN_BLOCK) + 2, (GLYPH_HEIGHT * MIN_BLOCK) + 2)
+}
+
+// Leaves every cell of `env` outside `text`, drawn as large as it fits and centered, out of the
+// maze. `env` should not be carved yet.
+pub fn mask(env: &mut Env, text: &str) -> Result<(), MazeError> {
+    let (pixels, columns) = rasterize(text)?;
+    let (width, height) = (env.width, env.height);
+    let block = (width.saturating_sub(2) / columns).min(height.saturating_sub(2) / GLYPH_HEIGHT);
+    if block < MIN_BLOCK {
+        let (min_width, min_height) = min_size(text);
+        return Err(MazeError::InvalidData(format!("`{}` needs a maze of at least {}x{}", text, min_width, min_height)));
+    }
+
+    let (top, left) = ((height - (GLYPH_HEIGHT * block)) / 2, (width - (columns * block)) / 2);
+    let mut included = vec![false; width * height];
+    for (i, _) in pixels.iter().enumerate().filter(|(_, &set)| set) {
+        let (row, col) = (top + ((i / columns) * block), left + ((i % columns) * block));
+        for r in row..(row + block) {
+            included[(r * width) + col..(r * width) + col + block].fill(true);
+        }
+    }
+    included[0] = true;
+    included[(width * height) - 1] = true;
+    bridge(&mut included, width);
+
+    for (i, _) in included.iter().enumerate().filter(|(_, &inside)| !inside) {
+        env.exclude(Cell { row: i / width, col: i % width });
+    }
+    Ok(())
+}
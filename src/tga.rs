@@ -0,0 +1,137 @@
+use std::io::{self, Write};
+
+use crate::draw::Canvas;
+use crate::error::MazeError;
+
+const HEADER_SIZE: usize = 18;
+// TGA 2.0 footer: no extension or developer area, then the signature
+const FOOTER: &[u8; 26] = b"\0\0\0\0\0\0\0\0TRUEVISION-XFILE.\0";
+// Pixels a single RLE packet can hold
+const MAX_PACKET: usize = 128;
+
+// Size in bytes of `write_tga`'s output for a `width`x`height` canvas: exact without RLE, and an
+// upper bound with it
+pub fn encoded_size(width: usize, height: usize, rle: bool) -> usize {
+    let packets = if rle { height * width.div_ceil(MAX_PACKET) } else { 0 };
+    HEADER_SIZE + (width * height * 4) + packets + FOOTER.len()
+}
+
+// Appends one row as run-length packets, each a run of one repeated pixel or a stretch of pixels
+// stored as they are
+fn rle_row(bytes: &mut Vec<u8>, row: &[[u8; 4]]) {
+    let mut x = 0;
+    while x < row.len() {
+        let run = row[x..].iter().take(MAX_PACKET).take_while(|p| **p == row[x]).count();
+        if run > 1 {
+            bytes.push(0x80 | (run - 1) as u8);
+            bytes.extend_from_slice(&row[x]);
+            x += run;
+            continue;
+        }
+        // Raw pixels up to the start of the next run
+        let mut end = x + 1;
+        while end < row.len() && end - x < MAX_PACKET && (end + 1 == row.len() || row[end] != row[end + 1]) {
+            end += 1;
+        }
+        bytes.push((end - x - 1) as u8);
+        for pixel in &row[x..end] {
+            bytes.extend_from_slice(pixel);
+        }
+        x = end;
+    }
+}
+
+// Pixels are in 0xRRGGBBAA format and are written as 32-bit BGRA, top row first, optionally run-length
+// encoded. Dimensions are stored in 16 bits, so images are at most 65535 pixels on a side.
+pub fn write_tga(out: &mut dyn Write, canvas: &Canvas, rle: bool) -> io::Result<()> {
+    let (width, height) = (canvas.width(), canvas.height());
+    let (Ok(w), Ok(h)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{}x{} px is too large for TGA (at most 65535 px a side)", width, height)));
+    };
+    let mut bytes = Vec::with_capacity(HEADER_SIZE);
+    // No image ID or color map; type 2 is uncompressed true-color and 10 its RLE variant
+    bytes.extend_from_slice(&[0, 0, if rle { 10 } else { 2 }, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&w.to_le_bytes());
+    bytes.extend_from_slice(&h.to_le_bytes());
+    // 32 bits per pixel, 8 of them alpha, origin at the top left
+    bytes.extend_from_slice(&[32, 0x28]);
+    out.write_all(&bytes)?;
+
+    for pixels in canvas.pixels().chunks(width.max(1)) {
+        let row: Vec<[u8; 4]> = pixels
+            .iter()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.to_be_bytes();
+                [b, g, r, a]
+            })
+            .collect();
+        bytes.clear();
+        if rle {
+            rle_row(&mut bytes, &row);
+        } else {
+            bytes.extend(row.iter().flatten());
+        }
+        out.write_all(&bytes)?;
+    }
+    out.write_all(FOOTER)?;
+    out.flush()
+}
+
+// Whether `bytes` start like a true-color TGA, plain or run-length encoded. TGA has no signature,
+// but a zero second byte never starts text.
+pub fn is_tga(bytes: &[u8]) -> bool {
+    bytes.len() >= HEADER_SIZE && bytes[1] == 0 && [2, 10].contains(&bytes[2]) && [24, 32].contains(&bytes[16])
+}
+
+// Decodes a 24- or 32-bit true-color TGA, plain or run-length encoded, into 0xRRGGBBAA pixels.
+// 32-bit pixels are read as BGRA, and an all-zero alpha channel as opaque.
+pub fn read_tga(bytes: &[u8]) -> Result<Canvas, MazeError> {
+    let invalid = |message: &str| MazeError::InvalidData(format!("unsupported or broken TGA: {}", message));
+    if !is_tga(bytes) {
+        return Err(invalid("only uncompressed or RLE true-color images without a color map are supported"));
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]) as usize;
+    let (rle, width, height) = (bytes[2] == 10, u16_at(12), u16_at(14));
+    let (depth, top_down) = (bytes[16] as usize / 8, bytes[17] & 0x20 != 0);
+    if width == 0 || height == 0 {
+        return Err(invalid("unsupported dimensions"));
+    }
+
+    // Pixels as stored, unpacking runs. A run packet holds at most 128 pixels in `1 + depth` bytes,
+    // so images larger than that are turned down before anything is allocated.
+    let mut data = bytes.get(HEADER_SIZE + bytes[0] as usize..).ok_or_else(|| invalid("missing image ID"))?;
+    let most = if rle { data.len() / (1 + depth) * 128 } else { data.len() / depth };
+    if width * height > most {
+        return Err(invalid("pixel data is too short"));
+    }
+    let mut pixels: Vec<&[u8]> = Vec::with_capacity(width * height);
+    while pixels.len() < width * height {
+        if !rle {
+            let pixel = data.get(..depth).ok_or_else(|| invalid("pixel data is too short"))?;
+            pixels.push(pixel);
+            data = &data[depth..];
+            continue;
+        }
+        let (&packet, rest) = data.split_first().ok_or_else(|| invalid("pixel data is too short"))?;
+        let count = (packet & 0x7F) as usize + 1;
+        if packet & 0x80 != 0 {
+            let pixel = rest.get(..depth).ok_or_else(|| invalid("pixel data is too short"))?;
+            pixels.extend(std::iter::repeat_n(pixel, count));
+            data = &rest[depth..];
+        } else {
+            let raw = rest.get(..count * depth).ok_or_else(|| invalid("pixel data is too short"))?;
+            pixels.extend(raw.chunks(depth));
+            data = &rest[count * depth..];
+        }
+    }
+    let opaque = depth != 4 || pixels.iter().all(|p| p[3] == 0);
+
+    let mut canvas = Canvas::new(width, height, 0);
+    for (i, p) in pixels.iter().take(width * height).enumerate() {
+        let (x, row) = (i % width, i / width);
+        let y = if top_down { row } else { height - 1 - row };
+        let a = if opaque { 0xFF } else { p[3] };
+        canvas.set(x, y, u32::from_be_bytes([p[2], p[1], p[0], a]));
+    }
+    Ok(canvas)
+}
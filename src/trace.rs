@@ -0,0 +1,239 @@
+// Generation traces: every step the depth-first generator took, stored compactly so a maze's
+// construction can be re-rendered or animated later without running the generator again.
+//
+// A trace file is little-endian binary:
+//
+//   "MZT1"                      magic
+//   u32 width, u32 height       maze size in cells
+//   u32 row, u32 col            start cell
+//   u64 count                   number of moves
+//   count nibbles               moves, two to a byte, low nibble first
+//
+// Each move carves from the current cell into its neighbour to the north (0), east (1), south (2)
+// or west (3), or steps back (4) to the cell before it, like popping the generator's stack.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+
+use rand::Rng;
+
+use crate::error::MazeError;
+use crate::progress::Progress;
+use crate::{gen_maze_events, Cell, Env, GenEvent, MAX_CELLS};
+
+pub const EXTENSION: &str = "trace";
+const MAGIC: &[u8; 4] = b"MZT1";
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Move {
+    North,
+    East,
+    South,
+    West,
+    Back,
+}
+
+impl Move {
+    const ALL: [Move; 5] = [Move::North, Move::East, Move::South, Move::West, Move::Back];
+
+    // Neighbour of `cell` in this direction, if it is inside a `width`x`height` grid
+    fn step(&self, cell: Cell, width: usize, height: usize) -> Option<Cell> {
+        let Cell { row, col } = cell;
+        match self {
+            Move::North => row.checked_sub(1).map(|row| Cell { row, col }),
+            Move::East => (col + 1 < width).then_some(Cell { row, col: col + 1 }),
+            Move::South => (row + 1 < height).then_some(Cell { row: row + 1, col }),
+            Move::West => col.checked_sub(1).map(|col| Cell { row, col }),
+            Move::Back => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Trace {
+    pub width: usize,
+    pub height: usize,
+    pub start: Cell,
+    pub moves: Vec<Move>,
+}
+
+impl Trace {
+    // Adds a generator step; pass it every event of one run, starting with `GenEvent::Start`
+    pub fn record(&mut self, event: GenEvent) {
+        match event {
+            GenEvent::Start(cell) => {
+                self.start = cell;
+                self.moves.clear();
+            },
+            GenEvent::Carve(from, to) => {
+                let dir = Move::ALL[..4].iter().find(|m| m.step(from, self.width, self.height) == Some(to));
+                self.moves.push(*dir.expect("carves only join neighbouring cells"));
+            },
+            GenEvent::Backtrack(_) => self.moves.push(Move::Back),
+        }
+    }
+
+    // The generator steps the trace stands for, or an error naming the first impossible move
+    fn try_events(&self) -> Result<Vec<GenEvent>, String> {
+        let inside = |c: Cell| c.row < self.height && c.col < self.width;
+        if !inside(self.start) {
+            return Err(String::from("start cell is outside the maze"));
+        }
+        let mut visited = vec![false; self.width * self.height];
+        visited[(self.start.row * self.width) + self.start.col] = true;
+        let (mut stack, mut events) = (vec![self.start], vec![GenEvent::Start(self.start)]);
+        for (i, m) in self.moves.iter().enumerate() {
+            let Some(&current) = stack.last() else {
+                return Err(format!("move {} comes after the generator finished", i + 1));
+            };
+            if *m == Move::Back {
+                stack.pop();
+                events.push(GenEvent::Backtrack(current));
+                continue;
+            }
+            match m.step(current, self.width, self.height) {
+                Some(next) if !visited[(next.row * self.width) + next.col] => {
+                    visited[(next.row * self.width) + next.col] = true;
+                    stack.push(next);
+                    events.push(GenEvent::Carve(current, next));
+                },
+                _ => return Err(format!("move {} carves outside the maze or into a visited cell", i + 1)),
+            }
+        }
+        Ok(events)
+    }
+
+    // Every generator step, `GenEvent::Start` first
+    pub fn events(&self) -> Vec<GenEvent> {
+        self.try_events().expect("traces are checked when they are read")
+    }
+
+    // The maze the traced run carved, with its visit order, without running the generator
+    pub fn replay(&self) -> Env {
+        let mut env = Env::new(self.width, self.height);
+        for event in self.events() {
+            let visited = match event {
+                GenEvent::Start(cell) => cell,
+                GenEvent::Carve(from, to) => {
+                    env.carve(from, to);
+                    to
+                },
+                GenEvent::Backtrack(_) => continue,
+            };
+            env.mark_visited(visited);
+            env.visit_order.push(env.ind(visited) as u32);
+        }
+        env
+    }
+
+    pub fn write(&self, out: &mut dyn Write) -> Result<(), MazeError> {
+        let mut bytes = MAGIC.to_vec();
+        for value in [self.width, self.height, self.start.row, self.start.col] {
+            bytes.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        bytes.extend_from_slice(&(self.moves.len() as u64).to_le_bytes());
+        for pair in self.moves.chunks(2) {
+            let code = |m: Option<&Move>| m.map_or(0, |m| Move::ALL.iter().position(|x| x == m).expect("every move is listed") as u8);
+            bytes.push(code(pair.first()) | (code(pair.get(1)) << 4));
+        }
+        out.write_all(&bytes)?;
+        Ok(())
+    }
+
+    pub fn read(input: &mut dyn Read) -> Result<Trace, MazeError> {
+        let invalid = |message: &str| MazeError::InvalidData(message.to_string());
+        let mut header = [0u8; 28];
+        input.read_exact(&mut header).map_err(|_| invalid("not a trace file (too short)"))?;
+        if &header[..4] != MAGIC {
+            return Err(invalid("not a trace file (bad magic)"));
+        }
+        let word = |i: usize| u32::from_le_bytes(header[4 + (4 * i)..8 + (4 * i)].try_into().expect("four bytes")) as usize;
+        let (width, height, start) = (word(0), word(1), Cell { row: word(2), col: word(3) });
+        let count = u64::from_le_bytes(header[20..28].try_into().expect("eight bytes")) as usize;
+        let cells = width.checked_mul(height).filter(|&cells| cells > 0 && cells <= MAX_CELLS);
+        let Some(cells) = cells else {
+            return Err(invalid(&format!("maze dimensions must be non-zero with at most {} cells", MAX_CELLS)));
+        };
+        // Every cell is carved into once and stepped back from once
+        if count > 2 * cells {
+            return Err(invalid("more moves than the maze has room for"));
+        }
+
+        // Buffered only as far as the file goes, whatever the header claims
+        let mut packed = vec![];
+        input.take(count.div_ceil(2) as u64).read_to_end(&mut packed)?;
+        if packed.len() < count.div_ceil(2) {
+            return Err(invalid("trace ends early"));
+        }
+        let mut moves = Vec::with_capacity(count);
+        for i in 0..count {
+            let code = (packed[i / 2] >> (4 * (i % 2))) & 0x0F;
+            moves.push(*Move::ALL.get(code as usize).ok_or_else(|| invalid(&format!("unknown move code {}", code)))?);
+        }
+        let trace = Trace { width, height, start, moves };
+        trace.try_events().map_err(|message| invalid(&message))?;
+        Ok(trace)
+    }
+
+    pub fn load(path: &Path) -> Result<Trace, MazeError> {
+        Trace::read(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+// Generates a maze into `env` like `gen_maze_progress` and returns the trace of the run
+pub fn gen_maze_traced(env: &mut Env, rng: &mut impl Rng, progress: &mut dyn Progress) -> Result<Trace, MazeError> {
+    let mut trace = Trace { width: env.width, height: env.height, start: Cell { row: 0, col: 0 }, moves: vec![] };
+    gen_maze_events(env, rng, progress, &mut |event| trace.record(event))?;
+    Ok(trace)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn traced() -> (Env, Trace) {
+        let mut env = Env::new(11, 8);
+        let trace = gen_maze_traced(&mut env, &mut StdRng::seed_from_u64(9), &mut ()).expect("`()` never cancels");
+        (env, trace)
+    }
+
+    fn written(trace: &Trace) -> Vec<u8> {
+        let mut bytes = vec![];
+        trace.write(&mut bytes).expect("writing to memory");
+        bytes
+    }
+
+    #[test]
+    fn replay_of_a_read_trace_is_the_generated_maze() {
+        let (env, trace) = traced();
+        let replayed = Trace::read(&mut &written(&trace)[..]).expect("a written trace reads back").replay();
+        assert_eq!(replayed.walls().map(|w| (w.start, w.target)).collect::<Vec<_>>(), env.walls().map(|w| (w.start, w.target)).collect::<Vec<_>>());
+        assert_eq!(replayed.visit_order, env.visit_order);
+    }
+
+    #[test]
+    fn corrupt_moves_are_rejected() {
+        let (_, trace) = traced();
+        // Stepping straight back into the start cell carves into a visited cell
+        let mut backwards = trace.clone();
+        let back = match trace.moves[0] {
+            Move::North => Move::South,
+            Move::East => Move::West,
+            Move::South => Move::North,
+            _ => Move::East,
+        };
+        backwards.moves[1] = back;
+        assert!(Trace::read(&mut &written(&backwards)[..]).is_err());
+
+        let mut bytes = written(&trace);
+        // Code 15 is no move at all
+        bytes[28] |= 0x0F;
+        assert!(Trace::read(&mut &bytes[..]).is_err());
+        bytes.truncate(30);
+        assert!(Trace::read(&mut &bytes[..]).is_err());
+    }
+}
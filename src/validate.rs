@@ -0,0 +1,191 @@
+use std::fmt;
+
+use crate::mazefile::MazeFile;
+use crate::{Cell, Env};
+
+// Ways a maze can fall short of a perfect maze
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    // Some cells cannot be reached from others; `regions` counts the separate parts
+    Disconnected { regions: usize },
+    // Passages beyond the `cells - regions` a loop-free maze has, i.e. independent loops
+    Loops(usize),
+    // A passage bit is set on the outer border, leading out of the grid
+    BorderOpening(Cell),
+    StartOutside(Cell),
+    GoalOutside(Cell),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Disconnected { regions } => write!(f, "not connected: {} separate regions", regions),
+            Problem::Loops(n) => write!(f, "{} extra passage{} forming loops", n, if *n == 1 { "" } else { "s" }),
+            Problem::BorderOpening(c) => write!(f, "cell ({}, {}) opens onto the outer border", c.row, c.col),
+            Problem::StartOutside(c) => write!(f, "start ({}, {}) is outside the maze", c.row, c.col),
+            Problem::GoalOutside(c) => write!(f, "goal ({}, {}) is outside the maze", c.row, c.col),
+        }
+    }
+}
+
+// Connected region of every cell, indexed by `Env::ind` and numbered from 0 in row-major order of
+// each region's first cell, together with the number of regions. Excluded cells are in none and
+// stay at `usize::MAX`.
+pub fn regions(env: &Env) -> (Vec<usize>, usize) {
+    let mut region = vec![usize::MAX; env.cell_count()];
+    let mut count = 0;
+    for first in 0..env.cell_count() {
+        if region[first] != usize::MAX || env.is_excluded(env.cell(first)) {
+            continue;
+        }
+        region[first] = count;
+        let mut stack = vec![env.cell(first)];
+        while let Some(cell) = stack.pop() {
+            for next in env.neighbors(cell) {
+                if region[env.ind(next)] == usize::MAX {
+                    region[env.ind(next)] = count;
+                    stack.push(next);
+                }
+            }
+        }
+        count += 1;
+    }
+    (region, count)
+}
+
+// Every problem of `env`; empty for a perfect maze. Checks the stored passage bits directly, so it
+// also catches bits the accessors hide, like openings onto the border.
+pub fn validate(env: &Env) -> Vec<Problem> {
+    let mut problems = vec![];
+    let (_, count) = regions(env);
+    if count > 1 {
+        problems.push(Problem::Disconnected { regions: count });
+    }
+    // A forest of `count` trees over the cells has `cells - count` edges; every extra one closes a loop
+    let loops = env.walls().count() + count - env.included_count();
+    if loops > 0 {
+        problems.push(Problem::Loops(loops));
+    }
+    for cell in env.cells() {
+        let (east, south) = env.border_openings(cell);
+        if east || south {
+            problems.push(Problem::BorderOpening(cell));
+        }
+    }
+    problems
+}
+
+// `validate` plus the start and goal of a loaded maze
+pub fn validate_file(maze: &MazeFile) -> Vec<Problem> {
+    let mut problems = validate(&maze.env);
+    let outside = |c: Cell| c.row >= maze.env.height || c.col >= maze.env.width || maze.env.is_excluded(c);
+    if outside(maze.start) {
+        problems.push(Problem::StartOutside(maze.start));
+    }
+    if outside(maze.goal) {
+        problems.push(Problem::GoalOutside(maze.goal));
+    }
+    problems
+}
+
+// A change `repair` made
+#[derive(Clone, Debug, PartialEq)]
+pub enum Repair {
+    // Opened the wall between two cells of different regions
+    Carved(Cell, Cell),
+    // Cleared a passage bit leading out of the grid
+    ClosedBorder(Cell),
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Repair::Carved(a, b) => write!(f, "carved a passage between ({}, {}) and ({}, {})", a.row, a.col, b.row, b.col),
+            Repair::ClosedBorder(c) => write!(f, "closed the border opening of ({}, {})", c.row, c.col),
+        }
+    }
+}
+
+// Representative of region `r` in a union-find forest, halving paths on the way
+fn root(parent: &mut [usize], mut r: usize) -> usize {
+    while parent[r] != r {
+        parent[r] = parent[parent[r]];
+        r = parent[r];
+    }
+    r
+}
+
+// Reconnects a maze with the fewest new passages: one wall between each pair of regions that get
+// joined, the first such wall in row-major order, so repairs are reproducible. Border openings are
+// cleared too. Loops are left alone; braided mazes have them on purpose.
+pub fn repair(env: &mut Env) -> Vec<Repair> {
+    let mut changes = vec![];
+    for cell in env.cells().collect::<Vec<_>>() {
+        if env.border_openings(cell) != (false, false) {
+            env.close_border(cell);
+            changes.push(Repair::ClosedBorder(cell));
+        }
+    }
+
+    // Union-find over the regions, merged as walls between them get carved
+    let (region, count) = regions(env);
+    let mut parent: Vec<usize> = (0..count).collect();
+    let mut joined = 1;
+    for cell in env.cells().collect::<Vec<_>>() {
+        if joined == count {
+            break;
+        }
+        let east = (cell.col + 1 < env.width).then_some(Cell { row: cell.row, col: cell.col + 1 });
+        let south = (cell.row + 1 < env.height).then_some(Cell { row: cell.row + 1, col: cell.col });
+        let joinable: Vec<Cell> = east.into_iter().chain(south).filter(|&n| !env.is_excluded(cell) && !env.is_excluded(n)).collect();
+        for next in joinable {
+            let (a, b) = (root(&mut parent, region[env.ind(cell)]), root(&mut parent, region[env.ind(next)]));
+            if a != b {
+                parent[b] = a;
+                joined += 1;
+                env.carve(cell, next);
+                changes.push(Repair::Carved(cell, next));
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::gen_maze_with;
+
+    fn generated(seed: u64) -> Env {
+        let mut env = Env::new(12, 9);
+        gen_maze_with(&mut env, &mut StdRng::seed_from_u64(seed));
+        env
+    }
+
+    #[test]
+    fn generated_maze_passes() {
+        assert_eq!(validate(&generated(1)), vec![]);
+    }
+
+    #[test]
+    fn removed_passage_is_disconnected() {
+        let mut env = generated(2);
+        let wall = env.walls().nth(5).expect("a carved maze has passages");
+        env.set_passage(wall.start, wall.target, false);
+        assert_eq!(validate(&env), vec![Problem::Disconnected { regions: 2 }]);
+    }
+
+    #[test]
+    fn extra_passage_is_a_loop() {
+        let mut env = generated(3);
+        let (a, b) = env.cells().find_map(|a| {
+            let b = Cell { row: a.row + 1, col: a.col };
+            (b.row < env.height && !env.has_passage(a, b)).then_some((a, b))
+        }).expect("a perfect maze has walls between neighbours");
+        env.carve(a, b);
+        assert_eq!(validate(&env), vec![Problem::Loops(1)]);
+    }
+}
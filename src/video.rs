@@ -0,0 +1,61 @@
+use std::io::{self, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::draw::Canvas;
+
+// Encodes frames into a video file by streaming them as raw RGBA to an `ffmpeg` child process.
+// The container and codec follow the file extension (`.mp4`, `.webm`, `.mkv`...). Alpha is
+// dropped, and odd image sizes are padded by a pixel as most codecs need even dimensions.
+pub struct VideoEncoder {
+    child: Child,
+    stdin: ChildStdin,
+    width: usize,
+    height: usize,
+}
+
+impl VideoEncoder {
+    // Starts `ffmpeg` writing to `path`, replacing it if it exists
+    pub fn new(path: &str, width: usize, height: usize, fps: usize) -> io::Result<Self> {
+        let size = format!("{}x{}", width, height);
+        let fps = fps.to_string();
+        let mut child = Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "error", "-y"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-video_size", &size, "-framerate", &fps, "-i", "-"])
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2", "-pix_fmt", "yuv420p", path])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => io::Error::new(err.kind(), "`ffmpeg` not found; install it or add it to PATH"),
+                _ => err,
+            })?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        Ok(VideoEncoder { child, stdin, width, height })
+    }
+
+    // Adds `canvas` as the next `repeat` frames
+    pub fn add_frame(&mut self, canvas: &Canvas, repeat: usize) -> io::Result<()> {
+        if (canvas.width(), canvas.height()) != (self.width, self.height) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame size differs from the video size"));
+        }
+        let bytes: Vec<u8> = canvas.pixels().iter().flat_map(|pixel| pixel.to_be_bytes()).collect();
+        for _ in 0..repeat {
+            if let Err(err) = self.stdin.write_all(&bytes) {
+                // `ffmpeg` quit early, and its exit status says more than the broken pipe
+                let status = self.child.wait()?;
+                return Err(if status.success() { err } else { io::Error::other(format!("`ffmpeg` failed ({})", status)) });
+            }
+        }
+        Ok(())
+    }
+
+    // Closes the stream and waits for `ffmpeg` to finish the file
+    pub fn finish(self) -> io::Result<()> {
+        let VideoEncoder { mut child, stdin, .. } = self;
+        drop(stdin);
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("`ffmpeg` failed ({})", status)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+// Mazes over a Voronoi diagram instead of the square grid. One site is dropped at a random spot in
+// every square of a `columns` x `rows` grid, every site owns the part of the plane closer to it
+// than to any other site, and two regions sharing a border are neighbours. The regions and borders
+// make a `Graph` that the spanning-tree algorithms carve like any other, and the maze is drawn
+// straight from the polygons: a wall along every border the tree does not cross.
+
+use std::collections::HashSet;
+use std::io::Write;
+
+use rand::Rng;
+
+use crate::draw::{draw_line, draw_polyline, Canvas};
+use crate::error::MazeError;
+use crate::graph::{tree_path, Algorithm, Graph};
+use crate::render::Theme;
+
+// Side of the grid square each site is dropped into, in logical pixels
+const SPACING: f32 = 16.0;
+// Sites stay this far from the edges of their square, so regions are never slivers
+const JITTER_MARGIN: f32 = 0.1;
+const WALL_THICKNESS: f32 = 2.0;
+const SOLUTION_COLOR: u32 = 0xE04040FF;
+const SOLUTION_THICKNESS: f32 = 4.0;
+// Region edges along the outline of the diagram rather than towards another site
+const OUTLINE: usize = usize::MAX;
+// Shared borders shorter than this are corners where regions only touch
+const MIN_BORDER: f32 = 1e-3;
+
+type Point = (f32, f32);
+
+pub struct Voronoi {
+    pub width: f32,
+    pub height: f32,
+    pub sites: Vec<Point>,
+    // Outline of every site's region, in order around it
+    pub regions: Vec<Vec<Point>>,
+    // Neighbouring regions, with the border between the two ends of every edge
+    pub graph: Graph,
+    pub borders: Vec<(Point, Point)>,
+}
+
+// Cuts away the part of `polygon` closer to `other` than to `site`; every vertex carries the tag of
+// the edge leaving it, and edges along the cut get `tag`
+fn clip(polygon: &[(Point, usize)], site: Point, other: Point, tag: usize) -> Vec<(Point, usize)> {
+    let normal = (other.0 - site.0, other.1 - site.1);
+    let limit = ((other.0 * other.0) + (other.1 * other.1) - (site.0 * site.0) - (site.1 * site.1)) / 2.0;
+    let past = |p: Point| (p.0 * normal.0) + (p.1 * normal.1) - limit;
+    let mut out = vec![];
+    for (i, &(a, edge)) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()].0;
+        let (da, db) = (past(a), past(b));
+        let crossing = || {
+            let t = da / (da - db);
+            (a.0 + (t * (b.0 - a.0)), a.1 + (t * (b.1 - a.1)))
+        };
+        match (da <= 0.0, db <= 0.0) {
+            (true, true) => out.push((a, edge)),
+            (true, false) => {
+                out.push((a, edge));
+                out.push((crossing(), tag));
+            },
+            (false, true) => out.push((crossing(), edge)),
+            (false, false) => {},
+        }
+    }
+    out
+}
+
+impl Voronoi {
+    // `columns` x `rows` sites, one at a random spot in each grid square, numbered row by row
+    pub fn jittered(columns: usize, rows: usize, rng: &mut impl Rng) -> Self {
+        let (width, height) = (columns as f32 * SPACING, rows as f32 * SPACING);
+        let mut sites = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for col in 0..columns {
+                let x = (col as f32 + rng.gen_range(JITTER_MARGIN..1.0 - JITTER_MARGIN)) * SPACING;
+                let y = (row as f32 + rng.gen_range(JITTER_MARGIN..1.0 - JITTER_MARGIN)) * SPACING;
+                sites.push((x, y));
+            }
+        }
+
+        let (mut regions, mut edges, mut borders) = (vec![], vec![], vec![]);
+        for (i, &site) in sites.iter().enumerate() {
+            let (row, col) = (i / columns, i % columns);
+            let mut polygon = vec![((0.0, 0.0), OUTLINE), ((width, 0.0), OUTLINE), ((width, height), OUTLINE), ((0.0, height), OUTLINE)];
+            // Any point of a region is within one grid diagonal of its site, so neighbours are
+            // never more than three squares away
+            for r in row.saturating_sub(3)..(row + 4).min(rows) {
+                for c in col.saturating_sub(3)..(col + 4).min(columns) {
+                    let j = (r * columns) + c;
+                    if j != i {
+                        polygon = clip(&polygon, site, sites[j], j);
+                    }
+                }
+            }
+            for (k, &(a, j)) in polygon.iter().enumerate() {
+                let b = polygon[(k + 1) % polygon.len()].0;
+                if j != OUTLINE && i < j && ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt() > MIN_BORDER {
+                    edges.push((i, j));
+                    borders.push((a, b));
+                }
+            }
+            regions.push(polygon.into_iter().map(|(p, _)| p).collect());
+        }
+        Self { width, height, sites, regions, graph: Graph { nodes: columns * rows, edges }, borders }
+    }
+}
+
+pub struct VoronoiMaze {
+    pub voronoi: Voronoi,
+    // Whether each edge of the graph is a passage
+    pub open: Vec<bool>,
+    // Regions a solver connects: the first site and the last one, in opposite corners
+    pub start: usize,
+    pub goal: usize,
+}
+
+// Carves a maze over a `columns` x `rows` Voronoi diagram with `algorithm`
+pub fn generate(columns: usize, rows: usize, algorithm: Algorithm, rng: &mut impl Rng) -> VoronoiMaze {
+    let voronoi = Voronoi::jittered(columns, rows, rng);
+    let tree: HashSet<(usize, usize)> = voronoi.graph.spanning_tree(algorithm, rng).into_iter().collect();
+    let open = voronoi.graph.edges.iter().map(|edge| tree.contains(edge)).collect();
+    let goal = voronoi.sites.len() - 1;
+    VoronoiMaze { voronoi, open, start: 0, goal }
+}
+
+impl VoronoiMaze {
+    // Regions from `start` to `goal` along open passages
+    pub fn solve(&self) -> Vec<usize> {
+        let passages: Vec<(usize, usize)> = self.voronoi.graph.edges.iter().zip(self.open.iter()).filter(|(_, &open)| open).map(|(&edge, _)| edge).collect();
+        tree_path(self.voronoi.sites.len(), &passages, self.start, self.goal)
+    }
+
+    // The solution drawn from site to site through the middle of every border it crosses
+    fn solution_points(&self) -> Vec<Point> {
+        let path = self.solve();
+        let mut points = vec![self.voronoi.sites[path[0]]];
+        for pair in path.windows(2) {
+            let edge = self.voronoi.graph.edges.iter().position(|&(a, b)| (a, b) == (pair[0], pair[1]) || (b, a) == (pair[0], pair[1])).expect("the path follows edges");
+            let (a, b) = self.voronoi.borders[edge];
+            points.push(((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0));
+            points.push(self.voronoi.sites[pair[1]]);
+        }
+        points
+    }
+
+    // Wall segments: every closed border, then the outline of the diagram
+    fn walls(&self) -> Vec<(Point, Point)> {
+        let (w, h) = (self.voronoi.width, self.voronoi.height);
+        let closed = self.voronoi.borders.iter().zip(self.open.iter()).filter(|(_, &open)| !open).map(|(&border, _)| border);
+        closed.chain([((0.0, 0.0), (w, 0.0)), ((w, 0.0), (w, h)), ((w, h), (0.0, h)), ((0.0, h), (0.0, 0.0))]).collect()
+    }
+
+    // Rasterized at `scale` pixels per logical pixel, optionally with the solution drawn in
+    pub fn render(&self, theme: &Theme, scale: usize, solve: bool) -> Canvas {
+        let (u, margin) = (scale as f32, WALL_THICKNESS);
+        let size = |length: f32| ((length + (2.0 * margin)) * u).ceil() as usize;
+        let mut canvas = Canvas::new(size(self.voronoi.width), size(self.voronoi.height), theme.open_color);
+        let at = |p: Point| ((p.0 + margin) * u, (p.1 + margin) * u);
+        for (a, b) in self.walls() {
+            draw_line(&mut canvas, at(a), at(b), WALL_THICKNESS * u, theme.solid_color);
+        }
+        if solve {
+            let points: Vec<Point> = self.solution_points().into_iter().map(at).collect();
+            draw_polyline(&mut canvas, &points, SOLUTION_THICKNESS * u, SOLUTION_COLOR);
+        }
+        canvas
+    }
+
+    // One stroked line per wall, like `SvgRenderer`, plus the solution as a polyline if asked for
+    pub fn write_svg(&self, out: &mut dyn Write, theme: &Theme, solve: bool) -> Result<(), MazeError> {
+        let margin = WALL_THICKNESS;
+        let (width, height) = (self.voronoi.width + (2.0 * margin), self.voronoi.height + (2.0 * margin));
+        let color = |c: u32| (format!("#{:06x}", c >> 8), (c & 0xFF) as f32 / 255.0);
+        let ((open, open_opacity), (solid, solid_opacity)) = (color(theme.open_color), color(theme.solid_color));
+        writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#)?;
+        writeln!(out, r#"<rect width="100%" height="100%" fill="{open}" fill-opacity="{open_opacity}"/>"#)?;
+        writeln!(out, r#"<g stroke="{solid}" stroke-opacity="{solid_opacity}" stroke-width="{WALL_THICKNESS}" stroke-linecap="round" transform="translate({margin} {margin})">"#)?;
+        for (a, b) in self.walls() {
+            writeln!(out, r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}"/>"#, a.0, a.1, b.0, b.1)?;
+        }
+        writeln!(out, "</g>")?;
+        if solve {
+            let points: Vec<String> = self.solution_points().iter().map(|p| format!("{:.2},{:.2}", p.0 + margin, p.1 + margin)).collect();
+            let (path, _) = color(SOLUTION_COLOR);
+            writeln!(out, r#"<polyline points="{}" fill="none" stroke="{path}" stroke-width="{SOLUTION_THICKNESS}" stroke-linecap="round" stroke-linejoin="round"/>"#, points.join(" "))?;
+        }
+        writeln!(out, "</svg>")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use std::f32::consts::PI;
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute, queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+
+use crate::Env;
+
+const FOV: f32 = PI / 3.0;
+const MOVE_STEP: f32 = 0.25;
+const TURN_STEP: f32 = PI / 12.0;
+// Furthest distance (in blocks) a ray travels before it gives up
+const MAX_DEPTH: f32 = 48.0;
+// Wall characters from nearest to furthest
+const WALL_SHADES: &[u8] = b"@%#*+=-:";
+
+struct Player {
+    x: f32,
+    y: f32,
+    angle: f32,
+    steps: usize,
+}
+
+impl Player {
+    fn try_move(&mut self, blocks: &[Vec<bool>], dist: f32) {
+        let nx = self.x + self.angle.cos() * dist;
+        let ny = self.y + self.angle.sin() * dist;
+        // Check each axis separately so the player slides along walls
+        if !blocks[self.y as usize][nx as usize] {
+            self.x = nx;
+        }
+        if !blocks[ny as usize][self.x as usize] {
+            self.y = ny;
+        }
+        self.steps += 1;
+    }
+}
+
+// DDA ray march through the block map; returns the perpendicular distance to the wall hit and
+// whether the wall face was hit along the x axis
+fn cast(blocks: &[Vec<bool>], ox: f32, oy: f32, angle: f32) -> (f32, bool) {
+    let (dx, dy) = (angle.cos(), angle.sin());
+    let (mut map_x, mut map_y) = (ox as i32, oy as i32);
+    let delta_x = if dx == 0.0 { f32::INFINITY } else { (1.0 / dx).abs() };
+    let delta_y = if dy == 0.0 { f32::INFINITY } else { (1.0 / dy).abs() };
+    let (step_x, mut side_x) = if dx < 0.0 {
+        (-1, (ox - map_x as f32) * delta_x)
+    } else {
+        (1, (map_x as f32 + 1.0 - ox) * delta_x)
+    };
+    let (step_y, mut side_y) = if dy < 0.0 {
+        (-1, (oy - map_y as f32) * delta_y)
+    } else {
+        (1, (map_y as f32 + 1.0 - oy) * delta_y)
+    };
+
+    loop {
+        let x_side = side_x < side_y;
+        let dist = if x_side {
+            map_x += step_x;
+            side_x += delta_x;
+            side_x - delta_x
+        } else {
+            map_y += step_y;
+            side_y += delta_y;
+            side_y - delta_y
+        };
+        let outside = map_x < 0 || map_y < 0 || map_y as usize >= blocks.len() || map_x as usize >= blocks[0].len();
+        if outside || dist > MAX_DEPTH || blocks[map_y as usize][map_x as usize] {
+            return (dist.min(MAX_DEPTH), x_side);
+        }
+    }
+}
+
+fn render_view(blocks: &[Vec<bool>], player: &Player, width: usize, height: usize) -> Vec<String> {
+    let mut rows = vec![vec![b' '; width]; height];
+    for (col, x) in (0..width).zip((0..width).map(|x| x as f32 / width as f32)) {
+        let ray_angle = player.angle - (FOV / 2.0) + (x * FOV);
+        let (dist, x_side) = cast(blocks, player.x, player.y, ray_angle);
+        // Correct the fish-eye distortion by projecting onto the view direction
+        let dist = (dist * (ray_angle - player.angle).cos()).max(0.01);
+        let wall = ((height as f32 / dist) as usize).min(height);
+        let top = (height - wall) / 2;
+        let mut shade = ((dist / MAX_DEPTH) * WALL_SHADES.len() as f32 * 2.0) as usize;
+        // Faces hit along the x axis are drawn one step darker to make corners readable
+        shade = (shade + x_side as usize).min(WALL_SHADES.len() - 1);
+        for (row, line) in rows.iter_mut().enumerate() {
+            line[col] = if row < top {
+                b' '
+            } else if row < top + wall {
+                WALL_SHADES[shade]
+            } else {
+                b'.'
+            };
+        }
+    }
+    rows.into_iter().map(|r| String::from_utf8(r).unwrap()).collect()
+}
+
+fn draw(out: &mut impl Write, blocks: &[Vec<bool>], player: &Player) -> io::Result<()> {
+    let (cols, rows) = terminal::size().unwrap_or((80, 24));
+    let (width, height) = (cols as usize, (rows as usize).saturating_sub(2).max(1));
+    queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+    for (i, line) in render_view(blocks, player, width, height).iter().enumerate() {
+        queue!(out, cursor::MoveTo(0, i as u16), Print(line))?;
+    }
+    let status = format!(
+        "W/S: move  A/D: turn  Q: quit   cell ({}, {})  steps {}",
+        (player.y as usize) / 2, (player.x as usize) / 2, player.steps
+    );
+    queue!(out, cursor::MoveTo(0, height as u16), Print(status))?;
+    out.flush()
+}
+
+// First-person walk through the maze, starting in the top-left cell facing east.
+// Reaching the bottom-right cell ends the walk.
+pub fn walk(env: &Env) -> io::Result<()> {
+    let blocks = env.blocks();
+    let mut player = Player { x: 1.5, y: 1.5, angle: 0.0, steps: 0 };
+    let (rows, cols) = env.block_dims();
+    let goal = (cols - 2, rows - 2);
+    let mut out = io::stdout();
+
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = (|| -> io::Result<bool> {
+        loop {
+            draw(&mut out, &blocks, &player)?;
+            if (player.x as usize, player.y as usize) == goal {
+                return Ok(true);
+            }
+            let Event::Key(key) = event::read()? else { continue };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('w') | KeyCode::Up => player.try_move(&blocks, MOVE_STEP),
+                KeyCode::Char('s') | KeyCode::Down => player.try_move(&blocks, -MOVE_STEP),
+                KeyCode::Char('a') | KeyCode::Left => player.angle -= TURN_STEP,
+                KeyCode::Char('d') | KeyCode::Right => player.angle += TURN_STEP,
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                _ => {},
+            }
+        }
+    })();
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    if result? {
+        println!("You escaped the maze in {} steps!", player.steps);
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use web_sys::{CanvasRenderingContext2d, ImageData};
+
+use crate::metadata::Metadata;
+use crate::render::raster::{ImageFormat, Overlays, RasterRenderer, Style};
+use crate::render::{self, Theme};
+use crate::{gen_maze_with, Env};
+
+// A zero `width` or `height` is an error for the JS caller rather than a trap
+fn seeded_maze(seed: u64, width: usize, height: usize) -> Result<Env, JsValue> {
+    let mut env = Env::try_new(width, height).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    gen_maze_with(&mut env, &mut StdRng::seed_from_u64(seed));
+    Ok(env)
+}
+
+// Generates a `width`x`height` maze and returns one byte per cell in row-major order, holding
+// the `maze::OPEN_*` bits of the sides that have a passage. The same seed gives the same maze as
+// `maze --seed`.
+#[wasm_bindgen]
+pub fn generate(seed: u64, width: usize, height: usize) -> Result<Vec<u8>, JsValue> {
+    let env = seeded_maze(seed, width, height)?;
+    Ok(env.cells().map(|cell| env.open_sides(cell)).collect())
+}
+
+// Pixel length of an image side spanning `cells` cells at `scale`; size the canvas with this
+#[wasm_bindgen(js_name = imageSize)]
+pub fn image_size(cells: usize, scale: usize) -> usize {
+    render::image_size(cells) * scale
+}
+
+// Generates a maze like `generate` and draws it, blocky style, at the top-left of `ctx`
+#[wasm_bindgen]
+pub fn render_to_canvas(ctx: &CanvasRenderingContext2d, seed: u64, width: usize, height: usize, scale: usize) -> Result<(), JsValue> {
+    let env = seeded_maze(seed, width, height)?;
+    let renderer = RasterRenderer {
+        format: ImageFormat::Png,
+        style: Style::Blocky,
+        scale: scale.max(1),
+        supersample: 1,
+        overlays: Overlays::default(),
+        metadata: Metadata::default(),
+    };
+    let canvas = renderer.rasterize(&env, &Theme::default());
+    let rgba: Vec<u8> = canvas.pixels().iter().flat_map(|pixel| pixel.to_be_bytes()).collect();
+    let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&rgba), canvas.width() as u32, canvas.height() as u32)?;
+    ctx.put_image_data(&image, 0.0, 0.0)
+}
@@ -0,0 +1,36 @@
+use std::io;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal;
+
+// Next key press, with the terminal in raw mode only while waiting so output in between prints
+// normally
+fn read_key() -> io::Result<KeyCode> {
+    terminal::enable_raw_mode()?;
+    let key = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => break Ok(key.code),
+            Ok(_) => continue,
+            Err(err) => break Err(err),
+        }
+    };
+    terminal::disable_raw_mode()?;
+    key
+}
+
+// Calls `regenerate` every time Space or Enter is pressed until Q or Escape is, printing the status
+// line it returns. Failures are reported without stopping, so e.g. a locked output file can be
+// retried.
+pub fn watch(mut regenerate: impl FnMut() -> io::Result<String>) -> io::Result<()> {
+    eprintln!("Space/Enter: new maze  Q: quit");
+    loop {
+        match read_key()? {
+            KeyCode::Char(' ') | KeyCode::Enter => match regenerate() {
+                Ok(status) => eprintln!("{}", status),
+                Err(err) => eprintln!("ERROR: {}", err),
+            },
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            _ => {},
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// Corridors of two widths. The maze is carved as usual and then redrawn at twice the resolution,
+// every cell becoming a 2x2 block of cells. Cells on wide corridors open up their whole block.
+// Narrow ones keep a corridor one cell wide through the top-left cell of the block, reaching east
+// through the top-right cell and south through the bottom-left one, and leave the rest of the
+// block out of the maze (see `Env::exclude`). The result is an ordinary `Env` that solvers and
+// renderers handle like any other, but the open blocks of wide corridors are loops, so it is not a
+// perfect maze.
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::solve::Solver;
+use crate::{Cell, Env};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Widths {
+    // The solution from the top-left cell to the bottom-right one is the wide main artery
+    Solution,
+    // About this share of the corridors, picked at random, are wide
+    Random(f32),
+}
+
+impl Widths {
+    // `solution`, or a share of wide corridors above 0 and at most 1
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "solution" => Some(Widths::Solution),
+            _ => text.parse().ok().filter(|&share: &f32| share > 0.0 && share <= 1.0).map(Widths::Random),
+        }
+    }
+}
+
+// Runs of cells between two cells that do not have exactly two open sides, both ends included
+fn corridors(env: &Env) -> Vec<Vec<Cell>> {
+    let is_end = |cell: Cell| !env.is_excluded(cell) && env.open_sides(cell).count_ones() != 2;
+    let mut corridors = vec![];
+    for first in env.cells().filter(|&cell| is_end(cell)) {
+        for second in env.neighbors(first) {
+            let mut run = vec![first, second];
+            while !is_end(run[run.len() - 1]) {
+                let (last, before) = (run[run.len() - 1], run[run.len() - 2]);
+                match env.neighbors(last).find(|&n| n != before) {
+                    Some(next) => run.push(next),
+                    None => break,
+                }
+            }
+            // Every corridor is walked from both ends; keep the walk from the earlier one
+            if env.ind(first) <= env.ind(run[run.len() - 1]) {
+                corridors.push(run);
+            }
+        }
+    }
+    corridors
+}
+
+// Which cells of `env` lie on wide corridors, indexed by `Env::ind`. The top-left and bottom-right
+// cells always do, so the start and goal are inside the maze at both resolutions.
+pub fn wide_cells(env: &Env, widths: Widths, rng: &mut impl Rng) -> Vec<bool> {
+    let mut wide = vec![false; env.cell_count()];
+    let (start, goal) = (Cell { row: 0, col: 0 }, Cell { row: env.height - 1, col: env.width - 1 });
+    match widths {
+        Widths::Solution => {
+            for cell in Solver::Bfs.solve(env, start, goal).map_or(vec![], |s| s.path) {
+                wide[env.ind(cell)] = true;
+            }
+        },
+        Widths::Random(share) => {
+            let mut corridors = corridors(env);
+            corridors.shuffle(rng);
+            let count = (corridors.len() as f32 * share).round() as usize;
+            for cell in corridors.iter().take(count).flatten() {
+                wide[env.ind(*cell)] = true;
+            }
+        },
+    }
+    wide[env.ind(start)] = true;
+    wide[env.ind(goal)] = true;
+    wide
+}
+
+// `env` redrawn at twice the resolution with the `wide` cells on wide corridors; see the top of
+// the file. The visit order carries over block by block.
+pub fn widen(env: &Env, wide: &[bool]) -> Env {
+    let mut out = Env::new(2 * env.width, 2 * env.height);
+    let block = |cell: Cell, row: usize, col: usize| Cell { row: (2 * cell.row) + row, col: (2 * cell.col) + col };
+    for cell in env.cells() {
+        let (top_left, top_right, bottom_left, bottom_right) = (block(cell, 0, 0), block(cell, 0, 1), block(cell, 1, 0), block(cell, 1, 1));
+        if env.is_excluded(cell) {
+            for sub in [top_left, top_right, bottom_left, bottom_right] {
+                out.exclude(sub);
+            }
+            continue;
+        }
+        let east = Cell { row: cell.row, col: cell.col + 1 };
+        let south = Cell { row: cell.row + 1, col: cell.col };
+        if wide[env.ind(cell)] {
+            out.carve(top_left, top_right);
+            out.carve(top_left, bottom_left);
+            out.carve(top_right, bottom_right);
+            out.carve(bottom_left, bottom_right);
+        } else {
+            out.exclude(bottom_right);
+            if env.open_east(cell) { out.carve(top_left, top_right) } else { out.exclude(top_right) }
+            if env.open_south(cell) { out.carve(top_left, bottom_left) } else { out.exclude(bottom_left) }
+        }
+        if env.open_east(cell) {
+            out.carve(top_right, block(east, 0, 0));
+            if wide[env.ind(cell)] && wide[env.ind(east)] {
+                out.carve(bottom_right, block(east, 1, 0));
+            }
+        }
+        if env.open_south(cell) {
+            out.carve(bottom_left, block(south, 0, 0));
+            if wide[env.ind(cell)] && wide[env.ind(south)] {
+                out.carve(bottom_right, block(south, 0, 1));
+            }
+        }
+    }
+    for &ind in env.visit_order.iter() {
+        let cell = env.cell(ind as usize);
+        for sub in [block(cell, 0, 0), block(cell, 0, 1), block(cell, 1, 0), block(cell, 1, 1)] {
+            if !out.is_excluded(sub) {
+                out.visit_order.push(out.ind(sub) as u32);
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,128 @@
+// Mazes whose solution spells a word. The path from the top-left start runs down the left edge,
+// along an underline beneath the word and down the right edge to the goal; at every letter it
+// climbs up into it, visits every cell of the letter and drops back down, so drawing the solution
+// writes the word out. Each glyph pixel is a 2x2 block of cells, and the path is the loop around a
+// spanning tree of the letter's pixels (see `shape`).
+
+use crate::error::MazeError;
+use crate::fixed::Fixed;
+use crate::font::{GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::shape::loop_around;
+use crate::Cell;
+
+// Cells along each side of a glyph pixel, as `shape::loop_around` lays them out
+const PIXEL: usize = 2;
+// Cells between two letters
+const SPACING: usize = 1;
+
+// 3x5 glyphs like `font`'s, with the leftmost pixel in bit 2, but redrawn so every pixel touches
+// the next one along an edge rather than only at a corner. Lowercase letters use the uppercase
+// glyphs.
+fn glyph(ch: char) -> Option<[u8; GLYPH_HEIGHT]> {
+    Some(match ch.to_ascii_uppercase() {
+        '0' | 'O' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' | 'Z' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' | 'S' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b111, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b111, 0b101, 0b110],
+        'C' => [0b111, 0b100, 0b100, 0b100, 0b111],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b111],
+        'E' => [0b111, 0b100, 0b111, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b111, 0b100, 0b100],
+        'G' => [0b111, 0b100, 0b101, 0b101, 0b111],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b111],
+        'K' => [0b101, 0b111, 0b100, 0b111, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b111, 0b101, 0b101, 0b101, 0b101],
+        'P' => [0b111, 0b101, 0b111, 0b100, 0b100],
+        'Q' => [0b111, 0b101, 0b101, 0b111, 0b001],
+        'R' => [0b111, 0b101, 0b110, 0b111, 0b101],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b111, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b111, 0b010, 0b111, 0b101],
+        'Y' => [0b101, 0b101, 0b111, 0b010, 0b010],
+        _ => return None,
+    })
+}
+
+fn is_set(rows: &[u8; GLYPH_HEIGHT], row: usize, col: usize) -> bool {
+    row < GLYPH_HEIGHT && col < GLYPH_WIDTH && rows[row] & (1 << (GLYPH_WIDTH - 1 - col)) != 0
+}
+
+// Cells of a letter in the order the path visits them, as (row, col) within its
+// `GLYPH_HEIGHT * PIXEL` by `GLYPH_WIDTH * PIXEL` box. The path starts at the bottom-left cell of
+// the leftmost pixel on the bottom row and ends at the bottom-right cell of the same pixel.
+fn letter_loop(rows: &[u8; GLYPH_HEIGHT]) -> Vec<(usize, usize)> {
+    let pixels: Vec<bool> = (0..GLYPH_HEIGHT).flat_map(|r| (0..GLYPH_WIDTH).map(move |c| is_set(rows, r, c))).collect();
+    let entry = (GLYPH_HEIGHT - 1, (0..GLYPH_WIDTH).find(|&c| is_set(rows, GLYPH_HEIGHT - 1, c)).expect("every glyph sits on the baseline"));
+    loop_around(&pixels, GLYPH_WIDTH, entry)
+}
+
+// Letters of `word` in order, `None` for spaces
+fn glyphs(word: &str) -> Result<Vec<Option<[u8; GLYPH_HEIGHT]>>, MazeError> {
+    if word.trim().is_empty() {
+        return Err(MazeError::InvalidData(String::from("the word is empty")));
+    }
+    word.chars().map(|ch| match ch {
+        ' ' => Ok(None),
+        ch => glyph(ch).map(Some).ok_or_else(|| MazeError::InvalidData(format!("`{}` cannot be spelled, only letters, digits and spaces can", ch))),
+    }).collect()
+}
+
+// Smallest maze `word` fits in, as width and height: the word with a column on either side for
+// the path down the edges, over the underline
+pub fn min_size(word: &str) -> (usize, usize) {
+    let letters = word.chars().count();
+    let width = (letters * GLYPH_WIDTH * PIXEL) + (letters.saturating_sub(1) * SPACING);
+    (width + 2, (GLYPH_HEIGHT * PIXEL) + 1)
+}
+
+// The solution path of a `width`x`height` maze spelling `word`, from the top-left cell to the
+// bottom-right one. The word is centred, with the underline just below the middle.
+pub fn path(word: &str, width: usize, height: usize) -> Result<Vec<Cell>, MazeError> {
+    let glyphs = glyphs(word)?;
+    let (min_width, min_height) = min_size(word);
+    if width < min_width || height < min_height {
+        return Err(MazeError::InvalidData(format!("`{}` needs a maze of at least {}x{}", word, min_width, min_height)));
+    }
+    let top = (height - min_height) / 2;
+    let underline = top + (GLYPH_HEIGHT * PIXEL);
+    let left = 1 + ((width - min_width) / 2);
+
+    let mut path: Vec<Cell> = (0..underline).map(|row| Cell { row, col: 0 }).collect();
+    let mut col = 0;
+    for (i, glyph) in glyphs.iter().enumerate() {
+        let start = left + (i * ((GLYPH_WIDTH * PIXEL) + SPACING));
+        path.extend((col..start).map(|col| Cell { row: underline, col }));
+        col = start;
+        if let Some(rows) = glyph {
+            let cells = letter_loop(rows);
+            // Up from the underline into the letter and back down next to where it went in
+            let (entry, exit) = (cells[0].1, cells[cells.len() - 1].1);
+            path.extend((col..=start + entry).map(|col| Cell { row: underline, col }));
+            path.extend(cells.iter().map(|&(r, c)| Cell { row: top + r, col: start + c }));
+            col = start + exit;
+        }
+    }
+    path.extend((col..width).map(|col| Cell { row: underline, col }));
+    path.extend((underline + 1..height).map(|row| Cell { row, col: width - 1 }));
+    Ok(path)
+}
+
+// Passages to carve a maze around so that its solution spells `word`; see `fixed::gen_maze_fixed`
+pub fn fixed(word: &str, width: usize, height: usize) -> Result<Fixed, MazeError> {
+    let path = path(word, width, height)?;
+    Ok(Fixed { passages: path.windows(2).map(|pair| (pair[0], pair[1])).collect(), walls: vec![] })
+}